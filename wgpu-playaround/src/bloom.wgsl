@@ -0,0 +1,96 @@
+// Bloom mip chain over the HDR scene, see `post/bloom.rs`
+struct BloomParams {
+    threshold: f32,
+    knee: f32,
+    // Tent filter size in texels
+    radius: f32,
+    intensity: f32,
+};
+@group(0) @binding(0)
+var source_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var source_sampler: sampler;
+@group(0) @binding(2)
+var<uniform> params: BloomParams;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// One triangle covering the target
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let position = vec2<f32>(f32(index & 1u) * 4.0 - 1.0, f32(index >> 1u) * 4.0 - 1.0);
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(position, 0.0, 1.0);
+    out.uv = vec2<f32>(position.x * 0.5 + 0.5, 0.5 - position.y * 0.5);
+    return out;
+}
+
+fn tap(uv: vec2<f32>) -> vec3<f32> {
+    return textureSampleLevel(source_texture, source_sampler, uv, 0.0).rgb;
+}
+
+fn texel_size() -> vec2<f32> {
+    return 1.0 / vec2<f32>(textureDimensions(source_texture));
+}
+
+// 13 bilinear taps over a 4x4 texel footprint, from Jimenez's "Next Generation
+// Post Processing in Call of Duty: Advanced Warfare"
+fn downsample(uv: vec2<f32>) -> vec3<f32> {
+    let t = texel_size();
+    let a = tap(uv + t * vec2<f32>(-2.0, 2.0));
+    let b = tap(uv + t * vec2<f32>(0.0, 2.0));
+    let c = tap(uv + t * vec2<f32>(2.0, 2.0));
+    let d = tap(uv + t * vec2<f32>(-2.0, 0.0));
+    let e = tap(uv);
+    let f = tap(uv + t * vec2<f32>(2.0, 0.0));
+    let g = tap(uv + t * vec2<f32>(-2.0, -2.0));
+    let h = tap(uv + t * vec2<f32>(0.0, -2.0));
+    let i = tap(uv + t * vec2<f32>(2.0, -2.0));
+    let j = tap(uv + t * vec2<f32>(-1.0, 1.0));
+    let k = tap(uv + t * vec2<f32>(1.0, 1.0));
+    let l = tap(uv + t * vec2<f32>(-1.0, -1.0));
+    let m = tap(uv + t * vec2<f32>(1.0, -1.0));
+    return e * 0.125 + (a + c + g + i) * 0.03125 + (b + d + f + h) * 0.0625 + (j + k + l + m) * 0.125;
+}
+
+// 3x3 tent, weights 1 2 1 / 2 4 2 / 1 2 1 over 16
+fn upsample(uv: vec2<f32>) -> vec3<f32> {
+    let t = texel_size() * params.radius;
+    var color = tap(uv) * 4.0;
+    color += (tap(uv + vec2<f32>(t.x, 0.0)) + tap(uv - vec2<f32>(t.x, 0.0))) * 2.0;
+    color += (tap(uv + vec2<f32>(0.0, t.y)) + tap(uv - vec2<f32>(0.0, t.y))) * 2.0;
+    color += tap(uv + t) + tap(uv - t) + tap(uv + vec2<f32>(t.x, -t.y)) + tap(uv + vec2<f32>(-t.x, t.y));
+    return color / 16.0;
+}
+
+// Mirrors `bloom_threshold` in `post/bloom.rs`
+fn threshold(color: vec3<f32>) -> vec3<f32> {
+    let brightness = max(color.r, max(color.g, color.b));
+    var soft = clamp(brightness - params.threshold + params.knee, 0.0, 2.0 * params.knee);
+    soft = soft * soft / (4.0 * params.knee + 1e-5);
+    let contribution = max(soft, brightness - params.threshold) / max(brightness, 1e-5);
+    return color * contribution;
+}
+
+@fragment
+fn fs_prefilter(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(threshold(max(downsample(in.uv), vec3<f32>(0.0))), 1.0);
+}
+
+@fragment
+fn fs_downsample(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(downsample(in.uv), 1.0);
+}
+
+@fragment
+fn fs_upsample(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(upsample(in.uv), 1.0);
+}
+
+@fragment
+fn fs_composite(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(upsample(in.uv) * params.intensity, 1.0);
+}
@@ -23,11 +23,12 @@ pub const INDICES: &[u16] = &[
 pub const STAR_VERTICES: &[Vertex] = &[
     // Outer points (tips of the star) - radius 0.5
     // Angles: 90°, 18°, -54°, -126°, 162°
-    Vertex { position: [0.0, 0.5, 0.0], color: [1.0, 0.0, 0.0] },           // 0 - Top (90°)
-    Vertex { position: [0.475528, 0.154509, 0.0], color: [0.0, 1.0, 0.0] }, // 1 - Right-upper (18°)
-    Vertex { position: [0.293893, -0.404509, 0.0], color: [0.0, 0.0, 1.0] }, // 2 - Right-lower (-54°)
-    Vertex { position: [-0.293893, -0.404509, 0.0], color: [1.0, 0.0, 1.0] }, // 3 - Left-lower (-126°)
-    Vertex { position: [-0.475528, 0.154509, 0.0], color: [0.0, 1.0, 1.0] }, // 4 - Left-upper (162°)
+    // Brighter than white so they bloom
+    Vertex { position: [0.0, 0.5, 0.0], color: [3.0, 0.0, 0.0] },           // 0 - Top (90°)
+    Vertex { position: [0.475528, 0.154509, 0.0], color: [0.0, 3.0, 0.0] }, // 1 - Right-upper (18°)
+    Vertex { position: [0.293893, -0.404509, 0.0], color: [0.0, 0.0, 3.0] }, // 2 - Right-lower (-54°)
+    Vertex { position: [-0.293893, -0.404509, 0.0], color: [3.0, 0.0, 3.0] }, // 3 - Left-lower (-126°)
+    Vertex { position: [-0.475528, 0.154509, 0.0], color: [0.0, 3.0, 3.0] }, // 4 - Left-upper (162°)
 
     // Inner points (valleys between tips) - radius 0.2
     // Angles: 54°, -18°, -90°, -162°, 126°
@@ -60,3 +61,100 @@ pub const STAR_INDICES: &[u16] = &[
     10, 8, 9,  // Center to valley between tip 4 and 0
 ];
 
+
+// Outlines walk the boundary of each shape counter-clockwise. They are used to
+// morph between shapes, which need matching point counts rather than triangles.
+pub const PENTAGON_OUTLINE: &[u16] = &[0, 1, 2, 3, 4];
+
+pub const STAR_OUTLINE: &[u16] = &[0, 9, 4, 8, 3, 7, 2, 6, 1, 5];
+
+// Morph settings
+pub const MORPH_POINT_COUNT: usize = 60;
+pub const MORPH_DURATION_SECS: f32 = 0.6;
+
+// Vertex editor settings. Every handle is a quad with u16 indices and the marquee takes four more.
+pub const EDIT_MAX_VERTICES: usize = (u16::MAX as usize + 1) / 4 - 4;
+pub const EDIT_GRID_SIZE: f32 = 0.05;
+// Handle size in physical pixels
+pub const EDIT_HANDLE_PIXELS: f32 = 10.0;
+
+// Undo history is trimmed from the oldest end past this size
+pub const HISTORY_MAX_BYTES: usize = 4 * 1024 * 1024;
+
+// Tints cycled through with C, white keeps the vertex colors. The scene is
+// HDR, so the last one glows past white before tonemapping.
+pub const SHAPE_TINTS: &[[f32; 3]] = &[
+    [1.0, 1.0, 1.0],
+    [1.0, 0.4, 0.4],
+    [0.4, 1.0, 0.4],
+    [0.4, 0.6, 1.0],
+    [1.0, 1.0, 0.3],
+    [4.0, 2.5, 1.0],
+];
+
+// Keyboard transform steps
+pub const MOVE_STEP: f32 = 0.05;
+pub const ROTATE_STEP_DEGREES: f32 = 15.0;
+pub const SCALE_STEP: f32 = 1.1;
+
+// Saved with Ctrl+S and loaded with Ctrl+O, .ron or .json
+pub const SCENE_FILE: &str = "scene.ron";
+
+// Shape outlines toggled with O
+pub const OUTLINE_WIDTH: f32 = 0.015;
+pub const OUTLINE_COLOR: [f32; 3] = [1.0, 1.0, 1.0];
+
+// Written with Ctrl+E, the SVG shows what the camera sees
+pub const EXPORT_SVG_FILE: &str = "scene.svg";
+pub const EXPORT_OBJ_FILE: &str = "scene.obj";
+
+// Equirectangular panorama loaded at startup and with Ctrl+L, drawn as the
+// skybox and lighting PBR surfaces
+pub const ENVIRONMENT_FILE: &str = "environment.hdr";
+
+// Status text in the top left corner, toggled with H. Sizes are in logical pixels.
+pub const HUD_FONT_SIZE: f32 = 15.0;
+pub const HUD_MARGIN: f32 = 10.0;
+pub const HUD_COLOR: [f32; 3] = [1.0, 1.0, 1.0];
+// Weight of the newest frame in the smoothed FPS
+pub const FPS_SMOOTHING: f32 = 0.05;
+
+// Added as a vector text shape with T
+pub const TITLE_TEXT: &str = "wgpu\nplayaround";
+
+// Debug panel in the top right corner, toggled with F1. Sizes are in logical pixels.
+pub const UI_PANEL_WIDTH: f32 = 260.0;
+pub const UI_FONT_SIZE: f32 = 14.0;
+pub const UI_MARGIN: f32 = 10.0;
+
+// Picking and bounds overlay made of debug lines, toggled with B
+pub const DEBUG_GRID_SPACING: f32 = 0.1;
+pub const DEBUG_GRID_COLOR: [f32; 3] = [0.15, 0.15, 0.15];
+pub const DEBUG_BOUNDS_COLOR: [f32; 3] = [1.0, 0.8, 0.0];
+pub const DEBUG_PICK_COLOR: [f32; 3] = [0.0, 1.0, 1.0];
+// Marker and normal sizes in physical pixels
+pub const DEBUG_MARKER_PIXELS: f32 = 8.0;
+pub const DEBUG_NORMAL_PIXELS: f32 = 24.0;
+// Clicks stay marked for this many seconds
+pub const DEBUG_CLICK_LIFETIME: f32 = 1.0;
+
+// Particles spraying from the tips of the active shape, toggled with P.
+// Holding the right mouse button attracts them to the cursor.
+pub const PARTICLE_CAPACITY: usize = 8192;
+pub const PARTICLE_ATTRACTOR_STRENGTH: f32 = 1.5;
+
+// Shadow map sizes offered by the lighting panel
+pub const SHADOW_MAP_SIZES: [u32; 4] = [512, 1024, 2048, 4096];
+
+// Camera controllers cycled with V. Every wheel line zooms by ZOOM_STEP,
+// touchpads scroll in pixels.
+pub const ZOOM_STEP: f32 = 1.1;
+pub const WHEEL_PIXELS_PER_LINE: f32 = 40.0;
+pub const MIN_ZOOM: f32 = 0.05;
+pub const MAX_ZOOM: f32 = 50.0;
+pub const ORBIT_MIN_DISTANCE: f32 = 0.1;
+pub const ORBIT_MAX_DISTANCE: f32 = 50.0;
+// Radians the camera turns per pixel of mouse movement
+pub const LOOK_SENSITIVITY: f32 = 0.005;
+// World units per second
+pub const FLY_SPEED: f32 = 1.5;
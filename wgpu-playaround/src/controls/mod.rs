@@ -0,0 +1,242 @@
+use glam::{Vec2, Vec3};
+use winit::{event::MouseButton, keyboard::KeyCode};
+
+use crate::{
+    consts::{
+        FLY_SPEED, LOOK_SENSITIVITY, MAX_ZOOM, MIN_ZOOM, ORBIT_MAX_DISTANCE, ORBIT_MIN_DISTANCE,
+        ZOOM_STEP,
+    },
+    environment::MAX_PITCH,
+    models::camera::{Camera2d, Camera3d},
+};
+
+/// Turns the camera by mouse movement in pixels, dragging right turns right.
+fn turn(yaw: &mut f32, pitch: &mut f32, pixels: Vec2) {
+    *yaw += pixels.x * LOOK_SENSITIVITY;
+    *pitch = (*pitch - pixels.y * LOOK_SENSITIVITY).clamp(-MAX_PITCH, MAX_PITCH);
+}
+
+/// Circles a perspective camera around `target`. The left button rotates,
+/// the right button pans and the wheel zooms.
+#[derive(Clone, Debug)]
+pub struct OrbitController {
+    pub target: Vec3,
+    /// Direction the camera looks at the target from, as in [`Camera3d`]
+    pub yaw: f32,
+    pub pitch: f32,
+    pub distance: f32,
+    pub fov_y: f32,
+    dragging: Option<MouseButton>,
+    cursor: Option<Vec2>,
+}
+
+impl Default for OrbitController {
+    fn default() -> Self {
+        let camera = Camera3d::default();
+        Self {
+            target: Vec3::ZERO,
+            yaw: camera.yaw,
+            pitch: camera.pitch,
+            distance: camera.eye.length(),
+            fov_y: camera.fov_y,
+            dragging: None,
+            cursor: None,
+        }
+    }
+}
+
+impl OrbitController {
+    pub fn camera(&self) -> Camera3d {
+        let mut camera = Camera3d {
+            yaw: self.yaw,
+            pitch: self.pitch,
+            fov_y: self.fov_y,
+            ..Camera3d::default()
+        };
+        camera.eye = self.target - camera.forward() * self.distance;
+        camera
+    }
+
+    /// Starts and ends drags, returns whether the button is one it uses.
+    pub fn mouse_input(&mut self, button: MouseButton, is_pressed: bool) -> bool {
+        if !matches!(button, MouseButton::Left | MouseButton::Right) {
+            return false;
+        }
+        if is_pressed {
+            self.dragging = Some(button);
+        } else if self.dragging == Some(button) {
+            self.dragging = None;
+        }
+        true
+    }
+
+    /// Follows the cursor in physical pixels, rotating or panning while a
+    /// button is held. The viewport height sets how far panning goes.
+    pub fn cursor_moved(&mut self, position: Vec2, viewport_height: f32) {
+        let Some(last) = self.cursor.replace(position) else {
+            return;
+        };
+        match self.dragging {
+            Some(MouseButton::Left) => self.rotate(position - last),
+            Some(MouseButton::Right) => self.pan(position - last, viewport_height),
+            _ => {}
+        }
+    }
+
+    pub fn cursor_left(&mut self) {
+        self.cursor = None;
+    }
+
+    pub fn rotate(&mut self, pixels: Vec2) {
+        turn(&mut self.yaw, &mut self.pitch, pixels);
+    }
+
+    /// Moves the target so that points at its depth follow the cursor.
+    pub fn pan(&mut self, pixels: Vec2, viewport_height: f32) {
+        let camera = self.camera();
+        let world_per_pixel =
+            2.0 * self.distance * (self.fov_y / 2.0).tan() / viewport_height.max(1.0);
+        self.target += (camera.up() * pixels.y - camera.right() * pixels.x) * world_per_pixel;
+    }
+
+    /// Moves closer to the target for positive wheel lines.
+    pub fn zoom(&mut self, lines: f32) {
+        self.distance =
+            (self.distance * ZOOM_STEP.powf(-lines)).clamp(ORBIT_MIN_DISTANCE, ORBIT_MAX_DISTANCE);
+    }
+}
+
+/// Flies a perspective camera with WASD and looks around with the mouse
+/// while the cursor is grabbed.
+#[derive(Clone, Debug)]
+pub struct FlyController {
+    pub position: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub fov_y: f32,
+    /// World units per second
+    pub speed: f32,
+    /// Held keys, forward, back, left and right
+    held: [bool; 4],
+    grabbed: bool,
+}
+
+impl Default for FlyController {
+    fn default() -> Self {
+        let camera = Camera3d::default();
+        Self {
+            position: camera.eye,
+            yaw: camera.yaw,
+            pitch: camera.pitch,
+            fov_y: camera.fov_y,
+            speed: FLY_SPEED,
+            held: [false; 4],
+            grabbed: false,
+        }
+    }
+}
+
+impl FlyController {
+    pub fn camera(&self) -> Camera3d {
+        Camera3d {
+            eye: self.position,
+            yaw: self.yaw,
+            pitch: self.pitch,
+            fov_y: self.fov_y,
+            ..Camera3d::default()
+        }
+    }
+
+    /// Tracks the movement keys, returns whether `code` is one of them.
+    pub fn key(&mut self, code: KeyCode, is_pressed: bool) -> bool {
+        let slot = match code {
+            KeyCode::KeyW => 0,
+            KeyCode::KeyS => 1,
+            KeyCode::KeyA => 2,
+            KeyCode::KeyD => 3,
+            _ => return false,
+        };
+        self.held[slot] = is_pressed;
+        true
+    }
+
+    /// Stops moving, for when the window no longer gets key releases.
+    pub fn release_keys(&mut self) {
+        self.held = [false; 4];
+    }
+
+    pub fn is_grabbed(&self) -> bool {
+        self.grabbed
+    }
+
+    /// Whether the window holds on to the cursor, mouse look only works then.
+    pub fn set_grabbed(&mut self, grabbed: bool) {
+        self.grabbed = grabbed;
+    }
+
+    /// Turns by raw mouse movement in pixels.
+    pub fn look(&mut self, pixels: Vec2) {
+        if self.grabbed {
+            turn(&mut self.yaw, &mut self.pitch, pixels);
+        }
+    }
+
+    /// Moves along the view direction for the held keys.
+    pub fn update(&mut self, dt: f32) {
+        let axis = |positive, negative| match (positive, negative) {
+            (true, false) => 1.0,
+            (false, true) => -1.0,
+            _ => 0.0,
+        };
+        let [forward, back, left, right] = self.held;
+        let camera = self.camera();
+        let direction = camera.forward() * axis(forward, back) + camera.right() * axis(right, left);
+        self.position += direction.normalize_or_zero() * self.speed * dt;
+    }
+}
+
+/// Pans a [`Camera2d`] with the middle button and zooms it towards the
+/// cursor with the wheel. Cursor positions are in NDC.
+#[derive(Clone, Debug, Default)]
+pub struct PanZoomController {
+    dragging: bool,
+    cursor: Option<Vec2>,
+}
+
+impl PanZoomController {
+    /// Starts and ends drags, returns whether the button is the one it uses.
+    pub fn mouse_input(&mut self, button: MouseButton, is_pressed: bool) -> bool {
+        if button != MouseButton::Middle {
+            return false;
+        }
+        self.dragging = is_pressed;
+        true
+    }
+
+    /// Drags the world along with the cursor, returns whether the camera moved.
+    pub fn cursor_moved(&mut self, camera: &mut Camera2d, ndc: Vec2) -> bool {
+        let last = self.cursor.replace(ndc);
+        match last {
+            Some(last) if self.dragging => {
+                camera.center -= (ndc - last) / camera.zoom;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn cursor_left(&mut self) {
+        self.cursor = None;
+    }
+
+    /// Zooms in for positive wheel lines. The world point under the cursor
+    /// stays put, without a cursor the center does.
+    pub fn zoom(&self, camera: &mut Camera2d, lines: f32) {
+        let zoom = (camera.zoom * ZOOM_STEP.powf(lines)).clamp(MIN_ZOOM, MAX_ZOOM);
+        if let Some(ndc) = self.cursor {
+            let world = camera.center + ndc / camera.zoom;
+            camera.center = world - ndc / zoom;
+        }
+        camera.zoom = zoom;
+    }
+}
@@ -0,0 +1,197 @@
+pub mod renderer;
+
+use std::{cell::RefCell, f32::consts::TAU};
+
+use glam::Vec2;
+
+use crate::models::vertex::Vertex;
+
+const CIRCLE_SEGMENTS: usize = 32;
+// Arrow heads take this much of the arrow, at this angle to the shaft
+const ARROW_HEAD_FRACTION: f32 = 0.2;
+const ARROW_HEAD_DEGREES: f32 = 25.0;
+/// Grids with more lines than this along an axis are not drawn.
+pub const MAX_GRID_LINES: usize = 512;
+
+/// Color and lifetime of a debug primitive. Colors alone convert into a
+/// style that lasts for the current frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DebugStyle {
+    pub color: [f32; 3],
+    /// Seconds to keep drawing, zero for the current frame only
+    pub lifetime: f32,
+}
+
+impl Default for DebugStyle {
+    fn default() -> Self {
+        Self {
+            color: [1.0, 1.0, 1.0],
+            lifetime: 0.0,
+        }
+    }
+}
+
+impl From<[f32; 3]> for DebugStyle {
+    fn from(color: [f32; 3]) -> Self {
+        Self {
+            color,
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct DebugLine {
+    start: Vec2,
+    end: Vec2,
+    color: [f32; 3],
+    remaining: f32,
+}
+
+/// World space line primitives collected during a frame.
+///
+/// Every shape is broken into line segments right away. `advance` ages them
+/// at the start of a frame and drops the ones whose lifetime is over, so a
+/// primitive with no lifetime is drawn exactly once.
+#[derive(Clone, Debug, Default)]
+pub struct DebugDraw {
+    lines: Vec<DebugLine>,
+}
+
+impl DebugDraw {
+    pub fn line(&mut self, start: Vec2, end: Vec2, style: impl Into<DebugStyle>) {
+        let style = style.into();
+        self.lines.push(DebugLine {
+            start,
+            end,
+            color: style.color,
+            remaining: style.lifetime,
+        });
+    }
+
+    /// Line from `start` to `end` with a head at `end`.
+    pub fn arrow(&mut self, start: Vec2, end: Vec2, style: impl Into<DebugStyle>) {
+        let style = style.into();
+        self.line(start, end, style);
+        let back = (start - end) * ARROW_HEAD_FRACTION;
+        if back == Vec2::ZERO {
+            return;
+        }
+        for angle in [ARROW_HEAD_DEGREES, -ARROW_HEAD_DEGREES] {
+            let side = Vec2::from_angle(angle.to_radians()).rotate(back);
+            self.line(end, end + side, style);
+        }
+    }
+
+    /// Axis aligned box between its lower left and upper right corners.
+    pub fn aabb(&mut self, min: Vec2, max: Vec2, style: impl Into<DebugStyle>) {
+        let style = style.into();
+        let corners = [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)];
+        for (i, &corner) in corners.iter().enumerate() {
+            self.line(corner, corners[(i + 1) % 4], style);
+        }
+    }
+
+    pub fn circle(&mut self, center: Vec2, radius: f32, style: impl Into<DebugStyle>) {
+        let style = style.into();
+        let point =
+            |i: usize| center + Vec2::from_angle(i as f32 / CIRCLE_SEGMENTS as f32 * TAU) * radius;
+        for i in 0..CIRCLE_SEGMENTS {
+            self.line(point(i), point(i + 1), style);
+        }
+    }
+
+    /// Axis aligned plus sign reaching `half_size` from `center` in every direction.
+    pub fn cross(&mut self, center: Vec2, half_size: f32, style: impl Into<DebugStyle>) {
+        let style = style.into();
+        let (x, y) = (Vec2::X * half_size, Vec2::Y * half_size);
+        self.line(center - x, center + x, style);
+        self.line(center - y, center + y, style);
+    }
+
+    /// Lines at every multiple of `spacing` between `min` and `max`, the
+    /// world origin always being on a line. Nothing is drawn when an axis
+    /// would need more than `MAX_GRID_LINES` lines.
+    pub fn grid(&mut self, min: Vec2, max: Vec2, spacing: f32, style: impl Into<DebugStyle>) {
+        let style = style.into();
+        if spacing <= 0.0 || !min.cmple(max).all() {
+            return;
+        }
+        let first = (min / spacing).ceil();
+        let last = (max / spacing).floor();
+        let counts = (last - first + 1.0).max(Vec2::ZERO);
+        if counts.max_element() > MAX_GRID_LINES as f32 {
+            return;
+        }
+        for i in 0..counts.x as usize {
+            let x = (first.x + i as f32) * spacing;
+            self.line(Vec2::new(x, min.y), Vec2::new(x, max.y), style);
+        }
+        for i in 0..counts.y as usize {
+            let y = (first.y + i as f32) * spacing;
+            self.line(Vec2::new(min.x, y), Vec2::new(max.x, y), style);
+        }
+    }
+
+    /// Ages every primitive by `dt` seconds and drops the ones that are done.
+    pub fn advance(&mut self, dt: f32) {
+        self.lines.retain_mut(|line| {
+            line.remaining -= dt;
+            line.remaining > 0.0
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.lines.clear();
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.lines.len()
+    }
+
+    /// Two vertices per line, ready for a line list.
+    pub fn vertices(&self) -> Vec<Vertex> {
+        self.lines
+            .iter()
+            .flat_map(|line| {
+                [line.start, line.end].map(|p| Vertex {
+                    position: [p.x, p.y, 0.0],
+                    color: line.color,
+                })
+            })
+            .collect()
+    }
+}
+
+thread_local! {
+    static DEBUG_DRAW: RefCell<DebugDraw> = RefCell::new(DebugDraw::default());
+}
+
+/// Runs `f` on the primitives collected on this thread, which the window draws every frame.
+pub fn with<R>(f: impl FnOnce(&mut DebugDraw) -> R) -> R {
+    DEBUG_DRAW.with_borrow_mut(f)
+}
+
+pub fn line(start: Vec2, end: Vec2, style: impl Into<DebugStyle>) {
+    with(|draw| draw.line(start, end, style));
+}
+
+pub fn arrow(start: Vec2, end: Vec2, style: impl Into<DebugStyle>) {
+    with(|draw| draw.arrow(start, end, style));
+}
+
+pub fn aabb(min: Vec2, max: Vec2, style: impl Into<DebugStyle>) {
+    with(|draw| draw.aabb(min, max, style));
+}
+
+pub fn circle(center: Vec2, radius: f32, style: impl Into<DebugStyle>) {
+    with(|draw| draw.circle(center, radius, style));
+}
+
+pub fn cross(center: Vec2, half_size: f32, style: impl Into<DebugStyle>) {
+    with(|draw| draw.cross(center, half_size, style));
+}
+
+pub fn grid(min: Vec2, max: Vec2, spacing: f32, style: impl Into<DebugStyle>) {
+    with(|draw| draw.grid(min, max, spacing, style));
+}
@@ -0,0 +1,180 @@
+use glam::Vec2;
+
+use crate::{
+    document::{Document, ShapeGeometry, ShapeNode, Transform2d},
+    history::Command,
+};
+
+/// Every change to the document goes through one of these, so it can be undone.
+#[derive(Clone, Debug, PartialEq)]
+pub enum EditCommand {
+    /// Moves outline vertices of a shape, in shape-local space
+    MoveVertices {
+        shape: usize,
+        vertices: Vec<usize>,
+        from: Vec<Vec2>,
+        to: Vec<Vec2>,
+    },
+    SetGeometry {
+        shape: usize,
+        before: ShapeGeometry,
+        after: ShapeGeometry,
+    },
+    SetColor {
+        shape: usize,
+        before: [f32; 3],
+        after: [f32; 3],
+    },
+    SetTransform {
+        shape: usize,
+        before: Transform2d,
+        after: Transform2d,
+    },
+    AddShape {
+        index: usize,
+        shape: ShapeNode,
+    },
+    RemoveShape {
+        index: usize,
+        shape: ShapeNode,
+    },
+    /// Several commands undone and redone together, applied in order
+    Batch(Vec<EditCommand>),
+}
+
+fn move_vertices(document: &mut Document, shape: usize, vertices: &[usize], positions: &[Vec2]) {
+    if let ShapeGeometry::Outline(points) = &mut document.shapes[shape].geometry {
+        for (&i, p) in vertices.iter().zip(positions) {
+            points[i].position[0] = p.x;
+            points[i].position[1] = p.y;
+        }
+    }
+}
+
+impl Command<Document> for EditCommand {
+    fn apply(&self, document: &mut Document) {
+        match self {
+            EditCommand::MoveVertices {
+                shape,
+                vertices,
+                to,
+                ..
+            } => move_vertices(document, *shape, vertices, to),
+            EditCommand::SetGeometry { shape, after, .. } => {
+                document.shapes[*shape].geometry = after.clone();
+            }
+            EditCommand::SetColor { shape, after, .. } => document.shapes[*shape].color = *after,
+            EditCommand::SetTransform { shape, after, .. } => {
+                document.shapes[*shape].transform = *after;
+            }
+            EditCommand::AddShape { index, shape } => document.shapes.insert(*index, shape.clone()),
+            EditCommand::RemoveShape { index, .. } => {
+                document.shapes.remove(*index);
+            }
+            EditCommand::Batch(commands) => {
+                for command in commands {
+                    command.apply(document);
+                }
+            }
+        }
+    }
+
+    fn revert(&self, document: &mut Document) {
+        match self {
+            EditCommand::MoveVertices {
+                shape,
+                vertices,
+                from,
+                ..
+            } => move_vertices(document, *shape, vertices, from),
+            EditCommand::SetGeometry { shape, before, .. } => {
+                document.shapes[*shape].geometry = before.clone();
+            }
+            EditCommand::SetColor { shape, before, .. } => document.shapes[*shape].color = *before,
+            EditCommand::SetTransform { shape, before, .. } => {
+                document.shapes[*shape].transform = *before;
+            }
+            EditCommand::AddShape { index, .. } => {
+                document.shapes.remove(*index);
+            }
+            EditCommand::RemoveShape { index, shape } => {
+                document.shapes.insert(*index, shape.clone())
+            }
+            EditCommand::Batch(commands) => {
+                for command in commands.iter().rev() {
+                    command.revert(document);
+                }
+            }
+        }
+    }
+
+    /// Continuous drags collapse into a single move or transform change.
+    fn merge(&mut self, next: &Self) -> bool {
+        match (self, next) {
+            (
+                EditCommand::MoveVertices {
+                    shape,
+                    vertices,
+                    to,
+                    ..
+                },
+                EditCommand::MoveVertices {
+                    shape: next_shape,
+                    vertices: next_vertices,
+                    to: next_to,
+                    ..
+                },
+            ) if shape == next_shape && vertices == next_vertices => {
+                to.clone_from(next_to);
+                true
+            }
+            (
+                EditCommand::SetTransform { shape, after, .. },
+                EditCommand::SetTransform {
+                    shape: next_shape,
+                    after: next_after,
+                    ..
+                },
+            ) if shape == next_shape => {
+                *after = *next_after;
+                true
+            }
+            (
+                EditCommand::SetColor { shape, after, .. },
+                EditCommand::SetColor {
+                    shape: next_shape,
+                    after: next_after,
+                    ..
+                },
+            ) if shape == next_shape => {
+                *after = *next_after;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn size_bytes(&self) -> usize {
+        let heap = match self {
+            EditCommand::MoveVertices {
+                vertices, from, to, ..
+            } => {
+                std::mem::size_of_val(vertices.as_slice())
+                    + std::mem::size_of_val(from.as_slice())
+                    + std::mem::size_of_val(to.as_slice())
+            }
+            EditCommand::SetGeometry { before, after, .. } => {
+                before.heap_size() + after.heap_size()
+            }
+            EditCommand::AddShape { shape, .. } | EditCommand::RemoveShape { shape, .. } => {
+                shape.geometry.heap_size()
+            }
+            EditCommand::SetColor { .. } | EditCommand::SetTransform { .. } => 0,
+            EditCommand::Batch(commands) => {
+                return commands.iter().map(Command::size_bytes).sum::<usize>()
+                    + std::mem::size_of::<Self>();
+            }
+        };
+        std::mem::size_of::<Self>() + heap
+    }
+}
@@ -0,0 +1,404 @@
+use std::path::Path;
+
+use anyhow::{Context, bail, ensure};
+use glam::Vec2;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+use crate::{
+    document::{Document, ShapeGeometry, ShapeNode, Transform2d},
+    enums::{PipelineKind, ShapeType},
+    models::{camera::Camera2d, vertex::Vertex},
+};
+
+/// Schema version written into new scene files.
+///
+/// To change the format, copy the current schema into a new module, bump the
+/// version and teach [`SceneDescription::parse`] to upgrade the old module
+/// into the new one. Old files keep loading that way.
+pub const SCENE_FILE_VERSION: u32 = 1;
+
+/// Longest outline a file may hold, fills are indexed with u16.
+pub const MAX_OUTLINE_POINTS: usize = u16::MAX as usize;
+
+/// Text format of a scene file.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SceneFormat {
+    Ron,
+    Json,
+}
+
+impl SceneFormat {
+    /// Picks the format from the file extension, `.ron` or `.json`.
+    pub fn from_path(path: &Path) -> anyhow::Result<Self> {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("ron") => Ok(SceneFormat::Ron),
+            Some("json") => Ok(SceneFormat::Json),
+            _ => bail!("{} is not a .ron or .json file", path.display()),
+        }
+    }
+
+    fn serialize<T: Serialize>(self, value: &T) -> anyhow::Result<String> {
+        Ok(match self {
+            SceneFormat::Ron => {
+                ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())?
+            }
+            SceneFormat::Json => serde_json::to_string_pretty(value)?,
+        })
+    }
+
+    fn deserialize<T: DeserializeOwned>(self, text: &str) -> anyhow::Result<T> {
+        Ok(match self {
+            SceneFormat::Ron => ron::from_str(text)?,
+            SceneFormat::Json => serde_json::from_str(text)?,
+        })
+    }
+}
+
+/// Everything a scene file stores: the shapes and how they are looked at.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SceneDescription {
+    pub document: Document,
+    pub pipeline: PipelineKind,
+    pub clear_color: wgpu::Color,
+    pub camera: Camera2d,
+}
+
+impl Default for SceneDescription {
+    fn default() -> Self {
+        Self {
+            document: Document {
+                shapes: vec![ShapeNode::new(ShapeGeometry::Preset(ShapeType::Pentagon))],
+            },
+            pipeline: PipelineKind::Solid,
+            clear_color: wgpu::Color {
+                r: 0.0,
+                g: 0.0,
+                b: 0.3,
+                a: 1.0,
+            },
+            camera: Camera2d::default(),
+        }
+    }
+}
+
+/// Just enough of any version of the schema to know how to read the rest.
+#[derive(Deserialize)]
+struct Header {
+    /// Missing in files from before the schema was versioned
+    #[serde(default)]
+    version: u32,
+}
+
+impl SceneDescription {
+    pub fn serialize(&self, format: SceneFormat) -> anyhow::Result<String> {
+        format.serialize(&v1::SceneFile::from(self))
+    }
+
+    /// Reads a scene written by this or an older version of the schema.
+    pub fn parse(text: &str, format: SceneFormat) -> anyhow::Result<Self> {
+        let header: Header = format
+            .deserialize(text)
+            .context("scene file is not a scene")?;
+        match header.version {
+            0 => v1::SceneFile::from(format.deserialize::<v0::SceneFile>(text)?).try_into(),
+            1 => format.deserialize::<v1::SceneFile>(text)?.try_into(),
+            version if version > SCENE_FILE_VERSION => bail!(
+                "scene file version {version} is newer than the supported version {SCENE_FILE_VERSION}"
+            ),
+            version => bail!("unknown scene file version {version}"),
+        }
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+        let text = self.serialize(SceneFormat::from_path(path)?)?;
+        std::fs::write(path, text).with_context(|| format!("writing {}", path.display()))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let text =
+            std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        Self::parse(&text, SceneFormat::from_path(path)?)
+            .with_context(|| format!("loading {}", path.display()))
+    }
+}
+
+/// Files from before the schema had a version. The layout is the same as
+/// [`v1`] without the `version` field.
+mod v0 {
+    use super::*;
+
+    #[derive(Deserialize)]
+    pub struct SceneFile {
+        pub pipeline: PipelineKind,
+        pub clear_color: [f64; 4],
+        pub camera: v1::Camera,
+        pub shapes: Vec<v1::Shape>,
+    }
+
+    impl From<SceneFile> for v1::SceneFile {
+        fn from(file: SceneFile) -> Self {
+            Self {
+                version: 1,
+                pipeline: file.pipeline,
+                clear_color: file.clear_color,
+                camera: file.camera,
+                shapes: file.shapes,
+            }
+        }
+    }
+}
+
+/// First version of the schema. Kept separate from the in-memory types so
+/// those can change without breaking saved files.
+mod v1 {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    pub struct SceneFile {
+        pub version: u32,
+        pub pipeline: PipelineKind,
+        pub clear_color: [f64; 4],
+        pub camera: Camera,
+        pub shapes: Vec<Shape>,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Camera {
+        pub center: [f32; 2],
+        pub zoom: f32,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Shape {
+        pub geometry: Geometry,
+        pub translation: [f32; 2],
+        /// Radians, counter-clockwise
+        pub rotation: f32,
+        pub scale: [f32; 2],
+        pub color: [f32; 3],
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub enum Geometry {
+        Preset(ShapeType),
+        RegularPolygon {
+            sides: u32,
+            radius: f32,
+        },
+        Star {
+            points: u32,
+            outer_radius: f32,
+            inner_radius: f32,
+        },
+        Outline {
+            points: Vec<Point>,
+        },
+        Mesh {
+            vertices: Vec<Point>,
+            indices: Vec<u16>,
+        },
+    }
+
+    #[derive(Serialize, Deserialize)]
+    pub struct Point {
+        pub position: [f32; 3],
+        pub color: [f32; 3],
+    }
+
+    fn points(vertices: &[Vertex]) -> Vec<Point> {
+        vertices
+            .iter()
+            .map(|v| Point {
+                position: v.position,
+                color: v.color,
+            })
+            .collect()
+    }
+
+    fn ensure_finite(values: &[f32], what: &str) -> anyhow::Result<()> {
+        ensure!(
+            values.iter().all(|v| v.is_finite()),
+            "{what} is not finite: {values:?}"
+        );
+        Ok(())
+    }
+
+    fn vertices(points: Vec<Point>) -> anyhow::Result<Vec<Vertex>> {
+        for point in &points {
+            ensure_finite(&point.position, "point position")?;
+        }
+        Ok(points
+            .into_iter()
+            .map(|p| Vertex {
+                position: p.position,
+                color: p.color,
+            })
+            .collect())
+    }
+
+    impl From<&SceneDescription> for SceneFile {
+        fn from(scene: &SceneDescription) -> Self {
+            let c = scene.clear_color;
+            Self {
+                version: 1,
+                pipeline: scene.pipeline,
+                clear_color: [c.r, c.g, c.b, c.a],
+                camera: Camera {
+                    center: scene.camera.center.to_array(),
+                    zoom: scene.camera.zoom,
+                },
+                shapes: scene
+                    .document
+                    .shapes
+                    .iter()
+                    .map(|node| Shape {
+                        geometry: match &node.geometry {
+                            ShapeGeometry::Preset(shape) => Geometry::Preset(*shape),
+                            ShapeGeometry::RegularPolygon { sides, radius } => {
+                                Geometry::RegularPolygon {
+                                    sides: *sides,
+                                    radius: *radius,
+                                }
+                            }
+                            ShapeGeometry::Star {
+                                points,
+                                outer_radius,
+                                inner_radius,
+                            } => Geometry::Star {
+                                points: *points,
+                                outer_radius: *outer_radius,
+                                inner_radius: *inner_radius,
+                            },
+                            ShapeGeometry::Outline(outline) => Geometry::Outline {
+                                points: points(outline),
+                            },
+                            ShapeGeometry::Mesh { vertices, indices } => Geometry::Mesh {
+                                vertices: points(vertices),
+                                indices: indices.clone(),
+                            },
+                        },
+                        translation: node.transform.translation.to_array(),
+                        rotation: node.transform.rotation,
+                        scale: node.transform.scale.to_array(),
+                        color: node.color,
+                    })
+                    .collect(),
+            }
+        }
+    }
+
+    impl TryFrom<Geometry> for ShapeGeometry {
+        type Error = anyhow::Error;
+
+        fn try_from(geometry: Geometry) -> anyhow::Result<Self> {
+            Ok(match geometry {
+                Geometry::Preset(shape) => ShapeGeometry::Preset(shape),
+                Geometry::RegularPolygon { sides, radius } => {
+                    ensure!(sides >= 3, "a polygon needs at least 3 sides, got {sides}");
+                    ensure!(
+                        sides as usize <= MAX_OUTLINE_POINTS,
+                        "a polygon can have at most {MAX_OUTLINE_POINTS} sides, got {sides}"
+                    );
+                    ensure_finite(&[radius], "polygon radius")?;
+                    ShapeGeometry::RegularPolygon { sides, radius }
+                }
+                Geometry::Star {
+                    points,
+                    outer_radius,
+                    inner_radius,
+                } => {
+                    ensure!(points >= 2, "a star needs at least 2 points, got {points}");
+                    ensure!(
+                        points as usize <= MAX_OUTLINE_POINTS / 2,
+                        "a star can have at most {} points, got {points}",
+                        MAX_OUTLINE_POINTS / 2
+                    );
+                    ensure_finite(&[outer_radius, inner_radius], "star radius")?;
+                    ShapeGeometry::Star {
+                        points,
+                        outer_radius,
+                        inner_radius,
+                    }
+                }
+                Geometry::Outline { points } => {
+                    ensure!(points.len() >= 3, "an outline needs at least 3 points");
+                    ensure!(
+                        points.len() <= MAX_OUTLINE_POINTS,
+                        "an outline can have at most {MAX_OUTLINE_POINTS} points, got {}",
+                        points.len()
+                    );
+                    ShapeGeometry::Outline(vertices(points)?)
+                }
+                Geometry::Mesh {
+                    vertices: points,
+                    indices,
+                } => {
+                    ensure!(
+                        indices.len() % 3 == 0,
+                        "mesh index count {} is not a multiple of 3",
+                        indices.len()
+                    );
+                    if let Some(&index) = indices.iter().find(|&&i| i as usize >= points.len()) {
+                        bail!(
+                            "mesh index {index} is out of range for {} vertices",
+                            points.len()
+                        );
+                    }
+                    ShapeGeometry::Mesh {
+                        vertices: vertices(points)?,
+                        indices,
+                    }
+                }
+            })
+        }
+    }
+
+    impl TryFrom<SceneFile> for SceneDescription {
+        type Error = anyhow::Error;
+
+        fn try_from(file: SceneFile) -> anyhow::Result<Self> {
+            let shapes = file
+                .shapes
+                .into_iter()
+                .enumerate()
+                .map(|(i, shape)| {
+                    ensure_finite(&shape.translation, "translation")
+                        .and_then(|()| ensure_finite(&[shape.rotation], "rotation"))
+                        .and_then(|()| ensure_finite(&shape.scale, "scale"))
+                        .with_context(|| format!("shape {i}"))?;
+                    Ok(ShapeNode {
+                        geometry: shape
+                            .geometry
+                            .try_into()
+                            .with_context(|| format!("shape {i}"))?,
+                        transform: Transform2d {
+                            translation: Vec2::from_array(shape.translation),
+                            rotation: shape.rotation,
+                            scale: Vec2::from_array(shape.scale),
+                        },
+                        color: shape.color,
+                    })
+                })
+                .collect::<anyhow::Result<_>>()?;
+            let [r, g, b, a] = file.clear_color;
+            ensure_finite(&file.camera.center, "camera center")?;
+            let zoom = file.camera.zoom;
+            ensure!(
+                zoom.is_finite() && zoom > 0.0,
+                "camera zoom must be positive, got {zoom}"
+            );
+
+            Ok(Self {
+                document: Document { shapes },
+                pipeline: file.pipeline,
+                clear_color: wgpu::Color { r, g, b, a },
+                camera: Camera2d {
+                    center: Vec2::from_array(file.camera.center),
+                    zoom,
+                },
+            })
+        }
+    }
+}
@@ -0,0 +1,184 @@
+pub mod commands;
+pub mod file;
+
+use glam::{Mat4, Quat, Vec2, Vec3};
+
+use crate::{
+    enums::ShapeType,
+    geometry::{
+        outline::Outline,
+        procedural::{regular_polygon, star},
+        triangulate::triangulate_polygon,
+    },
+    models::vertex::Vertex,
+};
+
+/// Position, rotation (radians, counter-clockwise) and scale of a shape.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform2d {
+    pub translation: Vec2,
+    pub rotation: f32,
+    pub scale: Vec2,
+}
+
+impl Default for Transform2d {
+    fn default() -> Self {
+        Self {
+            translation: Vec2::ZERO,
+            rotation: 0.0,
+            scale: Vec2::ONE,
+        }
+    }
+}
+
+impl Transform2d {
+    pub fn to_mat4(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(
+            self.scale.extend(1.0),
+            Quat::from_rotation_z(self.rotation),
+            self.translation.extend(0.0),
+        )
+    }
+
+    pub fn apply(&self, p: Vec2) -> Vec2 {
+        self.to_mat4().transform_point3(p.extend(0.0)).truncate()
+    }
+
+    pub fn apply_inverse(&self, p: Vec2) -> Vec2 {
+        self.to_mat4()
+            .inverse()
+            .transform_point3(Vec3::new(p.x, p.y, 0.0))
+            .truncate()
+    }
+}
+
+/// Where the triangles of a shape come from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShapeGeometry {
+    /// One of the built-in shapes from `consts.rs`
+    Preset(ShapeType),
+    /// Regular polygon generated from its parameters
+    RegularPolygon { sides: u32, radius: f32 },
+    /// Star generated from its parameters
+    Star {
+        points: u32,
+        outer_radius: f32,
+        inner_radius: f32,
+    },
+    /// A closed outline, counter-clockwise, triangulated on demand
+    Outline(Vec<Vertex>),
+    /// Triangles used as they are
+    Mesh {
+        vertices: Vec<Vertex>,
+        indices: Vec<u16>,
+    },
+}
+
+impl ShapeGeometry {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ShapeGeometry::Preset(shape) => shape.name(),
+            ShapeGeometry::RegularPolygon { .. } => "Polygon",
+            ShapeGeometry::Star { .. } => "Star",
+            ShapeGeometry::Outline(_) => "Custom",
+            ShapeGeometry::Mesh { .. } => "Mesh",
+        }
+    }
+
+    pub fn preset(&self) -> Option<ShapeType> {
+        match self {
+            ShapeGeometry::Preset(shape) => Some(*shape),
+            _ => None,
+        }
+    }
+
+    pub fn outline(&self) -> Outline {
+        match self {
+            ShapeGeometry::Preset(shape) => shape.outline(),
+            ShapeGeometry::RegularPolygon { sides, radius } => regular_polygon(*sides, *radius),
+            ShapeGeometry::Star {
+                points,
+                outer_radius,
+                inner_radius,
+            } => star(*points, *outer_radius, *inner_radius),
+            ShapeGeometry::Outline(points) => Outline {
+                points: points.clone(),
+            },
+            ShapeGeometry::Mesh { vertices, indices } => {
+                Outline::from_mesh_boundary(vertices, indices)
+            }
+        }
+    }
+
+    /// Vertices and indices in shape-local space. Fails for outlines that
+    /// cross themselves.
+    pub fn mesh(&self) -> anyhow::Result<(Vec<Vertex>, Vec<u16>)> {
+        Ok(match self {
+            ShapeGeometry::Preset(shape) => (shape.vertices().to_vec(), shape.indices().to_vec()),
+            ShapeGeometry::Mesh { vertices, indices } => (vertices.clone(), indices.clone()),
+            _ => {
+                let outline = self.outline();
+                let indices = triangulate_polygon(&outline.positions_2d())?;
+                (outline.points, indices)
+            }
+        })
+    }
+
+    /// Rough heap usage, for capping the undo history.
+    pub fn heap_size(&self) -> usize {
+        match self {
+            ShapeGeometry::Preset(_)
+            | ShapeGeometry::RegularPolygon { .. }
+            | ShapeGeometry::Star { .. } => 0,
+            ShapeGeometry::Outline(points) => std::mem::size_of_val(points.as_slice()),
+            ShapeGeometry::Mesh { vertices, indices } => {
+                std::mem::size_of_val(vertices.as_slice())
+                    + std::mem::size_of_val(indices.as_slice())
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShapeNode {
+    pub geometry: ShapeGeometry,
+    pub transform: Transform2d,
+    /// Multiplied with the vertex colors, white keeps them as they are
+    pub color: [f32; 3],
+}
+
+impl ShapeNode {
+    pub fn new(geometry: ShapeGeometry) -> Self {
+        Self {
+            geometry,
+            transform: Transform2d::default(),
+            color: [1.0, 1.0, 1.0],
+        }
+    }
+
+    /// Transforms and tints a mesh given in shape-local space.
+    pub fn to_world(&self, vertices: &mut [Vertex]) {
+        let matrix = self.transform.to_mat4();
+        for vertex in vertices {
+            vertex.position = matrix
+                .transform_point3(Vec3::from_array(vertex.position))
+                .to_array();
+            for (c, tint) in vertex.color.iter_mut().zip(self.color) {
+                *c *= tint;
+            }
+        }
+    }
+
+    /// Vertices and indices with the transform and color applied.
+    pub fn world_mesh(&self) -> anyhow::Result<(Vec<Vertex>, Vec<u16>)> {
+        let (mut vertices, indices) = self.geometry.mesh()?;
+        self.to_world(&mut vertices);
+        Ok((vertices, indices))
+    }
+}
+
+/// The shapes in the scene, in draw order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Document {
+    pub shapes: Vec<ShapeNode>,
+}
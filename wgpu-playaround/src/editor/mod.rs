@@ -0,0 +1,300 @@
+use std::collections::BTreeSet;
+
+use glam::Vec2;
+
+use crate::{consts::EDIT_MAX_VERTICES, geometry::outline::lerp_vertex, models::vertex::Vertex};
+
+const HANDLE_COLOR: [f32; 3] = [1.0, 1.0, 1.0];
+const SELECTED_HANDLE_COLOR: [f32; 3] = [1.0, 0.9, 0.0];
+const MARQUEE_COLOR: [f32; 3] = [0.3, 0.8, 1.0];
+
+struct Drag {
+    start: Vec2,
+    // Selected vertices and where they were when the drag started
+    originals: Vec<(usize, Vec2)>,
+}
+
+enum Gesture {
+    Idle,
+    Drag(Drag),
+    Marquee { start: Vec2, end: Vec2 },
+}
+
+/// A change the editor wants to make to the outline it is editing.
+#[derive(Clone, Debug, PartialEq)]
+pub enum EditorAction {
+    /// Move vertices from their drag start positions to new ones
+    Move {
+        vertices: Vec<usize>,
+        from: Vec<Vec2>,
+        to: Vec<Vec2>,
+    },
+    /// Replace the whole outline, after inserting or deleting vertices
+    Replace(Vec<Vertex>),
+}
+
+/// Edits the outline of a 2D shape with the mouse.
+///
+/// The editor only keeps the selection and the gesture in progress. The
+/// outline is passed in on every call and changes come back as
+/// [`EditorAction`]s, so they can go through the undo history. All positions
+/// are in world space. `pick_radius` is how close the pointer has to be to a
+/// vertex or edge, and also the size of the drawn handles.
+pub struct VertexEditor {
+    selected: BTreeSet<usize>,
+    gesture: Gesture,
+    /// Grid spacing to snap dragged vertices to, `None` to move freely
+    pub grid: Option<f32>,
+    pub pick_radius: f32,
+    // Set whenever the overlay needs rebuilding
+    dirty: bool,
+}
+
+fn distance_to_segment(p: Vec2, a: Vec2, b: Vec2) -> (f32, f32) {
+    let ab = b - a;
+    let t = if ab.length_squared() > 0.0 {
+        ((p - a).dot(ab) / ab.length_squared()).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (p.distance(a + ab * t), t)
+}
+
+fn position(points: &[Vertex], index: usize) -> Vec2 {
+    let p = points[index].position;
+    Vec2::new(p[0], p[1])
+}
+
+impl Default for VertexEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl VertexEditor {
+    pub fn new() -> Self {
+        Self {
+            selected: BTreeSet::new(),
+            gesture: Gesture::Idle,
+            grid: None,
+            pick_radius: 0.02,
+            dirty: true,
+        }
+    }
+
+    pub fn selected(&self) -> &BTreeSet<usize> {
+        &self.selected
+    }
+
+    /// Drops selected vertices that no longer exist, e.g. after an undo.
+    pub fn clamp_selection(&mut self, len: usize) {
+        self.selected.retain(|&i| i < len);
+        self.gesture = Gesture::Idle;
+        self.dirty = true;
+    }
+
+    fn snap(&self, p: Vec2) -> Vec2 {
+        match self.grid {
+            Some(grid) if grid > 0.0 => (p / grid).round() * grid,
+            _ => p,
+        }
+    }
+
+    fn vertex_at(&self, points: &[Vertex], p: Vec2) -> Option<usize> {
+        (0..points.len())
+            .map(|i| (i, position(points, i).distance(p)))
+            .filter(|&(_, d)| d <= self.pick_radius)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+
+    /// Closest edge within the pick radius, with the parameter of the closest point on it.
+    fn edge_at(&self, points: &[Vertex], p: Vec2) -> Option<(usize, f32)> {
+        let n = points.len();
+        (0..n)
+            .map(|i| {
+                let a = position(points, i);
+                let b = position(points, (i + 1) % n);
+                let (d, t) = distance_to_segment(p, a, b);
+                (i, d, t)
+            })
+            .filter(|&(_, d, _)| d <= self.pick_radius)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _, t)| (i, t))
+    }
+
+    fn start_drag(&mut self, points: &[Vertex], start: Vec2) {
+        let originals = self
+            .selected
+            .iter()
+            .map(|&i| (i, position(points, i)))
+            .collect();
+        self.gesture = Gesture::Drag(Drag { start, originals });
+    }
+
+    /// Left button pressed at `p`.
+    ///
+    /// Grabs the vertex under the pointer, or inserts a new vertex when the
+    /// pointer is on an edge, or starts a marquee selection otherwise. With
+    /// `additive` the selection is extended instead of replaced.
+    pub fn pointer_down(
+        &mut self,
+        points: &[Vertex],
+        p: Vec2,
+        additive: bool,
+    ) -> Option<EditorAction> {
+        self.dirty = true;
+
+        if let Some(index) = self.vertex_at(points, p) {
+            if additive {
+                if !self.selected.remove(&index) {
+                    self.selected.insert(index);
+                }
+            } else if !self.selected.contains(&index) {
+                self.selected = BTreeSet::from([index]);
+            }
+            self.start_drag(points, p);
+            None
+        } else if let Some((edge, t)) = self.edge_at(points, p) {
+            let (index, outline) = self.insert_vertex(points, edge, t)?;
+            self.selected = BTreeSet::from([index]);
+            self.start_drag(&outline, p);
+            Some(EditorAction::Replace(outline))
+        } else {
+            if !additive {
+                self.selected.clear();
+            }
+            self.gesture = Gesture::Marquee { start: p, end: p };
+            None
+        }
+    }
+
+    pub fn pointer_moved(&mut self, p: Vec2) -> Option<EditorAction> {
+        let grid = self.grid;
+        match &mut self.gesture {
+            Gesture::Idle => None,
+            Gesture::Drag(drag) => {
+                let delta = p - drag.start;
+                let (vertices, from): (Vec<usize>, Vec<Vec2>) =
+                    drag.originals.iter().copied().unzip();
+                let to = from
+                    .iter()
+                    .map(|&original| match grid {
+                        Some(grid) if grid > 0.0 => ((original + delta) / grid).round() * grid,
+                        _ => original + delta,
+                    })
+                    .collect();
+                self.dirty = true;
+                Some(EditorAction::Move { vertices, from, to })
+            }
+            Gesture::Marquee { end, .. } => {
+                *end = p;
+                self.dirty = true;
+                None
+            }
+        }
+    }
+
+    pub fn pointer_up(&mut self, points: &[Vertex]) {
+        if let Gesture::Marquee { start, end } = self.gesture {
+            let min = start.min(end);
+            let max = start.max(end);
+            for i in 0..points.len() {
+                let p = position(points, i);
+                if p.cmpge(min).all() && p.cmple(max).all() {
+                    self.selected.insert(i);
+                }
+            }
+            self.dirty = true;
+        }
+        self.gesture = Gesture::Idle;
+    }
+
+    /// Splits edge `edge` at parameter `t`. Returns the new vertex index and
+    /// outline, or `None` when the overlay has no room for another handle.
+    fn insert_vertex(
+        &self,
+        points: &[Vertex],
+        edge: usize,
+        t: f32,
+    ) -> Option<(usize, Vec<Vertex>)> {
+        let n = points.len();
+        if n >= EDIT_MAX_VERTICES {
+            return None;
+        }
+        let mut vertex = lerp_vertex(&points[edge], &points[(edge + 1) % n], t);
+        let snapped = self.snap(Vec2::new(vertex.position[0], vertex.position[1]));
+        vertex.position[0] = snapped.x;
+        vertex.position[1] = snapped.y;
+
+        let index = edge + 1;
+        let mut outline = points.to_vec();
+        outline.insert(index, vertex);
+        Some((index, outline))
+    }
+
+    /// Removes the selected vertices, always keeping at least a triangle.
+    pub fn delete_selected(&mut self, points: &[Vertex]) -> Option<EditorAction> {
+        if self.selected.is_empty() || points.len() - self.selected.len() < 3 {
+            return None;
+        }
+        let outline = points
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !self.selected.contains(i))
+            .map(|(_, v)| *v)
+            .collect();
+        self.selected.clear();
+        self.gesture = Gesture::Idle;
+        self.dirty = true;
+        Some(EditorAction::Replace(outline))
+    }
+
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Returns true once after every change, so buffers are only rewritten when needed.
+    pub fn take_dirty(&mut self) -> bool {
+        std::mem::take(&mut self.dirty)
+    }
+
+    /// Square handles on every vertex, plus the marquee frame while selecting.
+    pub fn overlay_mesh(&self, points: &[Vertex]) -> (Vec<Vertex>, Vec<u16>) {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        let mut quad = |min: Vec2, max: Vec2, color: [f32; 3]| {
+            let base = vertices.len() as u16;
+            for p in [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)] {
+                vertices.push(Vertex {
+                    position: [p.x, p.y, 0.0],
+                    color,
+                });
+            }
+            indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+        };
+
+        let half = Vec2::splat(self.pick_radius * 0.5);
+        for i in 0..points.len() {
+            let p = position(points, i);
+            let color = if self.selected.contains(&i) {
+                SELECTED_HANDLE_COLOR
+            } else {
+                HANDLE_COLOR
+            };
+            quad(p - half, p + half, color);
+        }
+
+        if let Gesture::Marquee { start, end } = self.gesture {
+            let min = start.min(end);
+            let max = start.max(end);
+            let w = self.pick_radius * 0.15;
+            quad(min, Vec2::new(max.x, min.y + w), MARQUEE_COLOR);
+            quad(Vec2::new(min.x, max.y - w), max, MARQUEE_COLOR);
+            quad(min, Vec2::new(min.x + w, max.y), MARQUEE_COLOR);
+            quad(Vec2::new(max.x - w, min.y), max, MARQUEE_COLOR);
+        }
+
+        (vertices, indices)
+    }
+}
@@ -1,5 +1,137 @@
-#[derive(Clone, Copy, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    consts::{INDICES, PENTAGON_OUTLINE, STAR_INDICES, STAR_OUTLINE, STAR_VERTICES, VERTICES},
+    geometry::outline::Outline,
+    models::vertex::Vertex,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub enum ShapeType{
     Pentagon,
     Star,
 }
+
+impl ShapeType {
+    pub fn next(self) -> Self {
+        match self {
+            ShapeType::Pentagon => ShapeType::Star,
+            ShapeType::Star => ShapeType::Pentagon,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ShapeType::Pentagon => "Pentagon",
+            ShapeType::Star => "Star",
+        }
+    }
+
+    pub fn vertices(self) -> &'static [Vertex] {
+        match self {
+            ShapeType::Pentagon => VERTICES,
+            ShapeType::Star => STAR_VERTICES,
+        }
+    }
+
+    pub fn indices(self) -> &'static [u16] {
+        match self {
+            ShapeType::Pentagon => INDICES,
+            ShapeType::Star => STAR_INDICES,
+        }
+    }
+
+    pub fn outline(self) -> Outline {
+        let order = match self {
+            ShapeType::Pentagon => PENTAGON_OUTLINE,
+            ShapeType::Star => STAR_OUTLINE,
+        };
+        Outline::from_indices(self.vertices(), order)
+    }
+}
+
+/// Which vertex shader colors the shapes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum PipelineKind {
+    /// Every shape is solid red
+    #[default]
+    Solid,
+    /// Shapes use their vertex colors
+    Colored,
+    /// Vertex colors shaded by the scene lights
+    Lit,
+    /// Physically based materials shaded by the scene lights
+    Pbr,
+}
+
+impl PipelineKind {
+    /// Whether the pipeline uses the scene lights and shadows.
+    pub fn is_lit(self) -> bool {
+        matches!(self, PipelineKind::Lit | PipelineKind::Pbr)
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            PipelineKind::Solid => PipelineKind::Colored,
+            PipelineKind::Colored => PipelineKind::Lit,
+            PipelineKind::Lit => PipelineKind::Pbr,
+            PipelineKind::Pbr => PipelineKind::Solid,
+        }
+    }
+
+    pub fn previous(self) -> Self {
+        match self {
+            PipelineKind::Solid => PipelineKind::Pbr,
+            PipelineKind::Colored => PipelineKind::Solid,
+            PipelineKind::Lit => PipelineKind::Colored,
+            PipelineKind::Pbr => PipelineKind::Lit,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PipelineKind::Solid => "Solid red",
+            PipelineKind::Colored => "Colored",
+            PipelineKind::Lit => "Lit",
+            PipelineKind::Pbr => "PBR",
+        }
+    }
+}
+
+/// Which controller moves the camera, cycled with V.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum CameraMode {
+    /// The 2D camera, dragged with the middle button and zoomed towards the cursor
+    #[default]
+    PanZoom,
+    /// Perspective camera circling a target
+    Orbit,
+    /// Perspective camera flown with WASD
+    Fly,
+}
+
+impl CameraMode {
+    pub fn next(self) -> Self {
+        match self {
+            CameraMode::PanZoom => CameraMode::Orbit,
+            CameraMode::Orbit => CameraMode::Fly,
+            CameraMode::Fly => CameraMode::PanZoom,
+        }
+    }
+
+    pub fn previous(self) -> Self {
+        match self {
+            CameraMode::PanZoom => CameraMode::Fly,
+            CameraMode::Orbit => CameraMode::PanZoom,
+            CameraMode::Fly => CameraMode::Orbit,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CameraMode::PanZoom => "Pan and zoom",
+            CameraMode::Orbit => "Orbit",
+            CameraMode::Fly => "Fly",
+        }
+    }
+}
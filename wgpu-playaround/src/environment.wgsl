@@ -0,0 +1,221 @@
+// Bakes an equirectangular panorama into a mipmapped cubemap, then the
+// irradiance and prefiltered specular cubemaps and the BRDF LUT for image
+// based lighting. Mirrors the CPU references in `environment/mod.rs`.
+
+const PI: f32 = 3.14159265;
+const MIN_ROUGHNESS: f32 = 0.045;
+
+struct BakeParams {
+    // Edge length of the faces being written
+    size: u32,
+    sample_count: u32,
+    roughness: f32,
+    // Edge length of the top mip of the source cube
+    source_size: f32,
+};
+
+@group(0) @binding(0)
+var<uniform> params: BakeParams;
+@group(0) @binding(1)
+var equirect: texture_2d<f32>;
+@group(0) @binding(2)
+var source: texture_cube<f32>;
+@group(0) @binding(3)
+var source_sampler: sampler;
+@group(0) @binding(4)
+var faces: texture_storage_2d_array<rgba16float, write>;
+@group(0) @binding(5)
+var lut: texture_storage_2d<rgba16float, write>;
+@group(0) @binding(6)
+var previous_mip: texture_2d_array<f32>;
+
+fn cube_direction(face: u32, uv: vec2<f32>) -> vec3<f32> {
+    let st = uv * 2.0 - 1.0;
+    switch face {
+        case 0u: { return normalize(vec3<f32>(1.0, -st.y, -st.x)); }
+        case 1u: { return normalize(vec3<f32>(-1.0, -st.y, st.x)); }
+        case 2u: { return normalize(vec3<f32>(st.x, 1.0, st.y)); }
+        case 3u: { return normalize(vec3<f32>(st.x, -1.0, -st.y)); }
+        case 4u: { return normalize(vec3<f32>(st.x, -st.y, 1.0)); }
+        default: { return normalize(vec3<f32>(-st.x, -st.y, -1.0)); }
+    }
+}
+
+// Texel center of the invocation on the face being written, or false past the edge
+fn face_uv(id: vec3<u32>, uv: ptr<function, vec2<f32>>) -> bool {
+    if id.x >= params.size || id.y >= params.size {
+        return false;
+    }
+    *uv = (vec2<f32>(id.xy) + 0.5) / f32(params.size);
+    return true;
+}
+
+fn equirect_uv(direction: vec3<f32>) -> vec2<f32> {
+    return vec2<f32>(
+        0.5 + atan2(direction.x, -direction.z) / (2.0 * PI),
+        acos(clamp(direction.y, -1.0, 1.0)) / PI,
+    );
+}
+
+fn equirect_texel(x: i32, y: i32) -> vec3<f32> {
+    let size = vec2<i32>(textureDimensions(equirect));
+    let wrapped = ((x % size.x) + size.x) % size.x;
+    return textureLoad(equirect, vec2<i32>(wrapped, clamp(y, 0, size.y - 1)), 0).rgb;
+}
+
+// Bilinear by hand, 32 bit float textures cannot be filtered
+fn sample_equirect(direction: vec3<f32>) -> vec3<f32> {
+    let p = equirect_uv(direction) * vec2<f32>(textureDimensions(equirect)) - 0.5;
+    let base = floor(p);
+    let f = p - base;
+    let x = i32(base.x);
+    let y = i32(base.y);
+    let top = mix(equirect_texel(x, y), equirect_texel(x + 1, y), f.x);
+    let bottom = mix(equirect_texel(x, y + 1), equirect_texel(x + 1, y + 1), f.x);
+    return mix(top, bottom, f.y);
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn cs_equirect_to_cube(@builtin(global_invocation_id) id: vec3<u32>) {
+    var uv: vec2<f32>;
+    if !face_uv(id, &uv) {
+        return;
+    }
+    let color = sample_equirect(cube_direction(id.z, uv));
+    textureStore(faces, id.xy, id.z, vec4<f32>(color, 1.0));
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn cs_downsample(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x >= params.size || id.y >= params.size {
+        return;
+    }
+    let corner = vec2<i32>(id.xy) * 2;
+    let face = i32(id.z);
+    let sum = textureLoad(previous_mip, corner, face, 0)
+        + textureLoad(previous_mip, corner + vec2<i32>(1, 0), face, 0)
+        + textureLoad(previous_mip, corner + vec2<i32>(0, 1), face, 0)
+        + textureLoad(previous_mip, corner + vec2<i32>(1, 1), face, 0);
+    textureStore(faces, id.xy, id.z, sum * 0.25);
+}
+
+fn hammersley(i: u32, count: u32) -> vec2<f32> {
+    return vec2<f32>(f32(i) / f32(count), f32(reverseBits(i)) / 4294967296.0);
+}
+
+fn to_world(v: vec3<f32>, normal: vec3<f32>) -> vec3<f32> {
+    var up = vec3<f32>(1.0, 0.0, 0.0);
+    if abs(normal.z) < 0.999 {
+        up = vec3<f32>(0.0, 0.0, 1.0);
+    }
+    let tangent = normalize(cross(up, normal));
+    let bitangent = cross(normal, tangent);
+    return tangent * v.x + bitangent * v.y + normal * v.z;
+}
+
+fn cosine_sample(xi: vec2<f32>, normal: vec3<f32>) -> vec3<f32> {
+    let phi = 2.0 * PI * xi.x;
+    let cos_theta = sqrt(1.0 - xi.y);
+    let sin_theta = sqrt(xi.y);
+    return to_world(vec3<f32>(cos(phi) * sin_theta, sin(phi) * sin_theta, cos_theta), normal);
+}
+
+fn importance_sample_ggx(xi: vec2<f32>, normal: vec3<f32>, roughness: f32) -> vec3<f32> {
+    let alpha = roughness * roughness;
+    let phi = 2.0 * PI * xi.x;
+    let cos_theta = sqrt((1.0 - xi.y) / (1.0 + (alpha * alpha - 1.0) * xi.y));
+    let sin_theta = sqrt(max(1.0 - cos_theta * cos_theta, 0.0));
+    return to_world(vec3<f32>(cos(phi) * sin_theta, sin(phi) * sin_theta, cos_theta), normal);
+}
+
+fn distribution_ggx(n_dot_h: f32, roughness: f32) -> f32 {
+    let alpha = roughness * roughness;
+    let alpha2 = alpha * alpha;
+    let d = n_dot_h * n_dot_h * (alpha2 - 1.0) + 1.0;
+    return alpha2 / (PI * d * d);
+}
+
+// Source mip whose texels cover about the solid angle of one sample, so
+// few samples still see all of the environment
+fn source_mip(pdf: f32) -> f32 {
+    let sample_angle = 1.0 / (f32(params.sample_count) * max(pdf, 1e-6));
+    let texel_angle = 4.0 * PI / (6.0 * params.source_size * params.source_size);
+    return max(0.5 * log2(sample_angle / texel_angle) + 1.0, 0.0);
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn cs_irradiance(@builtin(global_invocation_id) id: vec3<u32>) {
+    var uv: vec2<f32>;
+    if !face_uv(id, &uv) {
+        return;
+    }
+    let normal = cube_direction(id.z, uv);
+    var sum = vec3<f32>(0.0);
+    for (var i = 0u; i < params.sample_count; i++) {
+        let to_light = cosine_sample(hammersley(i, params.sample_count), normal);
+        let pdf = max(dot(normal, to_light), 0.0) / PI;
+        sum += textureSampleLevel(source, source_sampler, to_light, source_mip(pdf)).rgb;
+    }
+    textureStore(faces, id.xy, id.z, vec4<f32>(sum / f32(params.sample_count), 1.0));
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn cs_prefilter(@builtin(global_invocation_id) id: vec3<u32>) {
+    var uv: vec2<f32>;
+    if !face_uv(id, &uv) {
+        return;
+    }
+    let reflected = cube_direction(id.z, uv);
+    if params.roughness <= 0.0 {
+        let color = textureSampleLevel(source, source_sampler, reflected, 0.0).rgb;
+        textureStore(faces, id.xy, id.z, vec4<f32>(color, 1.0));
+        return;
+    }
+    let roughness = max(params.roughness, MIN_ROUGHNESS);
+    var sum = vec3<f32>(0.0);
+    var weight = 0.0;
+    for (var i = 0u; i < params.sample_count; i++) {
+        let half_vector = importance_sample_ggx(hammersley(i, params.sample_count), reflected, roughness);
+        let to_light = normalize(2.0 * dot(reflected, half_vector) * half_vector - reflected);
+        let n_dot_l = dot(reflected, to_light);
+        if n_dot_l > 0.0 {
+            // With the view along the normal n.h and v.h are the same
+            let n_dot_h = max(dot(reflected, half_vector), 0.0);
+            let pdf = distribution_ggx(n_dot_h, roughness) / 4.0;
+            sum += textureSampleLevel(source, source_sampler, to_light, source_mip(pdf)).rgb * n_dot_l;
+            weight += n_dot_l;
+        }
+    }
+    textureStore(faces, id.xy, id.z, vec4<f32>(sum / max(weight, 1e-4), 1.0));
+}
+
+@compute @workgroup_size(8, 8, 1)
+fn cs_brdf_lut(@builtin(global_invocation_id) id: vec3<u32>) {
+    var uv: vec2<f32>;
+    if !face_uv(id, &uv) {
+        return;
+    }
+    // n.v along x, roughness along y
+    let n_dot_v = max(uv.x, 1e-4);
+    let to_eye = vec3<f32>(sqrt(1.0 - n_dot_v * n_dot_v), 0.0, n_dot_v);
+    let roughness = max(uv.y, MIN_ROUGHNESS);
+    let k = roughness * roughness / 2.0;
+    let normal = vec3<f32>(0.0, 0.0, 1.0);
+    var scale_bias = vec2<f32>(0.0);
+    for (var i = 0u; i < params.sample_count; i++) {
+        let half_vector = importance_sample_ggx(hammersley(i, params.sample_count), normal, roughness);
+        let to_light = normalize(2.0 * dot(to_eye, half_vector) * half_vector - to_eye);
+        let n_dot_l = max(to_light.z, 0.0);
+        let n_dot_h = max(half_vector.z, 0.0);
+        let v_dot_h = max(dot(to_eye, half_vector), 0.0);
+        if n_dot_l > 0.0 {
+            let g_v = n_dot_v / (n_dot_v * (1.0 - k) + k);
+            let g_l = n_dot_l / (n_dot_l * (1.0 - k) + k);
+            let visibility = g_v * g_l * v_dot_h / max(n_dot_h * n_dot_v, 1e-4);
+            let fresnel = pow(1.0 - v_dot_h, 5.0);
+            scale_bias += vec2<f32>((1.0 - fresnel) * visibility, fresnel * visibility);
+        }
+    }
+    scale_bias /= f32(params.sample_count);
+    textureStore(lut, id.xy, vec4<f32>(scale_bias, 0.0, 1.0));
+}
@@ -0,0 +1,595 @@
+use bytemuck::{Pod, Zeroable};
+use glam::Vec4;
+use wgpu::util::DeviceExt;
+
+use super::{EnvironmentSettings, hdr::HdrImage};
+
+const WORKGROUP_SIZE: u32 = 8;
+const CUBE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+/// Laid out like `BakeParams` in `environment.wgsl`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct BakeParams {
+    size: u32,
+    sample_count: u32,
+    roughness: f32,
+    source_size: f32,
+}
+
+/// The maps an [`Environment`] bakes, for reading them back.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnvironmentMap {
+    /// The panorama as a cubemap, what the skybox shows
+    Cube,
+    /// Cosine weighted radiance, for diffuse light
+    Irradiance,
+    /// Radiance blurred by rougher GGX lobes in each mip, for specular light
+    Prefiltered,
+    /// Scale and bias of the base reflectance in red and green
+    BrdfLut,
+}
+
+fn texture_entry(
+    binding: u32,
+    view_dimension: wgpu::TextureViewDimension,
+    filterable: bool,
+) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Texture {
+            multisampled: false,
+            view_dimension,
+            sample_type: wgpu::TextureSampleType::Float { filterable },
+        },
+        count: None,
+    }
+}
+
+fn storage_entry(
+    binding: u32,
+    view_dimension: wgpu::TextureViewDimension,
+) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::StorageTexture {
+            access: wgpu::StorageTextureAccess::WriteOnly,
+            format: CUBE_FORMAT,
+            view_dimension,
+        },
+        count: None,
+    }
+}
+
+fn create_cube(device: &wgpu::Device, label: &str, size: u32, mips: u32) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(label),
+        size: wgpu::Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 6,
+        },
+        mip_level_count: mips,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: CUBE_FORMAT,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING
+            | wgpu::TextureUsages::STORAGE_BINDING
+            | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
+fn cube_view(texture: &wgpu::Texture) -> wgpu::TextureView {
+    texture.create_view(&wgpu::TextureViewDescriptor {
+        dimension: Some(wgpu::TextureViewDimension::Cube),
+        ..Default::default()
+    })
+}
+
+/// One mip of all six faces, as compute shaders see cubemaps.
+fn mip_view(texture: &wgpu::Texture, mip: u32) -> wgpu::TextureView {
+    texture.create_view(&wgpu::TextureViewDescriptor {
+        dimension: Some(wgpu::TextureViewDimension::D2Array),
+        base_mip_level: mip,
+        mip_level_count: Some(1),
+        ..Default::default()
+    })
+}
+
+fn mip_size(size: u32, mip: u32) -> u32 {
+    (size >> mip).max(1)
+}
+
+/// Decodes half floats as read back from `Rgba16Float` textures.
+fn f16_to_f32(bits: u16) -> f32 {
+    let sign = if bits & 0x8000 == 0 { 1.0 } else { -1.0 };
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f32;
+    match exponent {
+        0 => sign * mantissa * 2f32.powi(-24),
+        31 if mantissa == 0.0 => sign * f32::INFINITY,
+        31 => f32::NAN,
+        _ => sign * (1.0 + mantissa / 1024.0) * 2f32.powi(exponent - 15),
+    }
+}
+
+/// A panorama baked for the skybox and for image based lighting.
+///
+/// The maps have fixed sizes from the settings and start out black, so they
+/// can be bound before anything is loaded and add no light until then.
+/// [`Environment::load`] converts an equirectangular image into the skybox
+/// cubemap and bakes the lighting maps from it in compute passes, without
+/// needing a window.
+pub struct Environment {
+    settings: EnvironmentSettings,
+    loaded: bool,
+
+    equirect_pipeline: wgpu::ComputePipeline,
+    downsample_pipeline: wgpu::ComputePipeline,
+    irradiance_pipeline: wgpu::ComputePipeline,
+    prefilter_pipeline: wgpu::ComputePipeline,
+    brdf_lut_pipeline: wgpu::ComputePipeline,
+    equirect_layout: wgpu::BindGroupLayout,
+    downsample_layout: wgpu::BindGroupLayout,
+    convolve_layout: wgpu::BindGroupLayout,
+    brdf_lut_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+
+    cube: wgpu::Texture,
+    irradiance: wgpu::Texture,
+    prefiltered: wgpu::Texture,
+    brdf_lut: wgpu::Texture,
+    cube_view: wgpu::TextureView,
+    irradiance_view: wgpu::TextureView,
+    prefiltered_view: wgpu::TextureView,
+    brdf_lut_view: wgpu::TextureView,
+}
+
+impl Environment {
+    pub fn new(device: &wgpu::Device, settings: EnvironmentSettings) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Environment Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../environment.wgsl").into()),
+        });
+
+        let params_entry = wgpu::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let sampler_entry = wgpu::BindGroupLayoutEntry {
+            binding: 3,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+            count: None,
+        };
+        let layout = |label: &str, entries: &[wgpu::BindGroupLayoutEntry]| {
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some(label),
+                entries,
+            })
+        };
+        let faces_entry = storage_entry(4, wgpu::TextureViewDimension::D2Array);
+        let equirect_layout = layout(
+            "Equirect Bind Group Layout",
+            &[
+                params_entry,
+                texture_entry(1, wgpu::TextureViewDimension::D2, false),
+                faces_entry,
+            ],
+        );
+        let downsample_layout = layout(
+            "Cube Downsample Bind Group Layout",
+            &[
+                params_entry,
+                faces_entry,
+                texture_entry(6, wgpu::TextureViewDimension::D2Array, false),
+            ],
+        );
+        let convolve_layout = layout(
+            "Cube Convolve Bind Group Layout",
+            &[
+                params_entry,
+                texture_entry(2, wgpu::TextureViewDimension::Cube, true),
+                sampler_entry,
+                faces_entry,
+            ],
+        );
+        let brdf_lut_layout = layout(
+            "BRDF LUT Bind Group Layout",
+            &[
+                params_entry,
+                storage_entry(5, wgpu::TextureViewDimension::D2),
+            ],
+        );
+
+        let pipeline = |label: &str, layout: &wgpu::BindGroupLayout, entry_point: &str| {
+            let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some(label),
+                bind_group_layouts: &[layout],
+                immediate_size: 0,
+            });
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(label),
+                layout: Some(&pipeline_layout),
+                module: &shader,
+                entry_point: Some(entry_point),
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                cache: None,
+            })
+        };
+        let equirect_pipeline = pipeline(
+            "Equirect To Cube Pipeline",
+            &equirect_layout,
+            "cs_equirect_to_cube",
+        );
+        let downsample_pipeline = pipeline(
+            "Cube Downsample Pipeline",
+            &downsample_layout,
+            "cs_downsample",
+        );
+        let irradiance_pipeline =
+            pipeline("Irradiance Pipeline", &convolve_layout, "cs_irradiance");
+        let prefilter_pipeline = pipeline("Prefilter Pipeline", &convolve_layout, "cs_prefilter");
+        let brdf_lut_pipeline = pipeline("BRDF LUT Pipeline", &brdf_lut_layout, "cs_brdf_lut");
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Environment Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::MipmapFilterMode::Linear,
+            ..Default::default()
+        });
+
+        let cube_mips = settings.cube_size.max(1).ilog2() + 1;
+        let cube = create_cube(device, "Environment Cube", settings.cube_size, cube_mips);
+        let irradiance = create_cube(device, "Irradiance Cube", settings.irradiance_size, 1);
+        let prefiltered_mips = settings
+            .prefiltered_mips
+            .clamp(1, settings.prefiltered_size.max(1).ilog2() + 1);
+        let prefiltered = create_cube(
+            device,
+            "Prefiltered Cube",
+            settings.prefiltered_size,
+            prefiltered_mips,
+        );
+        let brdf_lut = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("BRDF LUT"),
+            size: wgpu::Extent3d {
+                width: settings.brdf_lut_size,
+                height: settings.brdf_lut_size,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: CUBE_FORMAT,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::STORAGE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        Self {
+            settings: EnvironmentSettings {
+                prefiltered_mips,
+                ..settings
+            },
+            loaded: false,
+            equirect_pipeline,
+            downsample_pipeline,
+            irradiance_pipeline,
+            prefilter_pipeline,
+            brdf_lut_pipeline,
+            equirect_layout,
+            downsample_layout,
+            convolve_layout,
+            brdf_lut_layout,
+            sampler,
+            cube_view: cube_view(&cube),
+            irradiance_view: cube_view(&irradiance),
+            prefiltered_view: cube_view(&prefiltered),
+            brdf_lut_view: brdf_lut.create_view(&wgpu::TextureViewDescriptor::default()),
+            cube,
+            irradiance,
+            prefiltered,
+            brdf_lut,
+        }
+    }
+
+    pub fn settings(&self) -> &EnvironmentSettings {
+        &self.settings
+    }
+
+    /// Whether a panorama has been loaded, until then the maps are black.
+    pub fn is_loaded(&self) -> bool {
+        self.loaded
+    }
+
+    pub fn cube_view(&self) -> &wgpu::TextureView {
+        &self.cube_view
+    }
+
+    pub fn irradiance_view(&self) -> &wgpu::TextureView {
+        &self.irradiance_view
+    }
+
+    pub fn prefiltered_view(&self) -> &wgpu::TextureView {
+        &self.prefiltered_view
+    }
+
+    pub fn brdf_lut_view(&self) -> &wgpu::TextureView {
+        &self.brdf_lut_view
+    }
+
+    /// Filtering sampler for the baked maps.
+    pub fn sampler(&self) -> &wgpu::Sampler {
+        &self.sampler
+    }
+
+    /// Converts `image` to the skybox cubemap and bakes the lighting maps,
+    /// the work is submitted to `queue` right away.
+    pub fn load(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, image: &HdrImage) {
+        let size = wgpu::Extent3d {
+            width: image.width,
+            height: image.height,
+            depth_or_array_layers: 1,
+        };
+        let equirect = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Equirect Texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba32Float,
+            usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        });
+        let texels: Vec<[f32; 4]> = image
+            .pixels
+            .iter()
+            .map(|p| p.extend(1.0).to_array())
+            .collect();
+        queue.write_texture(
+            equirect.as_image_copy(),
+            bytemuck::cast_slice(&texels),
+            wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(image.width * 16),
+                rows_per_image: None,
+            },
+            size,
+        );
+        let equirect_view = equirect.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let settings = self.settings;
+        let params_buffer = |size: u32, roughness: f32| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("Bake Params Buffer"),
+                contents: bytemuck::bytes_of(&BakeParams {
+                    size,
+                    sample_count: settings.sample_count,
+                    roughness,
+                    source_size: settings.cube_size as f32,
+                }),
+                usage: wgpu::BufferUsages::UNIFORM,
+            })
+        };
+        // Everything a dispatch needs, bound at group 0
+        let mut dispatches: Vec<(&wgpu::ComputePipeline, wgpu::BindGroup, u32, u32)> = Vec::new();
+        let bind_group = |layout: &wgpu::BindGroupLayout, entries: &[wgpu::BindGroupEntry]| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Bake Bind Group"),
+                layout,
+                entries,
+            })
+        };
+
+        let cube_params = params_buffer(settings.cube_size, 0.0);
+        let top_mip = mip_view(&self.cube, 0);
+        let group = bind_group(
+            &self.equirect_layout,
+            &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: cube_params.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&equirect_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: wgpu::BindingResource::TextureView(&top_mip),
+                },
+            ],
+        );
+        dispatches.push((&self.equirect_pipeline, group, settings.cube_size, 6));
+
+        for mip in 1..self.cube.mip_level_count() {
+            let size = mip_size(settings.cube_size, mip);
+            let params = params_buffer(size, 0.0);
+            let group = bind_group(
+                &self.downsample_layout,
+                &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: params.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 4,
+                        resource: wgpu::BindingResource::TextureView(&mip_view(&self.cube, mip)),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 6,
+                        resource: wgpu::BindingResource::TextureView(&mip_view(
+                            &self.cube,
+                            mip - 1,
+                        )),
+                    },
+                ],
+            );
+            dispatches.push((&self.downsample_pipeline, group, size, 6));
+        }
+
+        let convolve = |pipeline, target: &wgpu::Texture, mip: u32, roughness: f32| {
+            let size = mip_size(target.width(), mip);
+            let params = params_buffer(size, roughness);
+            let group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Bake Bind Group"),
+                layout: &self.convolve_layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: params.as_entire_binding(),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::TextureView(&self.cube_view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: wgpu::BindingResource::Sampler(&self.sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 4,
+                        resource: wgpu::BindingResource::TextureView(&mip_view(target, mip)),
+                    },
+                ],
+            });
+            (pipeline, group, size, 6)
+        };
+        dispatches.push(convolve(
+            &self.irradiance_pipeline,
+            &self.irradiance,
+            0,
+            0.0,
+        ));
+        for mip in 0..settings.prefiltered_mips {
+            dispatches.push(convolve(
+                &self.prefilter_pipeline,
+                &self.prefiltered,
+                mip,
+                settings.prefiltered_roughness(mip),
+            ));
+        }
+
+        let lut_params = params_buffer(settings.brdf_lut_size, 0.0);
+        let group = bind_group(
+            &self.brdf_lut_layout,
+            &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: lut_params.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 5,
+                    resource: wgpu::BindingResource::TextureView(&self.brdf_lut_view),
+                },
+            ],
+        );
+        dispatches.push((&self.brdf_lut_pipeline, group, settings.brdf_lut_size, 1));
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Environment Bake Encoder"),
+        });
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Environment Bake Pass"),
+                timestamp_writes: None,
+            });
+            // In order, each step reads what the one before wrote
+            for (pipeline, group, size, layers) in &dispatches {
+                let groups = size.div_ceil(WORKGROUP_SIZE);
+                pass.set_pipeline(pipeline);
+                pass.set_bind_group(0, group, &[]);
+                pass.dispatch_workgroups(groups, groups, *layers);
+            }
+        }
+        queue.submit(std::iter::once(encoder.finish()));
+        self.loaded = true;
+    }
+
+    /// Reads `mip` of a baked map back, the faces of cubemaps one after the
+    /// other in layer order with rows from top to bottom.
+    pub fn read(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        map: EnvironmentMap,
+        mip: u32,
+    ) -> anyhow::Result<Vec<Vec4>> {
+        let texture = match map {
+            EnvironmentMap::Cube => &self.cube,
+            EnvironmentMap::Irradiance => &self.irradiance,
+            EnvironmentMap::Prefiltered => &self.prefiltered,
+            EnvironmentMap::BrdfLut => &self.brdf_lut,
+        };
+        anyhow::ensure!(mip < texture.mip_level_count(), "no mip {mip} in {map:?}");
+        let size = mip_size(texture.width(), mip);
+        let layers = texture.depth_or_array_layers();
+        // Rows copied out of a texture have to be padded to 256 bytes
+        let row_bytes = size * 8;
+        let padded_bytes_per_row = row_bytes.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Environment Readback Buffer"),
+            size: (padded_bytes_per_row * size * layers) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Environment Readback Encoder"),
+        });
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture,
+                mip_level: mip,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(size),
+                },
+            },
+            wgpu::Extent3d {
+                width: size,
+                height: size,
+                depth_or_array_layers: layers,
+            },
+        );
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::PollType::wait_indefinitely())?;
+        receiver.recv()??;
+
+        let data = slice.get_mapped_range();
+        let texels = data
+            .chunks(padded_bytes_per_row as usize)
+            .flat_map(|row| row[..row_bytes as usize].chunks_exact(8))
+            .map(|texel| {
+                Vec4::from_array(std::array::from_fn(|k| {
+                    f16_to_f32(u16::from_le_bytes([texel[2 * k], texel[2 * k + 1]]))
+                }))
+            })
+            .collect();
+        Ok(texels)
+    }
+}
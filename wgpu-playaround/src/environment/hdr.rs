@@ -0,0 +1,193 @@
+// Radiance `.hdr` files, RGBE pixels with a shared exponent per pixel.
+// Scanlines are either flat or use the run-length encoding of newer files.
+
+use std::path::Path;
+
+use anyhow::{Context, bail, ensure};
+use glam::{Vec2, Vec3};
+
+/// Linear RGB pixels, rows from top to bottom.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HdrImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<Vec3>,
+}
+
+fn rgbe_to_rgb([r, g, b, e]: [u8; 4]) -> Vec3 {
+    if e == 0 {
+        return Vec3::ZERO;
+    }
+    // Mantissas are fractions of 256 scaled by 2^(e - 128)
+    let scale = 2f32.powi(e as i32 - 136);
+    Vec3::new(r as f32, g as f32, b as f32) * scale
+}
+
+fn rgb_to_rgbe(rgb: Vec3) -> [u8; 4] {
+    let largest = rgb.max_element();
+    if largest < 1e-32 {
+        return [0; 4];
+    }
+    // largest = mantissa * 2^exponent with the mantissa in [0.5, 1)
+    let mut exponent = largest.log2().floor() as i32 + 1;
+    if largest / 2f32.powi(exponent) >= 1.0 {
+        exponent += 1;
+    }
+    let scale = 256.0 / 2f32.powi(exponent);
+    let [r, g, b] = (rgb.max(Vec3::ZERO) * scale)
+        .to_array()
+        .map(|c| c.min(255.0) as u8);
+    [r, g, b, (exponent + 128).clamp(0, 255) as u8]
+}
+
+/// Reads one run-length encoded scanline, each channel stored separately.
+fn decode_rle_scanline(data: &mut &[u8], width: usize) -> anyhow::Result<Vec<[u8; 4]>> {
+    let mut scanline = vec![[0; 4]; width];
+    for channel in 0..4 {
+        let mut x = 0;
+        while x < width {
+            let (&count, rest) = data.split_first().context("scanline ends early")?;
+            *data = rest;
+            if count > 128 {
+                // A run of one repeated value
+                let count = count as usize - 128;
+                let (&value, rest) = data.split_first().context("scanline ends early")?;
+                *data = rest;
+                ensure!(x + count <= width, "run overflows the scanline");
+                for pixel in &mut scanline[x..x + count] {
+                    pixel[channel] = value;
+                }
+                x += count;
+            } else {
+                let count = count as usize;
+                ensure!(count > 0 && x + count <= width, "bad literal run");
+                ensure!(data.len() >= count, "scanline ends early");
+                for (pixel, &value) in scanline[x..x + count].iter_mut().zip(&data[..count]) {
+                    pixel[channel] = value;
+                }
+                *data = &data[count..];
+                x += count;
+            }
+        }
+    }
+    Ok(scanline)
+}
+
+impl HdrImage {
+    pub fn from_fn(width: u32, height: u32, pixel: impl Fn(u32, u32) -> Vec3) -> Self {
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| pixel(x, y))
+            .collect();
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+        Self::decode(&bytes).with_context(|| format!("decoding {}", path.display()))
+    }
+
+    /// Reads a Radiance file with top to bottom rows, `-Y height +X width`.
+    pub fn decode(bytes: &[u8]) -> anyhow::Result<Self> {
+        let mut data = bytes;
+        let mut line = || -> anyhow::Result<&str> {
+            let end = data
+                .iter()
+                .position(|&b| b == b'\n')
+                .context("header ends early")?;
+            let line = std::str::from_utf8(&data[..end])?;
+            data = &data[end + 1..];
+            Ok(line.trim_end_matches('\r'))
+        };
+
+        let magic = line()?;
+        ensure!(
+            magic.starts_with("#?RADIANCE") || magic.starts_with("#?RGBE"),
+            "not a Radiance file"
+        );
+        loop {
+            let header = line()?;
+            if header.is_empty() {
+                break;
+            }
+            if let Some(format) = header.strip_prefix("FORMAT=") {
+                ensure!(format == "32-bit_rle_rgbe", "unsupported format {format}");
+            }
+        }
+        let resolution = line()?;
+        let (height, width) = match resolution.split_whitespace().collect::<Vec<_>>()[..] {
+            ["-Y", height, "+X", width] => (height.parse::<u32>()?, width.parse::<u32>()?),
+            _ => bail!("unsupported orientation {resolution}"),
+        };
+
+        let row = width as usize;
+        let mut pixels = Vec::with_capacity(row * height as usize);
+        for _ in 0..height {
+            let scanline = match data {
+                [2, 2, hi, lo, rest @ ..] if (8..0x8000).contains(&row) => {
+                    ensure!(
+                        ((*hi as usize) << 8 | *lo as usize) == row,
+                        "scanline width does not match the image"
+                    );
+                    data = rest;
+                    decode_rle_scanline(&mut data, row)?
+                }
+                _ => {
+                    ensure!(data.len() >= row * 4, "pixel data ends early");
+                    let (flat, rest) = data.split_at(row * 4);
+                    data = rest;
+                    let scanline: Vec<[u8; 4]> = flat
+                        .chunks_exact(4)
+                        .map(|c| [c[0], c[1], c[2], c[3]])
+                        .collect();
+                    ensure!(
+                        !scanline.iter().any(|p| p[..3] == [1, 1, 1]),
+                        "old style run-length encoding is not supported"
+                    );
+                    scanline
+                }
+            };
+            pixels.extend(scanline.into_iter().map(rgbe_to_rgb));
+        }
+        Ok(Self {
+            width,
+            height,
+            pixels,
+        })
+    }
+
+    /// Writes the image as a Radiance file with flat scanlines.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = format!(
+            "#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n",
+            self.height, self.width
+        )
+        .into_bytes();
+        bytes.extend(self.pixels.iter().flat_map(|&rgb| rgb_to_rgbe(rgb)));
+        bytes
+    }
+
+    /// Pixel at `x` wrapped around horizontally and `y` clamped to the image.
+    pub fn texel(&self, x: i32, y: i32) -> Vec3 {
+        let x = x.rem_euclid(self.width as i32) as usize;
+        let y = y.clamp(0, self.height as i32 - 1) as usize;
+        self.pixels[y * self.width as usize + x]
+    }
+
+    /// Bilinear lookup at `uv`, wrapping around horizontally like a
+    /// panorama. Mirrors `sample_equirect` in `environment.wgsl`.
+    pub fn sample(&self, uv: Vec2) -> Vec3 {
+        let p = uv * Vec2::new(self.width as f32, self.height as f32) - 0.5;
+        let base = p.floor();
+        let f = p - base;
+        let (x, y) = (base.x as i32, base.y as i32);
+        let top = self.texel(x, y).lerp(self.texel(x + 1, y), f.x);
+        let bottom = self.texel(x, y + 1).lerp(self.texel(x + 1, y + 1), f.x);
+        top.lerp(bottom, f.y)
+    }
+}
@@ -0,0 +1,215 @@
+pub mod gpu;
+pub mod hdr;
+pub mod skybox;
+
+use std::f32::consts::PI;
+
+use glam::{Mat4, Vec2, Vec3};
+
+use crate::material::brdf::MIN_ROUGHNESS;
+
+/// Pitch of the sky view stays short of straight up or down.
+pub(crate) const MAX_PITCH: f32 = 1.55;
+
+/// Sizes and sample counts of the maps baked from an environment.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EnvironmentSettings {
+    /// Edge length of the skybox faces, it is mipmapped down to one texel
+    pub cube_size: u32,
+    pub irradiance_size: u32,
+    /// Edge length of the sharpest prefiltered mip, the roughest is the last
+    pub prefiltered_size: u32,
+    pub prefiltered_mips: u32,
+    pub brdf_lut_size: u32,
+    /// Importance samples per texel of the irradiance, prefiltered and LUT maps
+    pub sample_count: u32,
+}
+
+impl Default for EnvironmentSettings {
+    fn default() -> Self {
+        Self {
+            cube_size: 512,
+            irradiance_size: 32,
+            prefiltered_size: 128,
+            prefiltered_mips: 5,
+            brdf_lut_size: 128,
+            sample_count: 256,
+        }
+    }
+}
+
+impl EnvironmentSettings {
+    /// Roughness the prefiltered map is convolved with at `mip`.
+    pub fn prefiltered_roughness(&self, mip: u32) -> f32 {
+        mip as f32 / (self.prefiltered_mips.max(2) - 1) as f32
+    }
+}
+
+/// Direction through the center of `uv` on cubemap face `face`, in the
+/// `+X, -X, +Y, -Y, +Z, -Z` order of cube texture layers.
+pub fn cube_direction(face: u32, uv: Vec2) -> Vec3 {
+    let st = uv * 2.0 - 1.0;
+    let direction = match face {
+        0 => Vec3::new(1.0, -st.y, -st.x),
+        1 => Vec3::new(-1.0, -st.y, st.x),
+        2 => Vec3::new(st.x, 1.0, st.y),
+        3 => Vec3::new(st.x, -1.0, -st.y),
+        4 => Vec3::new(st.x, -st.y, 1.0),
+        _ => Vec3::new(-st.x, -st.y, -1.0),
+    };
+    direction.normalize()
+}
+
+/// Where `direction` lands on an equirectangular panorama, `-Z` in the
+/// middle and `+Y` along the top edge.
+pub fn equirect_uv(direction: Vec3) -> Vec2 {
+    Vec2::new(
+        0.5 + direction.x.atan2(-direction.z) / (2.0 * PI),
+        direction.y.clamp(-1.0, 1.0).acos() / PI,
+    )
+}
+
+/// Point `i` of `count` in the Hammersley set, evenly spread over the unit square.
+pub fn hammersley(i: u32, count: u32) -> Vec2 {
+    Vec2::new(
+        i as f32 / count as f32,
+        i.reverse_bits() as f32 / 4_294_967_296.0,
+    )
+}
+
+/// Rotates `v` from the frame where `normal` is `+Z` to world space.
+fn to_world(v: Vec3, normal: Vec3) -> Vec3 {
+    let up = if normal.z.abs() < 0.999 {
+        Vec3::Z
+    } else {
+        Vec3::X
+    };
+    let tangent = up.cross(normal).normalize();
+    let bitangent = normal.cross(tangent);
+    tangent * v.x + bitangent * v.y + normal * v.z
+}
+
+/// Direction around `normal` with a density proportional to the cosine.
+pub fn cosine_sample(xi: Vec2, normal: Vec3) -> Vec3 {
+    let phi = 2.0 * PI * xi.x;
+    let cos_theta = (1.0 - xi.y).sqrt();
+    let sin_theta = xi.y.sqrt();
+    to_world(
+        Vec3::new(phi.cos() * sin_theta, phi.sin() * sin_theta, cos_theta),
+        normal,
+    )
+}
+
+/// Half vector around `normal` distributed like GGX microfacets.
+pub fn importance_sample_ggx(xi: Vec2, normal: Vec3, roughness: f32) -> Vec3 {
+    let alpha = roughness * roughness;
+    let phi = 2.0 * PI * xi.x;
+    let cos_theta = ((1.0 - xi.y) / (1.0 + (alpha * alpha - 1.0) * xi.y)).sqrt();
+    let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+    to_world(
+        Vec3::new(phi.cos() * sin_theta, phi.sin() * sin_theta, cos_theta),
+        normal,
+    )
+}
+
+/// Cosine weighted average of the radiance around `normal`, the diffuse
+/// light of a white Lambert surface. Mirrors `cs_irradiance` without the
+/// mip selection that keeps the GPU version from aliasing.
+pub fn irradiance(radiance: impl Fn(Vec3) -> Vec3, normal: Vec3, sample_count: u32) -> Vec3 {
+    (0..sample_count)
+        .map(|i| radiance(cosine_sample(hammersley(i, sample_count), normal)))
+        .sum::<Vec3>()
+        / sample_count as f32
+}
+
+/// Radiance around the mirror direction `reflected` blurred by the GGX lobe
+/// of `roughness`, assuming the view is along the normal. Mirrors
+/// `cs_prefilter` without the mip selection.
+pub fn prefiltered(
+    radiance: impl Fn(Vec3) -> Vec3,
+    reflected: Vec3,
+    roughness: f32,
+    sample_count: u32,
+) -> Vec3 {
+    if roughness <= 0.0 {
+        return radiance(reflected);
+    }
+    let roughness = roughness.max(MIN_ROUGHNESS);
+    let mut sum = Vec3::ZERO;
+    let mut weight = 0.0;
+    for i in 0..sample_count {
+        let half = importance_sample_ggx(hammersley(i, sample_count), reflected, roughness);
+        let to_light = (2.0 * reflected.dot(half) * half - reflected).normalize();
+        let n_dot_l = reflected.dot(to_light);
+        if n_dot_l > 0.0 {
+            sum += radiance(to_light) * n_dot_l;
+            weight += n_dot_l;
+        }
+    }
+    sum / weight.max(1e-4)
+}
+
+/// Scale and bias applied to the base reflectance by the split sum
+/// approximation, integrated over a white environment. Mirrors `cs_brdf_lut`.
+pub fn brdf_lut(n_dot_v: f32, roughness: f32, sample_count: u32) -> Vec2 {
+    let n_dot_v = n_dot_v.max(1e-4);
+    let to_eye = Vec3::new((1.0 - n_dot_v * n_dot_v).sqrt(), 0.0, n_dot_v);
+    let roughness = roughness.max(MIN_ROUGHNESS);
+    // Image based lighting uses k = alpha / 2 in the Schlick-Smith term
+    let k = roughness * roughness / 2.0;
+    let g1 = |n_dot_x: f32| n_dot_x / (n_dot_x * (1.0 - k) + k);
+    let mut lut = Vec2::ZERO;
+    for i in 0..sample_count {
+        let half = importance_sample_ggx(hammersley(i, sample_count), Vec3::Z, roughness);
+        let to_light = (2.0 * to_eye.dot(half) * half - to_eye).normalize();
+        let n_dot_l = to_light.z.max(0.0);
+        let n_dot_h = half.z.max(0.0);
+        let v_dot_h = to_eye.dot(half).max(0.0);
+        if n_dot_l > 0.0 {
+            let visibility = g1(n_dot_v) * g1(n_dot_l) * v_dot_h / (n_dot_h * n_dot_v).max(1e-4);
+            let fresnel = (1.0 - v_dot_h).powi(5);
+            lut += Vec2::new((1.0 - fresnel) * visibility, fresnel * visibility);
+        }
+    }
+    lut / sample_count as f32
+}
+
+/// Orientation and field of view the skybox is seen with. The 2D camera
+/// looks down `-Z`, which is the default.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SkyView {
+    /// Radians to the right of `-Z`
+    pub yaw: f32,
+    /// Radians above the horizon
+    pub pitch: f32,
+    /// Vertical field of view in radians
+    pub fov_y: f32,
+}
+
+impl Default for SkyView {
+    fn default() -> Self {
+        Self {
+            yaw: 0.0,
+            pitch: 0.0,
+            fov_y: 60f32.to_radians(),
+        }
+    }
+}
+
+impl SkyView {
+    pub fn forward(&self) -> Vec3 {
+        let pitch = self.pitch.clamp(-MAX_PITCH, MAX_PITCH);
+        Vec3::new(
+            self.yaw.sin() * pitch.cos(),
+            pitch.sin(),
+            -self.yaw.cos() * pitch.cos(),
+        )
+    }
+
+    /// Maps NDC back to view directions, the camera sits at the origin.
+    pub fn inverse_view_proj(&self, aspect: f32) -> Mat4 {
+        let view = Mat4::look_to_rh(Vec3::ZERO, self.forward(), Vec3::Y);
+        let proj = Mat4::perspective_rh(self.fov_y, aspect.max(1e-3), 0.1, 10.0);
+        (proj * view).inverse()
+    }
+}
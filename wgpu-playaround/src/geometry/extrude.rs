@@ -0,0 +1,417 @@
+use std::{collections::HashMap, f32::consts::FRAC_PI_2};
+
+use glam::{IVec2, Vec2, Vec3};
+
+use crate::{
+    geometry::{
+        normals::{CREASE_ANGLE, planar_uv},
+        primitives::Mesh3d,
+    },
+    models::vertex::Vertex,
+};
+
+/// Points of a flat mesh closer than this are the same point.
+const WELD_DISTANCE: f32 = 1e-5;
+/// Longest inset of a corner relative to the bevel width, so sharp tips do
+/// not shoot across the shape.
+const MAX_MITER: f32 = 4.0;
+
+/// Rounded or chamfered edge between the caps and the side of an extrusion.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bevel {
+    /// How far the caps shrink into the outline
+    pub width: f32,
+    /// How far the bevel reaches along Z from each cap, at most half the depth
+    pub depth: f32,
+    /// Steps around the rounded edge, 1 is a flat chamfer
+    pub segments: u32,
+}
+
+impl Default for Bevel {
+    fn default() -> Self {
+        Self {
+            width: 0.03,
+            depth: 0.03,
+            segments: 3,
+        }
+    }
+}
+
+/// Flat triangles with coincident points merged and counter-clockwise winding.
+struct Welded {
+    points: Vec<Vec2>,
+    triangles: Vec<[u32; 3]>,
+}
+
+impl Welded {
+    fn new(vertices: &[Vertex], indices: &[u16]) -> Self {
+        let mut welded = Self {
+            points: Vec::new(),
+            triangles: Vec::new(),
+        };
+        let mut cells: HashMap<IVec2, Vec<u32>> = HashMap::new();
+        let mut weld = |p: Vec2| {
+            let cell = (p / WELD_DISTANCE).floor().as_ivec2();
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    let nearby = cells.get(&(cell + IVec2::new(dx, dy)));
+                    for &i in nearby.into_iter().flatten() {
+                        if welded.points[i as usize].distance(p) <= WELD_DISTANCE {
+                            return i;
+                        }
+                    }
+                }
+            }
+            welded.points.push(p);
+            let i = (welded.points.len() - 1) as u32;
+            cells.entry(cell).or_default().push(i);
+            i
+        };
+        let mut triangles = Vec::new();
+        for triangle in indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|k| {
+                let p = vertices[triangle[k] as usize].position;
+                weld(Vec2::new(p[0], p[1]))
+            });
+            if a != b && b != c && c != a {
+                triangles.push([a, b, c]);
+            }
+        }
+        for [a, b, c] in triangles {
+            let [pa, pb, pc] = [a, b, c].map(|i| welded.points[i as usize]);
+            let area = (pb - pa).perp_dot(pc - pa);
+            if area > 0.0 {
+                welded.triangles.push([a, b, c]);
+            } else if area < 0.0 {
+                welded.triangles.push([a, c, b]);
+            }
+        }
+        welded
+    }
+
+    /// Edges not cancelled by a neighbour running the other way, each
+    /// repeated by how many more times it runs this way.
+    fn unmatched(edges: impl IntoIterator<Item = (u32, u32)>) -> Vec<(u32, u32)> {
+        let mut balance: HashMap<(u32, u32), i32> = HashMap::new();
+        for (a, b) in edges {
+            if a < b {
+                *balance.entry((a, b)).or_default() += 1;
+            } else {
+                *balance.entry((b, a)).or_default() -= 1;
+            }
+        }
+        let mut open: Vec<(u32, u32)> = balance
+            .into_iter()
+            .flat_map(|((a, b), count)| {
+                let edge = if count > 0 { (a, b) } else { (b, a) };
+                std::iter::repeat_n(edge, count.unsigned_abs() as usize)
+            })
+            .collect();
+        open.sort_unstable();
+        open
+    }
+
+    /// Points out of `candidates` on the segment from `a` to `b`, in order.
+    fn points_between(&self, a: u32, b: u32, candidates: &[u32]) -> Vec<u32> {
+        let (pa, pb) = (self.points[a as usize], self.points[b as usize]);
+        let along = pb - pa;
+        let mut inner: Vec<(f32, u32)> = candidates
+            .iter()
+            .filter(|&&v| v != a && v != b)
+            .filter_map(|&v| {
+                let p = self.points[v as usize];
+                let t = (p - pa).dot(along) / along.length_squared();
+                let off_line = (pa + along * t).distance(p);
+                (t > 0.0 && t < 1.0 && off_line <= WELD_DISTANCE).then_some((t, v))
+            })
+            .collect();
+        inner.sort_by(|x, y| x.0.total_cmp(&y.0));
+        inner.into_iter().map(|(_, v)| v).collect()
+    }
+
+    /// Fans triangles with `points` in the middle of their edges around
+    /// their center, so the caps have no cracks where the side walls meet them.
+    fn split_at(&mut self, points: &[u32]) {
+        let mut split = Vec::with_capacity(self.triangles.len());
+        for [a, b, c] in std::mem::take(&mut self.triangles) {
+            let mut corners = Vec::new();
+            for (from, to) in [(a, b), (b, c), (c, a)] {
+                corners.push(from);
+                corners.extend(self.points_between(from, to, points));
+            }
+            if corners.len() == 3 {
+                split.push([a, b, c]);
+                continue;
+            }
+            let [pa, pb, pc] = [a, b, c].map(|i| self.points[i as usize]);
+            self.points.push((pa + pb + pc) / 3.0);
+            let center = (self.points.len() - 1) as u32;
+            for k in 0..corners.len() {
+                split.push([center, corners[k], corners[(k + 1) % corners.len()]]);
+            }
+        }
+        self.triangles = split;
+    }
+
+    /// Closed loops around the filled area, counter-clockwise around the
+    /// outside and clockwise around holes.
+    fn boundary(&self) -> Vec<Vec<u32>> {
+        let edges = self
+            .triangles
+            .iter()
+            .flat_map(|&[a, b, c]| [(a, b), (b, c), (c, a)]);
+        let open = Self::unmatched(edges);
+
+        // Neighbouring triangles that do not share corners, like the
+        // trapezoids of filled paths, meet at points in the middle of an edge
+        let mut on_boundary: Vec<u32> = open.iter().flat_map(|&(a, b)| [a, b]).collect();
+        on_boundary.sort_unstable();
+        on_boundary.dedup();
+        let mut split = Vec::with_capacity(open.len());
+        for (a, b) in open {
+            let mut from = a;
+            for v in self.points_between(a, b, &on_boundary) {
+                split.push((from, v));
+                from = v;
+            }
+            split.push((from, b));
+        }
+
+        let mut next: HashMap<u32, Vec<u32>> = HashMap::new();
+        for (a, b) in Self::unmatched(split).into_iter().rev() {
+            next.entry(a).or_default().push(b);
+        }
+        let mut loops = Vec::new();
+        while let Some(&start) = next.keys().min() {
+            let mut boundary = vec![start];
+            let mut current = start;
+            loop {
+                let Some(to) = next.get_mut(&current).and_then(Vec::pop) else {
+                    // Chains that do not close are left out
+                    boundary.clear();
+                    break;
+                };
+                if next[&current].is_empty() {
+                    next.remove(&current);
+                }
+                if to == start {
+                    break;
+                }
+                boundary.push(to);
+                current = to;
+            }
+            if boundary.len() >= 3 {
+                loops.push(boundary);
+            }
+        }
+        loops
+    }
+}
+
+/// Outlines around the filled parts of a flat triangle mesh in the xy plane.
+/// Triangles that only touch at their corners or along parts of their edges
+/// count as connected, so triangulated SVG paths give one loop per contour.
+/// Outer loops run counter-clockwise, holes clockwise.
+pub fn boundary_loops(vertices: &[Vertex], indices: &[u16]) -> Vec<Vec<Vec2>> {
+    let welded = Welded::new(vertices, indices);
+    welded
+        .boundary()
+        .into_iter()
+        .map(|boundary| {
+            boundary
+                .into_iter()
+                .map(|i| welded.points[i as usize])
+                .collect()
+        })
+        .collect()
+}
+
+/// Point on the profile of the side, from the front cap to the back one.
+#[derive(Clone, Copy)]
+struct ProfilePoint {
+    inset: f32,
+    z: f32,
+    /// Outwards along x and along z along y
+    normal: Vec2,
+}
+
+/// Strips of the side from front to back, each with its own end normals so
+/// chamfers and the corners with the caps stay sharp.
+fn side_profile(depth: f32, bevel: Option<Bevel>) -> Vec<[ProfilePoint; 2]> {
+    let half = depth / 2.0;
+    let Some(bevel) = bevel.filter(|b| b.width > 0.0 && b.depth > 0.0) else {
+        return vec![[
+            ProfilePoint {
+                inset: 0.0,
+                z: half,
+                normal: Vec2::X,
+            },
+            ProfilePoint {
+                inset: 0.0,
+                z: -half,
+                normal: Vec2::X,
+            },
+        ]];
+    };
+    let (width, bevel_depth) = (bevel.width, bevel.depth.min(half));
+    let segments = bevel.segments.max(1);
+    // Quarter of an ellipse from the edge of the cap to the side
+    let front: Vec<ProfilePoint> = (0..=segments)
+        .map(|k| {
+            let (sin, cos) = (FRAC_PI_2 * k as f32 / segments as f32).sin_cos();
+            ProfilePoint {
+                inset: width * (1.0 - sin),
+                z: half - bevel_depth * (1.0 - cos),
+                normal: Vec2::new(bevel_depth * sin, width * cos).normalize(),
+            }
+        })
+        .collect();
+    let back: Vec<ProfilePoint> = front
+        .iter()
+        .rev()
+        .map(|p| ProfilePoint {
+            z: -p.z,
+            normal: Vec2::new(p.normal.x, -p.normal.y),
+            ..*p
+        })
+        .collect();
+
+    let strip = |from: ProfilePoint, to: ProfilePoint| {
+        if segments > 1 {
+            return [from, to];
+        }
+        // Chamfers are flat, their normal is square to the strip
+        let along = Vec2::new(from.inset - to.inset, to.z - from.z);
+        let normal = along.perp().normalize();
+        [
+            ProfilePoint { normal, ..from },
+            ProfilePoint { normal, ..to },
+        ]
+    };
+    let mut strips: Vec<[ProfilePoint; 2]> = front.windows(2).map(|p| strip(p[0], p[1])).collect();
+    if bevel_depth < half {
+        strips.push([front[front.len() - 1], back[0]]);
+    }
+    strips.extend(back.windows(2).map(|p| strip(p[0], p[1])));
+    strips
+}
+
+/// Outward normal of the edge from `a` to `b` of a counter-clockwise loop.
+fn edge_normal(a: Vec2, b: Vec2) -> Vec2 {
+    -(b - a).perp().normalize()
+}
+
+/// Extrudes a flat triangle mesh in the xy plane, like the mesh of any
+/// shape, into a solid from `z = depth / 2` at the front to `-depth / 2` at
+/// the back.
+///
+/// The triangles become the front and back caps and every boundary loop,
+/// holes included, gets a side wall. Side normals are shared between
+/// segments meeting at less than [`CREASE_ANGLE`], so flattened curves look
+/// round and corners stay sharp. Side texture coordinates run around each
+/// loop in `u` and from front to back in `v`, caps use [`planar_uv`]. The
+/// bevel should stay narrower than the thinnest part of the shape.
+///
+/// Shapes in the scene stay flat, the solid is only returned to the caller.
+pub fn extrude(vertices: &[Vertex], indices: &[u16], depth: f32, bevel: Option<Bevel>) -> Mesh3d {
+    let mut welded = Welded::new(vertices, indices);
+    let loops = welded.boundary();
+    let mut on_boundary: Vec<u32> = loops.iter().flatten().copied().collect();
+    on_boundary.sort_unstable();
+    welded.split_at(&on_boundary);
+    let profile = side_profile(depth, bevel);
+    let cap_inset = profile[0][0].inset;
+    let mut mesh = Mesh3d::default();
+
+    // Offset of each boundary point that moves its edges inwards by one
+    let mut miters: HashMap<u32, Vec2> = HashMap::new();
+    for boundary in &loops {
+        let n = boundary.len();
+        let point = |i: usize| welded.points[boundary[i % n] as usize];
+        for (i, &index) in boundary.iter().enumerate() {
+            let before = edge_normal(point(i + n - 1), point(i));
+            let after = edge_normal(point(i), point(i + 1));
+            let cos = before.dot(after);
+            let miter = if cos > -0.99 {
+                (before + after) / (1.0 + cos)
+            } else {
+                after
+            };
+            miters
+                .entry(index)
+                .or_insert(miter.clamp_length_max(MAX_MITER));
+        }
+    }
+    let inset = |i: u32, amount: f32| {
+        welded.points[i as usize] - miters.get(&i).copied().unwrap_or(Vec2::ZERO) * amount
+    };
+
+    for (z, normal) in [(depth / 2.0, Vec3::Z), (-depth / 2.0, -Vec3::Z)] {
+        let first = mesh.vertex_count() as u32;
+        for i in 0..welded.points.len() as u32 {
+            let position = inset(i, cap_inset).extend(z);
+            let uv = Vec2::from(planar_uv(position.to_array()));
+            mesh.vertex(position, normal, uv);
+        }
+        for &[a, b, c] in &welded.triangles {
+            mesh.triangle(first + a, first + b, first + c);
+        }
+    }
+
+    let profile_lengths: Vec<f32> = profile
+        .iter()
+        .map(|[from, to]| Vec2::new(from.inset - to.inset, from.z - to.z).length())
+        .collect();
+    let profile_length: f32 = profile_lengths.iter().sum();
+    let smooth = CREASE_ANGLE.cos();
+    for boundary in &loops {
+        let n = boundary.len();
+        let point = |i: usize| welded.points[boundary[i % n] as usize];
+        let edge_lengths: Vec<f32> = (0..n).map(|i| point(i).distance(point(i + 1))).collect();
+        let perimeter: f32 = edge_lengths.iter().sum();
+        let mut along = 0.0;
+        for (i, &length) in edge_lengths.iter().enumerate() {
+            let edge = edge_normal(point(i), point(i + 1));
+            // Shared with the neighbouring edge when the corner is shallow
+            let corner_normal = |neighbour: Vec2| {
+                if edge.dot(neighbour) >= smooth {
+                    (edge + neighbour).normalize()
+                } else {
+                    edge
+                }
+            };
+            let ends = [
+                (
+                    i,
+                    corner_normal(edge_normal(point(i + n - 1), point(i))),
+                    along,
+                ),
+                (
+                    i + 1,
+                    corner_normal(edge_normal(point(i + 1), point(i + 2))),
+                    along + length,
+                ),
+            ];
+            along += length;
+
+            let mut v = 0.0;
+            for (strip, strip_length) in profile.iter().zip(&profile_lengths) {
+                let corners = [(0, 0), (1, 0), (1, 1), (0, 1)].map(|(end, side)| {
+                    let (k, outward, u) = ends[end];
+                    let p = strip[side];
+                    let position = inset(boundary[k % n], p.inset).extend(p.z);
+                    let normal = (outward * p.normal.x).extend(p.normal.y).normalize();
+                    let uv = Vec2::new(
+                        u / perimeter,
+                        (v + side as f32 * strip_length) / profile_length,
+                    );
+                    mesh.vertex(position, normal, uv)
+                });
+                mesh.triangle(corners[0], corners[1], corners[2]);
+                mesh.triangle(corners[0], corners[2], corners[3]);
+                v += strip_length;
+            }
+        }
+    }
+    mesh
+}
@@ -0,0 +1,136 @@
+use glam::{DVec2, Vec2};
+
+use crate::{geometry::mesh_builder::MeshBuilder, models::vertex::Vertex};
+
+/// Decides which regions of overlapping or self-intersecting contours are filled.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum FillRule {
+    /// Filled wherever the contours wind around the point at all
+    #[default]
+    NonZero,
+    /// Filled wherever the point is inside an odd number of contours
+    EvenOdd,
+}
+
+impl FillRule {
+    fn is_inside(self, winding: i32) -> bool {
+        match self {
+            FillRule::NonZero => winding != 0,
+            FillRule::EvenOdd => winding % 2 != 0,
+        }
+    }
+}
+
+/// Contour edge, stored top (smaller y) to bottom.
+struct Edge {
+    top: DVec2,
+    bottom: DVec2,
+    // +1 when the contour runs towards larger y along this edge, -1 otherwise
+    winding: i32,
+}
+
+impl Edge {
+    fn x_at(&self, y: f64) -> f64 {
+        let t = (y - self.top.y) / (self.bottom.y - self.top.y);
+        self.top.x + (self.bottom.x - self.top.x) * t
+    }
+
+    /// `y` where the two edges cross, when they do inside both of them.
+    fn crossing(&self, other: &Edge) -> Option<f64> {
+        let top = self.top.y.max(other.top.y);
+        let bottom = self.bottom.y.min(other.bottom.y);
+        if top >= bottom {
+            return None;
+        }
+        let d_top = self.x_at(top) - other.x_at(top);
+        let d_bottom = self.x_at(bottom) - other.x_at(bottom);
+        if d_top * d_bottom >= 0.0 {
+            return None;
+        }
+        Some(top + (bottom - top) * d_top / (d_top - d_bottom))
+    }
+}
+
+/// Fills closed contours according to `rule`.
+///
+/// The plane is cut into horizontal slabs at every contour vertex and edge
+/// crossing. Inside a slab no edges cross, so the filled spans between
+/// neighbouring edges are trapezoids. This handles holes, overlaps and self
+/// intersections alike, at the cost of more triangles than strictly needed.
+/// Contours are closed implicitly and their orientation only matters for
+/// [`FillRule::NonZero`].
+pub fn fill_contours(
+    contours: &[Vec<Vec2>],
+    rule: FillRule,
+    color: [f32; 3],
+) -> anyhow::Result<(Vec<Vertex>, Vec<u16>)> {
+    let mut edges = Vec::new();
+    for contour in contours {
+        for (i, &a) in contour.iter().enumerate() {
+            let b = contour[(i + 1) % contour.len()];
+            let (a, b) = (a.as_dvec2(), b.as_dvec2());
+            // Horizontal edges never change the winding inside a slab
+            if a.y == b.y || !a.is_finite() || !b.is_finite() {
+                continue;
+            }
+            edges.push(if a.y < b.y {
+                Edge {
+                    top: a,
+                    bottom: b,
+                    winding: 1,
+                }
+            } else {
+                Edge {
+                    top: b,
+                    bottom: a,
+                    winding: -1,
+                }
+            });
+        }
+    }
+
+    let mut ys: Vec<f64> = edges.iter().flat_map(|e| [e.top.y, e.bottom.y]).collect();
+    for (i, a) in edges.iter().enumerate() {
+        ys.extend(edges[i + 1..].iter().filter_map(|b| a.crossing(b)));
+    }
+    ys.sort_by(f64::total_cmp);
+    ys.dedup();
+
+    let mut builder = MeshBuilder::new(color);
+    let mut active: Vec<(f64, f64, f64, i32)> = Vec::new();
+    for slab in ys.windows(2) {
+        let (y0, y1) = (slab[0], slab[1]);
+        let mid = (y0 + y1) * 0.5;
+
+        active.clear();
+        active.extend(
+            edges
+                .iter()
+                .filter(|e| e.top.y < mid && mid < e.bottom.y)
+                .map(|e| (e.x_at(mid), e.x_at(y0), e.x_at(y1), e.winding)),
+        );
+        active.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut winding = 0;
+        let mut span_start = None;
+        for &(_, x0, x1, edge_winding) in &active {
+            let was_inside = rule.is_inside(winding);
+            winding += edge_winding;
+            match (was_inside, rule.is_inside(winding)) {
+                (false, true) => span_start = Some((x0, x1)),
+                (true, false) => {
+                    let (left0, left1) = span_start.take().expect("span was opened");
+                    builder.quad([
+                        DVec2::new(left0, y0).as_vec2(),
+                        DVec2::new(x0, y0).as_vec2(),
+                        DVec2::new(x1, y1).as_vec2(),
+                        DVec2::new(left1, y1).as_vec2(),
+                    ])?;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(builder.finish())
+}
@@ -0,0 +1,46 @@
+use glam::Vec2;
+
+/// Most segments a single curve is split into, however tight the tolerance.
+const MAX_SEGMENTS: u32 = 1024;
+
+fn segment_count(error_scale: f32, tolerance: f32) -> u32 {
+    if tolerance <= 0.0 || !error_scale.is_finite() {
+        return MAX_SEGMENTS;
+    }
+    ((error_scale / tolerance).sqrt().ceil() as u32).clamp(1, MAX_SEGMENTS)
+}
+
+/// Appends points approximating the quadratic Bézier from `p0` to `p2`,
+/// excluding `p0` itself. No point of the curve is farther than `tolerance`
+/// from the resulting polyline.
+pub fn flatten_quadratic(p0: Vec2, p1: Vec2, p2: Vec2, tolerance: f32, out: &mut Vec<Vec2>) {
+    // The second derivative is constant, 2 * (p0 - 2 p1 + p2). A chord over a
+    // parameter step h is at most h^2 / 8 times that away from the curve.
+    let dd = (p0 - 2.0 * p1 + p2).length();
+    let n = segment_count(dd / 4.0, tolerance);
+    for i in 1..=n {
+        let t = i as f32 / n as f32;
+        let mt = 1.0 - t;
+        out.push(p0 * (mt * mt) + p1 * (2.0 * mt * t) + p2 * (t * t));
+    }
+}
+
+/// Appends points approximating the cubic Bézier from `p0` to `p3`,
+/// excluding `p0` itself, within `tolerance`.
+pub fn flatten_cubic(p0: Vec2, p1: Vec2, p2: Vec2, p3: Vec2, tolerance: f32, out: &mut Vec<Vec2>) {
+    // The second derivative is bounded by 6 times the largest second difference
+    let dd = (p0 - 2.0 * p1 + p2)
+        .length()
+        .max((p1 - 2.0 * p2 + p3).length());
+    let n = segment_count(dd * 3.0 / 4.0, tolerance);
+    for i in 1..=n {
+        let t = i as f32 / n as f32;
+        let mt = 1.0 - t;
+        out.push(
+            p0 * (mt * mt * mt)
+                + p1 * (3.0 * mt * mt * t)
+                + p2 * (3.0 * mt * t * t)
+                + p3 * (t * t * t),
+        );
+    }
+}
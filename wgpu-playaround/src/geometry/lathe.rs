@@ -0,0 +1,82 @@
+use glam::{Vec2, Vec3};
+
+use crate::geometry::{
+    normals::CREASE_ANGLE,
+    primitives::{Mesh3d, azimuth},
+    triangulate::signed_area,
+};
+
+/// Revolves a profile around `+Y` into a vase, bottle or other shape of
+/// revolution, with `segments` columns around the axis.
+///
+/// Profile points are `(radius, height)`. The side facing away from the area
+/// between the profile and the axis is the outside, and the shape is closed
+/// when the profile starts and ends on the axis. Normals are shared between
+/// profile segments meeting at less than [`CREASE_ANGLE`]. Texture
+/// coordinates run around the axis in `u` and along the profile in `v`.
+/// Like the other [`Mesh3d`] generators, nothing in the scene draws it yet.
+pub fn lathe(profile: &[Vec2], segments: u32) -> Mesh3d {
+    let segments = segments.max(3);
+    let mut mesh = Mesh3d::default();
+    if profile.len() < 2 {
+        return mesh;
+    }
+
+    let mut closed: Vec<[f32; 2]> = profile.iter().map(|p| p.to_array()).collect();
+    closed.extend([[0.0, profile[profile.len() - 1].y], [0.0, profile[0].y]]);
+    let outside_left = signed_area(&closed) <= 0.0;
+    let normals: Vec<Vec2> = profile
+        .windows(2)
+        .map(|pair| {
+            let left = (pair[1] - pair[0]).perp().normalize_or_zero();
+            if outside_left { left } else { -left }
+        })
+        .collect();
+
+    let lengths: Vec<f32> = profile
+        .windows(2)
+        .map(|pair| pair[0].distance(pair[1]))
+        .collect();
+    let total: f32 = lengths.iter().sum();
+    let smooth = CREASE_ANGLE.cos();
+    let corner_normal = |own: Vec2, neighbour: Option<&Vec2>| match neighbour {
+        Some(&neighbour) if own.dot(neighbour) >= smooth => (own + neighbour).normalize(),
+        _ => own,
+    };
+
+    let mut v = 0.0;
+    for (i, (&normal, &length)) in normals.iter().zip(&lengths).enumerate() {
+        // Segments get their own rings so creases stay hard
+        let ends = [
+            (
+                profile[i],
+                corner_normal(normal, i.checked_sub(1).map(|j| &normals[j])),
+                v,
+            ),
+            (
+                profile[i + 1],
+                corner_normal(normal, normals.get(i + 1)),
+                v + length,
+            ),
+        ];
+        v += length;
+        let first = mesh.vertex_count() as u32;
+        for (point, normal, along) in ends {
+            for column in 0..=segments {
+                let out = azimuth(column, segments);
+                let position = out * point.x + Vec3::Y * point.y;
+                let normal = (out * normal.x + Vec3::Y * normal.y).normalize_or_zero();
+                let uv = Vec2::new(column as f32 / segments as f32, along / total);
+                mesh.vertex(position, normal, uv);
+            }
+        }
+        let row = segments + 1;
+        for column in 0..segments {
+            let (a, b) = (first + column, first + column + 1);
+            let (c, d) = (b + row, a + row);
+            mesh.triangle(a, b, c);
+            mesh.triangle(a, c, d);
+        }
+    }
+    mesh
+}
@@ -0,0 +1,109 @@
+use std::f32::consts::PI;
+
+use anyhow::bail;
+use glam::Vec2;
+
+use crate::models::vertex::Vertex;
+
+/// Collects triangles, fixing their winding so back face culling keeps them.
+pub(crate) struct MeshBuilder {
+    vertices: Vec<Vertex>,
+    indices: Vec<u16>,
+    color: [f32; 3],
+}
+
+impl MeshBuilder {
+    pub(crate) fn new(color: [f32; 3]) -> Self {
+        Self {
+            vertices: Vec::new(),
+            indices: Vec::new(),
+            color,
+        }
+    }
+
+    pub(crate) fn finish(self) -> (Vec<Vertex>, Vec<u16>) {
+        (self.vertices, self.indices)
+    }
+
+    /// Adds a vertex, failing once its index no longer fits the u16 index buffer.
+    pub(crate) fn vertex(&mut self, p: Vec2) -> anyhow::Result<u16> {
+        let index = self.vertices.len();
+        if index >= u16::MAX as usize {
+            bail!("mesh needs more than {} vertices", u16::MAX);
+        }
+        self.vertices.push(Vertex {
+            position: [p.x, p.y, 0.0],
+            color: self.color,
+        });
+        Ok(index as u16)
+    }
+
+    pub(crate) fn position(&self, index: u16) -> Vec2 {
+        let p = self.vertices[index as usize].position;
+        Vec2::new(p[0], p[1])
+    }
+
+    pub(crate) fn triangle(&mut self, a: u16, b: u16, c: u16) {
+        let (pa, pb, pc) = (self.position(a), self.position(b), self.position(c));
+        let area = (pb - pa).perp_dot(pc - pa);
+        if area > 0.0 {
+            self.indices.extend_from_slice(&[a, b, c]);
+        } else if area < 0.0 {
+            self.indices.extend_from_slice(&[a, c, b]);
+        }
+    }
+
+    pub(crate) fn quad(&mut self, [a, b, c, d]: [Vec2; 4]) -> anyhow::Result<()> {
+        let (a, b, c, d) = (self.vertex(a)?, self.vertex(b)?, self.vertex(c)?, self.vertex(d)?);
+        self.triangle(a, b, c);
+        self.triangle(a, c, d);
+        Ok(())
+    }
+
+    /// Triangle fan around `center` from angle `start`, sweeping `sweep` radians.
+    pub(crate) fn arc(
+        &mut self,
+        center: Vec2,
+        radius: f32,
+        start: f32,
+        sweep: f32,
+        tolerance: f32,
+    ) -> anyhow::Result<()> {
+        let segments = arc_segments(radius, sweep.abs(), tolerance);
+        let c = self.vertex(center)?;
+        let mut previous = self.vertex(center + radius * Vec2::from_angle(start))?;
+        for i in 1..=segments {
+            let angle = start + sweep * i as f32 / segments as f32;
+            let next = self.vertex(center + radius * Vec2::from_angle(angle))?;
+            self.triangle(c, previous, next);
+            previous = next;
+        }
+        Ok(())
+    }
+}
+
+/// Number of straight segments needed to stay within `tolerance` of an arc.
+fn arc_segments(radius: f32, sweep: f32, tolerance: f32) -> usize {
+    if radius <= tolerance || tolerance <= 0.0 {
+        return (sweep / (PI / 4.0)).ceil().max(1.0) as usize;
+    }
+    let step = 2.0 * (1.0 - tolerance / radius).acos();
+    (sweep / step).ceil().clamp(1.0, 256.0) as usize
+}
+
+/// Concatenates meshes into one, failing when it needs more than `u16` indices.
+pub(crate) fn merge_meshes(
+    meshes: impl IntoIterator<Item = (Vec<Vertex>, Vec<u16>)>,
+) -> anyhow::Result<(Vec<Vertex>, Vec<u16>)> {
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    for (mesh_vertices, mesh_indices) in meshes {
+        let base = vertices.len();
+        if base + mesh_vertices.len() > u16::MAX as usize {
+            bail!("mesh needs more than {} vertices", u16::MAX);
+        }
+        vertices.extend(mesh_vertices);
+        indices.extend(mesh_indices.into_iter().map(|i| i + base as u16));
+    }
+    Ok((vertices, indices))
+}
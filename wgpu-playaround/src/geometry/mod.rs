@@ -0,0 +1,12 @@
+pub mod extrude;
+pub mod fill;
+pub mod flatten;
+pub mod lathe;
+pub mod mesh_builder;
+pub mod morph;
+pub mod normals;
+pub mod outline;
+pub mod primitives;
+pub mod procedural;
+pub mod stroke;
+pub mod triangulate;
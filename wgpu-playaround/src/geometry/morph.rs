@@ -0,0 +1,74 @@
+use crate::{
+    geometry::{
+        outline::{Outline, lerp_vertex},
+        triangulate::triangulate_polygon,
+    },
+    models::vertex::Vertex,
+};
+
+/// Interpolates between two outlines over a fixed duration.
+pub struct Morph {
+    from: Outline,
+    to: Outline,
+    duration: f32,
+    elapsed: f32,
+}
+
+impl Morph {
+    /// Resamples both outlines to `count` points (or more if either outline
+    /// is already bigger) and lines up their starting points.
+    pub fn new(from: &Outline, to: &Outline, count: usize, duration: f32) -> Self {
+        let count = count.max(from.len()).max(to.len());
+        let to = to.resample(count);
+        let from = from.resample(count).aligned_to(&to);
+
+        Self {
+            from,
+            to,
+            duration: duration.max(0.0),
+            elapsed: 0.0,
+        }
+    }
+
+    pub fn advance(&mut self, dt: f32) {
+        self.elapsed = (self.elapsed + dt).min(self.duration);
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// Eased progress in `[0, 1]`.
+    pub fn progress(&self) -> f32 {
+        if self.duration <= 0.0 {
+            return 1.0;
+        }
+        let t = self.elapsed / self.duration;
+        t * t * (3.0 - 2.0 * t)
+    }
+
+    /// The outline at the current point of the morph.
+    pub fn current(&self) -> Outline {
+        let t = self.progress();
+        Outline {
+            points: self
+                .from
+                .points
+                .iter()
+                .zip(&self.to.points)
+                .map(|(a, b)| lerp_vertex(a, b, t))
+                .collect(),
+        }
+    }
+
+    /// Vertices and triangle indices for the current frame.
+    ///
+    /// Intermediate outlines can change from convex to concave, so they are
+    /// re-triangulated every time. Fails when the outline crosses itself on
+    /// the way.
+    pub fn mesh(&self) -> anyhow::Result<(Vec<Vertex>, Vec<u16>)> {
+        let outline = self.current();
+        let indices = triangulate_polygon(&outline.positions_2d())?;
+        Ok((outline.points, indices))
+    }
+}
@@ -0,0 +1,77 @@
+use anyhow::bail;
+use glam::Vec3;
+
+use crate::models::vertex::{LitVertex, Vertex};
+
+/// Neighbouring faces of extruded and lathed shapes that meet at less than
+/// this many radians share their normals, sharper corners stay hard.
+pub const CREASE_ANGLE: f32 = std::f32::consts::FRAC_PI_6;
+
+/// Normal of a counter-clockwise triangle, its length is twice the area.
+pub fn triangle_normal(a: Vec3, b: Vec3, c: Vec3) -> Vec3 {
+    (b - a).cross(c - a)
+}
+
+fn corners(vertices: &[Vertex], triangle: &[u16]) -> [Vec3; 3] {
+    [0, 1, 2].map(|k| Vec3::from_array(vertices[triangle[k] as usize].position))
+}
+
+/// Texture coordinates projected onto the xy plane, mapping the square the
+/// shapes are authored in to the whole texture.
+pub fn planar_uv(position: [f32; 3]) -> [f32; 2] {
+    [position[0] * 0.5 + 0.5, 0.5 - position[1] * 0.5]
+}
+
+fn lit(vertex: &Vertex, normal: Vec3) -> LitVertex {
+    // Flat shapes in the z = 0 plane face the 2D camera
+    let normal = normal.normalize_or(Vec3::Z);
+    LitVertex {
+        position: vertex.position,
+        color: vertex.color,
+        normal: normal.to_array(),
+        uv: planar_uv(vertex.position),
+    }
+}
+
+/// Gives every vertex the area weighted average normal of the triangles
+/// sharing it, so curved surfaces shade smoothly. The vertices keep their
+/// order and the indices stay valid. Texture coordinates are [`planar_uv`].
+pub fn smooth_normals(vertices: &[Vertex], indices: &[u16]) -> Vec<LitVertex> {
+    let mut normals = vec![Vec3::ZERO; vertices.len()];
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = corners(vertices, triangle);
+        let normal = triangle_normal(a, b, c);
+        for &index in triangle {
+            normals[index as usize] += normal;
+        }
+    }
+    vertices
+        .iter()
+        .zip(normals)
+        .map(|(vertex, normal)| lit(vertex, normal))
+        .collect()
+}
+
+/// Gives every triangle its own three vertices with the face normal, for
+/// hard edges. Fails when that needs more vertices than `u16` indices reach.
+pub fn flat_normals(
+    vertices: &[Vertex],
+    indices: &[u16],
+) -> anyhow::Result<(Vec<LitVertex>, Vec<u16>)> {
+    let count = indices.len() / 3 * 3;
+    if count > u16::MAX as usize {
+        bail!("flat shaded mesh needs more than {} vertices", u16::MAX);
+    }
+    let mut flat = Vec::with_capacity(count);
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = corners(vertices, triangle);
+        let normal = triangle_normal(a, b, c);
+        flat.extend(
+            triangle
+                .iter()
+                .map(|&index| lit(&vertices[index as usize], normal)),
+        );
+    }
+    let flat_indices = (0..flat.len() as u16).collect();
+    Ok((flat, flat_indices))
+}
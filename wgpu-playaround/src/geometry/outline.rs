@@ -0,0 +1,150 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{geometry::triangulate::signed_area, models::vertex::Vertex};
+
+/// A closed 2D outline. Points are stored counter-clockwise without repeating the first one.
+#[derive(Clone, Debug)]
+pub struct Outline {
+    pub points: Vec<Vertex>,
+}
+
+impl Outline {
+    /// Builds an outline by walking `vertices` in the order given by `order`.
+    pub fn from_indices(vertices: &[Vertex], order: &[u16]) -> Self {
+        Self {
+            points: order.iter().map(|&i| vertices[i as usize]).collect(),
+        }
+    }
+
+    /// Walks the boundary of a triangle mesh, for morphing shapes that only
+    /// exist as triangles. Boundary edges are the ones used by a single
+    /// triangle. When the mesh has several boundary loops (holes or separate
+    /// pieces) the longest one is returned.
+    pub fn from_mesh_boundary(vertices: &[Vertex], indices: &[u16]) -> Self {
+        let mut edges = HashSet::new();
+        for triangle in indices.chunks_exact(3) {
+            for k in 0..3 {
+                let (a, b) = (triangle[k], triangle[(k + 1) % 3]);
+                if !edges.remove(&(b, a)) {
+                    edges.insert((a, b));
+                }
+            }
+        }
+        let mut next: HashMap<u16, u16> = edges.into_iter().collect();
+
+        let mut longest: Vec<u16> = Vec::new();
+        while let Some((&start, _)) = next.iter().min() {
+            let mut order = vec![start];
+            let mut current = next.remove(&start);
+            while let Some(v) = current.filter(|&v| v != start) {
+                order.push(v);
+                current = next.remove(&v);
+            }
+            if order.len() > longest.len() {
+                longest = order;
+            }
+        }
+
+        let mut outline = Self::from_indices(vertices, &longest);
+        if signed_area(&outline.positions_2d()) < 0.0 {
+            outline.points.reverse();
+        }
+        outline
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn positions_2d(&self) -> Vec<[f32; 2]> {
+        self.points
+            .iter()
+            .map(|v| [v.position[0], v.position[1]])
+            .collect()
+    }
+
+    fn edge_length(&self, i: usize) -> f32 {
+        let a = self.points[i].position;
+        let b = self.points[(i + 1) % self.points.len()].position;
+        let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+        (dx * dx + dy * dy).sqrt()
+    }
+
+    /// Resamples the outline to exactly `count` points.
+    ///
+    /// The original corners are kept and extra points are inserted on the
+    /// longest edges, so a resampled star still has sharp tips. Positions and
+    /// colors of inserted points are linearly interpolated along the edge.
+    pub fn resample(&self, count: usize) -> Self {
+        let n = self.points.len();
+        if n == 0 || count <= n {
+            return self.clone();
+        }
+
+        // Number of segments each edge is split into
+        let mut splits = vec![1usize; n];
+        for _ in n..count {
+            let longest = (0..n)
+                .max_by(|&a, &b| {
+                    let la = self.edge_length(a) / splits[a] as f32;
+                    let lb = self.edge_length(b) / splits[b] as f32;
+                    la.total_cmp(&lb)
+                })
+                .unwrap();
+            splits[longest] += 1;
+        }
+
+        let mut points = Vec::with_capacity(count);
+        for (i, &segments) in splits.iter().enumerate() {
+            let a = self.points[i];
+            let b = self.points[(i + 1) % n];
+            for step in 0..segments {
+                points.push(lerp_vertex(&a, &b, step as f32 / segments as f32));
+            }
+        }
+
+        Self { points }
+    }
+
+    /// Rotates the starting point so that the outline lines up with `other` as
+    /// closely as possible. Both outlines must have the same number of points.
+    pub fn aligned_to(&self, other: &Outline) -> Self {
+        let n = self.points.len();
+        let cost = |offset: usize| -> f32 {
+            (0..n)
+                .map(|i| {
+                    let a = self.points[(i + offset) % n].position;
+                    let b = other.points[i].position;
+                    (a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)
+                })
+                .sum()
+        };
+        let best = (0..n)
+            .min_by(|&a, &b| cost(a).total_cmp(&cost(b)))
+            .unwrap_or(0);
+
+        let mut points = self.points.clone();
+        points.rotate_left(best);
+        Self { points }
+    }
+}
+
+pub fn lerp_vertex(a: &Vertex, b: &Vertex, t: f32) -> Vertex {
+    let lerp = |x: f32, y: f32| x + (y - x) * t;
+    Vertex {
+        position: [
+            lerp(a.position[0], b.position[0]),
+            lerp(a.position[1], b.position[1]),
+            lerp(a.position[2], b.position[2]),
+        ],
+        color: [
+            lerp(a.color[0], b.color[0]),
+            lerp(a.color[1], b.color[1]),
+            lerp(a.color[2], b.color[2]),
+        ],
+    }
+}
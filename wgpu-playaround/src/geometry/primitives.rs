@@ -0,0 +1,396 @@
+use std::{
+    collections::HashMap,
+    f32::consts::{PI, TAU},
+};
+
+use anyhow::bail;
+use glam::{Vec2, Vec3};
+
+use crate::{
+    environment::equirect_uv, geometry::normals::triangle_normal, models::vertex::LitVertex,
+};
+
+/// Indexed triangle mesh in 3D with per-vertex normals and texture
+/// coordinates. Shapes are centered on the origin with `+Y` up and their
+/// triangles wind counter-clockwise seen from outside.
+///
+/// Only the generators live here. The scene renders flat shapes without a
+/// depth buffer and does not draw these meshes, [`Mesh3d::lit_mesh`] is the
+/// hand off to a renderer that does.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Mesh3d {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    pub uvs: Vec<Vec2>,
+    pub indices: Vec<u32>,
+}
+
+impl Mesh3d {
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    pub(crate) fn vertex(&mut self, position: Vec3, normal: Vec3, uv: Vec2) -> u32 {
+        self.positions.push(position);
+        self.normals.push(normal);
+        self.uvs.push(uv);
+        (self.positions.len() - 1) as u32
+    }
+
+    /// Adds a triangle facing the way its vertex normals point. Triangles
+    /// collapsed to a line or point, like the ones touching a pole, are dropped.
+    pub(crate) fn triangle(&mut self, a: u32, b: u32, c: u32) {
+        let [pa, pb, pc] = [a, b, c].map(|i| self.positions[i as usize]);
+        let face = triangle_normal(pa, pb, pc);
+        if face == Vec3::ZERO {
+            return;
+        }
+        let normal: Vec3 = [a, b, c]
+            .map(|i| self.normals[i as usize])
+            .into_iter()
+            .sum();
+        if face.dot(normal) >= 0.0 {
+            self.indices.extend([a, b, c]);
+        } else {
+            self.indices.extend([a, c, b]);
+        }
+    }
+
+    /// Adds a `columns` by `rows` patch of quads. `surface` gives the position
+    /// and normal at a grid corner, texture coordinates span the whole patch.
+    fn grid(&mut self, columns: u32, rows: u32, surface: impl Fn(u32, u32) -> (Vec3, Vec3)) {
+        let first = self.positions.len() as u32;
+        for row in 0..=rows {
+            for column in 0..=columns {
+                let (position, normal) = surface(column, row);
+                let uv = Vec2::new(column as f32 / columns as f32, row as f32 / rows as f32);
+                self.vertex(position, normal, uv);
+            }
+        }
+        let corner = |column: u32, row: u32| first + row * (columns + 1) + column;
+        for row in 0..rows {
+            for column in 0..columns {
+                let a = corner(column, row);
+                let b = corner(column + 1, row);
+                let c = corner(column + 1, row + 1);
+                let d = corner(column, row + 1);
+                self.triangle(a, b, c);
+                self.triangle(a, c, d);
+            }
+        }
+    }
+
+    /// Flat disc closing the end of a shape of revolution at height `y`.
+    fn cap(&mut self, radius: f32, y: f32, segments: u32) {
+        let normal = Vec3::Y * y.signum();
+        let uv = |position: Vec3| {
+            Vec2::new(
+                0.5 + 0.5 * position.x / radius,
+                0.5 - 0.5 * position.z / radius,
+            )
+        };
+        let center = self.vertex(Vec3::new(0.0, y, 0.0), normal, Vec2::splat(0.5));
+        let ring: Vec<u32> = (0..segments)
+            .map(|i| {
+                let position = azimuth(i, segments) * radius + Vec3::Y * y;
+                self.vertex(position, normal, uv(position))
+            })
+            .collect();
+        for i in 0..ring.len() {
+            self.triangle(center, ring[i], ring[(i + 1) % ring.len()]);
+        }
+    }
+
+    /// Vertices and `u16` indices for the lit and PBR pipelines, failing when
+    /// the mesh has more vertices than they can address.
+    pub fn lit_mesh(&self, color: [f32; 3]) -> anyhow::Result<(Vec<LitVertex>, Vec<u16>)> {
+        if self.vertex_count() > u16::MAX as usize {
+            bail!("mesh needs more than {} vertices", u16::MAX);
+        }
+        let vertices = (0..self.vertex_count())
+            .map(|i| LitVertex {
+                position: self.positions[i].to_array(),
+                color,
+                normal: self.normals[i].to_array(),
+                uv: self.uvs[i].to_array(),
+            })
+            .collect();
+        let indices = self.indices.iter().map(|&i| i as u16).collect();
+        Ok((vertices, indices))
+    }
+}
+
+/// Direction in the xz plane `column` steps of `columns` around `+Y`. The
+/// last column lands exactly on the first so seams close, and the angles
+/// run like [`equirect_uv`] so panoramas wrap around shapes the same way.
+pub(crate) fn azimuth(column: u32, columns: u32) -> Vec3 {
+    let angle = TAU * (column % columns) as f32 / columns as f32;
+    Vec3::new(-angle.sin(), 0.0, angle.cos())
+}
+
+/// Sine and cosine of the angle from `+Y`, `step` of `steps` down to `-Y`,
+/// exact at the poles and the equator so rings meet.
+fn polar(step: u32, steps: u32) -> (f32, f32) {
+    match step {
+        0 => (0.0, 1.0),
+        s if s == steps => (0.0, -1.0),
+        s if 2 * s == steps => (1.0, 0.0),
+        s => (PI * s as f32 / steps as f32).sin_cos(),
+    }
+}
+
+/// Axis aligned cube with edges `size` long, each face split into
+/// `subdivisions` by `subdivisions` quads. Faces have their own vertices so
+/// the edges stay hard.
+pub fn cube(size: f32, subdivisions: u32) -> Mesh3d {
+    let subdivisions = subdivisions.max(1);
+    let mut mesh = Mesh3d::default();
+    // Normal and the directions of u and v on each face
+    let faces = [
+        (Vec3::X, -Vec3::Z, -Vec3::Y),
+        (-Vec3::X, Vec3::Z, -Vec3::Y),
+        (Vec3::Y, Vec3::X, Vec3::Z),
+        (-Vec3::Y, Vec3::X, -Vec3::Z),
+        (Vec3::Z, Vec3::X, -Vec3::Y),
+        (-Vec3::Z, -Vec3::X, -Vec3::Y),
+    ];
+    for (normal, u, v) in faces {
+        mesh.grid(subdivisions, subdivisions, |column, row| {
+            let s = 2.0 * column as f32 / subdivisions as f32 - 1.0;
+            let t = 2.0 * row as f32 / subdivisions as f32 - 1.0;
+            ((normal + u * s + v * t) * (size / 2.0), normal)
+        });
+    }
+    mesh
+}
+
+/// Square in the xz plane facing `+Y`, split into `subdivisions` quads along
+/// each edge. Texture coordinates run along `+X` and `+Z`.
+pub fn plane(size: f32, subdivisions: u32) -> Mesh3d {
+    let subdivisions = subdivisions.max(1);
+    let mut mesh = Mesh3d::default();
+    mesh.grid(subdivisions, subdivisions, |column, row| {
+        let x = column as f32 / subdivisions as f32 - 0.5;
+        let z = row as f32 / subdivisions as f32 - 0.5;
+        (Vec3::new(x, 0.0, z) * size, Vec3::Y)
+    });
+    mesh
+}
+
+/// Sphere of `segments` columns around `+Y` and `rings` rows from pole to
+/// pole, mapped like an equirectangular panorama.
+pub fn uv_sphere(radius: f32, segments: u32, rings: u32) -> Mesh3d {
+    let (segments, rings) = (segments.max(3), rings.max(2));
+    let mut mesh = Mesh3d::default();
+    mesh.grid(segments, rings, |column, row| {
+        let (sin, cos) = polar(row, rings);
+        let normal = azimuth(column, segments) * sin + Vec3::Y * cos;
+        (normal * radius, normal)
+    });
+    mesh
+}
+
+/// Sphere from an icosahedron whose triangles are split in four
+/// `subdivisions` times, more even than a [`uv_sphere`]. Vertices on the
+/// texture seam and at the poles are duplicated, so `u` runs past 1 along
+/// the seam and relies on repeating samplers.
+pub fn icosphere(radius: f32, subdivisions: u32) -> Mesh3d {
+    // 20 * 4^8 triangles is far past any useful detail
+    let subdivisions = subdivisions.min(8);
+    let phi = (1.0 + 5f32.sqrt()) / 2.0;
+    let mut directions: Vec<Vec3> = [
+        (-1.0, phi, 0.0),
+        (1.0, phi, 0.0),
+        (-1.0, -phi, 0.0),
+        (1.0, -phi, 0.0),
+        (0.0, -1.0, phi),
+        (0.0, 1.0, phi),
+        (0.0, -1.0, -phi),
+        (0.0, 1.0, -phi),
+        (phi, 0.0, -1.0),
+        (phi, 0.0, 1.0),
+        (-phi, 0.0, -1.0),
+        (-phi, 0.0, 1.0),
+    ]
+    .map(|(x, y, z)| Vec3::new(x, y, z).normalize())
+    .to_vec();
+    let mut triangles: Vec<[u32; 3]> = vec![
+        [0, 11, 5],
+        [0, 5, 1],
+        [0, 1, 7],
+        [0, 7, 10],
+        [0, 10, 11],
+        [1, 5, 9],
+        [5, 11, 4],
+        [11, 10, 2],
+        [10, 7, 6],
+        [7, 1, 8],
+        [3, 9, 4],
+        [3, 4, 2],
+        [3, 2, 6],
+        [3, 6, 8],
+        [3, 8, 9],
+        [4, 9, 5],
+        [2, 4, 11],
+        [6, 2, 10],
+        [8, 6, 7],
+        [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions {
+        // Edges share their midpoint with the triangle on the other side
+        let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+        let mut midpoint = |a: u32, b: u32| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                directions.push((directions[a as usize] + directions[b as usize]).normalize());
+                (directions.len() - 1) as u32
+            })
+        };
+        triangles = triangles
+            .into_iter()
+            .flat_map(|[a, b, c]| {
+                let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    let mut mesh = Mesh3d::default();
+    for &direction in &directions {
+        mesh.vertex(direction * radius, direction, equirect_uv(direction));
+    }
+    // The `u` of vertices at the poles depends on the triangle
+    let pole = |normal: Vec3| normal.x == 0.0 && normal.z == 0.0;
+    // Copies of seam vertices with `u` one higher
+    let mut wrapped: HashMap<u32, u32> = HashMap::new();
+    for mut triangle in triangles {
+        let (low, high) = triangle
+            .iter()
+            .map(|&i| i as usize)
+            .filter(|&i| !pole(mesh.normals[i]))
+            .map(|i| mesh.uvs[i].x)
+            .fold((f32::MAX, f32::MIN), |(low, high), u| {
+                (low.min(u), high.max(u))
+            });
+        if high - low > 0.5 {
+            for index in &mut triangle {
+                let i = *index as usize;
+                if mesh.uvs[i].x < 0.5 && !pole(mesh.normals[i]) {
+                    *index = *wrapped.entry(*index).or_insert_with(|| {
+                        mesh.vertex(mesh.positions[i], mesh.normals[i], mesh.uvs[i] + Vec2::X)
+                    });
+                }
+            }
+        }
+        for k in 0..3 {
+            let i = triangle[k] as usize;
+            if pole(mesh.normals[i]) {
+                let others = [triangle[(k + 1) % 3], triangle[(k + 2) % 3]];
+                let u = others.map(|j| mesh.uvs[j as usize].x);
+                let uv = Vec2::new((u[0] + u[1]) / 2.0, mesh.uvs[i].y);
+                triangle[k] = mesh.vertex(mesh.positions[i], mesh.normals[i], uv);
+            }
+        }
+        let [a, b, c] = triangle;
+        mesh.triangle(a, b, c);
+    }
+    mesh
+}
+
+/// Open tube around `+Y` between heights `bottom` and `top`, narrowing from
+/// `bottom_radius` to `top_radius`.
+fn tube(
+    mesh: &mut Mesh3d,
+    (bottom, top): (f32, f32),
+    (bottom_radius, top_radius): (f32, f32),
+    segments: u32,
+    height_segments: u32,
+) {
+    // Tilted out of the side by how much the radius shrinks over the height
+    let slope = (bottom_radius - top_radius) / (top - bottom);
+    mesh.grid(segments, height_segments, |column, row| {
+        let t = row as f32 / height_segments as f32;
+        let out = azimuth(column, segments);
+        let radius = top_radius + (bottom_radius - top_radius) * t;
+        let position = out * radius + Vec3::Y * (top + (bottom - top) * t);
+        (position, (out + Vec3::Y * slope).normalize())
+    });
+}
+
+/// Closed cylinder around `+Y` with `segments` columns and
+/// `height_segments` rows on its side.
+pub fn cylinder(radius: f32, height: f32, segments: u32, height_segments: u32) -> Mesh3d {
+    let (segments, height_segments) = (segments.max(3), height_segments.max(1));
+    let mut mesh = Mesh3d::default();
+    let half = height / 2.0;
+    tube(
+        &mut mesh,
+        (-half, half),
+        (radius, radius),
+        segments,
+        height_segments,
+    );
+    mesh.cap(radius, half, segments);
+    mesh.cap(radius, -half, segments);
+    mesh
+}
+
+/// Cone around `+Y` with its tip at the top and a capped base. Each column
+/// keeps its own normal at the tip, so the side shades smoothly.
+pub fn cone(radius: f32, height: f32, segments: u32, height_segments: u32) -> Mesh3d {
+    let (segments, height_segments) = (segments.max(3), height_segments.max(1));
+    let mut mesh = Mesh3d::default();
+    let half = height / 2.0;
+    tube(
+        &mut mesh,
+        (-half, half),
+        (radius, 0.0),
+        segments,
+        height_segments,
+    );
+    mesh.cap(radius, -half, segments);
+    mesh
+}
+
+/// Ring around `+Y` through the xz plane. `major_radius` reaches the middle
+/// of the tube, which is `minor_radius` thick, with `segments` columns
+/// around the ring and `sides` around the tube.
+pub fn torus(major_radius: f32, minor_radius: f32, segments: u32, sides: u32) -> Mesh3d {
+    let (segments, sides) = (segments.max(3), sides.max(3));
+    let mut mesh = Mesh3d::default();
+    mesh.grid(segments, sides, |column, row| {
+        let out = azimuth(column, segments);
+        // Starts on the outside of the ring and goes over the top
+        let angle = TAU * (row % sides) as f32 / sides as f32;
+        let normal = out * angle.cos() + Vec3::Y * angle.sin();
+        (out * major_radius + normal * minor_radius, normal)
+    });
+    mesh
+}
+
+/// Cylinder of `length` with hemispheres of `radius` on both ends, so
+/// `length + 2 * radius` tall. Each hemisphere has `rings` rows.
+pub fn capsule(radius: f32, length: f32, segments: u32, rings: u32) -> Mesh3d {
+    let (segments, rings) = (segments.max(3), rings.max(1));
+    let mut mesh = Mesh3d::default();
+    let half = length / 2.0;
+    mesh.grid(segments, 2 * rings + 1, |column, row| {
+        let (sin, cos, center) = match row {
+            row if row <= rings => {
+                let (sin, cos) = polar(row, 2 * rings);
+                (sin, cos, half)
+            }
+            row => {
+                let (sin, cos) = polar(row - 1, 2 * rings);
+                (sin, cos, -half)
+            }
+        };
+        let normal = azimuth(column, segments) * sin + Vec3::Y * cos;
+        (normal * radius + Vec3::Y * center, normal)
+    });
+    mesh
+}
@@ -0,0 +1,42 @@
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+use crate::{geometry::outline::Outline, models::vertex::Vertex};
+
+/// Fully saturated color for an angle in radians, going once around the color wheel.
+fn hue_color(angle: f32) -> [f32; 3] {
+    let channel = |offset: f32| 0.5 + 0.5 * (angle - offset).cos();
+    [channel(0.0), channel(TAU / 3.0), channel(2.0 * TAU / 3.0)]
+}
+
+fn point(angle: f32, radius: f32) -> Vertex {
+    Vertex {
+        position: [radius * angle.cos(), radius * angle.sin(), 0.0],
+        color: hue_color(angle),
+    }
+}
+
+/// Regular polygon centered on the origin with its first corner pointing up.
+pub fn regular_polygon(sides: u32, radius: f32) -> Outline {
+    let sides = sides.max(3);
+    let points = (0..sides)
+        .map(|i| point(FRAC_PI_2 + TAU * i as f32 / sides as f32, radius))
+        .collect();
+    Outline { points }
+}
+
+/// Star with `points` tips, alternating between the outer and inner radius.
+pub fn star(points: u32, outer_radius: f32, inner_radius: f32) -> Outline {
+    let points = points.max(2);
+    let step = PI / points as f32;
+    let points = (0..points * 2)
+        .map(|i| {
+            let radius = if i % 2 == 0 {
+                outer_radius
+            } else {
+                inner_radius
+            };
+            point(FRAC_PI_2 + step * i as f32, radius)
+        })
+        .collect();
+    Outline { points }
+}
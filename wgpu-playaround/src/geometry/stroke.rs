@@ -0,0 +1,226 @@
+use std::f32::consts::PI;
+
+use glam::Vec2;
+
+use crate::{geometry::mesh_builder::MeshBuilder, models::vertex::Vertex};
+
+/// Points closer than this are merged before stroking.
+const MERGE_DISTANCE: f32 = 1e-6;
+
+/// Shape drawn where two segments meet.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineJoin {
+    /// Outer edges extended until they meet, falling back to a bevel past the miter limit
+    Miter,
+    /// Arc around the corner
+    Round,
+    /// Outer corners connected with a straight edge
+    Bevel,
+}
+
+/// Shape drawn at both ends of an open path.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineCap {
+    /// Ends exactly at the end point
+    Butt,
+    /// Half circle around the end point
+    Round,
+    /// Extends half the width past the end point
+    Square,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StrokeStyle {
+    pub width: f32,
+    pub join: LineJoin,
+    pub cap: LineCap,
+    /// Longest allowed miter as a multiple of half the width, like SVG's `stroke-miterlimit`
+    pub miter_limit: f32,
+    pub color: [f32; 3],
+    /// Largest distance between round joins and caps and the true arc
+    pub tolerance: f32,
+}
+
+impl Default for StrokeStyle {
+    fn default() -> Self {
+        Self {
+            width: 0.02,
+            join: LineJoin::Miter,
+            cap: LineCap::Butt,
+            miter_limit: 4.0,
+            color: [1.0, 1.0, 1.0],
+            tolerance: 0.0005,
+        }
+    }
+}
+
+/// Drops repeated points, including a closing point equal to the first one.
+fn dedup(points: &[Vec2], closed: bool) -> Vec<Vec2> {
+    let mut result: Vec<Vec2> = Vec::with_capacity(points.len());
+    for &p in points {
+        if !p.is_finite() {
+            continue;
+        }
+        if result
+            .last()
+            .is_none_or(|&last| last.distance(p) > MERGE_DISTANCE)
+        {
+            result.push(p);
+        }
+    }
+    if closed && result.len() > 1 && result[0].distance(result[result.len() - 1]) <= MERGE_DISTANCE
+    {
+        result.pop();
+    }
+    result
+}
+
+/// Fills the gap on the outside of the corner at `p` between a segment coming
+/// in along `d0` and one leaving along `d1`, both normalized.
+fn join(
+    builder: &mut MeshBuilder,
+    p: Vec2,
+    d0: Vec2,
+    d1: Vec2,
+    style: &StrokeStyle,
+) -> anyhow::Result<()> {
+    let half = style.width * 0.5;
+    let turn = d0.perp_dot(d1);
+    if turn.abs() <= f32::EPSILON && d0.dot(d1) > 0.0 {
+        // Straight on, the segments already touch
+        return Ok(());
+    }
+
+    // The outer side is on the right for a left turn and vice versa.
+    // A full reversal has no outer side, so the left one is picked.
+    let side = if turn > 0.0 { -1.0 } else { 1.0 };
+    let n0 = d0.perp() * side;
+    let n1 = d1.perp() * side;
+    let a = p + n0 * half;
+    let b = p + n1 * half;
+
+    match style.join {
+        LineJoin::Round => {
+            builder.arc(p, half, n0.to_angle(), n0.angle_to(n1), style.tolerance)?;
+        }
+        LineJoin::Miter => {
+            // Half the angle between the normals decides how far the tip sticks out
+            let cos_half = ((1.0 + n0.dot(n1)) * 0.5).max(0.0).sqrt();
+            if cos_half > 0.0 && 1.0 / cos_half <= style.miter_limit {
+                let tip = p + (n0 + n1).normalize() * (half / cos_half);
+                builder.quad([p, a, tip, b])?;
+            } else {
+                bevel(builder, p, a, b)?;
+            }
+        }
+        LineJoin::Bevel => bevel(builder, p, a, b)?,
+    }
+    Ok(())
+}
+
+fn bevel(builder: &mut MeshBuilder, p: Vec2, a: Vec2, b: Vec2) -> anyhow::Result<()> {
+    let (c, a, b) = (builder.vertex(p)?, builder.vertex(a)?, builder.vertex(b)?);
+    builder.triangle(c, a, b);
+    Ok(())
+}
+
+/// Turns a polyline into triangles covering a band of `style.width` around it.
+///
+/// With `closed` the last point connects back to the first one and every
+/// corner gets a join, otherwise both ends get caps. Repeated points are
+/// ignored. A path that collapses to a single point still draws a dot for
+/// round and square caps. Segments are drawn as separate quads that overlap
+/// on the inside of corners, which is fine for opaque colors. Fails when the
+/// stroke needs more vertices than u16 indices can reach.
+pub fn stroke_polyline(
+    points: &[Vec2],
+    closed: bool,
+    style: &StrokeStyle,
+) -> anyhow::Result<(Vec<Vertex>, Vec<u16>)> {
+    let mut builder = MeshBuilder::new(style.color);
+    let half = style.width * 0.5;
+    let points = dedup(points, closed);
+    if points.is_empty() || !half.is_finite() || half <= 0.0 {
+        return Ok(builder.finish());
+    }
+
+    if points.len() == 1 {
+        let p = points[0];
+        match style.cap {
+            LineCap::Butt => {}
+            LineCap::Round => builder.arc(p, half, 0.0, 2.0 * PI, style.tolerance)?,
+            LineCap::Square => builder.quad([
+                p + Vec2::new(-half, -half),
+                p + Vec2::new(half, -half),
+                p + Vec2::new(half, half),
+                p + Vec2::new(-half, half),
+            ])?,
+        }
+        return Ok(builder.finish());
+    }
+
+    // Two points closed would trace the same segment twice
+    let closed = closed && points.len() > 2;
+    let segment_count = if closed {
+        points.len()
+    } else {
+        points.len() - 1
+    };
+    let segment = |i: usize| (points[i], points[(i + 1) % points.len()]);
+    let direction = |i: usize| {
+        let (a, b) = segment(i);
+        (b - a).normalize()
+    };
+
+    for i in 0..segment_count {
+        let (mut a, mut b) = segment(i);
+        let d = direction(i);
+        if !closed && style.cap == LineCap::Square {
+            if i == 0 {
+                a -= d * half;
+            }
+            if i == segment_count - 1 {
+                b += d * half;
+            }
+        }
+        let n = d.perp() * half;
+        builder.quad([a - n, b - n, b + n, a + n])?;
+    }
+
+    let corners = if closed {
+        0..points.len()
+    } else {
+        1..points.len() - 1
+    };
+    for i in corners {
+        let previous = (i + segment_count - 1) % segment_count;
+        join(
+            &mut builder,
+            points[i],
+            direction(previous),
+            direction(i),
+            style,
+        )?;
+    }
+
+    if !closed && style.cap == LineCap::Round {
+        let first = direction(0);
+        let last = direction(segment_count - 1);
+        builder.arc(
+            points[0],
+            half,
+            first.perp().to_angle(),
+            PI,
+            style.tolerance,
+        )?;
+        builder.arc(
+            points[points.len() - 1],
+            half,
+            (-last.perp()).to_angle(),
+            PI,
+            style.tolerance,
+        )?;
+    }
+
+    Ok(builder.finish())
+}
@@ -0,0 +1,104 @@
+/// Twice the signed area of a polygon. Positive when the points wind counter-clockwise.
+pub fn signed_area(points: &[[f32; 2]]) -> f32 {
+    let mut area = 0.0;
+    for i in 0..points.len() {
+        let a = points[i];
+        let b = points[(i + 1) % points.len()];
+        area += a[0] * b[1] - b[0] * a[1];
+    }
+    area
+}
+
+fn cross(o: [f32; 2], a: [f32; 2], b: [f32; 2]) -> f32 {
+    (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0])
+}
+
+fn point_in_triangle(p: [f32; 2], a: [f32; 2], b: [f32; 2], c: [f32; 2]) -> bool {
+    cross(a, b, p) >= 0.0 && cross(b, c, p) >= 0.0 && cross(c, a, p) >= 0.0
+}
+
+/// Whether two edges of the closed outline cross. Edges that only touch do not count.
+fn crosses_itself(points: &[[f32; 2]]) -> bool {
+    let n = points.len();
+    let edge = |i: usize| (points[i], points[(i + 1) % n]);
+    (0..n).any(|i| {
+        let (a, b) = edge(i);
+        // Neighbouring edges share a point and are skipped
+        (i + 2..n).filter(|&j| (j + 1) % n != i).any(|j| {
+            let (c, d) = edge(j);
+            cross(a, b, c) * cross(a, b, d) < 0.0 && cross(c, d, a) * cross(c, d, b) < 0.0
+        })
+    })
+}
+
+/// Whether `b` adds no area between its neighbours, because it repeats one
+/// of them or lies on the line through them. Relative to the edge lengths so
+/// that small shapes are judged like big ones.
+fn is_degenerate(a: [f32; 2], b: [f32; 2], c: [f32; 2]) -> bool {
+    let length = |p: [f32; 2], q: [f32; 2]| (q[0] - p[0]).hypot(q[1] - p[1]);
+    cross(a, b, c).abs() <= 1e-6 * length(a, b) * length(b, c)
+}
+
+/// Triangulates a simple polygon with ear clipping.
+///
+/// The returned indices refer to `points` and always wind counter-clockwise, so
+/// they survive the back-face culling used by the pipelines. Repeated and
+/// collinear points get no triangles of their own. Fails for outlines that
+/// cross themselves and for outlines too long for `u16` indices.
+pub fn triangulate_polygon(points: &[[f32; 2]]) -> anyhow::Result<Vec<u16>> {
+    if points.len() > u16::MAX as usize + 1 {
+        anyhow::bail!("{} points do not fit in u16 indices", points.len());
+    }
+    if points.len() < 3 {
+        return Ok(Vec::new());
+    }
+    if crosses_itself(points) {
+        anyhow::bail!("the outline crosses itself");
+    }
+
+    let mut remaining: Vec<usize> = (0..points.len()).collect();
+    if signed_area(points) < 0.0 {
+        remaining.reverse();
+    }
+
+    let mut indices = Vec::with_capacity((points.len() - 2) * 3);
+    while remaining.len() >= 3 {
+        let n = remaining.len();
+        let corner = |i: usize| {
+            (
+                points[remaining[(i + n - 1) % n]],
+                points[remaining[i]],
+                points[remaining[(i + 1) % n]],
+            )
+        };
+        if let Some(i) = (0..n).find(|&i| {
+            let (a, b, c) = corner(i);
+            is_degenerate(a, b, c)
+        }) {
+            remaining.remove(i);
+            continue;
+        }
+
+        let ear = (0..n).find(|&i| {
+            let (a, b, c) = corner(i);
+            if cross(a, b, c) <= 0.0 {
+                return false;
+            }
+            remaining.iter().all(|&other| {
+                let p = points[other];
+                p == a || p == b || p == c || !point_in_triangle(p, a, b, c)
+            })
+        });
+        let Some(i) = ear else {
+            anyhow::bail!("no ear left to clip, the outline overlaps itself");
+        };
+        indices.extend_from_slice(&[
+            remaining[(i + n - 1) % n] as u16,
+            remaining[i] as u16,
+            remaining[(i + 1) % n] as u16,
+        ]);
+        remaining.remove(i);
+    }
+
+    Ok(indices)
+}
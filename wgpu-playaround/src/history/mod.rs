@@ -0,0 +1,155 @@
+/// A reversible edit of some target `T`.
+pub trait Command<T> {
+    fn apply(&self, target: &mut T);
+    fn revert(&self, target: &mut T);
+
+    /// Folds `next` into `self` so both undo in one step. Only called while a
+    /// group is open, e.g. for every mouse move of a drag. Returns false when
+    /// the commands can not be combined.
+    fn merge(&mut self, _next: &Self) -> bool {
+        false
+    }
+
+    /// Approximate memory held by the command, used to cap the history size.
+    fn size_bytes(&self) -> usize {
+        std::mem::size_of_val(self)
+    }
+}
+
+struct Entry<C> {
+    command: C,
+    // Cached `size_bytes`, so trimming does not need to know the target type
+    size: usize,
+}
+
+/// Undo and redo stacks of commands applied to a target.
+///
+/// The history does not own the target, so it can be driven from `State` as
+/// well as from tests without any GPU objects around.
+pub struct History<C> {
+    undo: Vec<Entry<C>>,
+    redo: Vec<Entry<C>>,
+    max_bytes: usize,
+    bytes: usize,
+    group_open: bool,
+    // Whether the last undo entry was pushed by the open group
+    group_started: bool,
+}
+
+impl<C> History<C> {
+    /// Oldest commands are dropped once the undo stack holds more than `max_bytes`.
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            undo: Vec::new(),
+            redo: Vec::new(),
+            max_bytes,
+            bytes: 0,
+            group_open: false,
+            group_started: false,
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    pub fn undo_len(&self) -> usize {
+        self.undo.len()
+    }
+
+    pub fn redo_len(&self) -> usize {
+        self.redo.len()
+    }
+
+    /// Memory used by the undo stack, as reported by `Command::size_bytes`.
+    pub fn size_bytes(&self) -> usize {
+        self.bytes
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+        self.bytes = 0;
+        self.end_group();
+    }
+
+    /// Starts a group: commands executed until `end_group` are merged where possible.
+    pub fn begin_group(&mut self) {
+        self.group_open = true;
+        self.group_started = false;
+    }
+
+    pub fn end_group(&mut self) {
+        self.group_open = false;
+        self.group_started = false;
+    }
+
+    /// Applies `command` to `target` and records it.
+    pub fn execute<T>(&mut self, command: C, target: &mut T)
+    where
+        C: Command<T>,
+    {
+        command.apply(target);
+        self.redo.clear();
+
+        if self.group_open && self.group_started {
+            let last = self.undo.last_mut().expect("group has a command");
+            if last.command.merge(&command) {
+                let size = last.command.size_bytes();
+                self.bytes = self.bytes - last.size + size;
+                last.size = size;
+                self.trim();
+                return;
+            }
+        }
+
+        let size = command.size_bytes();
+        self.bytes += size;
+        self.undo.push(Entry { command, size });
+        self.group_started = self.group_open;
+        self.trim();
+    }
+
+    /// Reverts the last command. Returns false when there was nothing to undo.
+    pub fn undo<T>(&mut self, target: &mut T) -> bool
+    where
+        C: Command<T>,
+    {
+        self.end_group();
+        let Some(entry) = self.undo.pop() else {
+            return false;
+        };
+        entry.command.revert(target);
+        self.bytes -= entry.size;
+        self.redo.push(entry);
+        true
+    }
+
+    /// Re-applies the last undone command. Returns false when there was nothing to redo.
+    pub fn redo<T>(&mut self, target: &mut T) -> bool
+    where
+        C: Command<T>,
+    {
+        self.end_group();
+        let Some(entry) = self.redo.pop() else {
+            return false;
+        };
+        entry.command.apply(target);
+        self.bytes += entry.size;
+        self.undo.push(entry);
+        self.trim();
+        true
+    }
+
+    fn trim(&mut self) {
+        // The newest command is always kept, even if it alone is over the cap
+        while self.bytes > self.max_bytes && self.undo.len() > 1 {
+            let oldest = self.undo.remove(0);
+            self.bytes -= oldest.size;
+        }
+    }
+}
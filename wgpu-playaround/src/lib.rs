@@ -3,3 +3,21 @@
 pub mod models;
 pub mod consts;
 pub mod enums;
+pub mod geometry;
+pub mod recording;
+pub mod picking;
+pub mod editor;
+pub mod history;
+pub mod document;
+pub mod svg;
+pub mod obj;
+pub mod text;
+pub mod ui;
+pub mod debug_draw;
+pub mod particles;
+pub mod post;
+pub mod lighting;
+pub mod material;
+pub mod environment;
+pub mod controls;
+pub mod color;
@@ -0,0 +1,323 @@
+use wgpu::util::DeviceExt;
+
+use super::shadow::{MAX_SHADOW_MAPS, ShadowSettings, ShadowView};
+use crate::models::vertex::LitVertex;
+
+/// A depth texture array with one layer per [`ShadowView`].
+struct MapArray {
+    // The whole array, sampled by the lit pass
+    view: wgpu::TextureView,
+    // One per layer, rendered by the depth passes
+    layer_views: Vec<wgpu::TextureView>,
+    resolution: u32,
+}
+
+impl MapArray {
+    fn new(device: &wgpu::Device, resolution: u32) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Shadow Maps"),
+            size: wgpu::Extent3d {
+                width: resolution,
+                height: resolution,
+                depth_or_array_layers: MAX_SHADOW_MAPS as u32,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: ShadowMaps::FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            dimension: Some(wgpu::TextureViewDimension::D2Array),
+            ..Default::default()
+        });
+        let layer_views = (0..MAX_SHADOW_MAPS as u32)
+            .map(|layer| {
+                texture.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("Shadow Map Layer"),
+                    dimension: Some(wgpu::TextureViewDimension::D2),
+                    base_array_layer: layer,
+                    array_layer_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect();
+        Self {
+            view,
+            layer_views,
+            resolution,
+        }
+    }
+}
+
+/// Renders the shadow casters from each shadow-casting light and provides
+/// the result to the lit pass.
+///
+/// [`ShadowMaps::bind_group_layout`] is group 2 of the lit pipeline. The
+/// light transforms of the depth passes live in a single uniform buffer read
+/// at dynamic offsets.
+pub struct ShadowMaps {
+    pipeline: wgpu::RenderPipeline,
+    layer_buffer: wgpu::Buffer,
+    layer_stride: u32,
+    layer_bind_group: wgpu::BindGroup,
+    bind_group_layout: wgpu::BindGroupLayout,
+    uniform_buffer: wgpu::Buffer,
+    sampler: wgpu::Sampler,
+    maps: MapArray,
+    bind_group: wgpu::BindGroup,
+    layer_count: usize,
+}
+
+impl ShadowMaps {
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+    pub fn new(device: &wgpu::Device, settings: &ShadowSettings) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Shadow Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shadow.wgsl").into()),
+        });
+
+        let alignment = device.limits().min_uniform_buffer_offset_alignment;
+        let layer_size = std::mem::size_of::<[[f32; 4]; 4]>() as u64;
+        let layer_stride = (layer_size as u32).next_multiple_of(alignment);
+        let layer_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Shadow Layer Buffer"),
+            size: layer_stride as u64 * MAX_SHADOW_MAPS as u64,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let layer_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Shadow Layer Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: true,
+                    min_binding_size: wgpu::BufferSize::new(layer_size),
+                },
+                count: None,
+            }],
+        });
+        let layer_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Shadow Layer Bind Group"),
+            layout: &layer_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: &layer_buffer,
+                    offset: 0,
+                    size: wgpu::BufferSize::new(layer_size),
+                }),
+            }],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Shadow Pipeline Layout"),
+            bind_group_layouts: &[&layer_layout],
+            immediate_size: 0,
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Shadow Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_shadow"),
+                buffers: &[LitVertex::desc()],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: None,
+            // Flat shapes cast shadows from both sides
+            primitive: wgpu::PrimitiveState {
+                cull_mode: None,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: Self::FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview_mask: None,
+            cache: None,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Shadows Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2Array,
+                        sample_type: wgpu::TextureSampleType::Depth,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
+                    count: None,
+                },
+            ],
+        });
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Shadows Buffer"),
+            contents: bytemuck::bytes_of(&settings.uniform(&[])),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        // Linear filtering compares the four nearest texels, on top of the PCF kernel
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Shadow Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            compare: Some(wgpu::CompareFunction::LessEqual),
+            ..Default::default()
+        });
+        let maps = MapArray::new(device, clamp_resolution(device, settings.resolution));
+        let bind_group =
+            create_bind_group(device, &bind_group_layout, &uniform_buffer, &maps, &sampler);
+
+        Self {
+            pipeline,
+            layer_buffer,
+            layer_stride,
+            layer_bind_group,
+            bind_group_layout,
+            uniform_buffer,
+            sampler,
+            maps,
+            bind_group,
+            layer_count: 0,
+        }
+    }
+
+    pub fn bind_group_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.bind_group_layout
+    }
+
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    pub fn resolution(&self) -> u32 {
+        self.maps.resolution
+    }
+
+    /// Uploads the light transforms of `views`, recreating the maps when the
+    /// resolution changed.
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        views: &[ShadowView],
+        settings: &ShadowSettings,
+    ) {
+        let resolution = clamp_resolution(device, settings.resolution);
+        if resolution != self.maps.resolution {
+            self.maps = MapArray::new(device, resolution);
+            self.bind_group = create_bind_group(
+                device,
+                &self.bind_group_layout,
+                &self.uniform_buffer,
+                &self.maps,
+                &self.sampler,
+            );
+        }
+        self.layer_count = views.len().min(MAX_SHADOW_MAPS);
+        for (layer, view) in views[..self.layer_count].iter().enumerate() {
+            queue.write_buffer(
+                &self.layer_buffer,
+                (layer as u32 * self.layer_stride) as wgpu::BufferAddress,
+                bytemuck::bytes_of(&view.view_proj.to_cols_array_2d()),
+            );
+        }
+        queue.write_buffer(
+            &self.uniform_buffer,
+            0,
+            bytemuck::bytes_of(&settings.uniform(views)),
+        );
+    }
+
+    /// Records a depth pass per prepared layer. `draw_casters` binds a
+    /// [`LitVertex`] buffer and issues the draws.
+    pub fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        mut draw_casters: impl FnMut(&mut wgpu::RenderPass),
+    ) {
+        for (layer, view) in self.maps.layer_views[..self.layer_count].iter().enumerate() {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Shadow Pass"),
+                color_attachments: &[],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+                occlusion_query_set: None,
+                timestamp_writes: None,
+                multiview_mask: None,
+            });
+            render_pass.set_pipeline(&self.pipeline);
+            render_pass.set_bind_group(
+                0,
+                &self.layer_bind_group,
+                &[layer as u32 * self.layer_stride],
+            );
+            draw_casters(&mut render_pass);
+        }
+    }
+}
+
+fn clamp_resolution(device: &wgpu::Device, resolution: u32) -> u32 {
+    resolution.clamp(1, device.limits().max_texture_dimension_2d)
+}
+
+fn create_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    uniform_buffer: &wgpu::Buffer,
+    maps: &MapArray,
+    sampler: &wgpu::Sampler,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Shadows Bind Group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::TextureView(&maps.view),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: wgpu::BindingResource::Sampler(sampler),
+            },
+        ],
+    })
+}
@@ -0,0 +1,282 @@
+pub mod gpu;
+pub mod shadow;
+
+use bytemuck::{Pod, Zeroable};
+use glam::{Vec3, Vec4};
+
+/// Size of the light array in `lights.wgsl`, extra lights are ignored.
+pub const MAX_LIGHTS: usize = 8;
+
+// Distances below this do not make a light any brighter
+const MIN_LIGHT_DISTANCE: f32 = 0.1;
+
+/// A light source in world space. Intensities are linear HDR multipliers of
+/// the color.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Light {
+    /// Infinitely far away, like the sun
+    Directional {
+        /// Direction the light travels in
+        direction: Vec3,
+        color: Vec3,
+        intensity: f32,
+    },
+    /// Shines in every direction, fading out towards `range`
+    Point {
+        position: Vec3,
+        color: Vec3,
+        intensity: f32,
+        range: f32,
+    },
+    /// A point light limited to a cone, fading out between the two angles
+    Spot {
+        position: Vec3,
+        direction: Vec3,
+        color: Vec3,
+        intensity: f32,
+        range: f32,
+        /// Half angles of the cone in radians
+        inner_angle: f32,
+        outer_angle: f32,
+    },
+}
+
+impl Light {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Light::Directional { .. } => "Directional",
+            Light::Point { .. } => "Point",
+            Light::Spot { .. } => "Spot",
+        }
+    }
+
+    pub fn intensity_mut(&mut self) -> &mut f32 {
+        match self {
+            Light::Directional { intensity, .. }
+            | Light::Point { intensity, .. }
+            | Light::Spot { intensity, .. } => intensity,
+        }
+    }
+
+    /// Unit direction from `point` towards the light and the light arriving
+    /// there, after distance falloff and the spot cone.
+    pub fn incident(&self, point: Vec3) -> (Vec3, Vec3) {
+        match *self {
+            Light::Directional {
+                direction,
+                color,
+                intensity,
+            } => (-direction.normalize_or_zero(), color * intensity),
+            Light::Point {
+                position,
+                color,
+                intensity,
+                range,
+            } => {
+                let (to_light, falloff) = falloff(position - point, range);
+                (to_light, color * intensity * falloff)
+            }
+            Light::Spot {
+                position,
+                direction,
+                color,
+                intensity,
+                range,
+                inner_angle,
+                outer_angle,
+            } => {
+                let (to_light, falloff) = falloff(position - point, range);
+                let cos = (-to_light).dot(direction.normalize_or_zero());
+                let cone = smoothstep(outer_angle.cos(), inner_angle.cos(), cos);
+                (to_light, color * intensity * falloff * cone)
+            }
+        }
+    }
+
+    fn uniform(&self) -> LightUniform {
+        let (kind, position, direction, color, intensity, range, cone) = match *self {
+            Light::Directional {
+                direction,
+                color,
+                intensity,
+            } => (0, Vec3::ZERO, direction, color, intensity, 0.0, [0.0; 2]),
+            Light::Point {
+                position,
+                color,
+                intensity,
+                range,
+            } => (1, position, Vec3::ZERO, color, intensity, range, [0.0; 2]),
+            Light::Spot {
+                position,
+                direction,
+                color,
+                intensity,
+                range,
+                inner_angle,
+                outer_angle,
+            } => (
+                2,
+                position,
+                direction,
+                color,
+                intensity,
+                range,
+                [outer_angle.cos(), inner_angle.cos()],
+            ),
+        };
+        LightUniform {
+            position: position.to_array(),
+            kind,
+            direction: direction.normalize_or_zero().to_array(),
+            range,
+            color: color.to_array(),
+            intensity,
+            cone,
+            _padding: [0.0; 2],
+        }
+    }
+}
+
+/// Unit direction along `offset` and the inverse square falloff over its
+/// length, windowed to reach zero at `range`.
+fn falloff(offset: Vec3, range: f32) -> (Vec3, f32) {
+    let distance = offset.length().max(MIN_LIGHT_DISTANCE);
+    let ratio = distance / range.max(MIN_LIGHT_DISTANCE);
+    let window = (1.0 - ratio.powi(4)).clamp(0.0, 1.0);
+    (
+        offset.normalize_or_zero(),
+        window * window / (distance * distance),
+    )
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Lights of the scene and how its surfaces reflect them.
+///
+/// [`Lighting::shade`] is the CPU reference for `fs_main` in `lit.wgsl`.
+/// Surfaces are Lambert diffuse in their vertex color plus a white Blinn-Phong
+/// highlight, a `specular` of zero leaves plain Lambert.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lighting {
+    pub lights: Vec<Light>,
+    /// Light reaching every surface from everywhere
+    pub ambient: Vec3,
+    pub specular: f32,
+    /// Blinn-Phong exponent, higher values give smaller highlights
+    pub shininess: f32,
+    /// Scales the image based light of a loaded environment, PBR only
+    pub environment_intensity: f32,
+}
+
+impl Default for Lighting {
+    fn default() -> Self {
+        Self {
+            lights: vec![
+                Light::Directional {
+                    direction: Vec3::new(0.4, -0.6, -1.0),
+                    color: Vec3::ONE,
+                    intensity: 0.6,
+                },
+                Light::Point {
+                    position: Vec3::new(0.5, 0.5, 0.4),
+                    color: Vec3::new(1.0, 0.6, 0.3),
+                    intensity: 0.4,
+                    range: 2.0,
+                },
+                Light::Spot {
+                    position: Vec3::new(-0.6, -0.2, 0.8),
+                    direction: Vec3::new(0.3, 0.1, -1.0),
+                    color: Vec3::new(0.3, 0.5, 1.0),
+                    intensity: 1.5,
+                    range: 3.0,
+                    inner_angle: 15f32.to_radians(),
+                    outer_angle: 25f32.to_radians(),
+                },
+            ],
+            ambient: Vec3::splat(0.05),
+            specular: 0.5,
+            shininess: 32.0,
+            environment_intensity: 1.0,
+        }
+    }
+}
+
+impl Lighting {
+    /// Color of a surface point seen from `eye`. An `eye` with `w = 0` is a
+    /// direction towards the viewer, as for orthographic cameras, otherwise
+    /// it is the camera position.
+    pub fn shade(&self, eye: Vec4, position: Vec3, normal: Vec3, base_color: Vec3) -> Vec3 {
+        let normal = normal.normalize_or_zero();
+        let to_eye = view_direction(eye, position);
+        let mut color = self.ambient * base_color;
+        for light in self.lights.iter().take(MAX_LIGHTS) {
+            let (to_light, radiance) = light.incident(position);
+            let lambert = normal.dot(to_light);
+            if lambert <= 0.0 {
+                continue;
+            }
+            let half = (to_light + to_eye).normalize_or_zero();
+            let highlight = self.specular * normal.dot(half).max(0.0).powf(self.shininess);
+            color += radiance * (base_color * lambert + Vec3::splat(highlight));
+        }
+        color
+    }
+
+    /// Uniform contents for a camera at `eye`, see [`Lighting::shade`].
+    pub fn uniform(&self, eye: Vec4) -> LightsUniform {
+        let mut lights = [LightUniform::zeroed(); MAX_LIGHTS];
+        for (slot, light) in lights.iter_mut().zip(&self.lights) {
+            *slot = light.uniform();
+        }
+        LightsUniform {
+            lights,
+            ambient: self.ambient.to_array(),
+            count: self.lights.len().min(MAX_LIGHTS) as u32,
+            eye: eye.to_array(),
+            specular: self.specular,
+            shininess: self.shininess,
+            environment_intensity: self.environment_intensity,
+            _padding: 0.0,
+        }
+    }
+}
+
+pub(crate) fn view_direction(eye: Vec4, position: Vec3) -> Vec3 {
+    if eye.w == 0.0 {
+        eye.truncate().normalize_or_zero()
+    } else {
+        (eye.truncate() - position).normalize_or_zero()
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct LightUniform {
+    position: [f32; 3],
+    // 0 directional, 1 point, 2 spot
+    kind: u32,
+    direction: [f32; 3],
+    range: f32,
+    color: [f32; 3],
+    intensity: f32,
+    // Cosines of the outer and inner cone angles
+    cone: [f32; 2],
+    _padding: [f32; 2],
+}
+
+/// Layout of the lights uniform buffer in `lights.wgsl`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct LightsUniform {
+    lights: [LightUniform; MAX_LIGHTS],
+    ambient: [f32; 3],
+    count: u32,
+    eye: [f32; 4],
+    specular: f32,
+    shininess: f32,
+    environment_intensity: f32,
+    _padding: f32,
+}
@@ -0,0 +1,209 @@
+use bytemuck::{Pod, Zeroable};
+use glam::{Mat4, Vec3};
+
+use super::{Light, Lighting, MAX_LIGHTS};
+use crate::models::camera::ViewFrustum;
+
+/// Layers of the shadow map array, lights past it cast no shadows.
+pub const MAX_SHADOW_MAPS: usize = 8;
+pub const MAX_CASCADES: u32 = 4;
+
+// Spot light shadows start this fraction of the range away from the light
+const SPOT_NEAR_FRACTION: f32 = 0.01;
+// How far towards a directional light casters are still caught, in slice radii
+const CASTER_REACH: f32 = 3.0;
+
+/// How directional and spot lights cast shadows. Point lights never do.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShadowSettings {
+    pub enabled: bool,
+    /// Width and height of each shadow map in texels
+    pub resolution: u32,
+    /// Cascades per directional light, up to [`MAX_CASCADES`]
+    pub cascades: u32,
+    /// Blend from even (0) to logarithmic (1) cascade splits
+    pub split_lambda: f32,
+    /// Constant offset of the compared depth, in shadow map depth units
+    pub depth_bias: f32,
+    /// Extra offset that grows with the slope of the surface to the light
+    pub slope_bias: f32,
+    /// PCF kernel reaches this many texels to each side
+    pub pcf_radius: u32,
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            resolution: 1024,
+            cascades: 3,
+            split_lambda: 0.6,
+            depth_bias: 0.002,
+            slope_bias: 0.004,
+            pcf_radius: 1,
+        }
+    }
+}
+
+/// One layer of the shadow map array: the light it belongs to and the
+/// world to shadow map clip space transform it was rendered with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShadowView {
+    pub light: usize,
+    pub view_proj: Mat4,
+}
+
+/// Far distances of `count` cascades covering `near..far`, blending the
+/// even split scheme with the logarithmic one by `lambda`.
+pub fn cascade_splits(near: f32, far: f32, count: u32, lambda: f32) -> Vec<f32> {
+    // The logarithmic scheme needs a near plane in front of the camera
+    let log_near = near.max(far * 1e-3);
+    (1..=count)
+        .map(|i| {
+            let fraction = i as f32 / count as f32;
+            let even = near + (far - near) * fraction;
+            let log = log_near * (far / log_near).powf(fraction);
+            even + (log - even) * lambda
+        })
+        .collect()
+}
+
+fn up_for(direction: Vec3) -> Vec3 {
+    if direction.y.abs() > 0.99 {
+        Vec3::Z
+    } else {
+        Vec3::Y
+    }
+}
+
+/// Orthographic shadow projection of a directional light that covers the
+/// bounding sphere of `corners`. The sphere keeps the size of the map
+/// constant as the camera turns, and snapping its center to whole texels
+/// keeps the shadow edges from shimmering as the camera moves.
+pub fn directional_view_proj(direction: Vec3, corners: &[Vec3; 8], resolution: u32) -> Mat4 {
+    let direction = direction.normalize_or(-Vec3::Z);
+    let view = Mat4::look_to_rh(Vec3::ZERO, direction, up_for(direction));
+    let center = corners.iter().sum::<Vec3>() / 8.0;
+    let radius = corners
+        .iter()
+        .map(|corner| corner.distance(center))
+        .fold(1e-3, f32::max);
+    // Leave room for the center to move by up to a texel when snapped
+    let resolution = resolution.max(16) as f32;
+    let extent = radius * resolution / (resolution - 2.0);
+    let texel = 2.0 * extent / resolution;
+    let center = view.transform_point3(center);
+    let (x, y) = (
+        (center.x / texel).floor() * texel,
+        (center.y / texel).floor() * texel,
+    );
+    // The view looks down -z, depth grows away from the light
+    let projection = Mat4::orthographic_rh(
+        x - extent,
+        x + extent,
+        y - extent,
+        y + extent,
+        -center.z - radius * CASTER_REACH,
+        -center.z + radius,
+    );
+    projection * view
+}
+
+/// Perspective shadow projection of a spot light covering its cone.
+pub fn spot_view_proj(position: Vec3, direction: Vec3, outer_angle: f32, range: f32) -> Mat4 {
+    let direction = direction.normalize_or(-Vec3::Z);
+    let fov = (2.0 * outer_angle).clamp(0.01, 179f32.to_radians());
+    let near = (range * SPOT_NEAR_FRACTION).max(1e-3);
+    Mat4::perspective_rh(fov, 1.0, near, range.max(near * 2.0))
+        * Mat4::look_to_rh(position, direction, up_for(direction))
+}
+
+/// The shadow map layers for a frame. Directional lights take one layer
+/// per cascade, consecutive and from near to far, spot lights take one.
+/// Lights that do not fit into [`MAX_SHADOW_MAPS`] are left unshadowed.
+pub fn shadow_views(
+    lighting: &Lighting,
+    settings: &ShadowSettings,
+    frustum: &ViewFrustum,
+) -> Vec<ShadowView> {
+    let mut views = Vec::new();
+    if !settings.enabled {
+        return views;
+    }
+    let cascades = settings.cascades.clamp(1, MAX_CASCADES);
+    let splits = cascade_splits(frustum.near, frustum.far, cascades, settings.split_lambda);
+    for (light, source) in lighting.lights.iter().take(MAX_LIGHTS).enumerate() {
+        match *source {
+            Light::Directional { direction, .. } => {
+                if views.len() + splits.len() > MAX_SHADOW_MAPS {
+                    continue;
+                }
+                let mut from = frustum.near;
+                for &to in &splits {
+                    let corners = frustum.slice(from, to);
+                    views.push(ShadowView {
+                        light,
+                        view_proj: directional_view_proj(direction, &corners, settings.resolution),
+                    });
+                    from = to;
+                }
+            }
+            Light::Spot {
+                position,
+                direction,
+                range,
+                outer_angle,
+                ..
+            } if views.len() < MAX_SHADOW_MAPS => views.push(ShadowView {
+                light,
+                view_proj: spot_view_proj(position, direction, outer_angle, range),
+            }),
+            _ => {}
+        }
+    }
+    views
+}
+
+impl ShadowSettings {
+    /// Uniform contents for the lit pass after rendering `views`.
+    pub fn uniform(&self, views: &[ShadowView]) -> ShadowsUniform {
+        let mut uniform = ShadowsUniform {
+            view_proj: [[[0.0; 4]; 4]; MAX_SHADOW_MAPS],
+            layers: [[-1, 0, 0, 0]; MAX_LIGHTS],
+            depth_bias: self.depth_bias,
+            slope_bias: self.slope_bias,
+            pcf_radius: self.pcf_radius as i32,
+            _padding: 0.0,
+        };
+        for (layer, view) in views.iter().take(MAX_SHADOW_MAPS).enumerate() {
+            uniform.view_proj[layer] = view.view_proj.to_cols_array_2d();
+            let layers = &mut uniform.layers[view.light];
+            if layers[0] < 0 {
+                *layers = [layer as i32, 0, 0, 0];
+            }
+            layers[1] += 1;
+        }
+        uniform
+    }
+}
+
+/// Layout of the shadows uniform buffer in `lights.wgsl`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct ShadowsUniform {
+    view_proj: [[[f32; 4]; 4]; MAX_SHADOW_MAPS],
+    // First layer and layer count of each light, -1 without shadows
+    layers: [[i32; 4]; MAX_LIGHTS],
+    depth_bias: f32,
+    slope_bias: f32,
+    pcf_radius: i32,
+    _padding: f32,
+}
+
+impl ShadowsUniform {
+    /// First shadow map layer and number of layers of a light.
+    pub fn layers(&self, light: usize) -> Option<(usize, usize)> {
+        let [first, count, ..] = self.layers[light];
+        (first >= 0).then_some((first as usize, count as usize))
+    }
+}
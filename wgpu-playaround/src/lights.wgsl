@@ -0,0 +1,180 @@
+// Vertex stage, lights and shadows shared by `lit.wgsl` and `pbr.wgsl`,
+// which are appended to this file.
+
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
+const LIGHT_DIRECTIONAL: u32 = 0u;
+const LIGHT_POINT: u32 = 1u;
+const MAX_LIGHTS: u32 = 8u;
+const MAX_SHADOW_MAPS: u32 = 8u;
+const MIN_LIGHT_DISTANCE: f32 = 0.1;
+
+struct Light {
+    position: vec3<f32>,
+    kind: u32,
+    direction: vec3<f32>,
+    range: f32,
+    color: vec3<f32>,
+    intensity: f32,
+    // Cosines of the outer and inner cone angles
+    cone: vec2<f32>,
+    _padding: vec2<f32>,
+};
+
+struct Lights {
+    lights: array<Light, MAX_LIGHTS>,
+    ambient: vec3<f32>,
+    count: u32,
+    // Direction towards the viewer with w = 0, camera position with w = 1
+    eye: vec4<f32>,
+    specular: f32,
+    shininess: f32,
+    environment_intensity: f32,
+    _padding: f32,
+};
+
+@group(1) @binding(0)
+var<uniform> lighting: Lights;
+
+struct Shadows {
+    view_proj: array<mat4x4<f32>, MAX_SHADOW_MAPS>,
+    // First layer and layer count of each light, -1 without shadows
+    layers: array<vec4<i32>, MAX_LIGHTS>,
+    depth_bias: f32,
+    slope_bias: f32,
+    pcf_radius: i32,
+    _padding: f32,
+};
+
+@group(2) @binding(0)
+var<uniform> shadows: Shadows;
+@group(2) @binding(1)
+var shadow_maps: texture_depth_2d_array;
+@group(2) @binding(2)
+var shadow_sampler: sampler_comparison;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) color: vec3<f32>,
+    @location(2) normal: vec3<f32>,
+    @location(3) uv: vec2<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec3<f32>,
+    @location(1) @interpolate(flat) object_id: u32,
+    @location(2) world_position: vec3<f32>,
+    @location(3) normal: vec3<f32>,
+    @location(4) uv: vec2<f32>,
+};
+
+struct IdFragmentOutput {
+    @location(0) color: vec4<f32>,
+    @location(1) object_id: u32,
+};
+
+@vertex
+fn vs_main(
+    model: VertexInput,
+    @builtin(instance_index) instance: u32,
+) -> VertexOutput {
+    var out: VertexOutput;
+    out.clip_position = camera.view_proj * vec4<f32>(model.position, 1.0);
+    out.color = model.color;
+    out.object_id = instance;
+    // Vertices are already in world space
+    out.world_position = model.position;
+    out.normal = model.normal;
+    out.uv = model.uv;
+    return out;
+}
+
+fn normalize_or_zero(v: vec3<f32>) -> vec3<f32> {
+    let length_squared = dot(v, v);
+    if length_squared > 0.0 {
+        return v * inverseSqrt(length_squared);
+    }
+    return vec3<f32>(0.0);
+}
+
+// Inverse square falloff windowed to reach zero at `range`
+fn falloff(offset: vec3<f32>, range: f32) -> f32 {
+    let dist = max(length(offset), MIN_LIGHT_DISTANCE);
+    let ratio = dist / max(range, MIN_LIGHT_DISTANCE);
+    let window = clamp(1.0 - pow(ratio, 4.0), 0.0, 1.0);
+    return window * window / (dist * dist);
+}
+
+// Fraction of a light reaching `position`, 1 when nothing is in the way
+fn shadow_factor(light_index: u32, position: vec3<f32>, lambert: f32) -> f32 {
+    let layers = shadows.layers[light_index];
+    if layers.x < 0 {
+        return 1.0;
+    }
+    // Surfaces at grazing angles to the light need more bias
+    let slope = min(sqrt(max(1.0 - lambert * lambert, 0.0)) / max(lambert, 1e-3), 10.0);
+    let bias = shadows.depth_bias + shadows.slope_bias * slope;
+    let texel = 1.0 / vec2<f32>(textureDimensions(shadow_maps));
+    let radius = shadows.pcf_radius;
+
+    // Cascades go from near to far, the first one covering the point wins
+    for (var i = 0; i < layers.y; i++) {
+        let layer = layers.x + i;
+        let clip = shadows.view_proj[layer] * vec4<f32>(position, 1.0);
+        let ndc = clip.xyz / clip.w;
+        let uv = vec2<f32>(ndc.x * 0.5 + 0.5, 0.5 - ndc.y * 0.5);
+        if any(uv < vec2<f32>(0.0)) || any(uv > vec2<f32>(1.0)) || ndc.z < 0.0 || ndc.z > 1.0 {
+            continue;
+        }
+        var lit = 0.0;
+        for (var y = -radius; y <= radius; y++) {
+            for (var x = -radius; x <= radius; x++) {
+                let offset = vec2<f32>(f32(x), f32(y)) * texel;
+                lit += textureSampleCompareLevel(shadow_maps, shadow_sampler, uv + offset, layer, ndc.z - bias);
+            }
+        }
+        let taps = 2 * radius + 1;
+        return lit / f32(taps * taps);
+    }
+    return 1.0;
+}
+
+struct Incident {
+    // Unit direction towards the light
+    to_light: vec3<f32>,
+    radiance: vec3<f32>,
+};
+
+// Light `i` arriving at `position` after falloff and the spot cone, mirrors
+// `Light::incident`
+fn incident(i: u32, position: vec3<f32>) -> Incident {
+    let light = lighting.lights[i];
+    var out: Incident;
+    out.radiance = light.color * light.intensity;
+    if light.kind == LIGHT_DIRECTIONAL {
+        out.to_light = -light.direction;
+    } else {
+        let offset = light.position - position;
+        out.to_light = normalize_or_zero(offset);
+        out.radiance *= falloff(offset, light.range);
+        if light.kind != LIGHT_POINT {
+            let cos_angle = dot(-out.to_light, light.direction);
+            out.radiance *= smoothstep(light.cone.x, light.cone.y, cos_angle);
+        }
+    }
+    return out;
+}
+
+// Direction from `position` towards the viewer
+fn view_direction(position: vec3<f32>) -> vec3<f32> {
+    if lighting.eye.w != 0.0 {
+        return normalize_or_zero(lighting.eye.xyz - position);
+    }
+    return normalize_or_zero(lighting.eye.xyz);
+}
@@ -0,0 +1,35 @@
+// Blinn-Phong shading of the scene, mirrors `Lighting::shade` in
+// `lighting/mod.rs`. Appended to `lights.wgsl`.
+
+fn shade(position: vec3<f32>, normal_in: vec3<f32>, base_color: vec3<f32>) -> vec3<f32> {
+    let normal = normalize_or_zero(normal_in);
+    let to_eye = view_direction(position);
+
+    var color = lighting.ambient * base_color;
+    for (var i = 0u; i < min(lighting.count, MAX_LIGHTS); i++) {
+        let light = incident(i, position);
+        let lambert = dot(normal, light.to_light);
+        if lambert <= 0.0 {
+            continue;
+        }
+        let half_vector = normalize_or_zero(light.to_light + to_eye);
+        let highlight = lighting.specular * pow(max(dot(normal, half_vector), 0.0), lighting.shininess);
+        let shadow = shadow_factor(i, position, lambert);
+        color += light.radiance * shadow * (base_color * lambert + vec3<f32>(highlight));
+    }
+    return color;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(shade(in.world_position, in.normal, in.color), 1.0);
+}
+
+@fragment
+fn fs_main_id(in: VertexOutput) -> IdFragmentOutput {
+    var out: IdFragmentOutput;
+    out.color = vec4<f32>(shade(in.world_position, in.normal, in.color), 1.0);
+    // 0 is left for the cleared background
+    out.object_id = in.object_id + 1u;
+    return out;
+}
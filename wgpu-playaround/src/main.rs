@@ -1,4 +1,7 @@
-use wgpu_playaround::models::app::App;
+use wgpu_playaround::{
+    models::{app::App, headless::HeadlessRenderer},
+    recording::{RecordingSettings, record},
+};
 use winit::event_loop::EventLoop;
 
 fn main() {
@@ -15,6 +18,13 @@ pub fn run() -> anyhow::Result<()> {
         console_log::init_with_level(log::Level::Info).unwrap_throw();
     }
 
+    // Recordings render offscreen and never open a window
+    if let Some(settings) = RecordingSettings::from_args(std::env::args().skip(1))? {
+        let mut renderer =
+            pollster::block_on(HeadlessRenderer::new(settings.width, settings.height))?;
+        return record(&mut renderer, &settings);
+    }
+
     let event_loop = EventLoop::with_user_event().build()?;
     let mut app = App::new(
         #[cfg(target_arch = "wasm32")]
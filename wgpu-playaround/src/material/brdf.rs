@@ -0,0 +1,71 @@
+// Cook-Torrance BRDF with the GGX distribution, the Schlick approximation
+// of the Smith geometry term and Schlick's Fresnel. Mirrors the functions
+// of the same names in `pbr.wgsl`.
+
+use std::f32::consts::PI;
+
+use glam::Vec3;
+
+/// Roughness below this makes the highlight of point lights a singularity.
+pub const MIN_ROUGHNESS: f32 = 0.045;
+
+/// Reflectance at normal incidence of dielectrics, 4% for most of them.
+pub const DIELECTRIC_F0: f32 = 0.04;
+
+/// GGX normal distribution, the density of microfacets facing the half vector.
+pub fn distribution_ggx(n_dot_h: f32, roughness: f32) -> f32 {
+    // Remapped so roughness is perceptually linear
+    let alpha = roughness * roughness;
+    let alpha2 = alpha * alpha;
+    let d = n_dot_h * n_dot_h * (alpha2 - 1.0) + 1.0;
+    alpha2 / (PI * d * d)
+}
+
+/// Share of microfacets neither shadowed nor masked, with the `k` of
+/// direct lighting.
+pub fn geometry_smith(n_dot_v: f32, n_dot_l: f32, roughness: f32) -> f32 {
+    let k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+    let g1 = |n_dot_x: f32| n_dot_x / (n_dot_x * (1.0 - k) + k);
+    g1(n_dot_v) * g1(n_dot_l)
+}
+
+pub fn fresnel_schlick(cos_theta: f32, f0: Vec3) -> Vec3 {
+    f0 + (Vec3::ONE - f0) * (1.0 - cos_theta).clamp(0.0, 1.0).powi(5)
+}
+
+/// Reflectance at normal incidence, metals tint their reflections.
+pub fn base_reflectance(base_color: Vec3, metallic: f32) -> Vec3 {
+    Vec3::splat(DIELECTRIC_F0).lerp(base_color, metallic)
+}
+
+/// Light leaving towards `to_eye` per unit of radiance arriving from
+/// `to_light`, including the cosine of the light angle. All vectors are unit
+/// length.
+pub fn cook_torrance(
+    normal: Vec3,
+    to_eye: Vec3,
+    to_light: Vec3,
+    base_color: Vec3,
+    metallic: f32,
+    roughness: f32,
+) -> Vec3 {
+    let n_dot_l = normal.dot(to_light);
+    if n_dot_l <= 0.0 {
+        return Vec3::ZERO;
+    }
+    let roughness = roughness.clamp(MIN_ROUGHNESS, 1.0);
+    let half = (to_light + to_eye).normalize_or_zero();
+    let n_dot_v = normal.dot(to_eye).max(1e-4);
+    let n_dot_h = normal.dot(half).max(0.0);
+    let fresnel = fresnel_schlick(
+        half.dot(to_eye).max(0.0),
+        base_reflectance(base_color, metallic),
+    );
+    let specular = fresnel
+        * distribution_ggx(n_dot_h, roughness)
+        * geometry_smith(n_dot_v, n_dot_l, roughness)
+        / (4.0 * n_dot_v * n_dot_l).max(1e-4);
+    // Light not reflected at the surface enters it, metals absorb it
+    let diffuse = (Vec3::ONE - fresnel) * (1.0 - metallic) * base_color / PI;
+    (diffuse + specular) * n_dot_l
+}
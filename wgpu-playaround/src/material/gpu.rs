@@ -0,0 +1,209 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+
+use super::{Image, Material};
+
+/// Layout of the material uniform buffer in `pbr.wgsl`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct MaterialUniform {
+    base_color_factor: [f32; 4],
+    emissive_factor: [f32; 3],
+    metallic_factor: f32,
+    roughness_factor: f32,
+    normal_scale: f32,
+    occlusion_strength: f32,
+    _padding: f32,
+}
+
+impl MaterialUniform {
+    fn new(material: &Material) -> Self {
+        Self {
+            base_color_factor: material.base_color_factor.to_array(),
+            emissive_factor: material.emissive_factor.to_array(),
+            metallic_factor: material.metallic_factor,
+            roughness_factor: material.roughness_factor,
+            normal_scale: material.normal_scale,
+            occlusion_strength: material.occlusion_strength,
+            _padding: 0.0,
+        }
+    }
+}
+
+// Map slots in binding order with their encoding and the texel used when a map is missing
+const MAPS: [(&str, wgpu::TextureFormat, [u8; 4]); 5] = [
+    (
+        "Base Color Map",
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+        [255; 4],
+    ),
+    (
+        "Metallic Roughness Map",
+        wgpu::TextureFormat::Rgba8Unorm,
+        [255; 4],
+    ),
+    (
+        "Normal Map",
+        wgpu::TextureFormat::Rgba8Unorm,
+        [128, 128, 255, 255],
+    ),
+    ("Occlusion Map", wgpu::TextureFormat::Rgba8Unorm, [255; 4]),
+    (
+        "Emissive Map",
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+        [255; 4],
+    ),
+];
+
+/// Bind group layout of a [`GpuMaterial`], group 3 of the PBR pipeline.
+pub fn bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    let texture = |binding| wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::FRAGMENT,
+        ty: wgpu::BindingType::Texture {
+            multisampled: false,
+            view_dimension: wgpu::TextureViewDimension::D2,
+            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+        },
+        count: None,
+    };
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Material Bind Group Layout"),
+        entries: &[
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            },
+            texture(1),
+            texture(2),
+            texture(3),
+            texture(4),
+            texture(5),
+            wgpu::BindGroupLayoutEntry {
+                binding: 6,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ],
+    })
+}
+
+fn upload_map(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    label: &str,
+    format: wgpu::TextureFormat,
+    image: &Image,
+) -> wgpu::TextureView {
+    let size = wgpu::Extent3d {
+        width: image.width,
+        height: image.height,
+        depth_or_array_layers: 1,
+    };
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some(label),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    });
+    queue.write_texture(
+        texture.as_image_copy(),
+        &image.pixels,
+        wgpu::TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(image.width * 4),
+            rows_per_image: None,
+        },
+        size,
+    );
+    texture.create_view(&wgpu::TextureViewDescriptor::default())
+}
+
+/// A [`Material`] on the GPU, bound once per draw that uses it. The bind
+/// group keeps the factors buffer and the maps alive.
+pub struct GpuMaterial {
+    bind_group: wgpu::BindGroup,
+}
+
+impl GpuMaterial {
+    pub fn new(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        layout: &wgpu::BindGroupLayout,
+        material: &Material,
+    ) -> Self {
+        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Material Buffer"),
+            contents: bytemuck::bytes_of(&MaterialUniform::new(material)),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let images = [
+            &material.base_color_texture,
+            &material.metallic_roughness_texture,
+            &material.normal_texture,
+            &material.occlusion_texture,
+            &material.emissive_texture,
+        ];
+        let views: Vec<wgpu::TextureView> = images
+            .into_iter()
+            .zip(MAPS)
+            .map(|(image, (label, format, fallback))| match image {
+                Some(image) => upload_map(device, queue, label, format, image),
+                None => upload_map(
+                    device,
+                    queue,
+                    label,
+                    format,
+                    &Image::from_fn(1, 1, |_, _| fallback),
+                ),
+            })
+            .collect();
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Material Sampler"),
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let mut entries = vec![wgpu::BindGroupEntry {
+            binding: 0,
+            resource: uniform_buffer.as_entire_binding(),
+        }];
+        entries.extend(
+            views
+                .iter()
+                .enumerate()
+                .map(|(i, view)| wgpu::BindGroupEntry {
+                    binding: i as u32 + 1,
+                    resource: wgpu::BindingResource::TextureView(view),
+                }),
+        );
+        entries.push(wgpu::BindGroupEntry {
+            binding: 6,
+            resource: wgpu::BindingResource::Sampler(&sampler),
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some(&format!("{} Material Bind Group", material.name)),
+            layout,
+            entries: &entries,
+        });
+        Self { bind_group }
+    }
+
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+}
@@ -0,0 +1,255 @@
+pub mod brdf;
+pub mod gpu;
+
+use anyhow::bail;
+use glam::{Vec2, Vec3, Vec4};
+
+use crate::{
+    color::srgb_to_linear,
+    lighting::{Lighting, MAX_LIGHTS, view_direction},
+};
+
+/// RGBA8 pixels, rows from top to bottom.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+impl Image {
+    pub fn from_fn(width: u32, height: u32, pixel: impl Fn(u32, u32) -> [u8; 4]) -> Self {
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .flat_map(|(x, y)| pixel(x, y))
+            .collect();
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Reads a PNG of any color type and bit depth as RGBA8.
+    pub fn decode_png(bytes: &[u8]) -> anyhow::Result<Self> {
+        let mut decoder = png::Decoder::new(std::io::Cursor::new(bytes));
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let Some(size) = reader.output_buffer_size() else {
+            bail!("PNG is too large");
+        };
+        let mut data = vec![0; size];
+        let info = reader.next_frame(&mut data)?;
+        data.truncate(info.buffer_size());
+        let pixels = match info.color_type {
+            png::ColorType::Rgba => data,
+            png::ColorType::Rgb => data
+                .chunks_exact(3)
+                .flat_map(|c| [c[0], c[1], c[2], 255])
+                .collect(),
+            png::ColorType::GrayscaleAlpha => data
+                .chunks_exact(2)
+                .flat_map(|c| [c[0], c[0], c[0], c[1]])
+                .collect(),
+            png::ColorType::Grayscale => data.iter().flat_map(|&v| [v, v, v, 255]).collect(),
+            png::ColorType::Indexed => bail!("indexed PNG was not expanded"),
+        };
+        Ok(Self {
+            width: info.width,
+            height: info.height,
+            pixels,
+        })
+    }
+
+    /// Texel at `uv` with nearest filtering and repeat wrapping, as stored.
+    pub fn texel(&self, uv: Vec2) -> Vec4 {
+        let wrap = |t: f32, size: u32| ((t.rem_euclid(1.0) * size as f32) as u32).min(size - 1);
+        let (x, y) = (wrap(uv.x, self.width), wrap(uv.y, self.height));
+        let start = ((y * self.width + x) * 4) as usize;
+        Vec4::from_array(std::array::from_fn(|k| {
+            self.pixels[start + k] as f32 / 255.0
+        }))
+    }
+}
+
+/// A glTF metallic-roughness material. Each map is multiplied by its factor,
+/// missing maps count as white, or as a flat surface for the normal map.
+///
+/// Base color and emissive maps are sRGB encoded, the others are linear.
+/// Metallic is read from the blue channel of its map and roughness from the
+/// green one, occlusion from the red channel of its own.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Material {
+    pub name: String,
+    /// Linear RGBA, multiplied with the vertex colors
+    pub base_color_factor: Vec4,
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    pub emissive_factor: Vec3,
+    /// Scales the x and y of the tangent space normals
+    pub normal_scale: f32,
+    /// How much of the occlusion map is applied
+    pub occlusion_strength: f32,
+    pub base_color_texture: Option<Image>,
+    pub metallic_roughness_texture: Option<Image>,
+    pub normal_texture: Option<Image>,
+    pub occlusion_texture: Option<Image>,
+    pub emissive_texture: Option<Image>,
+}
+
+impl Default for Material {
+    /// The glTF defaults, a rough white metal.
+    fn default() -> Self {
+        Self {
+            name: String::from("Default"),
+            base_color_factor: Vec4::ONE,
+            metallic_factor: 1.0,
+            roughness_factor: 1.0,
+            emissive_factor: Vec3::ZERO,
+            normal_scale: 1.0,
+            occlusion_strength: 1.0,
+            base_color_texture: None,
+            metallic_roughness_texture: None,
+            normal_texture: None,
+            occlusion_texture: None,
+            emissive_texture: None,
+        }
+    }
+}
+
+/// A material at one point of a surface, what the BRDF works with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Surface {
+    /// Linear
+    pub base_color: Vec3,
+    pub metallic: f32,
+    pub roughness: f32,
+    pub occlusion: f32,
+    pub emissive: Vec3,
+}
+
+impl Material {
+    /// The surface at `uv` of a vertex colored `vertex_color`, mirroring
+    /// the map lookups in `pbr.wgsl` with nearest filtering.
+    pub fn surface(&self, vertex_color: Vec3, uv: Vec2) -> Surface {
+        let map = |texture: &Option<Image>| texture.as_ref().map_or(Vec4::ONE, |t| t.texel(uv));
+        let srgb = |texel: Vec4| texel.truncate().to_array().map(srgb_to_linear).into();
+        let base_color: Vec3 = srgb(map(&self.base_color_texture));
+        let metallic_roughness = map(&self.metallic_roughness_texture);
+        let occlusion = map(&self.occlusion_texture).x;
+        let emissive: Vec3 = srgb(map(&self.emissive_texture));
+        Surface {
+            base_color: base_color * self.base_color_factor.truncate() * vertex_color,
+            metallic: (metallic_roughness.z * self.metallic_factor).clamp(0.0, 1.0),
+            roughness: (metallic_roughness.y * self.roughness_factor).clamp(0.0, 1.0),
+            occlusion: 1.0 + self.occlusion_strength * (occlusion - 1.0),
+            emissive: emissive * self.emissive_factor,
+        }
+    }
+
+    /// A few materials to tell them apart on the scene shapes.
+    pub fn presets() -> Vec<Material> {
+        let (normal, metallic_roughness, occlusion) = tile_maps(64, 4);
+        vec![
+            Material {
+                name: String::from("Gold"),
+                base_color_factor: Vec4::new(1.0, 0.766, 0.336, 1.0),
+                roughness_factor: 0.3,
+                ..Material::default()
+            },
+            Material {
+                name: String::from("Plastic"),
+                metallic_factor: 0.0,
+                roughness_factor: 0.4,
+                ..Material::default()
+            },
+            Material {
+                name: String::from("Tiles"),
+                metallic_factor: 0.0,
+                normal_texture: Some(normal),
+                metallic_roughness_texture: Some(metallic_roughness),
+                occlusion_texture: Some(occlusion),
+                ..Material::default()
+            },
+            Material {
+                name: String::from("Brushed steel"),
+                base_color_factor: Vec4::new(0.56, 0.57, 0.58, 1.0),
+                roughness_factor: 0.6,
+                ..Material::default()
+            },
+            Material {
+                name: String::from("Glowing"),
+                metallic_factor: 0.0,
+                roughness_factor: 0.8,
+                emissive_factor: Vec3::new(4.0, 1.5, 0.5),
+                ..Material::default()
+            },
+        ]
+    }
+}
+
+/// Normal, metallic-roughness and occlusion maps of `tiles` by `tiles`
+/// bevelled tiles with rough, dark grout between them.
+fn tile_maps(size: u32, tiles: u32) -> (Image, Image, Image) {
+    let tile = size / tiles;
+    let bevel = (tile / 6).max(1) as i32;
+    // Distances to the nearest tile edge along x and y, negative in the grout
+    let edges = |x: u32, y: u32| {
+        let edge = |p: u32| {
+            let p = (p % tile) as i32;
+            let to_edge = p.min(tile as i32 - 1 - p) - 1;
+            (to_edge, if p < tile as i32 / 2 { -1.0 } else { 1.0 })
+        };
+        (edge(x), edge(y))
+    };
+    let normal = Image::from_fn(size, size, |x, y| {
+        let ((dx, sx), (dy, sy)) = edges(x, y);
+        // Slopes falling away towards the nearest edge
+        let slope = |d: i32, s: f32| if (0..bevel).contains(&d) { s } else { 0.0 };
+        let n = Vec3::new(slope(dx, sx), -slope(dy, sy), 1.0).normalize();
+        let encode = |c: f32| ((c * 0.5 + 0.5) * 255.0).round() as u8;
+        [encode(n.x), encode(n.y), encode(n.z), 255]
+    });
+    let grout = |x, y| {
+        let ((dx, _), (dy, _)) = edges(x, y);
+        dx < 0 || dy < 0
+    };
+    let metallic_roughness = Image::from_fn(size, size, |x, y| {
+        [0, if grout(x, y) { 240 } else { 70 }, 0, 255]
+    });
+    let occlusion = Image::from_fn(size, size, |x, y| {
+        let value = if grout(x, y) { 90 } else { 255 };
+        [value, value, value, 255]
+    });
+    (normal, metallic_roughness, occlusion)
+}
+
+/// Color of a surface point lit by `lighting` and seen from `eye`, mirroring
+/// `fs_main` in `pbr.wgsl` without shadows, normal maps and image based light.
+///
+/// Ambient light reaches the surface diffusely and is darkened by the
+/// occlusion, emissive light is added on top.
+pub fn shade(
+    lighting: &Lighting,
+    eye: Vec4,
+    position: Vec3,
+    normal: Vec3,
+    surface: &Surface,
+) -> Vec3 {
+    let normal = normal.normalize_or_zero();
+    let to_eye = view_direction(eye, position);
+    let mut color = lighting.ambient * surface.base_color * surface.occlusion + surface.emissive;
+    for light in lighting.lights.iter().take(MAX_LIGHTS) {
+        let (to_light, radiance) = light.incident(position);
+        color += radiance
+            * brdf::cook_torrance(
+                normal,
+                to_eye,
+                to_light,
+                surface.base_color,
+                surface.metallic,
+                surface.roughness,
+            );
+    }
+    color
+}
@@ -3,7 +3,7 @@ use std::sync::Arc;
 #[cfg(target_arch = "wasm32")]
 use winit::event_loop::{EventLoop, EventLoopProxy};
 use winit::{
-    application::ApplicationHandler, dpi::PhysicalPosition, event::{KeyEvent, WindowEvent}, event_loop::ActiveEventLoop, keyboard::PhysicalKey, window::{WindowAttributes, WindowId}
+    application::ApplicationHandler, dpi::PhysicalPosition, event::{DeviceEvent, DeviceId, KeyEvent, WindowEvent}, event_loop::ActiveEventLoop, keyboard::PhysicalKey, window::{WindowAttributes, WindowId}
 };
 
 use crate::models::state::State;
@@ -26,6 +26,13 @@ impl App {
     }
 }
 
+#[cfg(not(target_arch = "wasm32"))]
+impl Default for App {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ApplicationHandler<State> for App {
     fn window_event(
         &mut self,
@@ -37,6 +44,9 @@ impl ApplicationHandler<State> for App {
             Some(canvas) => canvas,
             None => return,
         };
+        if state.handle_ui_event(&event) {
+            return;
+        }
 
         match event {
             // ...
@@ -70,10 +80,32 @@ impl ApplicationHandler<State> for App {
                 position:  PhysicalPosition { x, y },
                 ..
             } => state.handle_mouse_moved(x, y),
+            WindowEvent::CursorLeft { .. } => state.handle_cursor_left(),
+            WindowEvent::ModifiersChanged(modifiers) => state.handle_modifiers(modifiers.state()),
+            WindowEvent::MouseInput {
+                state: button_state,
+                button,
+                ..
+            } => state.handle_mouse_input(button, button_state.is_pressed()),
+            WindowEvent::MouseWheel { delta, .. } => state.handle_mouse_wheel(delta),
+            WindowEvent::Focused(false) => state.handle_focus_lost(),
+            WindowEvent::DroppedFile(path) => state.handle_dropped_file(&path),
             _ => {}
         }
     }
-    
+
+    fn device_event(
+        &mut self,
+        _event_loop: &ActiveEventLoop,
+        _device_id: DeviceId,
+        event: DeviceEvent,
+    ) {
+        // Raw movement keeps coming when the cursor is grabbed and stops moving
+        if let (Some(state), DeviceEvent::MouseMotion { delta: (dx, dy) }) = (&mut self.state, event) {
+            state.handle_mouse_motion(dx, dy);
+        }
+    }
+
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
         if self.state.is_none() {
             let window_attributes = WindowAttributes::default()
@@ -0,0 +1,167 @@
+use bytemuck::{Pod, Zeroable};
+use glam::{Mat4, Vec2, Vec3};
+
+use crate::environment::SkyView;
+
+/// Orthographic 2D camera looking at the `z = 0` plane.
+///
+/// With the default camera world space is the same as NDC, which is what the
+/// shapes in `consts.rs` were authored for.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera2d {
+    /// World position shown in the middle of the screen
+    pub center: Vec2,
+    /// Screen units per world unit, larger values zoom in
+    pub zoom: f32,
+}
+
+impl Default for Camera2d {
+    fn default() -> Self {
+        Self {
+            center: Vec2::ZERO,
+            zoom: 1.0,
+        }
+    }
+}
+
+impl Camera2d {
+    pub fn view_proj(&self) -> Mat4 {
+        Mat4::from_scale(Vec3::new(self.zoom, self.zoom, 1.0))
+            * Mat4::from_translation(-self.center.extend(0.0))
+    }
+
+    /// Lower left and upper right corners of the world area on screen.
+    pub fn visible_bounds(&self) -> (Vec2, Vec2) {
+        let half = Vec2::splat(1.0 / self.zoom);
+        (self.center - half, self.center + half)
+    }
+
+    /// The 2D camera sees world z from 0 to 1, its NDC depth.
+    pub fn frustum(&self) -> ViewFrustum {
+        ViewFrustum {
+            inverse_view_proj: self.view_proj().inverse(),
+            near: 0.0,
+            far: 1.0,
+        }
+    }
+
+    pub fn uniform(&self) -> CameraUniform {
+        CameraUniform {
+            view_proj: self.view_proj().to_cols_array_2d(),
+        }
+    }
+}
+
+/// Perspective camera looking from `eye` along its yaw and pitch.
+///
+/// The default camera sees about the same part of the `z = 0` plane as the
+/// default [`Camera2d`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera3d {
+    pub eye: Vec3,
+    /// Radians to the right of `-Z`
+    pub yaw: f32,
+    /// Radians above the horizon
+    pub pitch: f32,
+    /// Vertical field of view in radians
+    pub fov_y: f32,
+    pub near: f32,
+    pub far: f32,
+}
+
+impl Default for Camera3d {
+    fn default() -> Self {
+        Self {
+            eye: Vec3::new(0.0, 0.0, 2.0),
+            yaw: 0.0,
+            pitch: 0.0,
+            fov_y: 60f32.to_radians(),
+            near: 0.01,
+            far: 100.0,
+        }
+    }
+}
+
+impl Camera3d {
+    pub fn forward(&self) -> Vec3 {
+        Vec3::new(
+            self.yaw.sin() * self.pitch.cos(),
+            self.pitch.sin(),
+            -self.yaw.cos() * self.pitch.cos(),
+        )
+    }
+
+    /// Screen right, always level with the horizon.
+    pub fn right(&self) -> Vec3 {
+        Vec3::new(self.yaw.cos(), 0.0, self.yaw.sin())
+    }
+
+    pub fn up(&self) -> Vec3 {
+        self.right().cross(self.forward())
+    }
+
+    pub fn view_proj(&self, aspect: f32) -> Mat4 {
+        let view = Mat4::look_to_rh(self.eye, self.forward(), Vec3::Y);
+        let proj = Mat4::perspective_rh(self.fov_y, aspect.max(1e-3), self.near, self.far);
+        proj * view
+    }
+
+    pub fn frustum(&self, aspect: f32) -> ViewFrustum {
+        ViewFrustum {
+            inverse_view_proj: self.view_proj(aspect).inverse(),
+            near: self.near,
+            far: self.far,
+        }
+    }
+
+    pub fn uniform(&self, aspect: f32) -> CameraUniform {
+        CameraUniform {
+            view_proj: self.view_proj(aspect).to_cols_array_2d(),
+        }
+    }
+
+    /// The skybox seen in the same direction.
+    pub fn sky_view(&self) -> SkyView {
+        SkyView {
+            yaw: self.yaw,
+            pitch: self.pitch,
+            fov_y: self.fov_y,
+        }
+    }
+}
+
+/// The volume a camera sees, for fitting shadow maps to it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ViewFrustum {
+    pub inverse_view_proj: Mat4,
+    /// Distances of the near and far planes along the view direction
+    pub near: f32,
+    pub far: f32,
+}
+
+impl ViewFrustum {
+    /// World-space corners of the part between the distances `from` and
+    /// `to`, the four nearer ones first.
+    pub fn slice(&self, from: f32, to: f32) -> [Vec3; 8] {
+        let span = (self.far - self.near).max(f32::EPSILON);
+        let (from, to) = ((from - self.near) / span, (to - self.near) / span);
+        let mut corners = [Vec3::ZERO; 8];
+        for (i, (x, y)) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+            .into_iter()
+            .enumerate()
+        {
+            let near = self.inverse_view_proj.project_point3(Vec3::new(x, y, 0.0));
+            let far = self.inverse_view_proj.project_point3(Vec3::new(x, y, 1.0));
+            corners[i] = near.lerp(far, from);
+            corners[i + 4] = near.lerp(far, to);
+        }
+        corners
+    }
+}
+
+/// Layout of the camera uniform buffer in `shader.wgsl`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct CameraUniform {
+    pub view_proj: [[f32; 4]; 4],
+}
@@ -0,0 +1,52 @@
+use std::time::Instant;
+
+/// Drives animation time.
+///
+/// The window uses wall time, while recordings step a fixed amount per frame
+/// so the same number of frames always produces the same images.
+pub enum Clock {
+    Realtime { start: Instant, last: Instant },
+    Fixed { fps: u32, frame: u64 },
+}
+
+impl Clock {
+    pub fn realtime() -> Self {
+        let now = Instant::now();
+        Clock::Realtime {
+            start: now,
+            last: now,
+        }
+    }
+
+    pub fn fixed(fps: u32) -> Self {
+        Clock::Fixed {
+            fps: fps.max(1),
+            frame: 0,
+        }
+    }
+
+    /// Advances the clock and returns the seconds since the previous tick.
+    pub fn tick(&mut self) -> f32 {
+        match self {
+            Clock::Realtime { last, .. } => {
+                let now = Instant::now();
+                let dt = now.duration_since(*last).as_secs_f32();
+                *last = now;
+                dt
+            }
+            Clock::Fixed { fps, frame } => {
+                *frame += 1;
+                1.0 / *fps as f32
+            }
+        }
+    }
+
+    /// Seconds since the clock started.
+    pub fn elapsed(&self) -> f32 {
+        match self {
+            Clock::Realtime { start, last } => last.duration_since(*start).as_secs_f32(),
+            // Derived from the frame count so rounding never accumulates
+            Clock::Fixed { fps, frame } => (*frame as f64 / *fps as f64) as f32,
+        }
+    }
+}
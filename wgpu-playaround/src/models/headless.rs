@@ -0,0 +1,150 @@
+use crate::{
+    environment::hdr::HdrImage,
+    models::{scene::Scene, state::request_device},
+    post::{PostSettings, renderer::PostProcessor},
+};
+
+/// Renders the scene into an offscreen texture and reads the pixels back.
+///
+/// Used when there is no window, e.g. for recordings. Frames go through the
+/// same HDR post-processing and tonemapping as the window.
+pub struct HeadlessRenderer {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    readback_buffer: wgpu::Buffer,
+    padded_bytes_per_row: u32,
+    width: u32,
+    height: u32,
+    post: PostProcessor,
+    pub post_settings: PostSettings,
+    pub scene: Scene,
+}
+
+impl HeadlessRenderer {
+    /// Format of the tonemapped frames that are read back.
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+    pub async fn new(width: u32, height: u32) -> anyhow::Result<Self> {
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+            backends: wgpu::Backends::PRIMARY,
+            ..Default::default()
+        });
+
+        let adapter = instance
+            .request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::default(),
+                compatible_surface: None,
+                force_fallback_adapter: false,
+            })
+            .await?;
+
+        let (device, queue) = request_device(&adapter).await?;
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Headless Target"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        // Rows copied out of a texture have to be padded to 256 bytes
+        let padded_bytes_per_row = (width * 4).next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Headless Readback Buffer"),
+            size: (padded_bytes_per_row * height) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut scene = Scene::new(&device, PostProcessor::FORMAT);
+        scene.set_viewport_size(width, height);
+        let post = PostProcessor::new(&device, Self::FORMAT, width, height);
+
+        Ok(Self {
+            device,
+            queue,
+            texture,
+            view,
+            readback_buffer,
+            padded_bytes_per_row,
+            width,
+            height,
+            post,
+            post_settings: PostSettings::default(),
+            scene,
+        })
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Bakes `image` into the skybox and image based light of the scene.
+    pub fn load_environment(&mut self, image: &HdrImage) {
+        self.scene.load_environment(&self.device, &self.queue, image);
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.scene.update(&self.device, &self.queue, dt);
+    }
+
+    /// Draws one frame and returns it as tightly packed RGBA8 rows.
+    pub fn render_frame(&mut self) -> anyhow::Result<Vec<u8>> {
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Headless Encoder"),
+            });
+
+        self.scene.draw(&mut encoder, self.post.scene_view(), None);
+        self.post
+            .prepare(&self.device, &self.queue, &self.post_settings);
+        self.post.draw(&mut encoder, &self.view);
+
+        encoder.copy_texture_to_buffer(
+            self.texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &self.readback_buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(self.padded_bytes_per_row),
+                    rows_per_image: Some(self.height),
+                },
+            },
+            self.texture.size(),
+        );
+
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = self.readback_buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device.poll(wgpu::PollType::wait_indefinitely())?;
+        receiver.recv()??;
+
+        let row_bytes = (self.width * 4) as usize;
+        let mut pixels = Vec::with_capacity(row_bytes * self.height as usize);
+        {
+            let data = slice.get_mapped_range();
+            for row in data.chunks(self.padded_bytes_per_row as usize) {
+                pixels.extend_from_slice(&row[..row_bytes]);
+            }
+        }
+        self.readback_buffer.unmap();
+
+        Ok(pixels)
+    }
+}
@@ -1,3 +1,7 @@
 pub mod state;
 pub mod app;
 pub mod vertex;
+pub mod scene;
+pub mod clock;
+pub mod headless;
+pub mod camera;
@@ -0,0 +1,1418 @@
+use std::{ops::Range, sync::Arc};
+
+use glam::{Mat4, Vec2, Vec4};
+use wgpu::util::DeviceExt;
+
+use crate::{
+    consts::{
+        EDIT_MAX_VERTICES, HISTORY_MAX_BYTES, MORPH_DURATION_SECS, MORPH_POINT_COUNT, SHAPE_TINTS,
+    },
+    document::{
+        Document, ShapeGeometry, ShapeNode, Transform2d, commands::EditCommand,
+        file::SceneDescription,
+    },
+    editor::{EditorAction, VertexEditor},
+    enums::{PipelineKind, ShapeType},
+    environment::{
+        EnvironmentSettings, SkyView, gpu::Environment, hdr::HdrImage, skybox::Skybox,
+    },
+    geometry::{
+        morph::Morph,
+        normals::{planar_uv, smooth_normals},
+        outline::Outline,
+        stroke::{StrokeStyle, stroke_polyline},
+    },
+    history::History,
+    material::{
+        Material,
+        gpu::{self as material_gpu, GpuMaterial},
+    },
+    lighting::{
+        Lighting,
+        gpu::ShadowMaps,
+        shadow::{ShadowSettings, shadow_views},
+    },
+    models::{
+        camera::{Camera2d, Camera3d, CameraUniform},
+        vertex::{LitVertex, Vertex},
+    },
+    picking::{Hit, Picker, bvh::Bvh, gpu::IdBuffer},
+};
+
+/// Where one shape lives in the shared scene buffers.
+struct ShapeDraw {
+    indices: Range<u32>,
+    base_vertex: i32,
+}
+
+/// A shape's local mesh and hit testing hierarchy, kept until its geometry
+/// changes. Transforms and colors are applied on top when uploading.
+struct BakedShape {
+    geometry: ShapeGeometry,
+    vertices: Vec<Vertex>,
+    indices: Vec<u16>,
+    bvh: Arc<Bvh>,
+}
+
+impl BakedShape {
+    fn new(geometry: ShapeGeometry, (vertices, indices): (Vec<Vertex>, Vec<u16>)) -> Self {
+        let positions = vertices
+            .iter()
+            .map(|v| Vec2::new(v.position[0], v.position[1]))
+            .collect();
+        let bvh = Arc::new(Bvh::new(positions, &indices));
+        Self {
+            geometry,
+            vertices,
+            indices,
+            bvh,
+        }
+    }
+}
+
+/// Everything needed to draw a frame, independent of where it ends up.
+///
+/// `State` renders the scene into the window surface, while the headless
+/// renderer draws it into an offscreen texture. The shapes themselves live in
+/// a [`Document`] and every change to it goes through the undo history.
+pub struct Scene {
+    solid_pipeline: wgpu::RenderPipeline,
+    colored_pipeline: wgpu::RenderPipeline,
+    // Same pipelines with an extra ID buffer target
+    solid_id_pipeline: wgpu::RenderPipeline,
+    colored_id_pipeline: wgpu::RenderPipeline,
+    lit_pipeline: wgpu::RenderPipeline,
+    lit_id_pipeline: wgpu::RenderPipeline,
+    pbr_pipeline: wgpu::RenderPipeline,
+    pbr_id_pipeline: wgpu::RenderPipeline,
+    pipeline: PipelineKind,
+
+    // PBR palette, shape `i` uses material `i % len`
+    materials: Vec<Material>,
+    gpu_materials: Vec<GpuMaterial>,
+    material_layout: wgpu::BindGroupLayout,
+    materials_dirty: bool,
+
+    pub lighting: Lighting,
+    lights_buffer: wgpu::Buffer,
+    lights_bind_group: wgpu::BindGroup,
+    pub shadow_settings: ShadowSettings,
+    shadow_maps: ShadowMaps,
+
+    // Panorama behind the shapes and their image based light, once loaded
+    environment: Environment,
+    skybox: Skybox,
+    pub sky_view: SkyView,
+    pub show_skybox: bool,
+    aspect: f32,
+
+    camera: Camera2d,
+    view_3d: Option<Camera3d>,
+    camera_buffer: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
+    camera_dirty: bool,
+
+    document: Document,
+    history: History<EditCommand>,
+    // Shape that keyboard edits, morphs and the vertex editor act on
+    active: Option<usize>,
+
+    // All shapes baked into world space, rebuilt whenever the document changes
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    vertex_capacity: usize,
+    index_capacity: usize,
+    // The shapes again with normals, for the lit pipeline
+    lit_vertex_buffer: wgpu::Buffer,
+    draws: Vec<ShapeDraw>,
+    // Outlines of the shapes, drawn on top with their own colors when enabled
+    outline_stroke: Option<StrokeStyle>,
+    stroke_draws: Vec<ShapeDraw>,
+    mesh_dirty: bool,
+    // One per shape as last uploaded, `None` for the one being morphed
+    baked: Vec<Option<BakedShape>>,
+
+    // Morph of one shape towards its new geometry, drawn instead of the shape
+    morph: Option<(usize, Morph)>,
+    pub morph_duration: f32,
+
+    // Vertex editor for the active shape, only present while editing
+    editor: Option<VertexEditor>,
+    // Vertex handles and marquee, drawn on top while editing
+    overlay_vertex_buffer: wgpu::Buffer,
+    overlay_index_buffer: wgpu::Buffer,
+    overlay_vertex_capacity: usize,
+    overlay_index_capacity: usize,
+    overlay_num_indices: u32,
+
+    /// Background until an environment is loaded or while the skybox is hidden
+    pub clear_color: wgpu::Color,
+
+    // CPU copy of what is on screen, for hit testing
+    picker: Picker,
+}
+
+fn create_dynamic_buffers(
+    device: &wgpu::Device,
+    label: &str,
+    max_vertices: usize,
+    max_indices: usize,
+) -> (wgpu::Buffer, wgpu::Buffer) {
+    let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some(&format!("{label} Vertex Buffer")),
+        size: (max_vertices * std::mem::size_of::<Vertex>()) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let index_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some(&format!("{label} Index Buffer")),
+        size: (max_indices * std::mem::size_of::<u16>())
+            .next_multiple_of(wgpu::COPY_BUFFER_ALIGNMENT as usize)
+            as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    (vertex_buffer, index_buffer)
+}
+
+fn create_lit_vertex_buffer(device: &wgpu::Device, max_vertices: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Scene Lit Vertex Buffer"),
+        size: (max_vertices * std::mem::size_of::<LitVertex>()) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+/// Uploads a mesh into buffers made by `create_dynamic_buffers` and returns the index count.
+fn write_mesh(
+    queue: &wgpu::Queue,
+    vertex_buffer: &wgpu::Buffer,
+    index_buffer: &wgpu::Buffer,
+    vertices: &[Vertex],
+    indices: &[u16],
+) -> u32 {
+    if vertices.is_empty() || indices.is_empty() {
+        return 0;
+    }
+    queue.write_buffer(vertex_buffer, 0, bytemuck::cast_slice(vertices));
+    // write_buffer needs 4 byte aligned sizes
+    if indices.len() % 2 == 1 {
+        let mut padded = indices.to_vec();
+        padded.push(0);
+        queue.write_buffer(index_buffer, 0, bytemuck::cast_slice(&padded));
+    } else {
+        queue.write_buffer(index_buffer, 0, bytemuck::cast_slice(indices));
+    }
+    indices.len() as u32
+}
+
+/// Shader and vertex input of a scene pipeline. The module also provides
+/// `fs_main` and `fs_main_id`.
+struct VertexStage<'a> {
+    shader: &'a wgpu::ShaderModule,
+    entry_point: &'a str,
+    buffer: wgpu::VertexBufferLayout<'static>,
+}
+
+fn create_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    label: &str,
+    vertex: VertexStage,
+    format: wgpu::TextureFormat,
+    with_id: bool,
+) -> wgpu::RenderPipeline {
+    let shader = vertex.shader;
+    let color_target = Some(wgpu::ColorTargetState {
+        format,
+        blend: Some(wgpu::BlendState::REPLACE),
+        write_mask: wgpu::ColorWrites::ALL,
+    });
+    let id_target = Some(wgpu::ColorTargetState {
+        format: IdBuffer::FORMAT,
+        blend: None,
+        write_mask: wgpu::ColorWrites::ALL,
+    });
+    let targets = [color_target, id_target];
+
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(label),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: Some(vertex.entry_point),
+            buffers: &[vertex.buffer],
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: Some(if with_id { "fs_main_id" } else { "fs_main" }),
+            targets: if with_id { &targets } else { &targets[..1] },
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: Some(wgpu::Face::Back),
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: None,
+        multisample: wgpu::MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview_mask: None,
+        cache: None,
+    })
+}
+
+impl Scene {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shader.wgsl").into()),
+        });
+
+        let description = SceneDescription::default();
+
+        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Camera Buffer"),
+            contents: bytemuck::bytes_of(&description.camera.uniform()),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let camera_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Camera Bind Group Layout"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }],
+            });
+        let camera_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Camera Bind Group"),
+            layout: &camera_bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            }],
+        });
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Render pipeline layout"),
+                bind_group_layouts: &[&camera_bind_group_layout],
+                immediate_size: 0,
+            });
+
+        // Pipeline 1: Solid pipeline (uses solid red color)
+        let solid_pipeline = create_pipeline(
+            device,
+            &render_pipeline_layout,
+            "Solid Render Pipeline",
+            VertexStage {
+                shader: &shader,
+                entry_point: "vs_solid",
+                buffer: Vertex::desc(),
+            },
+            format,
+            false,
+        );
+
+        // Pipeline 2: Colored (uses vertex colors)
+        let colored_pipeline = create_pipeline(
+            device,
+            &render_pipeline_layout,
+            "Colored Render Pipeline",
+            VertexStage {
+                shader: &shader,
+                entry_point: "vs_main",
+                buffer: Vertex::desc(),
+            },
+            format,
+            false,
+        );
+
+        let solid_id_pipeline = create_pipeline(
+            device,
+            &render_pipeline_layout,
+            "Solid ID Render Pipeline",
+            VertexStage {
+                shader: &shader,
+                entry_point: "vs_solid",
+                buffer: Vertex::desc(),
+            },
+            format,
+            true,
+        );
+
+        let colored_id_pipeline = create_pipeline(
+            device,
+            &render_pipeline_layout,
+            "Colored ID Render Pipeline",
+            VertexStage {
+                shader: &shader,
+                entry_point: "vs_main",
+                buffer: Vertex::desc(),
+            },
+            format,
+            true,
+        );
+
+        // Pipeline 3: Lit (vertex colors shaded by the lights)
+        let lit_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Lit Shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                concat!(include_str!("../lights.wgsl"), include_str!("../lit.wgsl")).into(),
+            ),
+        });
+        let lighting = Lighting::default();
+        let lights_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Lights Buffer"),
+            contents: bytemuck::bytes_of(&lighting.uniform(Vec4::Z)),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        // The image based light of the environment is only read by PBR
+        let environment = Environment::new(device, EnvironmentSettings::default());
+        let environment_map = |binding, view_dimension| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                multisampled: false,
+                view_dimension,
+                sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            },
+            count: None,
+        };
+        let lights_bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Lights Bind Group Layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    environment_map(1, wgpu::TextureViewDimension::Cube),
+                    environment_map(2, wgpu::TextureViewDimension::Cube),
+                    environment_map(3, wgpu::TextureViewDimension::D2),
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 4,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                ],
+            });
+        let lights_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Lights Bind Group"),
+            layout: &lights_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: lights_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(environment.irradiance_view()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(environment.prefiltered_view()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: wgpu::BindingResource::TextureView(environment.brdf_lut_view()),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: wgpu::BindingResource::Sampler(environment.sampler()),
+                },
+            ],
+        });
+        let skybox = Skybox::new(device, format, &environment);
+        let shadow_settings = ShadowSettings::default();
+        let shadow_maps = ShadowMaps::new(device, &shadow_settings);
+        let lit_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Lit pipeline layout"),
+            bind_group_layouts: &[
+                &camera_bind_group_layout,
+                &lights_bind_group_layout,
+                shadow_maps.bind_group_layout(),
+            ],
+            immediate_size: 0,
+        });
+        let lit_stage = || VertexStage {
+            shader: &lit_shader,
+            entry_point: "vs_main",
+            buffer: LitVertex::desc(),
+        };
+        let lit_pipeline = create_pipeline(
+            device,
+            &lit_pipeline_layout,
+            "Lit Render Pipeline",
+            lit_stage(),
+            format,
+            false,
+        );
+        let lit_id_pipeline = create_pipeline(
+            device,
+            &lit_pipeline_layout,
+            "Lit ID Render Pipeline",
+            lit_stage(),
+            format,
+            true,
+        );
+
+        // Pipeline 4: PBR (the lit pipeline with a material bind group per draw)
+        let pbr_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("PBR Shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                concat!(include_str!("../lights.wgsl"), include_str!("../pbr.wgsl")).into(),
+            ),
+        });
+        let material_layout = material_gpu::bind_group_layout(device);
+        let pbr_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("PBR pipeline layout"),
+            bind_group_layouts: &[
+                &camera_bind_group_layout,
+                &lights_bind_group_layout,
+                shadow_maps.bind_group_layout(),
+                &material_layout,
+            ],
+            immediate_size: 0,
+        });
+        let pbr_stage = || VertexStage {
+            shader: &pbr_shader,
+            entry_point: "vs_main",
+            buffer: LitVertex::desc(),
+        };
+        let pbr_pipeline = create_pipeline(
+            device,
+            &pbr_pipeline_layout,
+            "PBR Render Pipeline",
+            pbr_stage(),
+            format,
+            false,
+        );
+        let pbr_id_pipeline = create_pipeline(
+            device,
+            &pbr_pipeline_layout,
+            "PBR ID Render Pipeline",
+            pbr_stage(),
+            format,
+            true,
+        );
+
+        // Grown on demand in `upload_mesh`
+        let vertex_capacity = MORPH_POINT_COUNT * 4;
+        let index_capacity = vertex_capacity * 3;
+        let (vertex_buffer, index_buffer) =
+            create_dynamic_buffers(device, "Scene", vertex_capacity, index_capacity);
+        let lit_vertex_buffer = create_lit_vertex_buffer(device, vertex_capacity);
+
+        // One quad per vertex handle plus four for the marquee frame, grown on demand in `update`
+        let overlay_vertex_capacity = (MORPH_POINT_COUNT + 4) * 4;
+        let overlay_index_capacity = (MORPH_POINT_COUNT + 4) * 6;
+        let (overlay_vertex_buffer, overlay_index_buffer) = create_dynamic_buffers(
+            device,
+            "Edit Overlay",
+            overlay_vertex_capacity,
+            overlay_index_capacity,
+        );
+
+        Self {
+            solid_pipeline,
+            colored_pipeline,
+            solid_id_pipeline,
+            colored_id_pipeline,
+            lit_pipeline,
+            lit_id_pipeline,
+            pbr_pipeline,
+            pbr_id_pipeline,
+            pipeline: description.pipeline,
+            materials: Material::presets(),
+            // Uploaded in `update`, which has the queue
+            gpu_materials: Vec::new(),
+            material_layout,
+            materials_dirty: true,
+            lighting,
+            lights_buffer,
+            lights_bind_group,
+            shadow_settings,
+            shadow_maps,
+            environment,
+            skybox,
+            sky_view: SkyView::default(),
+            show_skybox: true,
+            aspect: 1.0,
+            camera: description.camera,
+            view_3d: None,
+            camera_buffer,
+            camera_bind_group,
+            camera_dirty: false,
+            active: (!description.document.shapes.is_empty()).then_some(0),
+            document: description.document,
+            history: History::new(HISTORY_MAX_BYTES),
+            vertex_buffer,
+            index_buffer,
+            vertex_capacity,
+            index_capacity,
+            lit_vertex_buffer,
+            draws: Vec::new(),
+            outline_stroke: None,
+            stroke_draws: Vec::new(),
+            mesh_dirty: true,
+            baked: Vec::new(),
+            morph: None,
+            morph_duration: MORPH_DURATION_SECS,
+            editor: None,
+            overlay_vertex_buffer,
+            overlay_index_buffer,
+            overlay_vertex_capacity,
+            overlay_index_capacity,
+            overlay_num_indices: 0,
+            clear_color: description.clear_color,
+            picker: Picker::default(),
+        }
+    }
+
+    pub fn document(&self) -> &Document {
+        &self.document
+    }
+
+    pub fn history(&self) -> &History<EditCommand> {
+        &self.history
+    }
+
+    pub fn active_shape(&self) -> Option<usize> {
+        self.active
+    }
+
+    /// Makes `shape` the target of keyboard edits. Not recorded in the history.
+    pub fn set_active_shape(&mut self, shape: Option<usize>) {
+        self.active = shape.filter(|&i| i < self.document.shapes.len());
+        if self.editor.is_some() {
+            self.stop_editing();
+        }
+    }
+
+    pub fn shape_name(&self, shape: usize) -> &'static str {
+        self.document
+            .shapes
+            .get(shape)
+            .map_or("None", |node| node.geometry.name())
+    }
+
+    pub fn pipeline(&self) -> PipelineKind {
+        self.pipeline
+    }
+
+    pub fn set_pipeline(&mut self, pipeline: PipelineKind) {
+        self.pipeline = pipeline;
+    }
+
+    pub fn pipeline_name(&self) -> &'static str {
+        self.pipeline.name()
+    }
+
+    /// PBR material of a shape from the palette.
+    pub fn material(&self, shape: usize) -> &Material {
+        &self.materials[shape % self.materials.len()]
+    }
+
+    /// Changes are uploaded in the next [`Scene::update`].
+    pub fn material_mut(&mut self, shape: usize) -> &mut Material {
+        self.materials_dirty = true;
+        let len = self.materials.len();
+        &mut self.materials[shape % len]
+    }
+
+    pub fn camera(&self) -> &Camera2d {
+        &self.camera
+    }
+
+    pub fn set_camera(&mut self, camera: Camera2d) {
+        self.camera = camera;
+        self.camera_dirty = true;
+    }
+
+    /// Perspective camera that replaces the 2D one while it is set.
+    pub fn view_3d(&self) -> Option<&Camera3d> {
+        self.view_3d.as_ref()
+    }
+
+    pub fn set_view_3d(&mut self, camera: Option<Camera3d>) {
+        self.view_3d = camera;
+        self.camera_dirty = true;
+    }
+
+    /// Matrix of the camera the scene is drawn with.
+    pub fn view_proj(&self) -> Mat4 {
+        match &self.view_3d {
+            Some(camera) => camera.view_proj(self.aspect),
+            None => self.camera.view_proj(),
+        }
+    }
+
+    pub fn camera_uniform(&self) -> CameraUniform {
+        CameraUniform {
+            view_proj: self.view_proj().to_cols_array_2d(),
+        }
+    }
+
+    /// Aspect ratio the skybox and the 3D camera are projected with, the 2D
+    /// camera ignores it.
+    pub fn set_viewport_size(&mut self, width: u32, height: u32) {
+        self.aspect = width as f32 / height.max(1) as f32;
+        self.camera_dirty = true;
+    }
+
+    /// Converts an equirectangular panorama into the skybox and the image
+    /// based light of the PBR pipeline.
+    pub fn load_environment(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, image: &HdrImage) {
+        self.environment.load(device, queue, image);
+    }
+
+    pub fn environment(&self) -> &Environment {
+        &self.environment
+    }
+
+    /// Whether the skybox replaces the clear color.
+    pub fn skybox_visible(&self) -> bool {
+        self.show_skybox && self.environment.is_loaded()
+    }
+
+    pub fn outline_stroke(&self) -> Option<&StrokeStyle> {
+        self.outline_stroke.as_ref()
+    }
+
+    /// Strokes the outline of every shape with `style`, or stops with `None`.
+    pub fn set_outline_stroke(&mut self, style: Option<StrokeStyle>) {
+        self.outline_stroke = style;
+        self.mesh_dirty = true;
+    }
+
+    /// Snapshot of everything a scene file stores.
+    pub fn description(&self) -> SceneDescription {
+        SceneDescription {
+            document: self.document.clone(),
+            pipeline: self.pipeline,
+            clear_color: self.clear_color,
+            camera: self.camera,
+        }
+    }
+
+    /// Replaces the whole scene, e.g. with one loaded from a file. The undo
+    /// history is cleared since its commands refer to the old shapes.
+    pub fn set_description(&mut self, description: SceneDescription) {
+        self.stop_editing();
+        self.history.clear();
+        self.morph = None;
+        self.active = (!description.document.shapes.is_empty()).then_some(0);
+        self.document = description.document;
+        self.pipeline = description.pipeline;
+        self.clear_color = description.clear_color;
+        self.set_camera(description.camera);
+        self.mesh_dirty = true;
+    }
+
+    /// Applies `command` to the document and records it for undo.
+    pub fn execute(&mut self, command: EditCommand) {
+        self.history.execute(command, &mut self.document);
+        self.mesh_dirty = true;
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let undone = self.history.undo(&mut self.document);
+        self.after_history_jump();
+        undone
+    }
+
+    pub fn redo(&mut self) -> bool {
+        let redone = self.history.redo(&mut self.document);
+        self.after_history_jump();
+        redone
+    }
+
+    fn after_history_jump(&mut self) {
+        self.morph = None;
+        self.mesh_dirty = true;
+        let len = self.document.shapes.len();
+        self.active = match self.active {
+            _ if len == 0 => None,
+            Some(i) => Some(i.min(len - 1)),
+            None => None,
+        };
+        // Undo can turn the edited outline back into a preset
+        let outline_len = self.active_outline_len();
+        match (&mut self.editor, outline_len) {
+            (Some(editor), Some(len)) => editor.clamp_selection(len),
+            (Some(_), None) => self.editor = None,
+            _ => {}
+        }
+    }
+
+    fn active_outline_len(&self) -> Option<usize> {
+        match &self.document.shapes.get(self.active?)?.geometry {
+            ShapeGeometry::Outline(points) => Some(points.len()),
+            _ => None,
+        }
+    }
+
+    /// Switches the active shape to the next preset and flips between the
+    /// solid and colored pipelines, morphing towards the new shape. A morph that is still playing is
+    /// picked up from where it currently is.
+    pub fn toggle_shape(&mut self) {
+        let Some(index) = self.active else {
+            return;
+        };
+        let from = match &self.morph {
+            Some((shape, morph)) if *shape == index => morph.current(),
+            _ => self.document.shapes[index].geometry.outline(),
+        };
+        let before = self.document.shapes[index].geometry.clone();
+        let next = before.preset().map_or(ShapeType::Pentagon, ShapeType::next);
+
+        self.stop_editing();
+        self.history.end_group();
+        self.execute(EditCommand::SetGeometry {
+            shape: index,
+            before,
+            after: ShapeGeometry::Preset(next),
+        });
+        self.morph = Some((
+            index,
+            Morph::new(
+                &from,
+                &next.outline(),
+                MORPH_POINT_COUNT,
+                self.morph_duration,
+            ),
+        ));
+        // Lit pipelines stay put, they are picked from the panel
+        self.pipeline = match self.pipeline {
+            PipelineKind::Solid => PipelineKind::Colored,
+            PipelineKind::Colored => PipelineKind::Solid,
+            lit => lit,
+        };
+    }
+
+    pub fn is_editing(&self) -> bool {
+        self.editor.is_some()
+    }
+
+    /// Turns the vertex editor for the active shape on or off. Shapes that are
+    /// not outlines yet are turned into editable outlines first.
+    pub fn toggle_editing(&mut self) {
+        if self.editor.is_some() {
+            self.stop_editing();
+            return;
+        }
+        let Some(index) = self.active else {
+            return;
+        };
+
+        let geometry = &self.document.shapes[index].geometry;
+        let points = geometry.outline().points;
+        if points.len() > EDIT_MAX_VERTICES {
+            log::warn!(
+                "Shape {index} has {} points, only shapes with up to {EDIT_MAX_VERTICES} can be edited",
+                points.len()
+            );
+            return;
+        }
+
+        self.morph = None;
+        if !matches!(geometry, ShapeGeometry::Outline(_)) {
+            let before = geometry.clone();
+            let after = ShapeGeometry::Outline(points);
+            self.execute(EditCommand::SetGeometry {
+                shape: index,
+                before,
+                after,
+            });
+        }
+        self.editor = Some(VertexEditor::new());
+    }
+
+    fn stop_editing(&mut self) {
+        if self.editor.take().is_some() {
+            self.history.end_group();
+            self.overlay_num_indices = 0;
+        }
+    }
+
+    /// The editor, while editing is switched on.
+    pub fn editor_mut(&mut self) -> Option<&mut VertexEditor> {
+        self.editor.as_mut()
+    }
+
+    /// Outline of the edited shape with positions in world space.
+    fn edited_points(&self) -> Option<(usize, Vec<Vertex>)> {
+        self.editor.as_ref()?;
+        let index = self.active?;
+        let node = &self.document.shapes[index];
+        let ShapeGeometry::Outline(points) = &node.geometry else {
+            return None;
+        };
+        let world = points
+            .iter()
+            .map(|v| {
+                let p = node
+                    .transform
+                    .apply(Vec2::new(v.position[0], v.position[1]));
+                Vertex {
+                    position: [p.x, p.y, v.position[2]],
+                    color: v.color,
+                }
+            })
+            .collect();
+        Some((index, world))
+    }
+
+    fn apply_editor_action(&mut self, shape: usize, action: EditorAction) {
+        let transform = self.document.shapes[shape].transform;
+        let command = match action {
+            EditorAction::Move { vertices, from, to } => EditCommand::MoveVertices {
+                shape,
+                vertices,
+                from: from
+                    .into_iter()
+                    .map(|p| transform.apply_inverse(p))
+                    .collect(),
+                to: to.into_iter().map(|p| transform.apply_inverse(p)).collect(),
+            },
+            EditorAction::Replace(points) => EditCommand::SetGeometry {
+                shape,
+                before: self.document.shapes[shape].geometry.clone(),
+                after: ShapeGeometry::Outline(
+                    points
+                        .into_iter()
+                        .map(|mut v| {
+                            let p =
+                                transform.apply_inverse(Vec2::new(v.position[0], v.position[1]));
+                            v.position[0] = p.x;
+                            v.position[1] = p.y;
+                            v
+                        })
+                        .collect(),
+                ),
+            },
+        };
+        self.execute(command);
+    }
+
+    /// Left button pressed at `world` while editing. Everything until
+    /// `editor_pointer_up` undoes as one step where possible.
+    pub fn editor_pointer_down(&mut self, world: Vec2, additive: bool) {
+        let Some((shape, points)) = self.edited_points() else {
+            return;
+        };
+        self.history.begin_group();
+        let editor = self.editor.as_mut().expect("editing");
+        if let Some(action) = editor.pointer_down(&points, world, additive) {
+            self.apply_editor_action(shape, action);
+        }
+    }
+
+    pub fn editor_pointer_moved(&mut self, world: Vec2) {
+        let Some(shape) = self.active else {
+            return;
+        };
+        let Some(editor) = &mut self.editor else {
+            return;
+        };
+        if let Some(action) = editor.pointer_moved(world) {
+            self.apply_editor_action(shape, action);
+        }
+    }
+
+    pub fn editor_pointer_up(&mut self) {
+        let Some((_, points)) = self.edited_points() else {
+            return;
+        };
+        if let Some(editor) = &mut self.editor {
+            editor.pointer_up(&points);
+        }
+        self.history.end_group();
+    }
+
+    pub fn editor_delete_selected(&mut self) {
+        let Some((shape, points)) = self.edited_points() else {
+            return;
+        };
+        let editor = self.editor.as_mut().expect("editing");
+        if let Some(action) = editor.delete_selected(&points) {
+            self.history.end_group();
+            self.apply_editor_action(shape, action);
+        }
+    }
+
+    /// Records a transform change of the active shape.
+    pub fn transform_active(&mut self, change: impl FnOnce(&mut Transform2d)) {
+        let Some(shape) = self.active else {
+            return;
+        };
+        let before = self.document.shapes[shape].transform;
+        let mut after = before;
+        change(&mut after);
+        self.execute(EditCommand::SetTransform {
+            shape,
+            before,
+            after,
+        });
+    }
+
+    /// Gives the active shape the next tint from `SHAPE_TINTS`.
+    pub fn cycle_active_color(&mut self) {
+        let Some(shape) = self.active else {
+            return;
+        };
+        let before = self.document.shapes[shape].color;
+        let next = SHAPE_TINTS
+            .iter()
+            .position(|&tint| tint == before)
+            .map_or(0, |i| (i + 1) % SHAPE_TINTS.len());
+        self.execute(EditCommand::SetColor {
+            shape,
+            before,
+            after: SHAPE_TINTS[next],
+        });
+    }
+
+    /// Adds a copy of the active shape, slightly offset, and makes it active.
+    pub fn duplicate_active(&mut self) {
+        let Some(shape) = self.active else {
+            return;
+        };
+        let mut copy = self.document.shapes[shape].clone();
+        copy.transform.translation += Vec2::new(0.1, -0.1);
+        let index = self.document.shapes.len();
+        self.execute(EditCommand::AddShape { index, shape: copy });
+        self.set_active_shape(Some(index));
+    }
+
+    /// Appends `shapes` as a single undo step and makes the first one active.
+    pub fn add_shapes(&mut self, shapes: Vec<ShapeNode>) {
+        if shapes.is_empty() {
+            return;
+        }
+        let first = self.document.shapes.len();
+        let commands = shapes
+            .into_iter()
+            .enumerate()
+            .map(|(offset, shape)| EditCommand::AddShape {
+                index: first + offset,
+                shape,
+            })
+            .collect();
+        self.history.end_group();
+        self.execute(EditCommand::Batch(commands));
+        self.set_active_shape(Some(first));
+    }
+
+    pub fn remove_active(&mut self) {
+        let Some(index) = self.active else {
+            return;
+        };
+        self.stop_editing();
+        self.morph = None;
+        let shape = self.document.shapes[index].clone();
+        self.execute(EditCommand::RemoveShape { index, shape });
+        self.active = None;
+    }
+
+    /// Local-space mesh of a shape as it is currently drawn, including a running morph.
+    /// Outlines that cross themselves are not drawn.
+    fn shape_mesh(&self, index: usize) -> (Vec<Vertex>, Vec<u16>) {
+        let geometry = &self.document.shapes[index].geometry;
+        let mesh = match &self.morph {
+            // The shape being morphed to stands in while the outline crosses itself
+            Some((shape, morph)) if *shape == index => morph.mesh().or_else(|_| geometry.mesh()),
+            _ => geometry.mesh(),
+        };
+        mesh.unwrap_or_else(|e| {
+            log::warn!("Not drawing shape {index}: {e:#}");
+            (Vec::new(), Vec::new())
+        })
+    }
+
+    /// Local-space outline of a shape as it is currently drawn, including a running morph.
+    fn shape_outline(&self, index: usize) -> Outline {
+        match &self.morph {
+            Some((shape, morph)) if *shape == index => morph.current(),
+            _ => self.document.shapes[index].geometry.outline(),
+        }
+    }
+
+    /// World-space outline stroke of a shape.
+    fn stroke_mesh(&self, index: usize, style: &StrokeStyle) -> (Vec<Vertex>, Vec<u16>) {
+        let transform = self.document.shapes[index].transform;
+        let points: Vec<Vec2> = self
+            .shape_outline(index)
+            .positions_2d()
+            .into_iter()
+            .map(|p| transform.apply(Vec2::from_array(p)))
+            .collect();
+        stroke_polyline(&points, true, style).unwrap_or_else(|e| {
+            log::warn!("Not drawing the outline of shape {index}: {e:#}");
+            (Vec::new(), Vec::new())
+        })
+    }
+
+    /// Everything that is drawn, in world space and draw order: the shapes as
+    /// they currently look, followed by their outline strokes when enabled.
+    pub fn world_meshes(&self) -> Vec<(Vec<Vertex>, Vec<u16>)> {
+        let shapes = (0..self.document.shapes.len()).map(|index| {
+            let (mut vertices, indices) = self.shape_mesh(index);
+            self.document.shapes[index].to_world(&mut vertices);
+            (vertices, indices)
+        });
+        let strokes = self.outline_stroke.iter().flat_map(|style| {
+            (0..self.document.shapes.len()).map(|index| self.stroke_mesh(index, style))
+        });
+        shapes.chain(strokes).collect()
+    }
+
+    /// Takes the baked version of `geometry` out of `baked`, looking at
+    /// `index` first since most shapes keep their place.
+    fn take_baked(
+        baked: &mut [Option<BakedShape>],
+        index: usize,
+        geometry: &ShapeGeometry,
+    ) -> Option<BakedShape> {
+        let matches = |slot: &Option<BakedShape>| {
+            slot.as_ref()
+                .is_some_and(|shape| shape.geometry == *geometry)
+        };
+        let slot = if baked.get(index).is_some_and(matches) {
+            index
+        } else {
+            baked.iter().position(matches)?
+        };
+        baked[slot].take()
+    }
+
+    /// Bakes every shape into world space and uploads the result, growing the
+    /// buffers when needed. Also rebuilds the hit testing data.
+    ///
+    /// Only the morphing shape and shapes whose geometry changed since the
+    /// last upload are triangulated again, the others reuse their local mesh
+    /// and hierarchy under their current transform.
+    fn upload_mesh(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) {
+        let mut vertices = Vec::new();
+        let mut lit_vertices = Vec::new();
+        let mut indices = Vec::new();
+        self.draws.clear();
+        self.picker.clear();
+        let mut previous = std::mem::take(&mut self.baked);
+
+        for index in 0..self.document.shapes.len() {
+            let node = &self.document.shapes[index];
+            let morphing = matches!(&self.morph, Some((shape, _)) if *shape == index);
+            let cached = if morphing {
+                None
+            } else {
+                Self::take_baked(&mut previous, index, &node.geometry)
+            };
+            let shape = cached
+                .unwrap_or_else(|| BakedShape::new(node.geometry.clone(), self.shape_mesh(index)));
+            self.picker
+                .add_bvh(index, node.transform.to_mat4(), shape.bvh.clone());
+
+            let mut shape_vertices = shape.vertices.clone();
+            node.to_world(&mut shape_vertices);
+            let first = indices.len() as u32;
+            self.draws.push(ShapeDraw {
+                indices: first..first + shape.indices.len() as u32,
+                base_vertex: vertices.len() as i32,
+            });
+            // Texture coordinates from the local positions so maps move with the shape
+            let lit = smooth_normals(&shape_vertices, &shape.indices);
+            lit_vertices.extend(lit.into_iter().zip(&shape.vertices).map(|(vertex, local)| {
+                LitVertex {
+                    uv: planar_uv(local.position),
+                    ..vertex
+                }
+            }));
+            vertices.extend(shape_vertices);
+            indices.extend_from_slice(&shape.indices);
+            // A morph bakes a new mesh every frame, there is nothing to keep
+            self.baked.push((!morphing).then_some(shape));
+        }
+
+        // Stroked in world space so the line width does not scale with the shape
+        self.stroke_draws.clear();
+        if let Some(style) = &self.outline_stroke {
+            for index in 0..self.document.shapes.len() {
+                let (stroke_vertices, stroke_indices) = self.stroke_mesh(index, style);
+                let first = indices.len() as u32;
+                self.stroke_draws.push(ShapeDraw {
+                    indices: first..first + stroke_indices.len() as u32,
+                    base_vertex: vertices.len() as i32,
+                });
+                vertices.extend(stroke_vertices);
+                indices.extend(stroke_indices);
+            }
+        }
+
+        if vertices.len() > self.vertex_capacity || indices.len() > self.index_capacity {
+            self.vertex_capacity = vertices.len().next_power_of_two();
+            self.index_capacity = indices.len().next_power_of_two();
+            (self.vertex_buffer, self.index_buffer) =
+                create_dynamic_buffers(device, "Scene", self.vertex_capacity, self.index_capacity);
+            self.lit_vertex_buffer = create_lit_vertex_buffer(device, self.vertex_capacity);
+        }
+        if !lit_vertices.is_empty() {
+            queue.write_buffer(&self.lit_vertex_buffer, 0, bytemuck::cast_slice(&lit_vertices));
+        }
+        write_mesh(
+            queue,
+            &self.vertex_buffer,
+            &self.index_buffer,
+            &vertices,
+            &indices,
+        );
+    }
+
+    /// Hit testing data for the shapes as last uploaded.
+    pub fn picker(&self) -> &Picker {
+        &self.picker
+    }
+
+    /// Finds the shape and triangle under a point in world space.
+    pub fn pick(&self, world: Vec2) -> Option<Hit> {
+        self.picker.pick(world)
+    }
+
+    pub fn update(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, dt: f32) {
+        if std::mem::take(&mut self.camera_dirty) {
+            queue.write_buffer(
+                &self.camera_buffer,
+                0,
+                bytemuck::bytes_of(&self.camera_uniform()),
+            );
+        }
+        if self.skybox_visible() {
+            // A 3D camera looks at the sky the way it looks at the scene
+            let sky_view = self.view_3d.map_or(self.sky_view, |camera| camera.sky_view());
+            self.skybox.prepare(queue, &sky_view, self.aspect);
+        }
+        if self.pipeline == PipelineKind::Pbr && std::mem::take(&mut self.materials_dirty) {
+            self.gpu_materials = self
+                .materials
+                .iter()
+                .map(|material| GpuMaterial::new(device, queue, &self.material_layout, material))
+                .collect();
+        }
+        if self.pipeline.is_lit() {
+            let (eye, frustum) = match &self.view_3d {
+                Some(camera) => (camera.eye.extend(1.0), camera.frustum(self.aspect)),
+                // The orthographic camera looks down -Z from everywhere
+                None => (Vec4::Z, self.camera.frustum()),
+            };
+            queue.write_buffer(
+                &self.lights_buffer,
+                0,
+                bytemuck::bytes_of(&self.lighting.uniform(eye)),
+            );
+            let views = shadow_views(&self.lighting, &self.shadow_settings, &frustum);
+            self.shadow_maps
+                .prepare(device, queue, &views, &self.shadow_settings);
+        }
+
+        if let Some((_, morph)) = &mut self.morph {
+            morph.advance(dt);
+            if morph.is_finished() {
+                self.morph = None;
+            }
+            self.mesh_dirty = true;
+        }
+
+        if std::mem::take(&mut self.mesh_dirty) {
+            self.upload_mesh(device, queue);
+            if let Some(editor) = &mut self.editor {
+                editor.mark_dirty();
+            }
+        }
+
+        if self
+            .editor
+            .as_mut()
+            .is_some_and(|editor| editor.take_dirty())
+        {
+            let (overlay_vertices, overlay_indices) = match self.edited_points() {
+                Some((_, points)) => self.editor.as_ref().expect("editing").overlay_mesh(&points),
+                None => (Vec::new(), Vec::new()),
+            };
+            if overlay_vertices.len() > self.overlay_vertex_capacity
+                || overlay_indices.len() > self.overlay_index_capacity
+            {
+                self.overlay_vertex_capacity = overlay_vertices.len().next_power_of_two();
+                self.overlay_index_capacity = overlay_indices.len().next_power_of_two();
+                (self.overlay_vertex_buffer, self.overlay_index_buffer) = create_dynamic_buffers(
+                    device,
+                    "Edit Overlay",
+                    self.overlay_vertex_capacity,
+                    self.overlay_index_capacity,
+                );
+            }
+            self.overlay_num_indices = write_mesh(
+                queue,
+                &self.overlay_vertex_buffer,
+                &self.overlay_index_buffer,
+                &overlay_vertices,
+                &overlay_indices,
+            );
+        }
+    }
+
+    /// Records the main render pass into `encoder`, clearing `view` first or
+    /// drawing the skybox into it.
+    ///
+    /// With `id_view` every pixel also gets the id of the object covering it.
+    /// Object ids are the shape's index in the document and travel as the
+    /// instance index of each draw call.
+    pub fn draw(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        id_view: Option<&wgpu::TextureView>,
+    ) {
+        let color_attachments = [
+            Some(wgpu::RenderPassColorAttachment {
+                view,
+                depth_slice: None,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: if self.skybox_visible() {
+                        wgpu::LoadOp::Load
+                    } else {
+                        wgpu::LoadOp::Clear(self.clear_color)
+                    },
+                    store: wgpu::StoreOp::Store,
+                },
+            }),
+            id_view.map(|id_view| wgpu::RenderPassColorAttachment {
+                view: id_view,
+                depth_slice: None,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            }),
+        ];
+        let attachment_count = if id_view.is_some() { 2 } else { 1 };
+
+        if self.skybox_visible() {
+            self.skybox.draw(encoder, view);
+        }
+        if self.pipeline.is_lit() {
+            self.shadow_maps.draw(encoder, |render_pass| {
+                render_pass.set_vertex_buffer(0, self.lit_vertex_buffer.slice(..));
+                render_pass
+                    .set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+                for draw in &self.draws {
+                    render_pass.draw_indexed(draw.indices.clone(), draw.base_vertex, 0..1);
+                }
+            });
+        }
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render Pass"),
+            color_attachments: &color_attachments[..attachment_count],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+            multiview_mask: None,
+        });
+
+        let pipeline = match (self.pipeline, id_view.is_some()) {
+            (PipelineKind::Colored, false) => &self.colored_pipeline,
+            (PipelineKind::Solid, false) => &self.solid_pipeline,
+            (PipelineKind::Colored, true) => &self.colored_id_pipeline,
+            (PipelineKind::Solid, true) => &self.solid_id_pipeline,
+            (PipelineKind::Lit, false) => &self.lit_pipeline,
+            (PipelineKind::Lit, true) => &self.lit_id_pipeline,
+            (PipelineKind::Pbr, false) => &self.pbr_pipeline,
+            (PipelineKind::Pbr, true) => &self.pbr_id_pipeline,
+        };
+
+        render_pass.set_pipeline(pipeline);
+        render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+        if self.pipeline.is_lit() {
+            render_pass.set_bind_group(1, &self.lights_bind_group, &[]);
+            render_pass.set_bind_group(2, self.shadow_maps.bind_group(), &[]);
+            render_pass.set_vertex_buffer(0, self.lit_vertex_buffer.slice(..));
+        } else {
+            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        }
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+        for (object_id, draw) in self.draws.iter().enumerate() {
+            if self.pipeline == PipelineKind::Pbr {
+                // Nothing to bind before the first update
+                let Some(material) = self
+                    .gpu_materials
+                    .get(object_id % self.gpu_materials.len().max(1))
+                else {
+                    break;
+                };
+                render_pass.set_bind_group(3, material.bind_group(), &[]);
+            }
+            let object_id = object_id as u32;
+            render_pass.draw_indexed(
+                draw.indices.clone(),
+                draw.base_vertex,
+                object_id..object_id + 1,
+            );
+        }
+
+        // Strokes and handles always show their own colors
+        let own_colors_pipeline = if id_view.is_some() {
+            &self.colored_id_pipeline
+        } else {
+            &self.colored_pipeline
+        };
+
+        if !self.stroke_draws.is_empty() {
+            render_pass.set_pipeline(own_colors_pipeline);
+            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+            for (object_id, draw) in self.stroke_draws.iter().enumerate() {
+                let object_id = object_id as u32;
+                render_pass.draw_indexed(
+                    draw.indices.clone(),
+                    draw.base_vertex,
+                    object_id..object_id + 1,
+                );
+            }
+        }
+
+        if self.editor.is_some() && self.overlay_num_indices > 0 {
+            render_pass.set_pipeline(own_colors_pipeline);
+            render_pass.set_vertex_buffer(0, self.overlay_vertex_buffer.slice(..));
+            render_pass.set_index_buffer(
+                self.overlay_index_buffer.slice(..),
+                wgpu::IndexFormat::Uint16,
+            );
+            let object_id = self.active.unwrap_or_default() as u32;
+            render_pass.draw_indexed(0..self.overlay_num_indices, 0, object_id..object_id + 1);
+        }
+    }
+}
@@ -1,14 +1,49 @@
-use std::sync::Arc;
-use wgpu::util::DeviceExt;
-use winit::{event_loop::ActiveEventLoop, keyboard::KeyCode, window::Window};
+use std::{path::Path, sync::Arc};
+use winit::{
+    event::{MouseButton, MouseScrollDelta, WindowEvent},
+    event_loop::ActiveEventLoop,
+    keyboard::{KeyCode, ModifiersState},
+    window::{CursorGrabMode, Window},
+};
 
 #[cfg(target_arch = "wasm32")]
 use wasm_bindgen::prelude::*;
 
 use crate::{
-    consts::{INDICES, STAR_INDICES, STAR_VERTICES, VERTICES},
-    enums::ShapeType,
-    models::vertex::Vertex,
+    consts::{
+        DEBUG_BOUNDS_COLOR, DEBUG_CLICK_LIFETIME, DEBUG_GRID_COLOR, DEBUG_GRID_SPACING,
+        DEBUG_MARKER_PIXELS, DEBUG_NORMAL_PIXELS, DEBUG_PICK_COLOR, EDIT_GRID_SIZE, EDIT_HANDLE_PIXELS, ENVIRONMENT_FILE, EXPORT_OBJ_FILE, EXPORT_SVG_FILE, FPS_SMOOTHING,
+        HUD_COLOR, HUD_FONT_SIZE, HUD_MARGIN, MOVE_STEP, OUTLINE_COLOR, OUTLINE_WIDTH,
+        PARTICLE_ATTRACTOR_STRENGTH, PARTICLE_CAPACITY,
+        ROTATE_STEP_DEGREES, SCALE_STEP, SCENE_FILE, SHADOW_MAP_SIZES, TITLE_TEXT, UI_MARGIN, UI_PANEL_WIDTH,
+        WHEEL_PIXELS_PER_LINE,
+    },
+    controls::{FlyController, OrbitController, PanZoomController},
+    debug_draw::{self, DebugStyle, renderer::DebugRenderer},
+    document::{ShapeGeometry, ShapeNode, file::SceneDescription},
+    enums::{CameraMode, PipelineKind},
+    environment::hdr::HdrImage,
+    geometry::stroke::{LineJoin, StrokeStyle},
+    lighting::shadow::MAX_CASCADES,
+    models::{clock::Clock, scene::Scene},
+    picking::{
+        Hit, cursor_to_ndc,
+        gpu::{IdBuffer, IdPick},
+        ndc_to_world,
+    },
+    obj::{export_obj, import_obj},
+    particles::{Attractor, Emitter, ParticleSettings, gpu::ParticleSystem},
+    post::{Effect, PostSettings, renderer::PostProcessor, tonemap::Tonemap},
+    svg::{
+        export::{SvgExportOptions, export_svg},
+        import::{SvgImportOptions, import_svg},
+    },
+    text::{
+        layout::TextStyle,
+        renderer::TextRenderer,
+        vector::{VectorTextOptions, text_mesh},
+    },
+    ui::{Ui, UiOutput, renderer::UiRenderer},
 };
 pub struct State {
     surface: wgpu::Surface<'static>,
@@ -19,22 +54,68 @@ pub struct State {
     pub window: Arc<Window>,
     mouse_x: f32,
     mouse_y: f32,
-    solid_pipeline: wgpu::RenderPipeline,
-    colored_pipeline: wgpu::RenderPipeline,
-    use_colored_pipeline: bool,
-    vertex_buffer: wgpu::Buffer,
-
-    // New
-    index_buffer: wgpu::Buffer,
-    num_indices: u32,
-
-    // Start buffers
-    star_vertex_buffer: wgpu::Buffer,
-    star_index_buffer: wgpu::Buffer,
-    star_num_indices: u32,
-
-    // Shape toggle
-    current_shape: ShapeType,
+    // Last cursor position in physical pixels
+    cursor: Option<(f64, f64)>,
+    hovered: Option<Hit>,
+    modifiers: ModifiersState,
+    // GPU picking, toggled with I
+    id_buffer: Option<IdBuffer>,
+    gpu_hovered: Option<IdPick>,
+    pub scene: Scene,
+    clock: Clock,
+    // Status overlay, toggled with H
+    text: TextRenderer,
+    show_hud: bool,
+    fps: Option<f32>,
+    // Debug panel, toggled with F1
+    ui: Ui,
+    ui_renderer: UiRenderer,
+    show_ui: bool,
+    present_modes: Vec<wgpu::PresentMode>,
+    // The clear color, or the skybox view once an environment is loaded,
+    // tracks the cursor until it is set in the panel
+    follow_cursor: bool,
+    // Lines from `debug_draw`, plus a picking overlay toggled with B
+    debug_renderer: DebugRenderer,
+    show_debug: bool,
+    // Toggled with P, right dragging pulls them to the cursor
+    particles: ParticleSystem,
+    show_particles: bool,
+    attracting: bool,
+    // The scene is drawn in HDR, then gets bloom, the effects edited in the
+    // panel and tonemapping on its way to the surface under the overlays
+    post: PostProcessor,
+    post_settings: PostSettings,
+    // Index into `Effect::ALL` of what "Add effect" adds
+    next_effect: usize,
+
+    camera_mode: CameraMode,
+    pan_zoom: PanZoomController,
+    orbit: OrbitController,
+    fly: FlyController,
+}
+
+/// Requests the device and queue used by both the windowed and headless renderers.
+pub(crate) async fn request_device(
+    adapter: &wgpu::Adapter,
+) -> anyhow::Result<(wgpu::Device, wgpu::Queue)> {
+    let (device, queue) = adapter
+        .request_device(&wgpu::DeviceDescriptor {
+            label: None,
+            required_features: wgpu::Features::empty(),
+            experimental_features: wgpu::ExperimentalFeatures::disabled(),
+            required_limits: if cfg!(target_arch = "wasm32") {
+                wgpu::Limits::downlevel_webgl2_defaults()
+            } else {
+                wgpu::Limits::defaults()
+            },
+
+            memory_hints: Default::default(),
+            trace: wgpu::Trace::Off,
+        })
+        .await?;
+
+    Ok((device, queue))
 }
 
 impl State {
@@ -60,21 +141,7 @@ impl State {
             })
             .await?;
 
-        let (device, queue) = adapter
-            .request_device(&wgpu::DeviceDescriptor {
-                label: None,
-                required_features: wgpu::Features::empty(),
-                experimental_features: wgpu::ExperimentalFeatures::disabled(),
-                required_limits: if cfg!(target_arch = "wasm32") {
-                    wgpu::Limits::downlevel_webgl2_defaults()
-                } else {
-                    wgpu::Limits::defaults()
-                },
-
-                memory_hints: Default::default(),
-                trace: wgpu::Trace::Off,
-            })
-            .await?;
+        let (device, queue) = request_device(&adapter).await?;
 
         let surface_caps = surface.get_capabilities(&adapter);
 
@@ -96,124 +163,25 @@ impl State {
             desired_maximum_frame_latency: 2,
         };
 
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("../shader.wgsl").into()),
-        });
-
-        let render_pipeline_layout =
-            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-                label: Some("Render pipeline layout"),
-                bind_group_layouts: &[],
-                immediate_size: 0,
-            });
-
-        // Pipeline 1: Solid pipeline (uses solid red color)
-        let solid_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Solid Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_solid"),
-                buffers: &[Vertex::desc()],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview_mask: None,
-            cache: None,
-        });
-
-        // Pipeline 2: Colored (uses vertex colors)
-        let colored_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Colored Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),
-                buffers: &[Vertex::desc()],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-                compilation_options: wgpu::PipelineCompilationOptions::default(),
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
-            },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-            multiview_mask: None,
-            cache: None,
-        });
-
-        // Buffer
-        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Vertex Buffer"),
-            contents: bytemuck::cast_slice(VERTICES),
-            usage: wgpu::BufferUsages::VERTEX,
-        });
-
-        // New
-        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Index Buffer"),
-            contents: bytemuck::cast_slice(INDICES),
-            usage: wgpu::BufferUsages::INDEX,
-        });
-        let num_indices = INDICES.len() as u32;
-
-        let star_vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Start Vertex Buffer"),
-            contents: bytemuck::cast_slice(STAR_VERTICES),
-            usage: wgpu::BufferUsages::VERTEX,
-        });
-
-        let star_index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Star Index Buffer"),
-            contents: bytemuck::cast_slice(STAR_INDICES),
-            usage: wgpu::BufferUsages::INDEX,
-        });
-
-        let star_num_indices = STAR_INDICES.len() as u32;
+        let mut scene = Scene::new(&device, PostProcessor::FORMAT);
+        scene.set_viewport_size(config.width, config.height);
+        if Path::new(ENVIRONMENT_FILE).exists() {
+            match HdrImage::load(ENVIRONMENT_FILE) {
+                Ok(image) => scene.load_environment(&device, &queue, &image),
+                Err(e) => log::error!("Failed to load environment: {e:#}"),
+            }
+        }
+        let text = TextRenderer::new(&device, config.format);
+        let ui = Ui::new(text.font().clone());
+        let ui_renderer = UiRenderer::new(&device, config.format);
+        let debug_renderer = DebugRenderer::new(&device, PostProcessor::FORMAT);
+        let particles = ParticleSystem::new(
+            &device,
+            PostProcessor::FORMAT,
+            PARTICLE_CAPACITY,
+            ParticleSettings::default(),
+        );
+        let post = PostProcessor::new(&device, config.format, config.width, config.height);
 
         Ok(Self {
             surface,
@@ -224,16 +192,33 @@ impl State {
             window,
             mouse_x: 0.0,
             mouse_y: 0.0,
-            solid_pipeline,
-            colored_pipeline,
-            use_colored_pipeline: false,
-            vertex_buffer,
-            index_buffer,
-            num_indices,
-            star_vertex_buffer,
-            star_index_buffer,
-            star_num_indices,
-            current_shape: ShapeType::Pentagon,
+            cursor: None,
+            hovered: None,
+            modifiers: ModifiersState::empty(),
+            id_buffer: None,
+            gpu_hovered: None,
+            scene,
+            clock: Clock::realtime(),
+            text,
+            show_hud: true,
+            fps: None,
+            ui,
+            ui_renderer,
+            show_ui: true,
+            present_modes: surface_caps.present_modes,
+            follow_cursor: true,
+            debug_renderer,
+            show_debug: false,
+            particles,
+            show_particles: false,
+            attracting: false,
+            post,
+            post_settings: PostSettings::default(),
+            next_effect: 0,
+            camera_mode: CameraMode::default(),
+            pan_zoom: PanZoomController::default(),
+            orbit: OrbitController::default(),
+            fly: FlyController::default(),
         })
     }
     pub fn window(&self) -> &Arc<Window> {
@@ -247,32 +232,171 @@ impl State {
             self.config.height = height;
             self.surface.configure(&self.device, &self.config);
             self.is_surface_configured = true;
+            if let Some(id_buffer) = &mut self.id_buffer {
+                id_buffer.resize(&self.device, width, height);
+            }
+            self.post.resize(&self.device, width, height);
+            self.scene.set_viewport_size(width, height);
         }
     }
 
     pub fn handle_key(&mut self, event_loop: &ActiveEventLoop, code: KeyCode, is_pressed: bool) {
+        // Ctrl on most platforms, Cmd on macOS
+        let command = self.modifiers.control_key() || self.modifiers.super_key();
+        if self.camera_mode == CameraMode::Fly && !command && self.fly.key(code, is_pressed) {
+            return;
+        }
+
         match (code, is_pressed) {
+            // The first Escape lets go of a grabbed cursor
+            (KeyCode::Escape, true) if self.fly.is_grabbed() => self.set_cursor_grab(false),
             (KeyCode::Escape, true) => event_loop.exit(),
+            (KeyCode::KeyZ, true) if command => {
+                let changed = if self.modifiers.shift_key() {
+                    self.scene.redo()
+                } else {
+                    self.scene.undo()
+                };
+                if changed {
+                    let history = self.scene.history();
+                    println!(
+                        "History: {} undo, {} redo ({} bytes)",
+                        history.undo_len(),
+                        history.redo_len(),
+                        history.size_bytes()
+                    );
+                }
+            }
+            (KeyCode::KeyS, true) if command => {
+                match self.scene.description().save(SCENE_FILE) {
+                    Ok(()) => println!("Saved scene to {SCENE_FILE}"),
+                    Err(e) => log::error!("Failed to save scene: {e:#}"),
+                }
+            }
+            (KeyCode::KeyO, true) if command => match SceneDescription::load(SCENE_FILE) {
+                Ok(description) => {
+                    self.scene.set_description(description);
+                    println!("Loaded scene from {SCENE_FILE}");
+                }
+                Err(e) => log::error!("Failed to load scene: {e:#}"),
+            },
+            (KeyCode::KeyE, true) if command => self.export(),
+            (KeyCode::KeyL, true) if command => match HdrImage::load(ENVIRONMENT_FILE) {
+                Ok(image) => {
+                    self.scene.load_environment(&self.device, &self.queue, &image);
+                    println!(
+                        "Loaded {}x{} environment from {ENVIRONMENT_FILE}",
+                        image.width, image.height
+                    );
+                }
+                Err(e) => log::error!("Failed to load environment: {e:#}"),
+            },
             (KeyCode::Space, true) => {
                 // Toggle between shapes and pipelines
-                self.current_shape = match self.current_shape {
-                    ShapeType::Pentagon => ShapeType::Star,
-                    ShapeType::Star => ShapeType::Pentagon,
-                };
-                self.use_colored_pipeline = !self.use_colored_pipeline;
+                self.scene.toggle_shape();
 
-                let shape_name = match self.current_shape {
-                    ShapeType::Pentagon => "Pentagon",
-                    ShapeType::Star => "Star",
+                if let Some(shape) = self.scene.active_shape() {
+                    println!(
+                        "Switched to {} shape with {} pipeline",
+                        self.scene.shape_name(shape),
+                        self.scene.pipeline_name()
+                    );
+                }
+            }
+            (KeyCode::ArrowLeft | KeyCode::ArrowRight | KeyCode::ArrowUp | KeyCode::ArrowDown, true) => {
+                let step = match code {
+                    KeyCode::ArrowLeft => glam::Vec2::new(-MOVE_STEP, 0.0),
+                    KeyCode::ArrowRight => glam::Vec2::new(MOVE_STEP, 0.0),
+                    KeyCode::ArrowUp => glam::Vec2::new(0.0, MOVE_STEP),
+                    _ => glam::Vec2::new(0.0, -MOVE_STEP),
                 };
-                let pipeline_name = if self.use_colored_pipeline {
-                    "Colored"
+                self.scene.transform_active(|t| t.translation += step);
+            }
+            (KeyCode::BracketLeft | KeyCode::BracketRight, true) => {
+                let sign = if code == KeyCode::BracketLeft { 1.0 } else { -1.0 };
+                self.scene
+                    .transform_active(|t| t.rotation += sign * ROTATE_STEP_DEGREES.to_radians());
+            }
+            (KeyCode::Minus | KeyCode::Equal, true) => {
+                let factor = if code == KeyCode::Equal { SCALE_STEP } else { 1.0 / SCALE_STEP };
+                self.scene.transform_active(|t| t.scale *= factor);
+            }
+            (KeyCode::KeyO, true) => {
+                let style = match self.scene.outline_stroke() {
+                    Some(_) => None,
+                    None => Some(StrokeStyle {
+                        width: OUTLINE_WIDTH,
+                        join: LineJoin::Round,
+                        color: OUTLINE_COLOR,
+                        ..StrokeStyle::default()
+                    }),
+                };
+                self.scene.set_outline_stroke(style);
+            }
+            (KeyCode::KeyH, true) => self.show_hud = !self.show_hud,
+            (KeyCode::KeyV, true) => self.set_camera_mode(self.camera_mode.next()),
+            (KeyCode::F1, true) => self.show_ui = !self.show_ui,
+            (KeyCode::KeyB, true) => self.show_debug = !self.show_debug,
+            (KeyCode::KeyP, true) => {
+                self.show_particles = !self.show_particles;
+                if self.show_particles {
+                    self.particles.reset(&self.queue);
+                }
+            }
+            (KeyCode::KeyT, true) => {
+                match text_mesh(self.text.font(), TITLE_TEXT, &VectorTextOptions::default()) {
+                    Ok((vertices, indices)) => self.scene.add_shapes(vec![ShapeNode::new(
+                        ShapeGeometry::Mesh { vertices, indices },
+                    )]),
+                    Err(e) => log::error!("Failed to build title: {e:#}"),
+                }
+            }
+            (KeyCode::KeyC, true) => self.scene.cycle_active_color(),
+            (KeyCode::KeyN, true) => self.scene.duplicate_active(),
+            (KeyCode::Tab, true) => {
+                let count = self.scene.document().shapes.len();
+                if count > 0 {
+                    let next = self.scene.active_shape().map_or(0, |i| (i + 1) % count);
+                    self.scene.set_active_shape(Some(next));
+                    println!("Active shape {} ({})", next, self.scene.shape_name(next));
+                }
+            }
+            (KeyCode::KeyE, true) => {
+                self.scene.toggle_editing();
+                println!(
+                    "Vertex editing {}",
+                    if self.scene.is_editing() { "on" } else { "off" }
+                );
+            }
+            (KeyCode::KeyG, true) => {
+                if let Some(editor) = self.scene.editor_mut() {
+                    editor.grid = match editor.grid {
+                        Some(_) => None,
+                        None => Some(EDIT_GRID_SIZE),
+                    };
+                    println!("Grid snapping {}", if editor.grid.is_some() { "on" } else { "off" });
+                }
+            }
+            (KeyCode::Delete | KeyCode::Backspace, true) => {
+                if self.scene.is_editing() {
+                    self.scene.editor_delete_selected();
                 } else {
-                    "Solid red"
+                    self.scene.remove_active();
+                }
+            }
+            (KeyCode::KeyI, true) => {
+                self.id_buffer = match self.id_buffer {
+                    Some(_) => None,
+                    None => Some(IdBuffer::new(
+                        &self.device,
+                        self.config.width,
+                        self.config.height,
+                    )),
                 };
+                self.gpu_hovered = None;
                 println!(
-                    "Switched to {} shape with {} pipeline",
-                    shape_name, pipeline_name
+                    "GPU picking {}",
+                    if self.id_buffer.is_some() { "enabled" } else { "disabled" }
                 );
             }
             _ => {}
@@ -281,6 +405,155 @@ impl State {
 
     pub fn update(&mut self) {
         // Update application state
+        let dt = self.clock.tick();
+        // Lines from the last frame are done unless they asked to stay
+        debug_draw::with(|draw| draw.advance(dt));
+        if dt > 0.0 {
+            let fps = 1.0 / dt;
+            self.fps = Some(self.fps.map_or(fps, |smoothed| {
+                smoothed + (fps - smoothed) * FPS_SMOOTHING
+            }));
+        }
+        let view_3d = match self.camera_mode {
+            CameraMode::PanZoom => None,
+            CameraMode::Orbit => Some(self.orbit.camera()),
+            CameraMode::Fly => {
+                self.fly.update(dt);
+                Some(self.fly.camera())
+            }
+        };
+        if self.scene.view_3d() != view_3d.as_ref() {
+            self.scene.set_view_3d(view_3d);
+        }
+        if self.follow_cursor && self.scene.skybox_visible() {
+            // 3D cameras turn the sky along with the scene
+            if view_3d.is_none() {
+                // Across the window turns all the way around
+                self.scene.sky_view.yaw = (self.mouse_x - 0.5) * std::f32::consts::TAU;
+                self.scene.sky_view.pitch = (0.5 - self.mouse_y) * std::f32::consts::PI;
+            }
+        } else if self.follow_cursor {
+            self.scene.clear_color.r = self.mouse_x as f64;
+            self.scene.clear_color.g = self.mouse_y as f64;
+        }
+        self.scene.update(&self.device, &self.queue, dt);
+
+        // Shapes can move under a resting cursor, so hover is checked every frame
+        let hovered = self.pick_under_cursor();
+        if hovered.map(|h| (h.shape, h.triangle)) != self.hovered.map(|h| (h.shape, h.triangle)) {
+            match hovered {
+                Some(hit) => println!(
+                    "Hovering {} triangle {}",
+                    self.scene.shape_name(hit.shape),
+                    hit.triangle
+                ),
+                None if self.hovered.is_some() => println!("Hovering nothing"),
+                None => {}
+            }
+        }
+        self.hovered = hovered;
+
+        // Readbacks requested in earlier frames
+        if let Some(pick) = self.id_buffer.as_mut().and_then(|b| b.poll(&self.device)) {
+            if pick.object_id != self.gpu_hovered.and_then(|p| p.object_id) {
+                match pick.object_id {
+                    Some(id) => println!(
+                        "GPU pick: shape {} ({})",
+                        id,
+                        self.scene.shape_name(id as usize)
+                    ),
+                    None => println!("GPU pick: background"),
+                }
+            }
+            self.gpu_hovered = Some(pick);
+        }
+
+        if self.show_debug {
+            self.draw_debug_overlay();
+        }
+        if self.show_particles {
+            self.update_particles(dt);
+        }
+    }
+
+    /// Moves the emitter to the tips of the active shape and the attractor to the cursor.
+    fn update_particles(&mut self, dt: f32) {
+        let outline: Vec<glam::Vec2> = self
+            .scene
+            .active_shape()
+            .and_then(|index| self.scene.document().shapes.get(index))
+            .map(|node| {
+                node.geometry
+                    .outline()
+                    .points
+                    .iter()
+                    .map(|v| node.transform.apply(glam::Vec2::new(v.position[0], v.position[1])))
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.particles
+            .set_emitter(&self.device, &self.queue, &Emitter::tips(&outline));
+
+        let attractor = self
+            .cursor_world()
+            .filter(|_| self.attracting)
+            .map(|position| Attractor {
+                position,
+                strength: PARTICLE_ATTRACTOR_STRENGTH,
+            });
+        self.particles.settings_mut().attractor = attractor;
+        self.particles
+            .prepare(&self.queue, dt, &self.scene.camera_uniform());
+    }
+
+    /// Grid, shape bounds and the hovered triangle with its edge normals.
+    fn draw_debug_overlay(&self) {
+        let (min, max) = self.scene.camera().visible_bounds();
+        debug_draw::grid(min, max, DEBUG_GRID_SPACING, DEBUG_GRID_COLOR);
+        for (_, bounds) in self.scene.picker().bounds() {
+            debug_draw::aabb(bounds.min, bounds.max, DEBUG_BOUNDS_COLOR);
+        }
+
+        if let Some(world) = self.cursor_world() {
+            debug_draw::cross(world, self.pixels_to_world(DEBUG_MARKER_PIXELS), DEBUG_PICK_COLOR);
+        }
+        let Some(corners) = self.hovered.and_then(|hit| self.scene.picker().triangle(&hit)) else {
+            return;
+        };
+        let normal_length = self.pixels_to_world(DEBUG_NORMAL_PIXELS);
+        for i in 0..3 {
+            let (a, b) = (corners[i], corners[(i + 1) % 3]);
+            debug_draw::line(a, b, DEBUG_PICK_COLOR);
+            // Triangles are counter-clockwise, so outwards is to the right of each edge
+            let middle = (a + b) * 0.5;
+            let normal = -(b - a).perp().normalize_or_zero();
+            debug_draw::arrow(middle, middle + normal * normal_length, DEBUG_PICK_COLOR);
+        }
+    }
+
+    /// Object under the cursor according to the ID buffer, a frame or more behind.
+    pub fn gpu_hovered(&self) -> Option<IdPick> {
+        self.gpu_hovered
+    }
+
+    /// Cursor position in world space.
+    pub fn cursor_world(&self) -> Option<glam::Vec2> {
+        let (x, y) = self.cursor?;
+        let ndc = cursor_to_ndc(x, y, self.config.width, self.config.height);
+        let inverse_view_proj = self.scene.view_proj().inverse();
+        Some(ndc_to_world(ndc, inverse_view_proj))
+    }
+
+    /// Hit tests the shapes under the cursor.
+    pub fn pick_under_cursor(&self) -> Option<Hit> {
+        self.scene.pick(self.cursor_world()?)
+    }
+
+    /// World space size of `pixels` screen pixels.
+    fn pixels_to_world(&self, pixels: f32) -> f32 {
+        // NDC spans two units across the smaller window side
+        let ndc = pixels * 2.0 / self.config.width.min(self.config.height).max(1) as f32;
+        ndc / self.scene.camera().zoom
     }
 
     pub fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
@@ -291,6 +564,13 @@ impl State {
             return Ok(());
         }
 
+        // Widgets run before the frame is acquired since they can reconfigure the surface
+        let ui = if self.show_ui {
+            self.run_ui()
+        } else {
+            UiOutput::default()
+        };
+
         let output = self.surface.get_current_texture()?;
         let view = output
             .texture
@@ -301,63 +581,300 @@ impl State {
                 label: Some("Render Encoder"),
             });
 
-        {
-            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: Some("Render Pass"),
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    depth_slice: None,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: self.mouse_x as f64,
-                            g: self.mouse_y as f64,
-                            b: 0.3,
-                            a: 1.0,
-                        }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                occlusion_query_set: None,
-                timestamp_writes: None,
-                multiview_mask: None,
-            });
+        if self.show_particles {
+            self.particles.compute(&mut encoder);
+        }
+        // The overlays stay sharp on top of the processed scene
+        let scene_view = self.post.scene_view();
+        let id_view = self.id_buffer.as_ref().map(|b| &b.view);
+        self.scene.draw(&mut encoder, scene_view, id_view);
+        if self.show_particles {
+            self.particles.draw(&mut encoder, scene_view);
+        }
+        let lines = debug_draw::with(|draw| draw.vertices());
+        self.debug_renderer
+            .prepare(&self.device, &self.queue, &self.scene.camera_uniform(), &lines);
+        self.debug_renderer.draw(&mut encoder, scene_view);
+        self.post
+            .prepare(&self.device, &self.queue, &self.post_settings);
+        self.post.draw(&mut encoder, &view);
+        if self.show_hud {
+            self.queue_hud();
+        }
+        for text in &ui.texts {
+            self.text.queue(&text.text, text.position, &text.style);
+        }
+        self.ui_renderer.prepare(
+            &self.device,
+            &self.queue,
+            &ui.quads,
+            self.config.width,
+            self.config.height,
+        );
+        self.ui_renderer.draw(&mut encoder, &view);
+        self.text
+            .prepare(&self.device, &self.queue, self.config.width, self.config.height);
+        self.text.draw(&mut encoder, &view);
+        if let (Some(id_buffer), Some((x, y))) = (&mut self.id_buffer, self.cursor) {
+            id_buffer.request(&mut encoder, x, y);
+        }
 
-            let pipeline = if self.use_colored_pipeline {
-                &self.colored_pipeline
-            } else {
-                &self.solid_pipeline
-            };
+        self.queue.submit(std::iter::once(encoder.finish()));
+        if let Some(id_buffer) = &mut self.id_buffer {
+            id_buffer.after_submit();
+        }
+        output.present();
+
+        Ok(())
+    }
 
-            render_pass.set_pipeline(pipeline);
+    /// Active shape, pipeline and frame rate in the top left corner.
+    fn queue_hud(&mut self) {
+        let scale = self.window.scale_factor() as f32;
+        let shape = match self.scene.active_shape() {
+            Some(index) => self.scene.shape_name(index),
+            None => "No shape",
+        };
+        let mut status = format!("{shape} | {}", self.scene.pipeline_name());
+        if self.scene.is_editing() {
+            status.push_str(" | Editing");
+        }
+        if self.camera_mode != CameraMode::PanZoom {
+            status.push_str(&format!(" | {} camera", self.camera_mode.name()));
+        }
+        if let Some(fps) = self.fps {
+            status.push_str(&format!("\n{fps:.0} FPS"));
+        }
+        let style = TextStyle {
+            size: HUD_FONT_SIZE * scale,
+            color: HUD_COLOR,
+            ..TextStyle::default()
+        };
+        self.text
+            .queue(&status, glam::Vec2::splat(HUD_MARGIN * scale), &style);
+    }
 
-            /* render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-            render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-            render_pass.draw_indexed(0..self.num_indices, 0, 0..1); */
+    /// Declares the debug panel widgets and applies what the user changed.
+    fn run_ui(&mut self) -> UiOutput {
+        let scale = self.window.scale_factor() as f32;
+        let width = UI_PANEL_WIDTH * scale;
+        let origin = glam::Vec2::new(
+            self.config.width as f32 - width - UI_MARGIN * scale,
+            UI_MARGIN * scale,
+        );
+        self.ui.begin(origin, width, scale);
 
-            match self.current_shape {
-                ShapeType::Pentagon => {
-                    render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-                    render_pass
-                        .set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-                    render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
+        let count = self.scene.document().shapes.len();
+        match self.scene.active_shape() {
+            Some(active) => {
+                let name = format!("{active}: {}", self.scene.shape_name(active));
+                let step = self.ui.selector("Shape", &name);
+                if step != 0 {
+                    let next = (active as i32 + step).rem_euclid(count as i32) as usize;
+                    self.scene.set_active_shape(Some(next));
                 }
-                ShapeType::Star => {
-                    render_pass.set_vertex_buffer(0, self.star_vertex_buffer.slice(..));
-                    render_pass.set_index_buffer(
-                        self.star_index_buffer.slice(..),
-                        wgpu::IndexFormat::Uint16,
-                    );
-                    render_pass.draw_indexed(0..self.star_num_indices, 0, 0..1);
+                if self.ui.button("Next preset") {
+                    self.scene.toggle_shape();
                 }
             }
+            None => self.ui.label("No active shape"),
+        }
+        let step = self.ui.selector("Pipeline", self.scene.pipeline_name());
+        if step != 0 {
+            let pipeline = self.scene.pipeline();
+            self.scene.set_pipeline(if step > 0 { pipeline.next() } else { pipeline.previous() });
+        }
+        let step = self.ui.selector("Camera", self.camera_mode.name());
+        if step != 0 {
+            let mode = self.camera_mode;
+            self.set_camera_mode(if step > 0 { mode.next() } else { mode.previous() });
+        }
+        if self.scene.pipeline() == PipelineKind::Pbr
+            && let Some(active) = self.scene.active_shape()
+        {
+            self.material_ui(active);
+        }
+        if self.scene.pipeline().is_lit() {
+            self.lighting_ui();
         }
 
-        self.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
+        if self.scene.environment().is_loaded() {
+            self.ui.checkbox("Skybox", &mut self.scene.show_skybox);
+        }
+        if self.scene.skybox_visible() {
+            self.ui.label("Sky view");
+            self.ui.checkbox("Follow cursor", &mut self.follow_cursor);
+            let view = &mut self.scene.sky_view;
+            let mut fov = view.fov_y.to_degrees();
+            if self.ui.slider("Field of view", &mut fov, 20.0..=120.0) {
+                view.fov_y = fov.to_radians();
+                self.follow_cursor = false;
+            }
+        } else {
+            self.clear_color_ui();
+        }
 
-        Ok(())
+        let mode = self.config.present_mode;
+        let step = self.ui.selector("Present", &format!("{mode:?}"));
+        if step != 0 && !self.present_modes.is_empty() {
+            let index = self.present_modes.iter().position(|&m| m == mode).unwrap_or(0);
+            let len = self.present_modes.len() as i32;
+            self.config.present_mode =
+                self.present_modes[(index as i32 + step).rem_euclid(len) as usize];
+            self.surface.configure(&self.device, &self.config);
+        }
+
+        let stats = match self.fps {
+            Some(fps) => format!("{fps:.0} FPS, {:.2} ms", 1000.0 / fps),
+            None => "No frames yet".to_string(),
+        };
+        self.ui.label(&stats);
+        self.ui.label(&format!(
+            "{count} shapes, {} undo steps",
+            self.scene.history().undo_len()
+        ));
+        self.ui.checkbox("Status text", &mut self.show_hud);
+        self.ui.checkbox("Debug lines", &mut self.show_debug);
+        if self.ui.checkbox("Particles", &mut self.show_particles) && self.show_particles {
+            self.particles.reset(&self.queue);
+        }
+        self.effects_ui();
+        self.ui.end()
+    }
+
+    fn clear_color_ui(&mut self) {
+        self.ui.label("Clear color");
+        self.ui.checkbox("Follow cursor", &mut self.follow_cursor);
+        let color = &mut self.scene.clear_color;
+        for (label, channel) in [("Red", &mut color.r), ("Green", &mut color.g), ("Blue", &mut color.b)] {
+            let mut value = *channel as f32;
+            if self.ui.slider(label, &mut value, 0.0..=1.0) {
+                *channel = value as f64;
+                self.follow_cursor = false;
+            }
+        }
+    }
+
+    /// Factors of the PBR material the active shape uses. Only changes are
+    /// written back, every write rebuilds the material on the GPU.
+    fn material_ui(&mut self, shape: usize) {
+        let material = self.scene.material(shape);
+        self.ui.label(&format!("Material: {}", material.name));
+        let mut metallic = material.metallic_factor;
+        let mut roughness = material.roughness_factor;
+        let mut normal_scale = material.normal_scale;
+        let mut changed = self.ui.slider("Metallic", &mut metallic, 0.0..=1.0);
+        changed |= self.ui.slider("Roughness", &mut roughness, 0.0..=1.0);
+        changed |= self.ui.slider("Normal scale", &mut normal_scale, 0.0..=2.0);
+        if changed {
+            let material = self.scene.material_mut(shape);
+            material.metallic_factor = metallic;
+            material.roughness_factor = roughness;
+            material.normal_scale = normal_scale;
+        }
+    }
+
+    /// Material, light intensities and shadows of the lit pipeline in the panel.
+    fn lighting_ui(&mut self) {
+        let image_based =
+            self.scene.pipeline() == PipelineKind::Pbr && self.scene.environment().is_loaded();
+        let lighting = &mut self.scene.lighting;
+        let mut ambient = lighting.ambient.x;
+        if self.ui.slider("Ambient", &mut ambient, 0.0..=0.5) {
+            lighting.ambient = glam::Vec3::splat(ambient);
+        }
+        self.ui.slider("Specular", &mut lighting.specular, 0.0..=2.0);
+        self.ui.slider("Shininess", &mut lighting.shininess, 1.0..=128.0);
+        if image_based {
+            self.ui.slider("Environment", &mut lighting.environment_intensity, 0.0..=2.0);
+        }
+        for (index, light) in lighting.lights.iter_mut().enumerate() {
+            let label = format!("{}. {}", index + 1, light.name());
+            self.ui.slider(&label, light.intensity_mut(), 0.0..=4.0);
+        }
+
+        let shadows = &mut self.scene.shadow_settings;
+        self.ui.checkbox("Shadows", &mut shadows.enabled);
+        if shadows.enabled {
+            let size = shadows.resolution;
+            let step = self.ui.selector("Shadow map", &format!("{size}x{size}"));
+            if step != 0 {
+                let count = SHADOW_MAP_SIZES.len() as i32;
+                let index = SHADOW_MAP_SIZES.iter().position(|&s| s == size).unwrap_or(0);
+                shadows.resolution =
+                    SHADOW_MAP_SIZES[(index as i32 + step).rem_euclid(count) as usize];
+            }
+            let mut cascades = shadows.cascades as f32;
+            if self.ui.slider("Cascades", &mut cascades, 1.0..=MAX_CASCADES as f32) {
+                shadows.cascades = cascades.round() as u32;
+            }
+            let mut pcf_radius = shadows.pcf_radius as f32;
+            if self.ui.slider("PCF radius", &mut pcf_radius, 0.0..=3.0) {
+                shadows.pcf_radius = pcf_radius.round() as u32;
+            }
+            self.ui.slider("Depth bias", &mut shadows.depth_bias, 0.0..=0.02);
+            self.ui.slider("Slope bias", &mut shadows.slope_bias, 0.0..=0.02);
+        }
+    }
+
+    /// Bloom, post-processing chain and tonemapping in the panel. The arrows
+    /// of each effect move it through the chain.
+    fn effects_ui(&mut self) {
+        let settings = &mut self.post_settings;
+        let bloom = &mut settings.bloom;
+        self.ui.checkbox("Bloom", &mut bloom.enabled);
+        if bloom.enabled {
+            self.ui.slider("Threshold", &mut bloom.threshold, 0.0..=4.0);
+            self.ui.slider("Intensity", &mut bloom.intensity, 0.0..=2.0);
+            self.ui.slider("Radius", &mut bloom.radius, 0.5..=3.0);
+        }
+
+        let count = Tonemap::ALL.len() as i32;
+        let step = self.ui.selector("Tonemap", settings.tonemap.name());
+        if step != 0 {
+            let index = Tonemap::ALL.iter().position(|&t| t == settings.tonemap).unwrap_or(0);
+            settings.tonemap = Tonemap::ALL[(index as i32 + step).rem_euclid(count) as usize];
+        }
+        self.ui.slider("Exposure", &mut settings.exposure, -4.0..=4.0);
+
+        let effects = &mut settings.effects;
+        let len = Effect::ALL.len() as i32;
+        let step = self.ui.selector("Effect", Effect::ALL[self.next_effect].name());
+        self.next_effect = (self.next_effect as i32 + step).rem_euclid(len) as usize;
+        if self.ui.button("Add effect") {
+            effects.push(Effect::ALL[self.next_effect]);
+        }
+        let mut index = 0;
+        while index < effects.len() {
+            let number = index + 1;
+            let effect = &mut effects.effects[index];
+            let name = effect.name();
+            let step = self.ui.selector(&format!("{number}."), name);
+            let (parameter, value, range) = effect.parameter_mut();
+            self.ui.slider(&format!("{number}. {parameter}"), value, range);
+            if self.ui.button(&format!("Remove {number}. {name}")) {
+                effects.remove(index);
+                continue;
+            }
+            if step != 0 {
+                effects.shift(index, step);
+            }
+            index += 1;
+        }
+    }
+
+    /// Gives the debug panel the first look at a window event and returns
+    /// whether it took it, in which case the scene should not see it.
+    pub fn handle_ui_event(&mut self, event: &WindowEvent) -> bool {
+        if !self.show_ui {
+            return false;
+        }
+        let consumed = self.ui.handle_event(event);
+        if consumed && matches!(event, WindowEvent::CursorMoved { .. }) {
+            // Nothing in the scene is hovered through the panel
+            self.handle_cursor_left();
+        }
+        consumed
     }
 
     pub fn handle_mouse_moved(&mut self, x: f64, y: f64) {
@@ -366,5 +883,208 @@ impl State {
 
         self.mouse_x = norm_x as f32;
         self.mouse_y = norm_y as f32;
+        self.cursor = Some((x, y));
+
+        match self.camera_mode {
+            CameraMode::PanZoom => {
+                let ndc = cursor_to_ndc(x, y, self.config.width, self.config.height);
+                let mut camera = *self.scene.camera();
+                if self.pan_zoom.cursor_moved(&mut camera, ndc) {
+                    self.scene.set_camera(camera);
+                }
+            }
+            CameraMode::Orbit => self
+                .orbit
+                .cursor_moved(glam::Vec2::new(x as f32, y as f32), self.config.height as f32),
+            CameraMode::Fly => {}
+        }
+
+        if let Some(world) = self.cursor_world()
+            && self.scene.is_editing()
+        {
+            self.scene.editor_pointer_moved(world);
+        }
+    }
+
+    /// Writes what is on screen as SVG and the world-space meshes as OBJ.
+    fn export(&self) {
+        let meshes = self.scene.world_meshes();
+        let svg = export_svg(
+            &meshes,
+            self.scene.camera().visible_bounds(),
+            &SvgExportOptions::default(),
+        );
+        for (file, contents) in [(EXPORT_SVG_FILE, svg), (EXPORT_OBJ_FILE, export_obj(&meshes))] {
+            match std::fs::write(file, contents) {
+                Ok(()) => println!("Exported scene to {file}"),
+                Err(e) => log::error!("Failed to export {file}: {e}"),
+            }
+        }
+    }
+
+    /// Imports dropped SVG and OBJ files into the scene and opens dropped scene files.
+    pub fn handle_dropped_file(&mut self, path: &Path) {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+        let import: fn(&str) -> anyhow::Result<_> = match extension.as_deref() {
+            Some("svg") => |text| import_svg(text, &SvgImportOptions::default()),
+            Some("obj") => import_obj,
+            _ => {
+                match SceneDescription::load(path) {
+                    Ok(description) => {
+                        self.scene.set_description(description);
+                        println!("Loaded scene from {}", path.display());
+                    }
+                    Err(e) => log::error!("Failed to load {}: {e:#}", path.display()),
+                }
+                return;
+            }
+        };
+
+        let shapes = std::fs::read_to_string(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|text| import(&text));
+        match shapes {
+            Ok(shapes) => {
+                println!("Imported {} shapes from {}", shapes.len(), path.display());
+                self.scene.add_shapes(shapes);
+            }
+            Err(e) => log::error!("Failed to import {}: {e:#}", path.display()),
+        }
+    }
+
+    pub fn handle_modifiers(&mut self, modifiers: ModifiersState) {
+        self.modifiers = modifiers;
+    }
+
+    pub fn handle_cursor_left(&mut self) {
+        self.cursor = None;
+        self.pan_zoom.cursor_left();
+        self.orbit.cursor_left();
+    }
+
+    /// Zooms the 2D and orbit cameras towards the scene for scrolls up.
+    pub fn handle_mouse_wheel(&mut self, delta: MouseScrollDelta) {
+        let lines = match delta {
+            MouseScrollDelta::LineDelta(_, y) => y,
+            MouseScrollDelta::PixelDelta(position) => position.y as f32 / WHEEL_PIXELS_PER_LINE,
+        };
+        match self.camera_mode {
+            CameraMode::PanZoom => {
+                let mut camera = *self.scene.camera();
+                self.pan_zoom.zoom(&mut camera, lines);
+                self.scene.set_camera(camera);
+            }
+            CameraMode::Orbit => self.orbit.zoom(lines),
+            CameraMode::Fly => {}
+        }
+    }
+
+    /// Raw mouse movement, which keeps coming while the cursor is grabbed.
+    pub fn handle_mouse_motion(&mut self, dx: f64, dy: f64) {
+        if self.camera_mode == CameraMode::Fly {
+            self.fly.look(glam::Vec2::new(dx as f32, dy as f32));
+        }
+    }
+
+    /// Key releases and raw mouse movement go elsewhere until focus returns.
+    pub fn handle_focus_lost(&mut self) {
+        self.fly.release_keys();
+        if self.fly.is_grabbed() {
+            self.set_cursor_grab(false);
+        }
+    }
+
+    fn set_camera_mode(&mut self, mode: CameraMode) {
+        if self.camera_mode == CameraMode::Fly {
+            self.fly.release_keys();
+            if self.fly.is_grabbed() {
+                self.set_cursor_grab(false);
+            }
+        }
+        self.camera_mode = mode;
+        println!("Switched to {} camera", mode.name());
+    }
+
+    /// Locks the cursor in place and hides it for mouse look, or lets it go.
+    fn set_cursor_grab(&mut self, grab: bool) {
+        let result = if grab {
+            // Not every platform can lock the cursor, confining it still
+            // keeps the raw movement coming
+            self.window
+                .set_cursor_grab(CursorGrabMode::Locked)
+                .or_else(|_| self.window.set_cursor_grab(CursorGrabMode::Confined))
+        } else {
+            self.window.set_cursor_grab(CursorGrabMode::None)
+        };
+        match result {
+            Ok(()) => {
+                self.window.set_cursor_visible(!grab);
+                self.fly.set_grabbed(grab);
+            }
+            Err(e) => log::error!("Failed to grab the cursor: {e}"),
+        }
+    }
+
+    pub fn handle_mouse_input(&mut self, button: MouseButton, is_pressed: bool) {
+        match self.camera_mode {
+            CameraMode::PanZoom if self.pan_zoom.mouse_input(button, is_pressed) => return,
+            CameraMode::Orbit if self.orbit.mouse_input(button, is_pressed) => return,
+            // Clicking grabs the cursor for mouse look
+            CameraMode::Fly if button == MouseButton::Left => {
+                if is_pressed && !self.fly.is_grabbed() {
+                    self.set_cursor_grab(true);
+                }
+                return;
+            }
+            _ => {}
+        }
+        if button == MouseButton::Right {
+            self.attracting = is_pressed;
+        }
+        if button != MouseButton::Left {
+            return;
+        }
+
+        if self.scene.is_editing() {
+            let world = self.cursor_world();
+            let pick_radius = self.pixels_to_world(EDIT_HANDLE_PIXELS);
+            let additive = self.modifiers.shift_key();
+            if let Some(editor) = self.scene.editor_mut() {
+                editor.pick_radius = pick_radius;
+            }
+            match (is_pressed, world) {
+                (true, Some(world)) => self.scene.editor_pointer_down(world, additive),
+                (false, _) => self.scene.editor_pointer_up(),
+                _ => {}
+            }
+            return;
+        }
+
+        if !is_pressed {
+            return;
+        }
+        if self.show_debug
+            && let Some(world) = self.cursor_world()
+        {
+            let style = DebugStyle {
+                color: DEBUG_PICK_COLOR,
+                lifetime: DEBUG_CLICK_LIFETIME,
+            };
+            debug_draw::circle(world, self.pixels_to_world(DEBUG_MARKER_PIXELS), style);
+        }
+        // Clicking a shape makes it the target of keyboard edits
+        let hit = self.pick_under_cursor();
+        self.scene.set_active_shape(hit.map(|hit| hit.shape));
+        match hit {
+            Some(hit) => println!(
+                "Clicked {} triangle {}",
+                self.scene.shape_name(hit.shape),
+                hit.triangle
+            ),
+            None => println!("Clicked background"),
+        }
     }
 }
@@ -0,0 +1,128 @@
+use std::{collections::HashMap, fmt::Write};
+
+use anyhow::{Context, bail};
+
+use crate::{
+    document::{ShapeGeometry, ShapeNode},
+    models::vertex::Vertex,
+};
+
+/// Writes triangle meshes as Wavefront OBJ, one object per mesh.
+///
+/// Vertex colors are appended to the `v` lines as red, green and blue, the
+/// common extension most tools understand. They are written as stored, in
+/// linear space.
+pub fn export_obj(meshes: &[(Vec<Vertex>, Vec<u16>)]) -> String {
+    let mut obj = String::from("# wgpu-playaround scene\n");
+    // Face indices count from 1 across the whole file
+    let mut first = 1;
+    for (index, (vertices, indices)) in meshes.iter().enumerate() {
+        let _ = writeln!(obj, "o shape_{index}");
+        for v in vertices {
+            let [x, y, z] = v.position;
+            let [r, g, b] = v.color;
+            let _ = writeln!(obj, "v {x} {y} {z} {r} {g} {b}");
+        }
+        for triangle in indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|k| first + triangle[k] as usize);
+            let _ = writeln!(obj, "f {a} {b} {c}");
+        }
+        first += vertices.len();
+    }
+    obj
+}
+
+/// Adds the triangles of one object, given as file-wide vertex indices, as a
+/// mesh shape. It keeps the vertices it uses, in file order, renumbered from zero.
+fn push_object(
+    corners: Vec<usize>,
+    positions: &[Vertex],
+    shapes: &mut Vec<ShapeNode>,
+) -> anyhow::Result<()> {
+    if corners.is_empty() {
+        return Ok(());
+    }
+    let mut used = corners.clone();
+    used.sort_unstable();
+    used.dedup();
+    if used.len() > u16::MAX as usize {
+        bail!("object has more than {} vertices", u16::MAX);
+    }
+    let remap: HashMap<usize, u16> = used
+        .iter()
+        .enumerate()
+        .map(|(index, &file_index)| (file_index, index as u16))
+        .collect();
+    shapes.push(ShapeNode::new(ShapeGeometry::Mesh {
+        vertices: used.iter().map(|&i| positions[i]).collect(),
+        indices: corners.iter().map(|i| remap[i]).collect(),
+    }));
+    Ok(())
+}
+
+/// Reads the faces of a Wavefront OBJ file as one mesh shape per object or group.
+///
+/// Polygons are split into triangle fans. Texture coordinates, normals and
+/// materials are ignored, vertices without colors are white.
+pub fn import_obj(text: &str) -> anyhow::Result<Vec<ShapeNode>> {
+    let mut positions: Vec<Vertex> = Vec::new();
+    let mut shapes = Vec::new();
+    // Triangle corners of the current object
+    let mut object = Vec::new();
+
+    for (line_number, line) in text.lines().enumerate() {
+        let line_number = line_number + 1;
+        let mut fields = line.split_whitespace();
+        match fields.next() {
+            Some("v") => {
+                let values = fields
+                    .map(str::parse::<f32>)
+                    .collect::<Result<Vec<_>, _>>()
+                    .with_context(|| format!("line {line_number}: invalid vertex"))?;
+                let vertex = match values[..] {
+                    [x, y, z] | [x, y, z, _] => Vertex {
+                        position: [x, y, z],
+                        color: [1.0, 1.0, 1.0],
+                    },
+                    [x, y, z, r, g, b] => Vertex {
+                        position: [x, y, z],
+                        color: [r, g, b],
+                    },
+                    _ => bail!("line {line_number}: expected 3 or 6 vertex values"),
+                };
+                positions.push(vertex);
+            }
+            Some("f") => {
+                let mut corners = Vec::new();
+                for field in fields {
+                    // Only the position of `v/vt/vn` matters
+                    let position = field.split('/').next().unwrap_or_default();
+                    let value: isize = position
+                        .parse()
+                        .with_context(|| format!("line {line_number}: invalid face index"))?;
+                    // Negative indices count back from the latest vertex
+                    let file_index = match value {
+                        1.. => value as usize - 1,
+                        ..0 => positions.len().wrapping_add_signed(value),
+                        0 => bail!("line {line_number}: face index 0"),
+                    };
+                    if file_index >= positions.len() {
+                        bail!("line {line_number}: face index {value} out of range");
+                    }
+                    corners.push(file_index);
+                }
+                if corners.len() < 3 {
+                    bail!("line {line_number}: faces need at least 3 vertices");
+                }
+                for i in 1..corners.len() - 1 {
+                    object.extend([corners[0], corners[i], corners[i + 1]]);
+                }
+            }
+            Some("o" | "g") => push_object(std::mem::take(&mut object), &positions, &mut shapes)?,
+            _ => {}
+        }
+    }
+    push_object(object, &positions, &mut shapes)?;
+
+    Ok(shapes)
+}
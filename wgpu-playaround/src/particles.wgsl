@@ -0,0 +1,190 @@
+// Particle simulation and billboards, mirrored on the CPU in `particles/mod.rs`
+struct Particle {
+    position: vec2<f32>,
+    velocity: vec2<f32>,
+    age: f32,
+    generation: u32,
+    _padding: vec2<f32>,
+};
+
+struct EmitterPoint {
+    position: vec2<f32>,
+    direction: vec2<f32>,
+};
+
+struct SimParams {
+    gravity: vec2<f32>,
+    attractor: vec2<f32>,
+    dt: f32,
+    drag: f32,
+    attractor_strength: f32,
+    lifetime: f32,
+    speed: f32,
+    spread: f32,
+    point_count: u32,
+    particle_count: u32,
+    color_key_count: u32,
+    size_key_count: u32,
+    _padding: vec2<u32>,
+    // Colors in rgb and their t in w, sizes in x and their t in y
+    colors: array<vec4<f32>, 4>,
+    sizes: array<vec4<f32>, 4>,
+};
+
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+};
+
+const ATTRACTOR_SOFTENING: f32 = 0.01;
+
+@group(0) @binding(0)
+var<uniform> params: SimParams;
+@group(0) @binding(1)
+var<storage, read_write> particles: array<Particle>;
+@group(0) @binding(2)
+var<storage, read> emitter: array<EmitterPoint>;
+
+fn pcg(value: u32) -> u32 {
+    let state = value * 747796405u + 2891336453u;
+    let word = ((state >> ((state >> 28u) + 4u)) ^ state) * 277803737u;
+    return (word >> 22u) ^ word;
+}
+
+fn unit(hash: u32) -> f32 {
+    return f32(hash >> 8u) / 16777216.0;
+}
+
+@compute @workgroup_size(64)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let index = id.x;
+    if index >= params.particle_count {
+        return;
+    }
+    var particle = particles[index];
+    particle.age += params.dt;
+    var due = particle.age >= params.lifetime;
+    if particle.generation == 0u {
+        due = particle.age >= 0.0;
+    }
+
+    if due {
+        if params.point_count == 0u {
+            particles[index] = particle;
+            return;
+        }
+        particle.generation += 1u;
+        particle.age = 0.0;
+        let h0 = pcg(index ^ pcg(particle.generation));
+        let h1 = pcg(h0);
+        let h2 = pcg(h1);
+        let point = emitter[h0 % params.point_count];
+        let angle = (unit(h1) - 0.5) * params.spread;
+        let c = cos(angle);
+        let s = sin(angle);
+        let d = point.direction;
+        let direction = vec2<f32>(c * d.x - s * d.y, s * d.x + c * d.y);
+        particle.position = point.position;
+        particle.velocity = direction * params.speed * (0.5 + 0.5 * unit(h2));
+    } else if particle.generation > 0u && particle.age < params.lifetime {
+        var acceleration = params.gravity;
+        let offset = params.attractor - particle.position;
+        acceleration += offset * params.attractor_strength / (dot(offset, offset) + ATTRACTOR_SOFTENING);
+        var velocity = particle.velocity + acceleration * params.dt;
+        velocity /= 1.0 + params.drag * params.dt;
+        particle.velocity = velocity;
+        particle.position += velocity * params.dt;
+    }
+    particles[index] = particle;
+}
+
+// Rendering shares `params` with the simulation
+@group(0) @binding(3)
+var<uniform> camera: CameraUniform;
+
+fn sample_color(t: f32) -> vec3<f32> {
+    let count = params.color_key_count;
+    if count == 0u {
+        return vec3<f32>(1.0);
+    }
+    if t <= params.colors[0].w {
+        return params.colors[0].xyz;
+    }
+    for (var i = 1u; i < count; i += 1u) {
+        let a = params.colors[i - 1u];
+        let b = params.colors[i];
+        if t < b.w {
+            var f = 1.0;
+            if b.w > a.w {
+                f = (t - a.w) / (b.w - a.w);
+            }
+            return mix(a.xyz, b.xyz, f);
+        }
+    }
+    return params.colors[count - 1u].xyz;
+}
+
+fn sample_size(t: f32) -> f32 {
+    let count = params.size_key_count;
+    if count == 0u {
+        return 1.0;
+    }
+    if t <= params.sizes[0].y {
+        return params.sizes[0].x;
+    }
+    for (var i = 1u; i < count; i += 1u) {
+        let a = params.sizes[i - 1u];
+        let b = params.sizes[i];
+        if t < b.y {
+            var f = 1.0;
+            if b.y > a.y {
+                f = (t - a.y) / (b.y - a.y);
+            }
+            return mix(a.x, b.x, f);
+        }
+    }
+    return params.sizes[count - 1u].x;
+}
+
+struct InstanceInput {
+    @location(0) position: vec2<f32>,
+    @location(1) age: f32,
+    @location(2) generation: u32,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) corner: vec2<f32>,
+    @location(1) color: vec3<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) vertex: u32, particle: InstanceInput) -> VertexOutput {
+    // Two triangles covering [-1, 1]
+    var corners = array<vec2<f32>, 6>(
+        vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, -1.0), vec2<f32>(1.0, 1.0),
+        vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, 1.0), vec2<f32>(-1.0, 1.0),
+    );
+    let corner = corners[vertex];
+    let t = clamp(particle.age / params.lifetime, 0.0, 1.0);
+    var size = sample_size(t);
+    // Slots that are waiting or done collapse to nothing
+    if particle.generation == 0u || particle.age >= params.lifetime {
+        size = 0.0;
+    }
+
+    var out: VertexOutput;
+    let world = particle.position + corner * size * 0.5;
+    out.clip_position = camera.view_proj * vec4<f32>(world, 0.0, 1.0);
+    out.corner = corner;
+    out.color = sample_color(t);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let distance = length(in.corner);
+    if distance > 1.0 {
+        discard;
+    }
+    return vec4<f32>(in.color, 1.0 - smoothstep(0.5, 1.0, distance));
+}
@@ -0,0 +1,438 @@
+use bytemuck::{Pod, Zeroable};
+use wgpu::util::DeviceExt;
+
+use crate::{
+    models::camera::{Camera2d, CameraUniform},
+    particles::{
+        Emitter, EmitterPoint, MAX_CURVE_KEYS, Particle, ParticleSettings, initial_particles,
+    },
+};
+
+const WORKGROUP_SIZE: u32 = 64;
+
+/// Layout of `SimParams` in `particles.wgsl`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, Pod, Zeroable)]
+struct SimParams {
+    gravity: [f32; 2],
+    attractor: [f32; 2],
+    dt: f32,
+    drag: f32,
+    attractor_strength: f32,
+    lifetime: f32,
+    speed: f32,
+    spread: f32,
+    point_count: u32,
+    particle_count: u32,
+    color_key_count: u32,
+    size_key_count: u32,
+    _padding: [u32; 2],
+    colors: [[f32; 4]; MAX_CURVE_KEYS],
+    sizes: [[f32; 4]; MAX_CURVE_KEYS],
+}
+
+impl SimParams {
+    fn new(
+        settings: &ParticleSettings,
+        particle_count: usize,
+        point_count: usize,
+        dt: f32,
+    ) -> Self {
+        let attractor = settings.attractor;
+        let mut params = Self {
+            gravity: settings.gravity.to_array(),
+            attractor: attractor.map_or([0.0; 2], |a| a.position.to_array()),
+            dt,
+            drag: settings.drag,
+            attractor_strength: attractor.map_or(0.0, |a| a.strength),
+            lifetime: settings.lifetime,
+            speed: settings.speed,
+            spread: settings.spread,
+            point_count: point_count as u32,
+            particle_count: particle_count as u32,
+            color_key_count: settings.color.keys.len().min(MAX_CURVE_KEYS) as u32,
+            size_key_count: settings.size.keys.len().min(MAX_CURVE_KEYS) as u32,
+            ..Self::default()
+        };
+        for (slot, &(t, [r, g, b])) in params.colors.iter_mut().zip(&settings.color.keys) {
+            *slot = [r, g, b, t];
+        }
+        for (slot, &(t, [size])) in params.sizes.iter_mut().zip(&settings.size.keys) {
+            *slot = [size, t, 0.0, 0.0];
+        }
+        params
+    }
+}
+
+fn uniform_entry(binding: u32, visibility: wgpu::ShaderStages) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility,
+        ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }
+}
+
+fn storage_entry(binding: u32, read_only: bool) -> wgpu::BindGroupLayoutEntry {
+    wgpu::BindGroupLayoutEntry {
+        binding,
+        visibility: wgpu::ShaderStages::COMPUTE,
+        ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Storage { read_only },
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }
+}
+
+fn create_points_buffer(device: &wgpu::Device, points: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Emitter Buffer"),
+        // Storage bindings cannot be empty
+        size: (points.max(1) * std::mem::size_of::<EmitterPoint>()) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+/// Particles simulated by a compute shader and drawn as instanced billboards.
+///
+/// Particle state lives in a storage buffer that the compute pass updates in
+/// place and the render pass reads as per-instance vertex data, so nothing
+/// goes back to the CPU. `ParticleSim` runs the same simulation on the CPU.
+pub struct ParticleSystem {
+    settings: ParticleSettings,
+    capacity: usize,
+    compute_pipeline: wgpu::ComputePipeline,
+    render_pipeline: wgpu::RenderPipeline,
+    compute_layout: wgpu::BindGroupLayout,
+    params_buffer: wgpu::Buffer,
+    camera_buffer: wgpu::Buffer,
+    particle_buffer: wgpu::Buffer,
+    points_buffer: wgpu::Buffer,
+    point_capacity: usize,
+    point_count: usize,
+    compute_bind_group: wgpu::BindGroup,
+    render_bind_group: wgpu::BindGroup,
+}
+
+impl ParticleSystem {
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        capacity: usize,
+        settings: ParticleSettings,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Particle Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../particles.wgsl").into()),
+        });
+
+        let compute_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Particle Compute Bind Group Layout"),
+            entries: &[
+                uniform_entry(0, wgpu::ShaderStages::COMPUTE),
+                storage_entry(1, false),
+                storage_entry(2, true),
+            ],
+        });
+        let render_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Particle Render Bind Group Layout"),
+            entries: &[
+                uniform_entry(0, wgpu::ShaderStages::VERTEX),
+                uniform_entry(3, wgpu::ShaderStages::VERTEX),
+            ],
+        });
+
+        let compute_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Particle Compute Pipeline Layout"),
+                bind_group_layouts: &[&compute_layout],
+                immediate_size: 0,
+            });
+        let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Particle Compute Pipeline"),
+            layout: Some(&compute_pipeline_layout),
+            module: &shader,
+            entry_point: Some("cs_main"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
+        });
+
+        let render_pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Particle Render Pipeline Layout"),
+                bind_group_layouts: &[&render_layout],
+                immediate_size: 0,
+            });
+        // Only the fields the billboards need are read from each particle
+        const INSTANCE_ATTRIBS: [wgpu::VertexAttribute; 3] = [
+            wgpu::VertexAttribute {
+                format: wgpu::VertexFormat::Float32x2,
+                offset: 0,
+                shader_location: 0,
+            },
+            wgpu::VertexAttribute {
+                format: wgpu::VertexFormat::Float32,
+                offset: 16,
+                shader_location: 1,
+            },
+            wgpu::VertexAttribute {
+                format: wgpu::VertexFormat::Uint32,
+                offset: 20,
+                shader_location: 2,
+            },
+        ];
+        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Particle Render Pipeline"),
+            layout: Some(&render_pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<Particle>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &INSTANCE_ATTRIBS,
+                }],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview_mask: None,
+            cache: None,
+        });
+
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Particle Params Buffer"),
+            contents: bytemuck::bytes_of(&SimParams::new(&settings, capacity, 0, 0.0)),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let camera_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Particle Camera Buffer"),
+            contents: bytemuck::bytes_of(&Camera2d::default().uniform()),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+        let capacity = capacity.max(1);
+        let particle_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Particle Buffer"),
+            contents: bytemuck::cast_slice(&initial_particles(capacity, settings.lifetime)),
+            usage: wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::VERTEX
+                | wgpu::BufferUsages::COPY_DST
+                | wgpu::BufferUsages::COPY_SRC,
+        });
+        let point_capacity = 16;
+        let points_buffer = create_points_buffer(device, point_capacity);
+        let compute_bind_group = Self::create_compute_bind_group(
+            device,
+            &compute_layout,
+            &params_buffer,
+            &particle_buffer,
+            &points_buffer,
+        );
+        let render_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Particle Render Bind Group"),
+            layout: &render_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: params_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: camera_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        Self {
+            settings,
+            capacity,
+            compute_pipeline,
+            render_pipeline,
+            compute_layout,
+            params_buffer,
+            camera_buffer,
+            particle_buffer,
+            points_buffer,
+            point_capacity,
+            point_count: 0,
+            compute_bind_group,
+            render_bind_group,
+        }
+    }
+
+    fn create_compute_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        params_buffer: &wgpu::Buffer,
+        particle_buffer: &wgpu::Buffer,
+        points_buffer: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Particle Compute Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: params_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: particle_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: points_buffer.as_entire_binding(),
+                },
+            ],
+        })
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn settings(&self) -> &ParticleSettings {
+        &self.settings
+    }
+
+    /// Takes effect with the next `prepare`.
+    pub fn settings_mut(&mut self) -> &mut ParticleSettings {
+        &mut self.settings
+    }
+
+    /// Replaces where new particles spawn. Particles already flying keep going.
+    pub fn set_emitter(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, emitter: &Emitter) {
+        if emitter.points.len() > self.point_capacity {
+            self.point_capacity = emitter.points.len().next_power_of_two();
+            self.points_buffer = create_points_buffer(device, self.point_capacity);
+            self.compute_bind_group = Self::create_compute_bind_group(
+                device,
+                &self.compute_layout,
+                &self.params_buffer,
+                &self.particle_buffer,
+                &self.points_buffer,
+            );
+        }
+        if !emitter.points.is_empty() {
+            queue.write_buffer(
+                &self.points_buffer,
+                0,
+                bytemuck::cast_slice(&emitter.points),
+            );
+        }
+        self.point_count = emitter.points.len();
+    }
+
+    /// Puts every slot back to waiting for its first spawn.
+    pub fn reset(&self, queue: &wgpu::Queue) {
+        let particles = initial_particles(self.capacity, self.settings.lifetime);
+        queue.write_buffer(&self.particle_buffer, 0, bytemuck::cast_slice(&particles));
+    }
+
+    /// Uploads the settings for a step of `dt` seconds and the camera to draw with.
+    pub fn prepare(&self, queue: &wgpu::Queue, dt: f32, camera: &CameraUniform) {
+        let params = SimParams::new(&self.settings, self.capacity, self.point_count, dt);
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::bytes_of(&params));
+        queue.write_buffer(
+            &self.camera_buffer,
+            0,
+            bytemuck::bytes_of(camera),
+        );
+    }
+
+    /// Records the simulation step. Goes before any pass that draws the particles.
+    pub fn compute(&self, encoder: &mut wgpu::CommandEncoder) {
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Particle Compute Pass"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_pipeline(&self.compute_pipeline);
+        compute_pass.set_bind_group(0, &self.compute_bind_group, &[]);
+        compute_pass.dispatch_workgroups((self.capacity as u32).div_ceil(WORKGROUP_SIZE), 1, 1);
+    }
+
+    /// Draws the particles over the current contents of `view`.
+    pub fn draw(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Particle Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                depth_slice: None,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+            multiview_mask: None,
+        });
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &self.render_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.particle_buffer.slice(..));
+        render_pass.draw(0..6, 0..self.capacity as u32);
+    }
+
+    /// Copies the particles back from the GPU, waiting for the copy to finish.
+    pub fn read_particles(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+    ) -> anyhow::Result<Vec<Particle>> {
+        let size = self.particle_buffer.size();
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Particle Readback Buffer"),
+            size,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Particle Readback Encoder"),
+        });
+        encoder.copy_buffer_to_buffer(&self.particle_buffer, 0, &readback, 0, size);
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = readback.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::PollType::wait_indefinitely())?;
+        receiver.recv()??;
+        let particles = bytemuck::cast_slice(&slice.get_mapped_range()).to_vec();
+        readback.unmap();
+        Ok(particles)
+    }
+}
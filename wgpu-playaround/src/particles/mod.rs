@@ -0,0 +1,268 @@
+pub mod gpu;
+
+use bytemuck::{Pod, Zeroable};
+use glam::Vec2;
+
+/// Curves use this many keys at most, the rest are ignored.
+pub const MAX_CURVE_KEYS: usize = 4;
+// Keeps the attractor force finite near its center, same value in `particles.wgsl`
+const ATTRACTOR_SOFTENING: f32 = 0.01;
+
+/// One particle, laid out like `Particle` in `particles.wgsl`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct Particle {
+    pub position: [f32; 2],
+    pub velocity: [f32; 2],
+    /// Seconds since the last spawn, negative while waiting for the first one
+    pub age: f32,
+    /// Number of times this slot has spawned
+    pub generation: u32,
+    _padding: [f32; 2],
+}
+
+impl Particle {
+    pub fn is_alive(&self, lifetime: f32) -> bool {
+        self.generation > 0 && self.age < lifetime
+    }
+}
+
+/// Where particles spawn and which way they leave, in world space.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct EmitterPoint {
+    pub position: [f32; 2],
+    pub direction: [f32; 2],
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Emitter {
+    pub points: Vec<EmitterPoint>,
+}
+
+impl Emitter {
+    /// Spawns from every point of a closed outline, heading away from its center.
+    pub fn from_outline(outline: &[Vec2]) -> Self {
+        let center = centroid(outline);
+        Self {
+            points: outline.iter().map(|&p| emitter_point(p, center)).collect(),
+        }
+    }
+
+    /// Spawns only from the points that stick out further from the center
+    /// than their neighbours, like the tips of a star.
+    pub fn tips(outline: &[Vec2]) -> Self {
+        let center = centroid(outline);
+        let n = outline.len();
+        let distance = |i: usize| outline[i % n].distance(center);
+        let points = (0..n)
+            .filter(|&i| {
+                let here = distance(i);
+                here >= distance(i + n - 1) && here >= distance(i + 1)
+            })
+            .map(|i| emitter_point(outline[i], center))
+            .collect();
+        Self { points }
+    }
+}
+
+fn centroid(points: &[Vec2]) -> Vec2 {
+    points.iter().sum::<Vec2>() / points.len().max(1) as f32
+}
+
+fn emitter_point(position: Vec2, center: Vec2) -> EmitterPoint {
+    EmitterPoint {
+        position: position.to_array(),
+        direction: (position - center).normalize_or(Vec2::Y).to_array(),
+    }
+}
+
+/// Piecewise linear curve over a particle's life, `t` going from 0 to 1.
+///
+/// Keys are `(t, value)` pairs sorted by `t`. Before the first key and after
+/// the last one the curve stays flat.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Curve<const N: usize> {
+    pub keys: Vec<(f32, [f32; N])>,
+}
+
+impl<const N: usize> Curve<N> {
+    pub fn constant(value: [f32; N]) -> Self {
+        Self {
+            keys: vec![(0.0, value)],
+        }
+    }
+
+    pub fn sample(&self, t: f32) -> [f32; N] {
+        let keys = &self.keys[..self.keys.len().min(MAX_CURVE_KEYS)];
+        let Some(&(first_t, first)) = keys.first() else {
+            return [1.0; N];
+        };
+        if t <= first_t {
+            return first;
+        }
+        for pair in keys.windows(2) {
+            let ((t0, a), (t1, b)) = (pair[0], pair[1]);
+            if t < t1 {
+                let f = if t1 > t0 { (t - t0) / (t1 - t0) } else { 1.0 };
+                return std::array::from_fn(|i| a[i] + (b[i] - a[i]) * f);
+            }
+        }
+        keys[keys.len() - 1].1
+    }
+}
+
+/// Pulls particles towards `position`, harder the closer they are.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Attractor {
+    pub position: Vec2,
+    pub strength: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParticleSettings {
+    /// Seconds from spawn to respawn
+    pub lifetime: f32,
+    /// Largest spawn speed in world units per second, the smallest is half of it
+    pub speed: f32,
+    /// Angle in radians spawn directions are randomly spread over
+    pub spread: f32,
+    pub gravity: Vec2,
+    /// Fraction of the velocity lost per second, roughly
+    pub drag: f32,
+    pub attractor: Option<Attractor>,
+    /// Linear color over the particle's life, above 1 for sparks that glow
+    pub color: Curve<3>,
+    /// Diameter in world units over the particle's life
+    pub size: Curve<1>,
+}
+
+impl Default for ParticleSettings {
+    fn default() -> Self {
+        Self {
+            lifetime: 2.0,
+            speed: 0.6,
+            spread: 0.6,
+            gravity: Vec2::new(0.0, -0.8),
+            drag: 0.8,
+            attractor: None,
+            color: Curve {
+                keys: vec![
+                    (0.0, [4.0, 3.0, 1.0]),
+                    (0.4, [1.0, 0.3, 0.05]),
+                    (1.0, [0.2, 0.02, 0.0]),
+                ],
+            },
+            size: Curve {
+                keys: vec![(0.0, [0.012]), (0.2, [0.02]), (1.0, [0.0])],
+            },
+        }
+    }
+}
+
+/// PCG hash, the same integer math as `pcg` in `particles.wgsl`.
+pub(crate) fn pcg(value: u32) -> u32 {
+    let state = value.wrapping_mul(747796405).wrapping_add(2891336453);
+    let word = ((state >> ((state >> 28) + 4)) ^ state).wrapping_mul(277803737);
+    (word >> 22) ^ word
+}
+
+/// Uniform value in `[0, 1)` from a hash, exact in `f32`.
+fn unit(hash: u32) -> f32 {
+    (hash >> 8) as f32 / 16_777_216.0
+}
+
+/// Slots waiting to spawn, staggered so they start evenly over one lifetime.
+pub fn initial_particles(capacity: usize, lifetime: f32) -> Vec<Particle> {
+    (0..capacity)
+        .map(|i| Particle {
+            age: -(i as f32 / capacity as f32) * lifetime,
+            ..Particle::default()
+        })
+        .collect()
+}
+
+/// Advances one particle by `dt`, like `cs_main` in `particles.wgsl` does on the GPU.
+///
+/// A slot respawns once its lifetime is over. Where it respawns only depends
+/// on the slot index and its generation, so the CPU and GPU agree on it.
+pub fn step_particle(
+    index: u32,
+    particle: &mut Particle,
+    settings: &ParticleSettings,
+    emitter: &[EmitterPoint],
+    dt: f32,
+) {
+    particle.age += dt;
+    let due = if particle.generation == 0 {
+        particle.age >= 0.0
+    } else {
+        particle.age >= settings.lifetime
+    };
+    if due {
+        if emitter.is_empty() {
+            return;
+        }
+        particle.generation += 1;
+        particle.age = 0.0;
+        let h0 = pcg(index ^ pcg(particle.generation));
+        let (h1, h2) = (pcg(h0), pcg(pcg(h0)));
+        let point = emitter[(h0 % emitter.len() as u32) as usize];
+        let angle = (unit(h1) - 0.5) * settings.spread;
+        let direction = Vec2::from_angle(angle).rotate(Vec2::from_array(point.direction));
+        let speed = settings.speed * (0.5 + 0.5 * unit(h2));
+        particle.position = point.position;
+        particle.velocity = (direction * speed).to_array();
+        return;
+    }
+    if !particle.is_alive(settings.lifetime) {
+        return;
+    }
+
+    let position = Vec2::from_array(particle.position);
+    let mut acceleration = settings.gravity;
+    if let Some(attractor) = settings.attractor {
+        let offset = attractor.position - position;
+        acceleration +=
+            offset * attractor.strength / (offset.length_squared() + ATTRACTOR_SOFTENING);
+    }
+    let mut velocity = Vec2::from_array(particle.velocity) + acceleration * dt;
+    velocity /= 1.0 + settings.drag * dt;
+    particle.velocity = velocity.to_array();
+    particle.position = (position + velocity * dt).to_array();
+}
+
+/// CPU reference for the GPU particle simulation.
+pub struct ParticleSim {
+    pub particles: Vec<Particle>,
+    pub settings: ParticleSettings,
+    pub emitter: Emitter,
+}
+
+impl ParticleSim {
+    pub fn new(capacity: usize, settings: ParticleSettings, emitter: Emitter) -> Self {
+        Self {
+            particles: initial_particles(capacity, settings.lifetime),
+            settings,
+            emitter,
+        }
+    }
+
+    pub fn step(&mut self, dt: f32) {
+        for (index, particle) in self.particles.iter_mut().enumerate() {
+            step_particle(
+                index as u32,
+                particle,
+                &self.settings,
+                &self.emitter.points,
+                dt,
+            );
+        }
+    }
+
+    pub fn alive(&self) -> impl Iterator<Item = &Particle> {
+        self.particles
+            .iter()
+            .filter(|p| p.is_alive(self.settings.lifetime))
+    }
+}
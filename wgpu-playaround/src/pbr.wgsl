@@ -0,0 +1,183 @@
+// Metallic-roughness shading of the scene, mirrors `material::shade` and
+// `material/brdf.rs`. Appended to `lights.wgsl`.
+
+const PI: f32 = 3.14159265;
+const MIN_ROUGHNESS: f32 = 0.045;
+const DIELECTRIC_F0: f32 = 0.04;
+
+struct MaterialUniform {
+    base_color_factor: vec4<f32>,
+    emissive_factor: vec3<f32>,
+    metallic_factor: f32,
+    roughness_factor: f32,
+    normal_scale: f32,
+    occlusion_strength: f32,
+    _padding: f32,
+};
+
+// Image based light baked from the environment, black until one is loaded
+@group(1) @binding(1)
+var irradiance_map: texture_cube<f32>;
+@group(1) @binding(2)
+var prefiltered_map: texture_cube<f32>;
+@group(1) @binding(3)
+var brdf_lut: texture_2d<f32>;
+@group(1) @binding(4)
+var environment_sampler: sampler;
+
+@group(3) @binding(0)
+var<uniform> material: MaterialUniform;
+@group(3) @binding(1)
+var base_color_map: texture_2d<f32>;
+@group(3) @binding(2)
+var metallic_roughness_map: texture_2d<f32>;
+@group(3) @binding(3)
+var normal_map: texture_2d<f32>;
+@group(3) @binding(4)
+var occlusion_map: texture_2d<f32>;
+@group(3) @binding(5)
+var emissive_map: texture_2d<f32>;
+@group(3) @binding(6)
+var material_sampler: sampler;
+
+fn distribution_ggx(n_dot_h: f32, roughness: f32) -> f32 {
+    let alpha = roughness * roughness;
+    let alpha2 = alpha * alpha;
+    let d = n_dot_h * n_dot_h * (alpha2 - 1.0) + 1.0;
+    return alpha2 / (PI * d * d);
+}
+
+fn geometry_schlick(n_dot_x: f32, k: f32) -> f32 {
+    return n_dot_x / (n_dot_x * (1.0 - k) + k);
+}
+
+fn geometry_smith(n_dot_v: f32, n_dot_l: f32, roughness: f32) -> f32 {
+    let k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+    return geometry_schlick(n_dot_v, k) * geometry_schlick(n_dot_l, k);
+}
+
+fn fresnel_schlick(cos_theta: f32, f0: vec3<f32>) -> vec3<f32> {
+    return f0 + (vec3<f32>(1.0) - f0) * pow(clamp(1.0 - cos_theta, 0.0, 1.0), 5.0);
+}
+
+// Fresnel averaged over the lobe, rough surfaces reflect less at grazing angles
+fn fresnel_schlick_roughness(cos_theta: f32, f0: vec3<f32>, roughness: f32) -> vec3<f32> {
+    let grazing = max(vec3<f32>(1.0 - roughness), f0);
+    return f0 + (grazing - f0) * pow(clamp(1.0 - cos_theta, 0.0, 1.0), 5.0);
+}
+
+// Diffuse irradiance plus the split sum approximation of the specular light
+fn environment_light(
+    normal: vec3<f32>,
+    to_eye: vec3<f32>,
+    base_color: vec3<f32>,
+    metallic: f32,
+    roughness: f32,
+) -> vec3<f32> {
+    let n_dot_v = max(dot(normal, to_eye), 1e-4);
+    let f0 = mix(vec3<f32>(DIELECTRIC_F0), base_color, metallic);
+    let fresnel = fresnel_schlick_roughness(n_dot_v, f0, roughness);
+    let irradiance = textureSampleLevel(irradiance_map, environment_sampler, normal, 0.0).rgb;
+    let diffuse = (vec3<f32>(1.0) - fresnel) * (1.0 - metallic) * base_color * irradiance;
+
+    let reflected = reflect(-to_eye, normal);
+    let max_mip = f32(textureNumLevels(prefiltered_map) - 1u);
+    let prefiltered = textureSampleLevel(prefiltered_map, environment_sampler, reflected, roughness * max_mip).rgb;
+    let scale_bias = textureSampleLevel(brdf_lut, environment_sampler, vec2<f32>(n_dot_v, roughness), 0.0).rg;
+    let specular = prefiltered * (f0 * scale_bias.x + scale_bias.y);
+    return (diffuse + specular) * lighting.environment_intensity;
+}
+
+fn cook_torrance(
+    normal: vec3<f32>,
+    to_eye: vec3<f32>,
+    to_light: vec3<f32>,
+    base_color: vec3<f32>,
+    metallic: f32,
+    roughness_in: f32,
+) -> vec3<f32> {
+    let n_dot_l = dot(normal, to_light);
+    if n_dot_l <= 0.0 {
+        return vec3<f32>(0.0);
+    }
+    let roughness = clamp(roughness_in, MIN_ROUGHNESS, 1.0);
+    let half_vector = normalize_or_zero(to_light + to_eye);
+    let n_dot_v = max(dot(normal, to_eye), 1e-4);
+    let n_dot_h = max(dot(normal, half_vector), 0.0);
+    let f0 = mix(vec3<f32>(DIELECTRIC_F0), base_color, metallic);
+    let fresnel = fresnel_schlick(max(dot(half_vector, to_eye), 0.0), f0);
+    let specular = fresnel * distribution_ggx(n_dot_h, roughness)
+        * geometry_smith(n_dot_v, n_dot_l, roughness)
+        / max(4.0 * n_dot_v * n_dot_l, 1e-4);
+    let diffuse = (vec3<f32>(1.0) - fresnel) * (1.0 - metallic) * base_color / PI;
+    return (diffuse + specular) * n_dot_l;
+}
+
+// Applies the normal map with a tangent frame from screen-space derivatives,
+// so meshes need no tangents. glTF normal maps point +y towards -v.
+fn mapped_normal(normal: vec3<f32>, position: vec3<f32>, uv: vec2<f32>, texel: vec3<f32>) -> vec3<f32> {
+    let dp1 = dpdx(position);
+    let dp2 = dpdy(position);
+    let duv1 = dpdx(uv);
+    let duv2 = dpdy(uv);
+    let dp2_perp = cross(dp2, normal);
+    let dp1_perp = cross(normal, dp1);
+    let tangent = dp2_perp * duv1.x + dp1_perp * duv2.x;
+    let bitangent = dp2_perp * duv1.y + dp1_perp * duv2.y;
+    let scale = max(dot(tangent, tangent), dot(bitangent, bitangent));
+    var tangent_normal = texel * 2.0 - 1.0;
+    tangent_normal = vec3<f32>(tangent_normal.xy * material.normal_scale, tangent_normal.z);
+    if scale <= 0.0 {
+        return normal;
+    }
+    let inverse_length = inverseSqrt(scale);
+    let frame = mat3x3<f32>(tangent * inverse_length, -bitangent * inverse_length, normal);
+    return normalize_or_zero(frame * tangent_normal);
+}
+
+fn shade(in: VertexOutput) -> vec3<f32> {
+    // Sampled up front, derivatives need uniform control flow
+    let base_texel = textureSample(base_color_map, material_sampler, in.uv);
+    let metallic_roughness = textureSample(metallic_roughness_map, material_sampler, in.uv);
+    let normal_texel = textureSample(normal_map, material_sampler, in.uv).xyz;
+    let occlusion_texel = textureSample(occlusion_map, material_sampler, in.uv).r;
+    let emissive_texel = textureSample(emissive_map, material_sampler, in.uv).rgb;
+    let normal = mapped_normal(normalize_or_zero(in.normal), in.world_position, in.uv, normal_texel);
+
+    let base_color = base_texel.rgb * material.base_color_factor.rgb * in.color;
+    let metallic = clamp(metallic_roughness.b * material.metallic_factor, 0.0, 1.0);
+    let roughness = clamp(metallic_roughness.g * material.roughness_factor, 0.0, 1.0);
+    let occlusion = 1.0 + material.occlusion_strength * (occlusion_texel - 1.0);
+    let emissive = emissive_texel * material.emissive_factor;
+
+    let position = in.world_position;
+    let to_eye = view_direction(position);
+    let ambient = lighting.ambient * base_color
+        + environment_light(normal, to_eye, base_color, metallic, roughness);
+    var color = ambient * occlusion + emissive;
+    for (var i = 0u; i < min(lighting.count, MAX_LIGHTS); i++) {
+        let light = incident(i, position);
+        let n_dot_l = dot(normal, light.to_light);
+        if n_dot_l <= 0.0 {
+            continue;
+        }
+        let shadow = shadow_factor(i, position, n_dot_l);
+        color += light.radiance * shadow
+            * cook_torrance(normal, to_eye, light.to_light, base_color, metallic, roughness);
+    }
+    return color;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(shade(in), 1.0);
+}
+
+@fragment
+fn fs_main_id(in: VertexOutput) -> IdFragmentOutput {
+    var out: IdFragmentOutput;
+    out.color = vec4<f32>(shade(in), 1.0);
+    // 0 is left for the cleared background
+    out.object_id = in.object_id + 1u;
+    return out;
+}
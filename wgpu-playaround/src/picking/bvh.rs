@@ -0,0 +1,190 @@
+use glam::Vec2;
+
+/// Triangles per leaf before a node gets split.
+const LEAF_SIZE: usize = 4;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Aabb2 {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Aabb2 {
+    pub const EMPTY: Aabb2 = Aabb2 {
+        min: Vec2::splat(f32::INFINITY),
+        max: Vec2::splat(f32::NEG_INFINITY),
+    };
+
+    pub fn grow(&mut self, p: Vec2) {
+        self.min = self.min.min(p);
+        self.max = self.max.max(p);
+    }
+
+    pub fn contains(&self, p: Vec2) -> bool {
+        p.cmpge(self.min).all() && p.cmple(self.max).all()
+    }
+}
+
+enum Node {
+    Leaf {
+        bounds: Aabb2,
+        first: usize,
+        count: usize,
+    },
+    Branch {
+        bounds: Aabb2,
+        left: usize,
+        right: usize,
+    },
+}
+
+impl Node {
+    fn bounds(&self) -> &Aabb2 {
+        match self {
+            Node::Leaf { bounds, .. } | Node::Branch { bounds, .. } => bounds,
+        }
+    }
+}
+
+/// Bounding volume hierarchy over the triangles of a 2D mesh.
+///
+/// Triangles are stored in mesh-local space, so moving a shape only changes
+/// the transform used for queries and never requires a rebuild.
+pub struct Bvh {
+    positions: Vec<Vec2>,
+    triangles: Vec<[u32; 3]>,
+    // Triangle ids, reordered so every leaf owns a contiguous range
+    order: Vec<usize>,
+    nodes: Vec<Node>,
+}
+
+/// Barycentric coordinates of `p` in triangle `abc`, or `None` if it lies outside.
+pub fn barycentric(p: Vec2, a: Vec2, b: Vec2, c: Vec2) -> Option<[f32; 3]> {
+    // Only exactly flat triangles are skipped, tiny ones are still on screen
+    let area = (b - a).perp_dot(c - a);
+    if area == 0.0 {
+        return None;
+    }
+    let u = (c - b).perp_dot(p - b) / area;
+    let v = (a - c).perp_dot(p - c) / area;
+    let w = 1.0 - u - v;
+    (u >= 0.0 && v >= 0.0 && w >= 0.0).then_some([u, v, w])
+}
+
+impl Bvh {
+    pub fn new(positions: Vec<Vec2>, indices: &[u16]) -> Self {
+        let triangles: Vec<[u32; 3]> = indices
+            .chunks_exact(3)
+            .map(|t| [t[0] as u32, t[1] as u32, t[2] as u32])
+            .collect();
+        let mut bvh = Self {
+            order: (0..triangles.len()).collect(),
+            positions,
+            triangles,
+            nodes: Vec::new(),
+        };
+        if !bvh.triangles.is_empty() {
+            bvh.build(0, bvh.triangles.len());
+        }
+        bvh
+    }
+
+    fn corners(&self, triangle: usize) -> [Vec2; 3] {
+        self.triangles[triangle].map(|i| self.positions[i as usize])
+    }
+
+    fn build(&mut self, first: usize, count: usize) -> usize {
+        let mut bounds = Aabb2::EMPTY;
+        let mut centroids = Aabb2::EMPTY;
+        for &triangle in &self.order[first..first + count] {
+            let [a, b, c] = self.corners(triangle);
+            bounds.grow(a);
+            bounds.grow(b);
+            bounds.grow(c);
+            centroids.grow((a + b + c) / 3.0);
+        }
+
+        let index = self.nodes.len();
+        if count <= LEAF_SIZE {
+            self.nodes.push(Node::Leaf {
+                bounds,
+                first,
+                count,
+            });
+            return index;
+        }
+
+        // Median split along the longest axis of the centroid bounds
+        let extent = centroids.max - centroids.min;
+        let axis = if extent.x >= extent.y { 0 } else { 1 };
+        let mut order = std::mem::take(&mut self.order);
+        order[first..first + count].sort_by(|&a, &b| {
+            let ca = self.corners(a).iter().map(|p| p[axis]).sum::<f32>();
+            let cb = self.corners(b).iter().map(|p| p[axis]).sum::<f32>();
+            ca.total_cmp(&cb)
+        });
+        self.order = order;
+
+        // Reserve the slot, children are patched in once they exist
+        self.nodes.push(Node::Leaf {
+            bounds,
+            first,
+            count,
+        });
+        let half = count / 2;
+        let left = self.build(first, half);
+        let right = self.build(first + half, count - half);
+        self.nodes[index] = Node::Branch {
+            bounds,
+            left,
+            right,
+        };
+        index
+    }
+
+    /// Bounds of every triangle, empty when there are none.
+    pub fn bounds(&self) -> Aabb2 {
+        self.nodes.first().map_or(Aabb2::EMPTY, |node| *node.bounds())
+    }
+
+    /// Corners of a triangle in mesh-local space.
+    pub fn triangle(&self, triangle: usize) -> Option<[Vec2; 3]> {
+        (triangle < self.triangles.len()).then(|| self.corners(triangle))
+    }
+
+    /// Finds the triangle containing `p`, given in mesh-local space.
+    ///
+    /// Returns the triangle index (into the original index buffer, divided by
+    /// three) and the barycentric coordinates of the hit. When triangles
+    /// overlap the one drawn last wins, like it does on screen.
+    pub fn hit(&self, p: Vec2) -> Option<(usize, [f32; 3])> {
+        let mut best: Option<(usize, [f32; 3])> = None;
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let Some(node) = self.nodes.get(index) else {
+                break;
+            };
+            if !node.bounds().contains(p) {
+                continue;
+            }
+            match *node {
+                Node::Leaf { first, count, .. } => {
+                    for &triangle in &self.order[first..first + count] {
+                        if best.is_some_and(|(hit, _)| hit > triangle) {
+                            continue;
+                        }
+                        let [a, b, c] = self.corners(triangle);
+                        if let Some(weights) = barycentric(p, a, b, c) {
+                            best = Some((triangle, weights));
+                        }
+                    }
+                }
+                Node::Branch { left, right, .. } => {
+                    stack.push(left);
+                    stack.push(right);
+                }
+            }
+        }
+        best
+    }
+}
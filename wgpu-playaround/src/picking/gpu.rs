@@ -0,0 +1,208 @@
+use std::sync::{
+    Arc,
+    atomic::{AtomicU8, Ordering},
+};
+
+/// Number of readbacks that can be in flight at once.
+const READBACK_SLOTS: usize = 3;
+
+// Values of `ReadbackSlot::mapped`
+const MAP_PENDING: u8 = 0;
+const MAP_OK: u8 = 1;
+const MAP_FAILED: u8 = 2;
+
+/// Object id stored in an ID buffer pixel. Draw calls write their id plus
+/// one, so the cleared background reads as zero.
+pub fn decode_object_id(raw: u32) -> Option<u32> {
+    raw.checked_sub(1)
+}
+
+/// Result of reading the ID buffer under the cursor.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct IdPick {
+    /// Object id written by the draw call, `None` for the background
+    pub object_id: Option<u32>,
+    /// Pixel that was read, in physical pixels
+    pub pixel: (u32, u32),
+}
+
+struct ReadbackSlot {
+    buffer: wgpu::Buffer,
+    pixel: (u32, u32),
+    // Which request this slot holds, so the newest result wins
+    sequence: u64,
+    // Written by the map callback once the copy has landed
+    mapped: Arc<AtomicU8>,
+    state: SlotState,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum SlotState {
+    Free,
+    // Copy recorded, waiting for the submit
+    Copied,
+    // map_async issued, waiting for `mapped`
+    Mapping,
+}
+
+/// Extra `R32Uint` color attachment holding the object id of every pixel.
+///
+/// The pixel under the cursor is copied into a small ring of readback buffers
+/// and mapped asynchronously, so picking never waits for the GPU. Results show
+/// up in [`IdBuffer::poll`] one or more frames after they were requested.
+pub struct IdBuffer {
+    texture: wgpu::Texture,
+    pub view: wgpu::TextureView,
+    slots: Vec<ReadbackSlot>,
+    next_sequence: u64,
+}
+
+impl IdBuffer {
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::R32Uint;
+
+    pub fn new(device: &wgpu::Device, width: u32, height: u32) -> Self {
+        let (texture, view) = Self::create_target(device, width, height);
+        let slots = (0..READBACK_SLOTS)
+            .map(|_| ReadbackSlot {
+                buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("ID Readback Buffer"),
+                    size: wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as wgpu::BufferAddress,
+                    usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                    mapped_at_creation: false,
+                }),
+                pixel: (0, 0),
+                sequence: 0,
+                mapped: Arc::new(AtomicU8::new(MAP_PENDING)),
+                state: SlotState::Free,
+            })
+            .collect();
+
+        Self {
+            texture,
+            view,
+            slots,
+            next_sequence: 0,
+        }
+    }
+
+    fn create_target(
+        device: &wgpu::Device,
+        width: u32,
+        height: u32,
+    ) -> (wgpu::Texture, wgpu::TextureView) {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("ID Buffer"),
+            size: wgpu::Extent3d {
+                width: width.max(1),
+                height: height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        (texture, view)
+    }
+
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        let (texture, view) = Self::create_target(device, width, height);
+        self.texture = texture;
+        self.view = view;
+    }
+
+    /// Records a copy of the pixel under the cursor. Does nothing when every
+    /// slot is still busy, the next frame simply tries again.
+    pub fn request(&mut self, encoder: &mut wgpu::CommandEncoder, x: f64, y: f64) {
+        let size = self.texture.size();
+        if x < 0.0 || y < 0.0 || x >= size.width as f64 || y >= size.height as f64 {
+            return;
+        }
+        let Some(slot) = self.slots.iter_mut().find(|s| s.state == SlotState::Free) else {
+            return;
+        };
+
+        slot.pixel = (x as u32, y as u32);
+        slot.sequence = self.next_sequence;
+        self.next_sequence += 1;
+        encoder.copy_texture_to_buffer(
+            wgpu::TexelCopyTextureInfo {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d {
+                    x: slot.pixel.0,
+                    y: slot.pixel.1,
+                    z: 0,
+                },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::TexelCopyBufferInfo {
+                buffer: &slot.buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT),
+                    rows_per_image: Some(1),
+                },
+            },
+            wgpu::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+        );
+        slot.state = SlotState::Copied;
+    }
+
+    /// Starts mapping the copies recorded this frame. Call after `queue.submit`.
+    pub fn after_submit(&mut self) {
+        for slot in self.slots.iter_mut().filter(|s| s.state == SlotState::Copied) {
+            let mapped = slot.mapped.clone();
+            slot.buffer
+                .slice(..)
+                .map_async(wgpu::MapMode::Read, move |result| {
+                    let value = if result.is_ok() { MAP_OK } else { MAP_FAILED };
+                    mapped.store(value, Ordering::Release);
+                });
+            slot.state = SlotState::Mapping;
+        }
+    }
+
+    /// Collects finished readbacks without blocking and returns the newest one.
+    pub fn poll(&mut self, device: &wgpu::Device) -> Option<IdPick> {
+        let _ = device.poll(wgpu::PollType::Poll);
+
+        let mut latest: Option<(u64, IdPick)> = None;
+        for slot in &mut self.slots {
+            if slot.state != SlotState::Mapping {
+                continue;
+            }
+            match slot.mapped.swap(MAP_PENDING, Ordering::Acquire) {
+                MAP_PENDING => continue,
+                MAP_FAILED => {
+                    slot.state = SlotState::Free;
+                    continue;
+                }
+                _ => {}
+            }
+
+            let id = {
+                let data = slot.buffer.slice(..).get_mapped_range();
+                u32::from_le_bytes([data[0], data[1], data[2], data[3]])
+            };
+            slot.buffer.unmap();
+            slot.state = SlotState::Free;
+
+            if latest.is_none_or(|(sequence, _)| sequence < slot.sequence) {
+                let pick = IdPick {
+                    object_id: decode_object_id(id),
+                    pixel: slot.pixel,
+                };
+                latest = Some((slot.sequence, pick));
+            }
+        }
+        latest.map(|(_, pick)| pick)
+    }
+}
@@ -0,0 +1,125 @@
+pub mod bvh;
+pub mod gpu;
+
+use std::sync::Arc;
+
+use glam::{Mat4, Vec2, Vec3, Vec3Swizzles};
+
+use crate::picking::bvh::{Aabb2, Bvh};
+
+/// Converts a cursor position in physical pixels to normalized device coordinates.
+///
+/// winit reports `CursorMoved` in physical pixels and the surface is configured
+/// in physical pixels too, so the DPI scale cancels out. Logical positions have
+/// to be multiplied by the window's scale factor first.
+pub fn cursor_to_ndc(x: f64, y: f64, width: u32, height: u32) -> Vec2 {
+    let width = width.max(1) as f64;
+    let height = height.max(1) as f64;
+    Vec2::new(
+        (x / width * 2.0 - 1.0) as f32,
+        // Window y grows downwards, NDC y grows upwards
+        (1.0 - y / height * 2.0) as f32,
+    )
+}
+
+/// Maps a point in normalized device coordinates back into the world, on the `z = 0` plane.
+///
+/// `inverse_view_proj` undoes the camera. The ray through the cursor is
+/// intersected with the plane so perspective cameras work as well.
+pub fn ndc_to_world(ndc: Vec2, inverse_view_proj: Mat4) -> Vec2 {
+    let near = inverse_view_proj.project_point3(ndc.extend(0.0));
+    let far = inverse_view_proj.project_point3(ndc.extend(1.0));
+    let direction = far - near;
+    if direction.z.abs() <= f32::EPSILON {
+        return near.xy();
+    }
+    let t = -near.z / direction.z;
+    (near + direction * t).xy()
+}
+
+/// Which shape and triangle is under a point.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Hit {
+    pub shape: usize,
+    pub triangle: usize,
+    pub barycentric: [f32; 3],
+}
+
+struct PickTarget {
+    shape: usize,
+    transform: Mat4,
+    inverse_transform: Mat4,
+    bvh: Arc<Bvh>,
+}
+
+/// CPU hit testing against the triangles of the shapes on screen.
+#[derive(Default)]
+pub struct Picker {
+    targets: Vec<PickTarget>,
+}
+
+impl Picker {
+    pub fn clear(&mut self) {
+        self.targets.clear();
+    }
+
+    /// Adds a shape in draw order. `transform` maps the mesh into world space.
+    pub fn add(&mut self, shape: usize, transform: Mat4, positions: &[[f32; 3]], indices: &[u16]) {
+        let positions = positions.iter().map(|p| Vec2::new(p[0], p[1])).collect();
+        self.add_bvh(shape, transform, Arc::new(Bvh::new(positions, indices)));
+    }
+
+    /// Adds a shape whose mesh-local hierarchy is already built, so it can be
+    /// shared with a cache and reused while only the transform changes.
+    pub fn add_bvh(&mut self, shape: usize, transform: Mat4, bvh: Arc<Bvh>) {
+        self.targets.push(PickTarget {
+            shape,
+            transform,
+            inverse_transform: transform.inverse(),
+            bvh,
+        });
+    }
+
+    /// World space bounds of every shape, in draw order.
+    pub fn bounds(&self) -> impl Iterator<Item = (usize, Aabb2)> + '_ {
+        self.targets.iter().map(|target| {
+            let local = target.bvh.bounds();
+            let mut world = Aabb2::EMPTY;
+            if local.min.cmple(local.max).all() {
+                for corner in [
+                    local.min,
+                    Vec2::new(local.max.x, local.min.y),
+                    local.max,
+                    Vec2::new(local.min.x, local.max.y),
+                ] {
+                    world.grow(target.transform.transform_point3(corner.extend(0.0)).xy());
+                }
+            }
+            (target.shape, world)
+        })
+    }
+
+    /// Corners of the triangle that was hit, in world space.
+    pub fn triangle(&self, hit: &Hit) -> Option<[Vec2; 3]> {
+        let target = self.targets.iter().find(|target| target.shape == hit.shape)?;
+        let corners = target.bvh.triangle(hit.triangle)?;
+        Some(corners.map(|p| target.transform.transform_point3(p.extend(0.0)).xy()))
+    }
+
+    /// Finds the top-most shape under `world`. Shapes added later are drawn on top.
+    pub fn pick(&self, world: Vec2) -> Option<Hit> {
+        self.targets.iter().rev().find_map(|target| {
+            let local = target
+                .inverse_transform
+                .transform_point3(Vec3::new(world.x, world.y, 0.0));
+            target
+                .bvh
+                .hit(local.xy())
+                .map(|(triangle, barycentric)| Hit {
+                    shape: target.shape,
+                    triangle,
+                    barycentric,
+                })
+        })
+    }
+}
@@ -0,0 +1,144 @@
+// Fullscreen passes of the post-processing chain, one fragment entry point per effect
+struct PostParams {
+    // One pixel in uv units
+    texel_size: vec2<f32>,
+    direction: vec2<f32>,
+    amount: f32,
+    _padding0: f32,
+    _padding1: f32,
+    _padding2: f32,
+};
+@group(0) @binding(0)
+var source_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var source_sampler: sampler;
+@group(0) @binding(2)
+var<uniform> params: PostParams;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+};
+
+// One triangle covering the screen
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let position = vec2<f32>(f32(index & 1u) * 4.0 - 1.0, f32(index >> 1u) * 4.0 - 1.0);
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(position, 0.0, 1.0);
+    out.uv = vec2<f32>(position.x * 0.5 + 0.5, 0.5 - position.y * 0.5);
+    return out;
+}
+
+fn luminance(color: vec3<f32>) -> f32 {
+    return dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+}
+
+fn tap(uv: vec2<f32>) -> vec3<f32> {
+    return textureSampleLevel(source_texture, source_sampler, uv, 0.0).rgb;
+}
+
+@fragment
+fn fs_grayscale(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = tap(in.uv);
+    return vec4<f32>(mix(color, vec3<f32>(luminance(color)), params.amount), 1.0);
+}
+
+// Nine taps along `direction`, spaced so the kernel spans `amount` pixels each way
+@fragment
+fn fs_blur(in: VertexOutput) -> @location(0) vec4<f32> {
+    var weights = array<f32, 5>(0.227027, 0.1945946, 0.1216216, 0.054054, 0.016216);
+    let stride = params.direction * params.texel_size * params.amount / 4.0;
+    var color = tap(in.uv) * weights[0];
+    for (var i = 1; i < 5; i++) {
+        let offset = stride * f32(i);
+        color += (tap(in.uv + offset) + tap(in.uv - offset)) * weights[i];
+    }
+    return vec4<f32>(color, 1.0);
+}
+
+@fragment
+fn fs_vignette(in: VertexOutput) -> @location(0) vec4<f32> {
+    // 0 in the center, 1 in the corners
+    let radius = length(in.uv - 0.5) * sqrt(2.0);
+    let shade = 1.0 - params.amount * smoothstep(0.4, 1.0, radius);
+    return vec4<f32>(tap(in.uv) * shade, 1.0);
+}
+
+@fragment
+fn fs_chromatic(in: VertexOutput) -> @location(0) vec4<f32> {
+    let offset = (in.uv - 0.5) * 2.0 * params.amount * params.texel_size;
+    let red = tap(in.uv + offset).r;
+    let green = tap(in.uv).g;
+    let blue = tap(in.uv - offset).b;
+    return vec4<f32>(red, green, blue, 1.0);
+}
+
+@fragment
+fn fs_pixelate(in: VertexOutput) -> @location(0) vec4<f32> {
+    let block = max(params.amount, 1.0) * params.texel_size;
+    let uv = (floor(in.uv / block) + 0.5) * block;
+    return vec4<f32>(tap(uv), 1.0);
+}
+
+// Sobel gradient of the luminance
+@fragment
+fn fs_edges(in: VertexOutput) -> @location(0) vec4<f32> {
+    var l: array<f32, 9>;
+    for (var i = 0; i < 9; i++) {
+        let offset = vec2<f32>(f32(i % 3 - 1), f32(i / 3 - 1)) * params.texel_size;
+        l[i] = luminance(tap(in.uv + offset));
+    }
+    let gx = (l[2] + 2.0 * l[5] + l[8]) - (l[0] + 2.0 * l[3] + l[6]);
+    let gy = (l[6] + 2.0 * l[7] + l[8]) - (l[0] + 2.0 * l[1] + l[2]);
+    let edge = clamp(length(vec2<f32>(gx, gy)) * params.amount, 0.0, 1.0);
+    return vec4<f32>(vec3<f32>(edge), 1.0);
+}
+
+// Tonemapping, the last pass of the chain. `amount` is the exposure factor and
+// every curve mirrors `Tonemap::apply` in `post/tonemap.rs`.
+fn exposed(uv: vec2<f32>) -> vec3<f32> {
+    return max(tap(uv), vec3<f32>(0.0)) * params.amount;
+}
+
+@fragment
+fn fs_tonemap_clamp(in: VertexOutput) -> @location(0) vec4<f32> {
+    return vec4<f32>(min(exposed(in.uv), vec3<f32>(1.0)), 1.0);
+}
+
+@fragment
+fn fs_tonemap_reinhard(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = exposed(in.uv);
+    return vec4<f32>(color / (1.0 + color), 1.0);
+}
+
+@fragment
+fn fs_tonemap_aces(in: VertexOutput) -> @location(0) vec4<f32> {
+    let x = exposed(in.uv);
+    let color = (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14);
+    return vec4<f32>(clamp(color, vec3<f32>(0.0), vec3<f32>(1.0)), 1.0);
+}
+
+const AGX_MIN_EV: f32 = -12.47393;
+const AGX_MAX_EV: f32 = 4.026069;
+
+@fragment
+fn fs_tonemap_agx(in: VertexOutput) -> @location(0) vec4<f32> {
+    let inset = mat3x3<f32>(
+        0.842479062253094, 0.0423282422610123, 0.0423756549057051,
+        0.0784335999999992, 0.878468636469772, 0.0784336,
+        0.0792237451477643, 0.0791661274605434, 0.879142973793104,
+    );
+    let outset = mat3x3<f32>(
+        1.19687900512017, -0.0528968517574562, -0.0529716355144438,
+        -0.0980208811401368, 1.15190312990417, -0.0980434501171241,
+        -0.0990297440797205, -0.0989611768448433, 1.15107367264116,
+    );
+    let log = clamp(log2(max(inset * exposed(in.uv), vec3<f32>(1e-10))), vec3<f32>(AGX_MIN_EV), vec3<f32>(AGX_MAX_EV));
+    let x = (log - AGX_MIN_EV) / (AGX_MAX_EV - AGX_MIN_EV);
+    let x2 = x * x;
+    let x4 = x2 * x2;
+    let curve = 15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2 + 0.1191 * x - 0.00232;
+    let color = pow(clamp(outset * curve, vec3<f32>(0.0), vec3<f32>(1.0)), vec3<f32>(2.2));
+    return vec4<f32>(color, 1.0);
+}
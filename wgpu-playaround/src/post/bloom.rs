@@ -0,0 +1,368 @@
+use bytemuck::{Pod, Zeroable};
+use glam::Vec3;
+
+use super::renderer::PostProcessor;
+
+/// Most mips in the chain, the first one is half the scene size.
+pub const BLOOM_MAX_MIPS: usize = 6;
+// Width of the soft knee around the threshold, as a fraction of it
+const BLOOM_KNEE: f32 = 0.5;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BloomSettings {
+    pub enabled: bool,
+    /// Brightness where colors start to bloom, in linear HDR units
+    pub threshold: f32,
+    /// Share of the bright light spread back over the scene
+    pub intensity: f32,
+    /// Tent filter size in texels of each mip
+    pub radius: f32,
+}
+
+impl Default for BloomSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 1.0,
+            intensity: 0.6,
+            radius: 1.0,
+        }
+    }
+}
+
+/// Part of `color` that blooms, the CPU reference for `threshold` in `bloom.wgsl`.
+///
+/// Colors fade in over a soft knee below `threshold` instead of popping in.
+pub fn bloom_threshold(color: Vec3, threshold: f32) -> Vec3 {
+    let brightness = color.max_element();
+    let knee = threshold * BLOOM_KNEE;
+    let soft = (brightness - threshold + knee).clamp(0.0, 2.0 * knee);
+    let soft = soft * soft / (4.0 * knee + 1e-5);
+    let contribution = soft.max(brightness - threshold) / brightness.max(1e-5);
+    color * contribution
+}
+
+/// Sizes of the mip chain for a scene of `width` by `height`, each half the previous one.
+pub fn mip_sizes(width: u32, height: u32) -> Vec<(u32, u32)> {
+    let mut sizes = Vec::new();
+    let (mut width, mut height) = (width, height);
+    while sizes.len() < BLOOM_MAX_MIPS && width > 1 && height > 1 {
+        width = (width / 2).max(1);
+        height = (height / 2).max(1);
+        sizes.push((width, height));
+    }
+    sizes
+}
+
+/// Laid out like `BloomParams` in `bloom.wgsl`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct BloomParams {
+    threshold: f32,
+    knee: f32,
+    radius: f32,
+    // Already divided by the number of mips
+    intensity: f32,
+}
+
+/// Bloom over the HDR scene target, blended back into it.
+///
+/// The bright parts of the scene are thresholded into the first mip and
+/// downsampled with a 13 tap filter through the chain. Going back up, every
+/// mip is tent filtered and added to the next bigger one, and the first mip
+/// is finally added to the scene.
+pub struct Bloom {
+    prefilter_pipeline: wgpu::RenderPipeline,
+    downsample_pipeline: wgpu::RenderPipeline,
+    upsample_pipeline: wgpu::RenderPipeline,
+    composite_pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    params_buffer: wgpu::Buffer,
+    mips: Vec<wgpu::TextureView>,
+    // Reading the scene, then each mip
+    scene_bind_group: wgpu::BindGroup,
+    mip_bind_groups: Vec<wgpu::BindGroup>,
+}
+
+impl Bloom {
+    pub fn new(device: &wgpu::Device, scene: &wgpu::TextureView, width: u32, height: u32) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Bloom Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../bloom.wgsl").into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Bloom Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Bloom Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            immediate_size: 0,
+        });
+        let additive = wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
+            },
+            alpha: wgpu::BlendComponent::REPLACE,
+        };
+        let create_pipeline = |entry_point: &'static str, blend: wgpu::BlendState| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(entry_point),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_main"),
+                    buffers: &[],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some(entry_point),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format: PostProcessor::FORMAT,
+                        blend: Some(blend),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview_mask: None,
+                cache: None,
+            })
+        };
+        let prefilter_pipeline = create_pipeline("fs_prefilter", wgpu::BlendState::REPLACE);
+        let downsample_pipeline = create_pipeline("fs_downsample", wgpu::BlendState::REPLACE);
+        let upsample_pipeline = create_pipeline("fs_upsample", additive);
+        let composite_pipeline = create_pipeline("fs_composite", additive);
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Bloom Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let params_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Bloom Params Buffer"),
+            size: std::mem::size_of::<BloomParams>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let scene_bind_group =
+            create_bind_group(device, &bind_group_layout, &sampler, &params_buffer, scene);
+
+        let mut bloom = Self {
+            prefilter_pipeline,
+            downsample_pipeline,
+            upsample_pipeline,
+            composite_pipeline,
+            bind_group_layout,
+            sampler,
+            params_buffer,
+            mips: Vec::new(),
+            scene_bind_group,
+            mip_bind_groups: Vec::new(),
+        };
+        bloom.resize(device, scene, width, height);
+        bloom
+    }
+
+    /// Recreates the mip chain for a new scene target.
+    pub fn resize(
+        &mut self,
+        device: &wgpu::Device,
+        scene: &wgpu::TextureView,
+        width: u32,
+        height: u32,
+    ) {
+        self.mips = mip_sizes(width, height)
+            .into_iter()
+            .map(|(width, height)| {
+                device
+                    .create_texture(&wgpu::TextureDescriptor {
+                        label: Some("Bloom Mip"),
+                        size: wgpu::Extent3d {
+                            width,
+                            height,
+                            depth_or_array_layers: 1,
+                        },
+                        mip_level_count: 1,
+                        sample_count: 1,
+                        dimension: wgpu::TextureDimension::D2,
+                        format: PostProcessor::FORMAT,
+                        usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                            | wgpu::TextureUsages::TEXTURE_BINDING,
+                        view_formats: &[],
+                    })
+                    .create_view(&wgpu::TextureViewDescriptor::default())
+            })
+            .collect();
+        self.scene_bind_group = create_bind_group(
+            device,
+            &self.bind_group_layout,
+            &self.sampler,
+            &self.params_buffer,
+            scene,
+        );
+        self.mip_bind_groups = self
+            .mips
+            .iter()
+            .map(|mip| {
+                create_bind_group(
+                    device,
+                    &self.bind_group_layout,
+                    &self.sampler,
+                    &self.params_buffer,
+                    mip,
+                )
+            })
+            .collect();
+    }
+
+    pub fn mip_count(&self) -> usize {
+        self.mips.len()
+    }
+
+    pub fn prepare(&self, queue: &wgpu::Queue, settings: &BloomSettings) {
+        let params = BloomParams {
+            threshold: settings.threshold,
+            knee: settings.threshold * BLOOM_KNEE,
+            radius: settings.radius,
+            intensity: settings.intensity / self.mips.len().max(1) as f32,
+        };
+        queue.write_buffer(&self.params_buffer, 0, bytemuck::bytes_of(&params));
+    }
+
+    /// Adds the bloom of `scene` back into it.
+    pub fn draw(&self, encoder: &mut wgpu::CommandEncoder, scene: &wgpu::TextureView) {
+        let Some(first) = self.mips.first() else {
+            return;
+        };
+        run_pass(
+            encoder,
+            first,
+            &self.prefilter_pipeline,
+            &self.scene_bind_group,
+            true,
+        );
+        for (index, target) in self.mips.iter().enumerate().skip(1) {
+            run_pass(
+                encoder,
+                target,
+                &self.downsample_pipeline,
+                &self.mip_bind_groups[index - 1],
+                true,
+            );
+        }
+        for index in (1..self.mips.len()).rev() {
+            run_pass(
+                encoder,
+                &self.mips[index - 1],
+                &self.upsample_pipeline,
+                &self.mip_bind_groups[index],
+                false,
+            );
+        }
+        run_pass(
+            encoder,
+            scene,
+            &self.composite_pipeline,
+            &self.mip_bind_groups[0],
+            false,
+        );
+    }
+}
+
+fn run_pass(
+    encoder: &mut wgpu::CommandEncoder,
+    target: &wgpu::TextureView,
+    pipeline: &wgpu::RenderPipeline,
+    bind_group: &wgpu::BindGroup,
+    clear: bool,
+) {
+    let load = if clear {
+        wgpu::LoadOp::Clear(wgpu::Color::BLACK)
+    } else {
+        wgpu::LoadOp::Load
+    };
+    let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Bloom Render Pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: target,
+            depth_slice: None,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load,
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        occlusion_query_set: None,
+        timestamp_writes: None,
+        multiview_mask: None,
+    });
+    render_pass.set_pipeline(pipeline);
+    render_pass.set_bind_group(0, bind_group, &[]);
+    render_pass.draw(0..3, 0..1);
+}
+
+fn create_bind_group(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    sampler: &wgpu::Sampler,
+    params_buffer: &wgpu::Buffer,
+    source: &wgpu::TextureView,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("Bloom Bind Group"),
+        layout,
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(source),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Sampler(sampler),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: params_buffer.as_entire_binding(),
+            },
+        ],
+    })
+}
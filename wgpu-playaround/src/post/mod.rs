@@ -0,0 +1,168 @@
+pub mod bloom;
+pub mod renderer;
+pub mod tonemap;
+
+use std::ops::RangeInclusive;
+
+use glam::Vec2;
+
+use self::{bloom::BloomSettings, tonemap::Tonemap};
+
+/// A fullscreen effect applied to the rendered scene.
+///
+/// Pixel sizes are in physical pixels of the render target.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Effect {
+    /// Fades colors to their luminance, fully at 1
+    Grayscale { amount: f32 },
+    /// Separable gaussian blur
+    Blur { radius: f32 },
+    /// Darkens the corners
+    Vignette { strength: f32 },
+    /// Splits red and blue away from the center
+    ChromaticAberration { offset: f32 },
+    /// Snaps the image to square blocks
+    Pixelate { size: f32 },
+    /// White Sobel edges on black
+    EdgeDetect { strength: f32 },
+}
+
+impl Effect {
+    /// Every effect with its default settings, in the order the panel offers them.
+    pub const ALL: [Effect; 6] = [
+        Effect::Grayscale { amount: 1.0 },
+        Effect::Blur { radius: 4.0 },
+        Effect::Vignette { strength: 0.6 },
+        Effect::ChromaticAberration { offset: 3.0 },
+        Effect::Pixelate { size: 6.0 },
+        Effect::EdgeDetect { strength: 1.0 },
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Effect::Grayscale { .. } => "Grayscale",
+            Effect::Blur { .. } => "Blur",
+            Effect::Vignette { .. } => "Vignette",
+            Effect::ChromaticAberration { .. } => "Chromatic",
+            Effect::Pixelate { .. } => "Pixelate",
+            Effect::EdgeDetect { .. } => "Edges",
+        }
+    }
+
+    /// Fragment entry point in `post.wgsl`.
+    pub fn entry_point(&self) -> &'static str {
+        match self {
+            Effect::Grayscale { .. } => "fs_grayscale",
+            Effect::Blur { .. } => "fs_blur",
+            Effect::Vignette { .. } => "fs_vignette",
+            Effect::ChromaticAberration { .. } => "fs_chromatic",
+            Effect::Pixelate { .. } => "fs_pixelate",
+            Effect::EdgeDetect { .. } => "fs_edges",
+        }
+    }
+
+    /// The setting of the effect with its name and a sensible range.
+    pub fn parameter_mut(&mut self) -> (&'static str, &mut f32, RangeInclusive<f32>) {
+        match self {
+            Effect::Grayscale { amount } => ("Amount", amount, 0.0..=1.0),
+            Effect::Blur { radius } => ("Radius", radius, 0.0..=16.0),
+            Effect::Vignette { strength } => ("Strength", strength, 0.0..=1.0),
+            Effect::ChromaticAberration { offset } => ("Offset", offset, 0.0..=12.0),
+            Effect::Pixelate { size } => ("Size", size, 1.0..=32.0),
+            Effect::EdgeDetect { strength } => ("Strength", strength, 0.0..=4.0),
+        }
+    }
+
+    fn amount(&self) -> f32 {
+        match *self {
+            Effect::Grayscale { amount } => amount,
+            Effect::Blur { radius } => radius,
+            Effect::Vignette { strength } => strength,
+            Effect::ChromaticAberration { offset } => offset,
+            Effect::Pixelate { size } => size,
+            Effect::EdgeDetect { strength } => strength,
+        }
+    }
+}
+
+/// One fullscreen draw of the chain.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PostPass {
+    pub entry_point: &'static str,
+    /// Sampling direction for separable effects, zero otherwise
+    pub direction: Vec2,
+    pub amount: f32,
+}
+
+/// Effects applied in order, each one reading what the previous one wrote.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EffectChain {
+    pub effects: Vec<Effect>,
+}
+
+impl EffectChain {
+    pub fn push(&mut self, effect: Effect) {
+        self.effects.push(effect);
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<Effect> {
+        (index < self.effects.len()).then(|| self.effects.remove(index))
+    }
+
+    /// Moves the effect at `index` by `offset` places, clamped to the chain.
+    /// Returns its new index.
+    pub fn shift(&mut self, index: usize, offset: i32) -> Option<usize> {
+        if index >= self.effects.len() {
+            return None;
+        }
+        let last = self.effects.len() as i32 - 1;
+        let target = (index as i32 + offset).clamp(0, last) as usize;
+        let effect = self.effects.remove(index);
+        self.effects.insert(target, effect);
+        Some(target)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.effects.len()
+    }
+
+    /// The draws that apply the chain, blurs take one per axis.
+    pub fn passes(&self) -> Vec<PostPass> {
+        let mut passes = Vec::new();
+        for effect in &self.effects {
+            let pass = PostPass {
+                entry_point: effect.entry_point(),
+                direction: Vec2::ZERO,
+                amount: effect.amount(),
+            };
+            match effect {
+                Effect::Blur { .. } => {
+                    passes.push(PostPass {
+                        direction: Vec2::X,
+                        ..pass
+                    });
+                    passes.push(PostPass {
+                        direction: Vec2::Y,
+                        ..pass
+                    });
+                }
+                _ => passes.push(pass),
+            }
+        }
+        passes
+    }
+}
+
+/// Everything [`renderer::PostProcessor`] does to the HDR scene, in order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PostSettings {
+    pub bloom: BloomSettings,
+    pub effects: EffectChain,
+    pub tonemap: Tonemap,
+    /// In stops
+    pub exposure: f32,
+}
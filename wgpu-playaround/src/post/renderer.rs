@@ -0,0 +1,354 @@
+use bytemuck::{Pod, Zeroable};
+
+use glam::Vec2;
+
+use super::{
+    Effect, PostPass, PostSettings,
+    bloom::Bloom,
+    tonemap::{Tonemap, exposure_scale},
+};
+
+/// Laid out like `PostParams` in `post.wgsl`.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct PostParams {
+    texel_size: [f32; 2],
+    direction: [f32; 2],
+    amount: f32,
+    _padding: [f32; 3],
+}
+
+/// Two color targets the scene and the effects ping-pong between.
+struct Targets {
+    views: [wgpu::TextureView; 2],
+    size: (u32, u32),
+}
+
+impl Targets {
+    fn new(device: &wgpu::Device, width: u32, height: u32) -> Self {
+        let views = std::array::from_fn(|_| {
+            device
+                .create_texture(&wgpu::TextureDescriptor {
+                    label: Some("Post Target"),
+                    size: wgpu::Extent3d {
+                        width,
+                        height,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: PostProcessor::FORMAT,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                        | wgpu::TextureUsages::TEXTURE_BINDING,
+                    view_formats: &[],
+                })
+                .create_view(&wgpu::TextureViewDescriptor::default())
+        });
+        Self {
+            views,
+            size: (width, height),
+        }
+    }
+}
+
+/// Blooms the HDR scene, runs an [`EffectChain`](super::EffectChain) over it
+/// and tonemaps it to the output.
+///
+/// The scene is drawn into [`PostProcessor::scene_view`] instead of the
+/// surface. Bloom is added to it in place. Each pass then reads one target
+/// and writes the other, except the tonemapping pass at the end which writes
+/// the output view. Per pass settings live in a single uniform buffer read at
+/// dynamic offsets.
+pub struct PostProcessor {
+    pipelines: Vec<(&'static str, wgpu::RenderPipeline)>,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    targets: Targets,
+    params_buffer: wgpu::Buffer,
+    params_stride: u32,
+    params_capacity: usize,
+    // One per target, reading it
+    bind_groups: [wgpu::BindGroup; 2],
+    passes: Vec<PostPass>,
+    bloom: Bloom,
+    bloom_enabled: bool,
+}
+
+impl PostProcessor {
+    /// Format of the scene and the targets between passes.
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+
+    /// `output_format` is the format of the view given to [`PostProcessor::draw`].
+    pub fn new(
+        device: &wgpu::Device,
+        output_format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Post Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../post.wgsl").into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Post Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        multisampled: false,
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: wgpu::BufferSize::new(
+                            std::mem::size_of::<PostParams>() as u64
+                        ),
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Post Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            immediate_size: 0,
+        });
+        let create_pipeline = |entry_point: &'static str, format: wgpu::TextureFormat| {
+            let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(entry_point),
+                layout: Some(&pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_main"),
+                    buffers: &[],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some(entry_point),
+                    targets: &[Some(wgpu::ColorTargetState {
+                        format,
+                        blend: Some(wgpu::BlendState::REPLACE),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    })],
+                    compilation_options: wgpu::PipelineCompilationOptions::default(),
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                multiview_mask: None,
+                cache: None,
+            });
+            (entry_point, pipeline)
+        };
+        let effects = Effect::ALL
+            .iter()
+            .map(|effect| create_pipeline(effect.entry_point(), Self::FORMAT));
+        let tonemaps = Tonemap::ALL
+            .iter()
+            .map(|tonemap| create_pipeline(tonemap.entry_point(), output_format));
+        let pipelines = effects.chain(tonemaps).collect();
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Post Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let alignment = device.limits().min_uniform_buffer_offset_alignment;
+        let params_stride = (std::mem::size_of::<PostParams>() as u32).next_multiple_of(alignment);
+        let params_capacity = 8;
+        let params_buffer = create_params_buffer(device, params_stride, params_capacity);
+        let (width, height) = (width.max(1), height.max(1));
+        let targets = Targets::new(device, width, height);
+        let bloom = Bloom::new(device, &targets.views[0], width, height);
+        let bind_groups = create_bind_groups(
+            device,
+            &bind_group_layout,
+            &sampler,
+            &targets,
+            &params_buffer,
+        );
+
+        Self {
+            pipelines,
+            bind_group_layout,
+            sampler,
+            targets,
+            params_buffer,
+            params_stride,
+            params_capacity,
+            bind_groups,
+            passes: Vec::new(),
+            bloom,
+            bloom_enabled: false,
+        }
+    }
+
+    /// Recreates the targets and the bloom mips, call it whenever the surface changes size.
+    pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
+        if (width, height) == self.targets.size || width == 0 || height == 0 {
+            return;
+        }
+        self.targets = Targets::new(device, width, height);
+        self.bloom
+            .resize(device, &self.targets.views[0], width, height);
+        self.rebuild_bind_groups(device);
+    }
+
+    /// Where the scene should be drawn for the chain to pick it up.
+    pub fn scene_view(&self) -> &wgpu::TextureView {
+        &self.targets.views[0]
+    }
+
+    pub fn bloom(&self) -> &Bloom {
+        &self.bloom
+    }
+
+    /// Uploads the settings of every pass.
+    pub fn prepare(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, settings: &PostSettings) {
+        self.bloom_enabled = settings.bloom.enabled;
+        if self.bloom_enabled {
+            self.bloom.prepare(queue, &settings.bloom);
+        }
+        self.passes = settings.effects.passes();
+        self.passes.push(PostPass {
+            entry_point: settings.tonemap.entry_point(),
+            direction: Vec2::ZERO,
+            amount: exposure_scale(settings.exposure),
+        });
+        if self.passes.len() > self.params_capacity {
+            self.params_capacity = self.passes.len().next_power_of_two();
+            self.params_buffer =
+                create_params_buffer(device, self.params_stride, self.params_capacity);
+            self.rebuild_bind_groups(device);
+        }
+        let (width, height) = self.targets.size;
+        let texel_size = [1.0 / width as f32, 1.0 / height as f32];
+        let mut bytes = vec![0u8; self.passes.len() * self.params_stride as usize];
+        for (pass, chunk) in self
+            .passes
+            .iter()
+            .zip(bytes.chunks_exact_mut(self.params_stride as usize))
+        {
+            let params = PostParams {
+                texel_size,
+                direction: pass.direction.to_array(),
+                amount: pass.amount,
+                _padding: [0.0; 3],
+            };
+            chunk[..std::mem::size_of::<PostParams>()].copy_from_slice(bytemuck::bytes_of(&params));
+        }
+        queue.write_buffer(&self.params_buffer, 0, &bytes);
+    }
+
+    /// Applies the prepared passes to the scene and writes the tonemapped result to `output`.
+    pub fn draw(&self, encoder: &mut wgpu::CommandEncoder, output: &wgpu::TextureView) {
+        if self.bloom_enabled {
+            self.bloom.draw(encoder, &self.targets.views[0]);
+        }
+        for (index, pass) in self.passes.iter().enumerate() {
+            let source = index % 2;
+            let target = if index + 1 == self.passes.len() {
+                output
+            } else {
+                &self.targets.views[1 - source]
+            };
+            let Some((_, pipeline)) = self.pipelines.iter().find(|(e, _)| *e == pass.entry_point)
+            else {
+                continue;
+            };
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Post Render Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: target,
+                    depth_slice: None,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+                timestamp_writes: None,
+                multiview_mask: None,
+            });
+            render_pass.set_pipeline(pipeline);
+            let offset = index as u32 * self.params_stride;
+            render_pass.set_bind_group(0, &self.bind_groups[source], &[offset]);
+            render_pass.draw(0..3, 0..1);
+        }
+    }
+
+    fn rebuild_bind_groups(&mut self, device: &wgpu::Device) {
+        self.bind_groups = create_bind_groups(
+            device,
+            &self.bind_group_layout,
+            &self.sampler,
+            &self.targets,
+            &self.params_buffer,
+        );
+    }
+}
+
+fn create_params_buffer(device: &wgpu::Device, stride: u32, passes: usize) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Post Params Buffer"),
+        size: stride as wgpu::BufferAddress * passes as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    })
+}
+
+fn create_bind_groups(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    sampler: &wgpu::Sampler,
+    targets: &Targets,
+    params_buffer: &wgpu::Buffer,
+) -> [wgpu::BindGroup; 2] {
+    std::array::from_fn(|source| {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Post Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&targets.views[source]),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: params_buffer,
+                        offset: 0,
+                        size: wgpu::BufferSize::new(std::mem::size_of::<PostParams>() as u64),
+                    }),
+                },
+            ],
+        })
+    })
+}
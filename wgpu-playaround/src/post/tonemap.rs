@@ -0,0 +1,113 @@
+use glam::{Mat3, Vec3};
+
+/// Maps linear HDR colors into the `[0, 1]` range of the surface.
+///
+/// [`Tonemap::apply`] is the CPU reference for the `fs_tonemap_*` entry
+/// points in `post.wgsl`. Both take and return linear colors, the sRGB
+/// surface does the encoding.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Tonemap {
+    /// Clips at 1, what an SDR target would do
+    Clamp,
+    /// `c / (1 + c)` per channel
+    Reinhard,
+    /// Krzysztof Narkowicz's fit of the ACES filmic curve
+    #[default]
+    Aces,
+    /// Troy Sobotka's AgX with the default look
+    AgX,
+}
+
+// AgX works on log2 exposure between these stops
+const AGX_MIN_EV: f32 = -12.47393;
+const AGX_MAX_EV: f32 = 4.026069;
+
+// Column major, like `mat3x3` in WGSL
+const AGX_INSET: Mat3 = Mat3::from_cols_array(&[
+    0.84247905,
+    0.042328242,
+    0.042375654,
+    0.0784336,
+    0.87846863,
+    0.0784336,
+    0.079223745,
+    0.07916613,
+    0.879143,
+]);
+const AGX_OUTSET: Mat3 = Mat3::from_cols_array(&[
+    1.196879,
+    -0.052896854,
+    -0.052971635,
+    -0.09802088,
+    1.1519032,
+    -0.09804345,
+    -0.09902974,
+    -0.098961174,
+    1.1510737,
+]);
+
+impl Tonemap {
+    pub const ALL: [Tonemap; 4] = [
+        Tonemap::Clamp,
+        Tonemap::Reinhard,
+        Tonemap::Aces,
+        Tonemap::AgX,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tonemap::Clamp => "Clamp",
+            Tonemap::Reinhard => "Reinhard",
+            Tonemap::Aces => "ACES",
+            Tonemap::AgX => "AgX",
+        }
+    }
+
+    /// Fragment entry point in `post.wgsl`.
+    pub fn entry_point(&self) -> &'static str {
+        match self {
+            Tonemap::Clamp => "fs_tonemap_clamp",
+            Tonemap::Reinhard => "fs_tonemap_reinhard",
+            Tonemap::Aces => "fs_tonemap_aces",
+            Tonemap::AgX => "fs_tonemap_agx",
+        }
+    }
+
+    /// Tonemaps `color` after scaling it by `exposure` stops.
+    pub fn apply(&self, color: Vec3, exposure: f32) -> Vec3 {
+        let color = color.max(Vec3::ZERO) * exposure_scale(exposure);
+        match self {
+            Tonemap::Clamp => color.min(Vec3::ONE),
+            Tonemap::Reinhard => color / (Vec3::ONE + color),
+            Tonemap::Aces => aces(color),
+            Tonemap::AgX => agx(color),
+        }
+    }
+}
+
+/// Factor colors are multiplied by for `exposure` stops.
+pub fn exposure_scale(exposure: f32) -> f32 {
+    exposure.exp2()
+}
+
+fn aces(x: Vec3) -> Vec3 {
+    let numerator = x * (2.51 * x + 0.03);
+    let denominator = x * (2.43 * x + 0.59) + 0.14;
+    (numerator / denominator).clamp(Vec3::ZERO, Vec3::ONE)
+}
+
+fn agx(color: Vec3) -> Vec3 {
+    let log = (AGX_INSET * color)
+        .max(Vec3::splat(1e-10))
+        .log2()
+        .clamp(Vec3::splat(AGX_MIN_EV), Vec3::splat(AGX_MAX_EV));
+    let x = (log - AGX_MIN_EV) / (AGX_MAX_EV - AGX_MIN_EV);
+    // Polynomial fit of the default contrast curve
+    let x2 = x * x;
+    let x4 = x2 * x2;
+    let curve =
+        15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2 + 0.1191 * x
+            - 0.00232;
+    // The curve is display encoded with a 2.2 gamma
+    (AGX_OUTSET * curve).clamp(Vec3::ZERO, Vec3::ONE).powf(2.2)
+}
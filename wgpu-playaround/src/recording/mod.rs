@@ -0,0 +1,227 @@
+use std::{fs::File, io::BufWriter, path::PathBuf};
+
+use anyhow::{Context, bail};
+
+use crate::{
+    document::file::SceneDescription,
+    environment::hdr::HdrImage,
+    models::{clock::Clock, headless::HeadlessRenderer},
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RecordingFormat {
+    /// `frame_00000.png`, `frame_00001.png`, ... inside the output directory
+    PngSequence,
+    /// A single looping animated GIF
+    Gif,
+}
+
+#[derive(Clone, Debug)]
+pub struct RecordingSettings {
+    pub seconds: f32,
+    pub fps: u32,
+    pub format: RecordingFormat,
+    pub output: PathBuf,
+    pub width: u32,
+    pub height: u32,
+    /// Toggle the shape every this many seconds so the recording shows the morph
+    pub toggle_every: Option<f32>,
+    /// Scene file to record instead of the default scene
+    pub scene: Option<PathBuf>,
+    /// Equirectangular `.hdr` panorama drawn behind the scene
+    pub environment: Option<PathBuf>,
+}
+
+impl Default for RecordingSettings {
+    fn default() -> Self {
+        Self {
+            seconds: 2.0,
+            fps: 30,
+            format: RecordingFormat::PngSequence,
+            output: PathBuf::from("recording"),
+            width: 800,
+            height: 600,
+            toggle_every: Some(1.0),
+            scene: None,
+            environment: None,
+        }
+    }
+}
+
+impl RecordingSettings {
+    /// Parses `--record SECONDS [--fps N] [--gif] [--out PATH] [--size WxH] [--toggle-every SECONDS] [--scene PATH] [--environment PATH]`.
+    ///
+    /// Returns `None` when `--record` is not present, i.e. the app should open
+    /// a window. The other arguments are only looked at when recording.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> anyhow::Result<Option<Self>> {
+        let args: Vec<String> = args.into_iter().collect();
+        if !args.iter().any(|arg| arg == "--record") {
+            return Ok(None);
+        }
+        let mut settings = Self::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .with_context(|| format!("missing value for {arg}"))
+            };
+            match arg.as_str() {
+                "--record" => settings.seconds = value()?.parse()?,
+                "--fps" => settings.fps = value()?.parse()?,
+                "--gif" => settings.format = RecordingFormat::Gif,
+                "--out" => settings.output = PathBuf::from(value()?),
+                "--size" => {
+                    let size = value()?;
+                    let (width, height) = size
+                        .split_once('x')
+                        .with_context(|| format!("expected WIDTHxHEIGHT, got {size}"))?;
+                    settings.width = width.parse()?;
+                    settings.height = height.parse()?;
+                }
+                "--toggle-every" => {
+                    let seconds: f32 = value()?.parse()?;
+                    settings.toggle_every = (seconds > 0.0).then_some(seconds);
+                }
+                "--scene" => settings.scene = Some(PathBuf::from(value()?)),
+                "--environment" => settings.environment = Some(PathBuf::from(value()?)),
+                _ => bail!("unknown argument {arg}"),
+            }
+        }
+
+        if !(settings.seconds.is_finite() && settings.seconds > 0.0) {
+            bail!("--record needs a positive number of seconds, got {}", settings.seconds);
+        }
+        if settings.fps == 0 {
+            bail!("--fps must be at least 1");
+        }
+        if settings.width == 0 || settings.height == 0 {
+            bail!("--size must not be empty, got {}x{}", settings.width, settings.height);
+        }
+        Ok(Some(settings))
+    }
+
+    pub fn frame_count(&self) -> u64 {
+        (self.seconds * self.fps as f32).round() as u64
+    }
+}
+
+enum FrameSink {
+    Png(PathBuf),
+    Gif(gif::Encoder<BufWriter<File>>),
+}
+
+impl FrameSink {
+    fn new(settings: &RecordingSettings) -> anyhow::Result<Self> {
+        match settings.format {
+            RecordingFormat::PngSequence => {
+                std::fs::create_dir_all(&settings.output)?;
+                Ok(FrameSink::Png(settings.output.clone()))
+            }
+            RecordingFormat::Gif => {
+                let file = BufWriter::new(File::create(&settings.output)?);
+                let mut encoder =
+                    gif::Encoder::new(file, settings.width as u16, settings.height as u16, &[])?;
+                encoder.set_repeat(gif::Repeat::Infinite)?;
+                Ok(FrameSink::Gif(encoder))
+            }
+        }
+    }
+
+    fn write(
+        &mut self,
+        index: u64,
+        width: u32,
+        height: u32,
+        mut rgba: Vec<u8>,
+        fps: u32,
+    ) -> anyhow::Result<()> {
+        match self {
+            FrameSink::Png(dir) => write_png(
+                &dir.join(format!("frame_{index:05}.png")),
+                width,
+                height,
+                &rgba,
+            ),
+            FrameSink::Gif(encoder) => {
+                let mut frame =
+                    gif::Frame::from_rgba_speed(width as u16, height as u16, &mut rgba, 10);
+                frame.delay = gif_delay(index, fps);
+                encoder.write_frame(&frame)?;
+                Ok(())
+            }
+        }
+    }
+}
+
+/// How long frame `index` stays up in a GIF, in the hundredths of a second
+/// GIF delays are counted in. Rounding is carried over to the next frame, so
+/// at 30 fps every second still adds up to 100 instead of drifting to 99.
+pub fn gif_delay(index: u64, fps: u32) -> u16 {
+    let shown_until = |frame: u64| (frame * 100 + fps as u64 / 2) / fps.max(1) as u64;
+    (shown_until(index + 1) - shown_until(index)) as u16
+}
+
+pub fn write_png(
+    path: &std::path::Path,
+    width: u32,
+    height: u32,
+    rgba: &[u8],
+) -> anyhow::Result<()> {
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(rgba)?;
+    Ok(())
+}
+
+/// Renders `settings.seconds` worth of frames and writes them out.
+///
+/// Animation time comes from a fixed-step clock, so the output only depends
+/// on the settings and not on how fast frames can be rendered.
+pub fn record(renderer: &mut HeadlessRenderer, settings: &RecordingSettings) -> anyhow::Result<()> {
+    if settings.format == RecordingFormat::Gif
+        && (settings.width > u16::MAX as u32 || settings.height > u16::MAX as u32)
+    {
+        bail!("GIF frames are limited to {}x{}", u16::MAX, u16::MAX);
+    }
+
+    if let Some(path) = &settings.scene {
+        renderer.scene.set_description(SceneDescription::load(path)?);
+    }
+    if let Some(path) = &settings.environment {
+        renderer.load_environment(&HdrImage::load(path)?);
+    }
+
+    let (width, height) = renderer.size();
+    let mut sink = FrameSink::new(settings)?;
+    let mut clock = Clock::fixed(settings.fps);
+    let mut toggles = 0;
+
+    for index in 0..settings.frame_count() {
+        let dt = if index == 0 { 0.0 } else { clock.tick() };
+
+        if let Some(interval) = settings.toggle_every {
+            let due = (clock.elapsed() / interval) as u64;
+            while toggles < due {
+                renderer.scene.toggle_shape();
+                toggles += 1;
+            }
+        }
+
+        renderer.update(dt);
+        let pixels = renderer.render_frame()?;
+        sink.write(index, width, height, pixels, settings.fps)?;
+    }
+
+    log::info!(
+        "Recorded {} frames to {}",
+        settings.frame_count(),
+        settings.output.display()
+    );
+
+    Ok(())
+}
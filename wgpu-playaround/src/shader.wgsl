@@ -1,3 +1,10 @@
+struct CameraUniform {
+    view_proj: mat4x4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> camera: CameraUniform;
+
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) color: vec3<f32>
@@ -8,6 +15,13 @@ struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     // @location(0) vert_pos: vec3<f32>,
     @location(0) color: vec3<f32>,
+    // Object id for the ID buffer, the draw call passes it as the instance index
+    @location(1) @interpolate(flat) object_id: u32,
+};
+
+struct IdFragmentOutput {
+    @location(0) color: vec4<f32>,
+    @location(1) object_id: u32,
 };
 
 
@@ -17,13 +31,24 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     return vec4<f32>(in.color, 1.0);
 }
 
+@fragment
+fn fs_main_id(in: VertexOutput) -> IdFragmentOutput {
+    var out: IdFragmentOutput;
+    out.color = vec4<f32>(in.color, 1.0);
+    // 0 is left for the cleared background
+    out.object_id = in.object_id + 1u;
+    return out;
+}
+
 @vertex
 fn vs_main(
     model: VertexInput,
+    @builtin(instance_index) instance: u32,
 ) -> VertexOutput{
     var out: VertexOutput;
     out.color = model.color;
-    out.clip_position = vec4<f32>(model.position, 1.0);
+    out.clip_position = camera.view_proj * vec4<f32>(model.position, 1.0);
+    out.object_id = instance;
 
     return out;
 }
@@ -52,13 +77,15 @@ fn vs_main(
 @vertex
 fn vs_solid(
     model: VertexInput,
+    @builtin(instance_index) instance: u32,
 ) -> VertexOutput {
     var out: VertexOutput;
     // Use the actual vertex position from the buffer
-    out.clip_position = vec4<f32>(model.position, 1.0);
+    out.clip_position = camera.view_proj * vec4<f32>(model.position, 1.0);
 
     // Solid red color for all vertices (ignoring vertex color)
     out.color = vec3<f32>(1.0, 0.0, 0.0);
+    out.object_id = instance;
 
     return out;
 }
@@ -85,9 +112,11 @@ fn vs_colored(
 @vertex
 fn vs_colored(
     model: VertexInput,
+    @builtin(instance_index) instance: u32,
 ) -> VertexOutput {
     var out: VertexOutput;
-    out.clip_position = vec4<f32>(model.position, 1.0);
+    out.clip_position = camera.view_proj * vec4<f32>(model.position, 1.0);
+    out.object_id = instance;
 
     out.color = vec3<f32>(
         (model.position.x + 0.5),
@@ -0,0 +1,13 @@
+// Depth-only pass of the scene from a shadow-casting light.
+
+struct ShadowLayer {
+    view_proj: mat4x4<f32>,
+};
+
+@group(0) @binding(0)
+var<uniform> layer: ShadowLayer;
+
+@vertex
+fn vs_shadow(@location(0) position: vec3<f32>) -> @builtin(position) vec4<f32> {
+    return layer.view_proj * vec4<f32>(position, 1.0);
+}
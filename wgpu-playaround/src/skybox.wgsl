@@ -0,0 +1,33 @@
+// The environment cubemap behind the scene, seen from the sky view
+struct Sky {
+    // NDC back to view directions
+    inverse_view_proj: mat4x4<f32>,
+};
+@group(0) @binding(0)
+var<uniform> sky: Sky;
+@group(0) @binding(1)
+var sky_texture: texture_cube<f32>;
+@group(0) @binding(2)
+var sky_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) ndc: vec2<f32>,
+};
+
+// One triangle covering the screen
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let position = vec2<f32>(f32(index & 1u) * 4.0 - 1.0, f32(index >> 1u) * 4.0 - 1.0);
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(position, 0.0, 1.0);
+    out.ndc = position;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let far = sky.inverse_view_proj * vec4<f32>(in.ndc, 1.0, 1.0);
+    let direction = far.xyz / far.w;
+    return vec4<f32>(textureSampleLevel(sky_texture, sky_sampler, direction, 0.0).rgb, 1.0);
+}
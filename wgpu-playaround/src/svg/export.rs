@@ -0,0 +1,170 @@
+use std::fmt::Write;
+
+use glam::{Vec2, Vec3};
+
+use crate::{color::linear_to_srgb, models::vertex::Vertex};
+
+/// How vertex colors, which blend across each triangle, end up in the SVG.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum SvgColorMode {
+    /// Every triangle gets the average of its vertex colors
+    #[default]
+    Flat,
+    /// Triangles with differing vertex colors get a linear gradient along the
+    /// direction the colors change most, with the average as fallback
+    Gradient,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SvgExportOptions {
+    pub colors: SvgColorMode,
+    /// SVG user units per world unit
+    pub pixels_per_unit: f32,
+}
+
+impl Default for SvgExportOptions {
+    fn default() -> Self {
+        Self {
+            colors: SvgColorMode::Flat,
+            pixels_per_unit: 400.0,
+        }
+    }
+}
+
+fn hex(color: Vec3) -> String {
+    let [r, g, b] = color
+        .to_array()
+        .map(|c| (linear_to_srgb(c) * 255.0).round() as u8);
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// Rounds to a thousandth of a user unit, which keeps the files short.
+fn coordinate(value: f32) -> f32 {
+    (value * 1000.0).round() / 1000.0
+}
+
+/// Linear gradient approximating the vertex colors of a triangle, as the
+/// start and end points and colors, or `None` when one color is close enough.
+fn triangle_gradient(points: [Vec2; 3], colors: [Vec3; 3]) -> Option<[(Vec2, Vec3); 2]> {
+    let e1 = points[1] - points[0];
+    let e2 = points[2] - points[0];
+    let det = e1.perp_dot(e2);
+    if det.abs() <= f32::EPSILON {
+        return None;
+    }
+
+    // Colors are affine over the triangle, find each channel's gradient
+    let dc1 = colors[1] - colors[0];
+    let dc2 = colors[2] - colors[0];
+    let gradients = [0, 1, 2]
+        .map(|k| Vec2::new(e2.y * dc1[k] - e1.y * dc2[k], e1.x * dc2[k] - e2.x * dc1[k]) / det);
+
+    // The direction most channels change along is the main axis of their gradients
+    let (mut a, mut b, mut c) = (0.0, 0.0, 0.0);
+    for g in gradients {
+        a += g.x * g.x;
+        b += g.x * g.y;
+        c += g.y * g.y;
+    }
+    if a + c <= 1e-12 {
+        return None;
+    }
+    let direction = Vec2::from_angle(0.5 * (2.0 * b).atan2(a - c));
+
+    let centroid = (points[0] + points[1] + points[2]) / 3.0;
+    let average = (colors[0] + colors[1] + colors[2]) / 3.0;
+    let along = points.map(|p| (p - centroid).dot(direction));
+    let start = along.into_iter().fold(f32::MAX, f32::min);
+    let end = along.into_iter().fold(f32::MIN, f32::max);
+    let change = Vec3::from_array(gradients.map(|g| g.dot(direction)));
+    if ((end - start) * change).abs().max_element() < 0.5 / 255.0 {
+        return None;
+    }
+
+    let stop = |t: f32| {
+        (
+            centroid + direction * t,
+            (average + change * t).clamp(Vec3::ZERO, Vec3::ONE),
+        )
+    };
+    Some([stop(start), stop(end)])
+}
+
+/// Writes triangle meshes as SVG polygons, one per triangle and one group per mesh.
+///
+/// `bounds` are the lower left and upper right world corners of the view box,
+/// usually what the camera shows. Shapes outside of it are still written.
+/// Importing the result with [`super::import::SvgImportOptions::size`] set to
+/// the larger side of `bounds` puts the shapes back where they were, provided
+/// `bounds` are centered on the origin.
+pub fn export_svg(
+    meshes: &[(Vec<Vertex>, Vec<u16>)],
+    bounds: (Vec2, Vec2),
+    options: &SvgExportOptions,
+) -> String {
+    let (min, max) = bounds;
+    let scale = options.pixels_per_unit;
+    let size = (max - min) * scale;
+    // SVG's y axis points down
+    let to_svg = |p: [f32; 3]| Vec2::new((p[0] - min.x) * scale, (max.y - p[1]) * scale);
+
+    let mut defs = String::new();
+    let mut body = String::new();
+    let mut gradient_count = 0;
+    for (index, (vertices, indices)) in meshes.iter().enumerate() {
+        let _ = writeln!(body, "  <g id=\"shape-{index}\">");
+        for triangle in indices.chunks_exact(3) {
+            let corners = [0, 1, 2].map(|k| vertices[triangle[k] as usize]);
+            let points = corners.map(|v| to_svg(v.position));
+            let colors = corners.map(|v| Vec3::from_array(v.color));
+            let average = hex((colors[0] + colors[1] + colors[2]) / 3.0);
+
+            let gradient = match options.colors {
+                SvgColorMode::Flat => None,
+                SvgColorMode::Gradient => triangle_gradient(points, colors),
+            };
+            let fill = match gradient {
+                Some([(from, from_color), (to, to_color)]) => {
+                    let id = format!("gradient-{gradient_count}");
+                    gradient_count += 1;
+                    let _ = writeln!(
+                        defs,
+                        "    <linearGradient id=\"{id}\" gradientUnits=\"userSpaceOnUse\" \
+                         x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\">\
+                         <stop offset=\"0\" stop-color=\"{}\"/>\
+                         <stop offset=\"1\" stop-color=\"{}\"/></linearGradient>",
+                        coordinate(from.x),
+                        coordinate(from.y),
+                        coordinate(to.x),
+                        coordinate(to.y),
+                        hex(from_color),
+                        hex(to_color),
+                    );
+                    format!("url(#{id}) {average}")
+                }
+                None => average,
+            };
+
+            let points = points
+                .map(|p| format!("{},{}", coordinate(p.x), coordinate(p.y)))
+                .join(" ");
+            let _ = writeln!(body, "    <polygon points=\"{points}\" fill=\"{fill}\"/>");
+        }
+        let _ = writeln!(body, "  </g>");
+    }
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+         viewBox=\"0 0 {w} {h}\">\n",
+        w = coordinate(size.x),
+        h = coordinate(size.y),
+    );
+    if !defs.is_empty() {
+        svg.push_str("  <defs>\n");
+        svg.push_str(&defs);
+        svg.push_str("  </defs>\n");
+    }
+    svg.push_str(&body);
+    svg.push_str("</svg>\n");
+    svg
+}
@@ -0,0 +1,305 @@
+use std::borrow::Cow;
+
+use anyhow::{Context, bail};
+use glam::{DAffine2, DVec2, Vec2};
+use roxmltree::Node;
+use svgtypes::{Paint, PointsParser, SimplePathSegment, SimplifyingPathParser};
+
+use crate::{
+    color::srgb_to_linear,
+    document::{ShapeGeometry, ShapeNode},
+    geometry::{
+        fill::{FillRule, fill_contours},
+        flatten::{flatten_cubic, flatten_quadratic},
+        mesh_builder::merge_meshes,
+        stroke::{LineCap, LineJoin, StrokeStyle, stroke_polyline},
+    },
+};
+
+/// Elements whose children are never drawn directly.
+const NON_RENDERED: &[&str] = &["defs", "clipPath", "mask", "marker", "pattern", "symbol"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SvgImportOptions {
+    /// Largest distance between a curve and the line segments replacing it, in world units
+    pub tolerance: f32,
+    /// World size of the longer side of the SVG's view box, which is centered on the origin
+    pub size: f32,
+}
+
+impl Default for SvgImportOptions {
+    fn default() -> Self {
+        Self {
+            tolerance: 0.001,
+            size: 1.6,
+        }
+    }
+}
+
+/// One subpath, already transformed into world space and flattened.
+struct Subpath {
+    points: Vec<Vec2>,
+    closed: bool,
+}
+
+/// Looks `name` up in the `style` attribute and then as a presentation
+/// attribute, walking up to the ancestors for inherited properties.
+fn property<'a>(node: Node<'a, 'a>, name: &str) -> Option<&'a str> {
+    for element in node.ancestors().filter(Node::is_element) {
+        let from_style = element.attribute("style").and_then(|style| {
+            style.split(';').find_map(|declaration| {
+                let (key, value) = declaration.split_once(':')?;
+                (key.trim() == name).then(|| value.trim())
+            })
+        });
+        match from_style.or_else(|| element.attribute(name)) {
+            Some("inherit") | None => continue,
+            Some(value) => return Some(value),
+        }
+    }
+    None
+}
+
+/// Paint color as linear RGB, `None` for `none` or unsupported paints.
+fn paint(node: Node, name: &str, default: Option<[f32; 3]>) -> Option<[f32; 3]> {
+    let Some(value) = property(node, name) else {
+        return default;
+    };
+    let color = match Paint::from_str(value) {
+        Ok(Paint::Color(color)) => color,
+        Ok(Paint::CurrentColor) => property(node, "color")
+            .and_then(|color| color.parse::<svgtypes::Color>().ok())
+            .unwrap_or_else(svgtypes::Color::black),
+        Ok(Paint::FuncIRI(_, Some(svgtypes::PaintFallback::Color(color)))) => color,
+        Ok(Paint::None) => return None,
+        _ => {
+            log::warn!("Unsupported {name} \"{value}\", leaving it out");
+            return None;
+        }
+    };
+    Some([color.red, color.green, color.blue].map(|c| srgb_to_linear(c as f32 / 255.0)))
+}
+
+fn number(node: Node, name: &str, default: f32) -> f32 {
+    property(node, name)
+        .and_then(|value| value.parse::<svgtypes::Length>().ok())
+        .map_or(default, |length| length.number as f32)
+}
+
+fn transform(node: Node) -> DAffine2 {
+    node.attribute("transform")
+        .and_then(|value| value.parse::<svgtypes::Transform>().ok())
+        .map_or(DAffine2::IDENTITY, |t| {
+            DAffine2::from_cols_array(&[t.a, t.b, t.c, t.d, t.e, t.f])
+        })
+}
+
+/// Maps the view box onto a `size` square around the origin, flipping y up.
+fn view_transform(root: Node, size: f32) -> anyhow::Result<DAffine2> {
+    let view_box = match root.attribute("viewBox") {
+        Some(value) => value
+            .parse::<svgtypes::ViewBox>()
+            .context("invalid viewBox")?,
+        None => {
+            let length = |name| {
+                root.attribute(name)
+                    .and_then(|value| value.parse::<svgtypes::Length>().ok())
+                    .map(|length| length.number)
+            };
+            match (length("width"), length("height")) {
+                (Some(w), Some(h)) => svgtypes::ViewBox::new(0.0, 0.0, w, h),
+                _ => bail!("the svg element needs a viewBox or a width and height"),
+            }
+        }
+    };
+    if view_box.w <= 0.0 || view_box.h <= 0.0 {
+        bail!("the view box is empty");
+    }
+
+    let scale = size as f64 / view_box.w.max(view_box.h);
+    let center = DVec2::new(view_box.x + view_box.w / 2.0, view_box.y + view_box.h / 2.0);
+    Ok(DAffine2::from_scale(DVec2::new(scale, -scale)) * DAffine2::from_translation(-center))
+}
+
+/// Path data of a `path`, `polygon` or `polyline` element.
+fn path_data<'a>(node: Node<'a, 'a>) -> Option<Cow<'a, str>> {
+    match node.tag_name().name() {
+        "path" => node.attribute("d").map(Cow::Borrowed),
+        name @ ("polygon" | "polyline") => {
+            let mut data = String::new();
+            for (i, (x, y)) in PointsParser::from(node.attribute("points")?).enumerate() {
+                let command = if i == 0 { 'M' } else { 'L' };
+                data.push_str(&format!("{command} {x} {y} "));
+            }
+            if name == "polygon" {
+                data.push('Z');
+            }
+            Some(Cow::Owned(data))
+        }
+        _ => None,
+    }
+}
+
+/// Parses path data into flattened world space subpaths. Drawing stops at the
+/// first error, like browsers do.
+fn subpaths(data: &str, to_world: DAffine2, tolerance: f32) -> Vec<Subpath> {
+    let mut subpaths = Vec::new();
+    let mut current: Vec<Vec2> = Vec::new();
+    let mut start = Vec2::ZERO;
+    let point = |x: f64, y: f64| to_world.transform_point2(DVec2::new(x, y)).as_vec2();
+
+    for segment in SimplifyingPathParser::from(data) {
+        let segment = match segment {
+            Ok(segment) => segment,
+            Err(e) => {
+                log::warn!("Invalid path data, ignoring the rest: {e}");
+                break;
+            }
+        };
+        // Drawing after a close continues from where the closed subpath started
+        if current.is_empty() && !matches!(segment, SimplePathSegment::MoveTo { .. }) {
+            current.push(start);
+        }
+        let last = current.last().copied().unwrap_or(start);
+
+        match segment {
+            SimplePathSegment::MoveTo { x, y } => {
+                if current.len() > 1 {
+                    subpaths.push(Subpath {
+                        points: std::mem::take(&mut current),
+                        closed: false,
+                    });
+                }
+                start = point(x, y);
+                current = vec![start];
+            }
+            SimplePathSegment::LineTo { x, y } => current.push(point(x, y)),
+            SimplePathSegment::Quadratic { x1, y1, x, y } => {
+                flatten_quadratic(last, point(x1, y1), point(x, y), tolerance, &mut current);
+            }
+            SimplePathSegment::CurveTo {
+                x1,
+                y1,
+                x2,
+                y2,
+                x,
+                y,
+            } => {
+                flatten_cubic(
+                    last,
+                    point(x1, y1),
+                    point(x2, y2),
+                    point(x, y),
+                    tolerance,
+                    &mut current,
+                );
+            }
+            SimplePathSegment::ClosePath => {
+                subpaths.push(Subpath {
+                    points: std::mem::take(&mut current),
+                    closed: true,
+                });
+            }
+        }
+    }
+    if current.len() > 1 {
+        subpaths.push(Subpath {
+            points: current,
+            closed: false,
+        });
+    }
+    subpaths
+}
+
+fn stroke_style(node: Node, color: [f32; 3], width: f32, tolerance: f32) -> StrokeStyle {
+    StrokeStyle {
+        width,
+        join: match property(node, "stroke-linejoin") {
+            Some("round") => LineJoin::Round,
+            Some("bevel") => LineJoin::Bevel,
+            _ => LineJoin::Miter,
+        },
+        cap: match property(node, "stroke-linecap") {
+            Some("round") => LineCap::Round,
+            Some("square") => LineCap::Square,
+            _ => LineCap::Butt,
+        },
+        miter_limit: number(node, "stroke-miterlimit", 4.0),
+        color,
+        tolerance,
+    }
+}
+
+/// Imports the `path`, `polygon` and `polyline` elements of an SVG document
+/// as triangle meshes.
+///
+/// Every path becomes up to two shapes in world space: its fill, using the
+/// path's `fill-rule`, and its stroke on top. Fill and stroke colors,
+/// `stroke-width`, joins, caps, the miter limit and `transform`s are
+/// honored, including values inherited from groups or set in `style`
+/// attributes. Gradients, opacity and other elements are not supported.
+pub fn import_svg(text: &str, options: &SvgImportOptions) -> anyhow::Result<Vec<ShapeNode>> {
+    let svg = roxmltree::Document::parse(text).context("invalid SVG")?;
+    let root = svg.root_element();
+    if root.tag_name().name() != "svg" {
+        bail!("expected an svg element, found {}", root.tag_name().name());
+    }
+    let view = view_transform(root, options.size)?;
+
+    let mut shapes = Vec::new();
+    for node in root.descendants().filter(Node::is_element) {
+        if node
+            .ancestors()
+            .any(|a| NON_RENDERED.contains(&a.tag_name().name()))
+        {
+            continue;
+        }
+        let Some(data) = path_data(node) else {
+            continue;
+        };
+
+        let to_world = node
+            .ancestors()
+            .filter(Node::is_element)
+            .fold(DAffine2::IDENTITY, |inner, element| {
+                transform(element) * inner
+            });
+        let to_world = view * to_world;
+        let subpaths = subpaths(&data, to_world, options.tolerance);
+        if subpaths.is_empty() {
+            continue;
+        }
+
+        if let Some(color) = paint(node, "fill", Some([0.0; 3])) {
+            let rule = match property(node, "fill-rule") {
+                Some("evenodd") => FillRule::EvenOdd,
+                _ => FillRule::NonZero,
+            };
+            let contours: Vec<Vec<Vec2>> = subpaths.iter().map(|s| s.points.clone()).collect();
+            let (vertices, indices) = fill_contours(&contours, rule, color)
+                .with_context(|| format!("filling path {}", node.attribute("id").unwrap_or("")))?;
+            if !indices.is_empty() {
+                shapes.push(ShapeNode::new(ShapeGeometry::Mesh { vertices, indices }));
+            }
+        }
+
+        // Non-uniform scales stretch the stroke, the average scale keeps it close
+        let scale = to_world.matrix2.determinant().abs().sqrt() as f32;
+        let width = number(node, "stroke-width", 1.0) * scale;
+        if let Some(color) = paint(node, "stroke", None)
+            && width > 0.0
+        {
+            let style = stroke_style(node, color, width, options.tolerance);
+            let strokes = subpaths
+                .iter()
+                .map(|s| stroke_polyline(&s.points, s.closed, &style))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            let (vertices, indices) = merge_meshes(strokes)?;
+            if !indices.is_empty() {
+                shapes.push(ShapeNode::new(ShapeGeometry::Mesh { vertices, indices }));
+            }
+        }
+    }
+
+    Ok(shapes)
+}
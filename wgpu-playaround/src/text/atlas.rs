@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+
+use ab_glyph::{Font as _, GlyphId, point};
+use glam::{UVec2, Vec2};
+
+use crate::text::font::Font;
+
+/// Empty pixels around every glyph, so linear filtering never bleeds into neighbours.
+const PADDING: u32 = 1;
+
+/// Where a rasterized glyph lives in the atlas.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AtlasGlyph {
+    /// Top left corner in atlas pixels
+    pub origin: UVec2,
+    pub size: UVec2,
+    /// Top left of the bitmap relative to the pen position on the baseline
+    pub offset: Vec2,
+}
+
+/// Row of glyphs with the height of its tallest one.
+struct Shelf {
+    y: u32,
+    height: u32,
+    next_x: u32,
+}
+
+/// Square coverage bitmap that glyphs are rasterized into on first use.
+///
+/// Glyphs are packed onto shelves. When the atlas is full it doubles its size
+/// up to `max_size`, keeping everything where it is. Past that it starts over
+/// empty, which invalidates every glyph handed out before.
+pub struct GlyphAtlas {
+    size: u32,
+    max_size: u32,
+    pixels: Vec<u8>,
+    shelves: Vec<Shelf>,
+    // None for glyphs without pixels, like spaces
+    glyphs: HashMap<(GlyphId, u32), Option<AtlasGlyph>>,
+    dirty: bool,
+    generation: u32,
+}
+
+impl GlyphAtlas {
+    pub fn new(size: u32, max_size: u32) -> Self {
+        Self {
+            size,
+            max_size: max_size.max(size),
+            pixels: vec![0; (size * size) as usize],
+            shelves: Vec::new(),
+            glyphs: HashMap::new(),
+            dirty: true,
+            generation: 0,
+        }
+    }
+
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// One byte of coverage per pixel, row by row.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// Whether pixels changed since the last `mark_clean`.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    pub fn mark_clean(&mut self) {
+        self.dirty = false;
+    }
+
+    /// Counts how often the atlas was cleared, glyphs from older generations are gone.
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    pub fn glyph_count(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    /// Looks the glyph up, rasterizing it at `size` pixels per em if needed.
+    pub fn glyph(&mut self, font: &Font, id: GlyphId, size: f32) -> Option<AtlasGlyph> {
+        let key = (id, size.to_bits());
+        if let Some(&glyph) = self.glyphs.get(&key) {
+            return glyph;
+        }
+
+        let outlined = font
+            .inner()
+            .outline_glyph(id.with_scale_and_position(font.scale(size), point(0.0, 0.0)));
+        let glyph = outlined.and_then(|outlined| {
+            let bounds = outlined.px_bounds();
+            let glyph_size = UVec2::new(bounds.width() as u32, bounds.height() as u32);
+            if glyph_size.min_element() == 0 {
+                return None;
+            }
+            let origin = self.allocate(glyph_size)?;
+            let stride = self.size as usize;
+            outlined.draw(|x, y, coverage| {
+                let index = (origin.y + y) as usize * stride + (origin.x + x) as usize;
+                self.pixels[index] = (coverage.clamp(0.0, 1.0) * 255.0).round() as u8;
+            });
+            self.dirty = true;
+            Some(AtlasGlyph {
+                origin,
+                size: glyph_size,
+                offset: Vec2::new(bounds.min.x, bounds.min.y),
+            })
+        });
+        self.glyphs.insert(key, glyph);
+        glyph
+    }
+
+    /// Finds room for a `size` bitmap, growing or clearing the atlas when full.
+    fn allocate(&mut self, size: UVec2) -> Option<UVec2> {
+        let padded = size + PADDING;
+        if padded.max_element() + PADDING > self.max_size {
+            log::warn!("Glyph of {size} pixels does not fit into the atlas");
+            return None;
+        }
+        loop {
+            if let Some(origin) = self.allocate_on_shelf(padded) {
+                return Some(origin);
+            }
+            if self.size < self.max_size {
+                self.grow();
+            } else {
+                self.clear();
+            }
+        }
+    }
+
+    fn allocate_on_shelf(&mut self, padded: UVec2) -> Option<UVec2> {
+        // Smallest shelf that is tall enough and still has room
+        let shelf = self
+            .shelves
+            .iter_mut()
+            .filter(|s| s.height >= padded.y && s.next_x + padded.x <= self.size)
+            .min_by_key(|s| s.height);
+        if let Some(shelf) = shelf {
+            let origin = UVec2::new(shelf.next_x, shelf.y);
+            shelf.next_x += padded.x;
+            return Some(origin);
+        }
+
+        let y = self.shelves.last().map_or(PADDING, |s| s.y + s.height);
+        if y + padded.y > self.size || PADDING + padded.x > self.size {
+            return None;
+        }
+        self.shelves.push(Shelf {
+            y,
+            height: padded.y,
+            next_x: PADDING + padded.x,
+        });
+        Some(UVec2::new(PADDING, y))
+    }
+
+    fn grow(&mut self) {
+        let new_size = (self.size * 2).min(self.max_size);
+        let mut pixels = vec![0; (new_size * new_size) as usize];
+        for (row, old) in self.pixels.chunks_exact(self.size as usize).enumerate() {
+            let start = row * new_size as usize;
+            pixels[start..start + old.len()].copy_from_slice(old);
+        }
+        self.pixels = pixels;
+        self.size = new_size;
+        self.dirty = true;
+    }
+
+    pub fn clear(&mut self) {
+        self.pixels.fill(0);
+        self.shelves.clear();
+        self.glyphs.clear();
+        self.dirty = true;
+        self.generation += 1;
+    }
+}
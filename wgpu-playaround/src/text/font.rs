@@ -0,0 +1,79 @@
+use std::path::Path;
+
+use ab_glyph::{Font as _, FontArc, GlyphId, PxScale, ScaleFont};
+use anyhow::Context;
+
+/// DejaVu Sans, see `assets/fonts/DejaVuSans-LICENSE.txt`.
+pub const DEFAULT_FONT: &[u8] = include_bytes!("../../assets/fonts/DejaVuSans.ttf");
+
+/// Vertical metrics in pixels at one font size.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineMetrics {
+    /// Distance from the top of a line to the baseline
+    pub ascent: f32,
+    /// Distance from the baseline down to the bottom of a line, negative
+    pub descent: f32,
+    /// Distance between the baselines of two lines
+    pub line_height: f32,
+}
+
+/// TrueType or OpenType font, cheap to clone.
+#[derive(Clone, Debug)]
+pub struct Font {
+    inner: FontArc,
+}
+
+impl Default for Font {
+    fn default() -> Self {
+        let font = ab_glyph::FontRef::try_from_slice(DEFAULT_FONT).expect("embedded font is valid");
+        Self {
+            inner: FontArc::new(font),
+        }
+    }
+}
+
+impl Font {
+    pub fn from_bytes(data: Vec<u8>) -> anyhow::Result<Self> {
+        let inner = FontArc::try_from_vec(data).context("invalid font file")?;
+        Ok(Self { inner })
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let data = std::fs::read(path).with_context(|| format!("reading {}", path.display()))?;
+        Self::from_bytes(data).with_context(|| format!("loading {}", path.display()))
+    }
+
+    pub(crate) fn inner(&self) -> &FontArc {
+        &self.inner
+    }
+
+    /// Scale for a font `size` in pixels per em, like CSS font sizes.
+    pub(crate) fn scale(&self, size: f32) -> PxScale {
+        let units_per_em = self.inner.units_per_em().unwrap_or(1000.0);
+        PxScale::from(size * self.inner.height_unscaled() / units_per_em)
+    }
+
+    pub fn glyph_id(&self, c: char) -> GlyphId {
+        self.inner.glyph_id(c)
+    }
+
+    /// Horizontal distance from this glyph to the next one, in pixels.
+    pub fn advance(&self, id: GlyphId, size: f32) -> f32 {
+        self.inner.as_scaled(self.scale(size)).h_advance(id)
+    }
+
+    /// Adjustment between two glyphs in pixels, usually negative.
+    pub fn kern(&self, first: GlyphId, second: GlyphId, size: f32) -> f32 {
+        self.inner.as_scaled(self.scale(size)).kern(first, second)
+    }
+
+    pub fn line_metrics(&self, size: f32) -> LineMetrics {
+        let scaled = self.inner.as_scaled(self.scale(size));
+        LineMetrics {
+            ascent: scaled.ascent(),
+            descent: scaled.descent(),
+            line_height: scaled.height() + scaled.line_gap(),
+        }
+    }
+}
@@ -0,0 +1,183 @@
+use ab_glyph::GlyphId;
+use glam::Vec2;
+
+use crate::text::font::Font;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextStyle {
+    /// Pixels per em
+    pub size: f32,
+    pub color: [f32; 3],
+    /// Lines are aligned within `max_width` when set, otherwise within the widest line
+    pub align: TextAlign,
+    /// Lines longer than this wrap, at spaces where possible
+    pub max_width: Option<f32>,
+    /// Multiplier for the font's line height
+    pub line_spacing: f32,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        Self {
+            size: 16.0,
+            color: [1.0, 1.0, 1.0],
+            align: TextAlign::Left,
+            max_width: None,
+            line_spacing: 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PositionedGlyph {
+    pub id: GlyphId,
+    /// Pen position on the baseline, relative to the top left of the text
+    pub position: Vec2,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TextLayout {
+    pub glyphs: Vec<PositionedGlyph>,
+    /// Width of the aligned block and height of all lines
+    pub size: Vec2,
+    pub line_count: usize,
+}
+
+/// Width of `text` on one line, with kerning and without trailing spaces.
+pub fn measure_line(font: &Font, text: &str, size: f32) -> f32 {
+    let mut width = 0.0;
+    let mut previous = None;
+    for c in text.trim_end().chars() {
+        let id = font.glyph_id(c);
+        if let Some(previous) = previous {
+            width += font.kern(previous, id, size);
+        }
+        width += font.advance(id, size);
+        previous = Some(id);
+    }
+    width
+}
+
+/// Splits one paragraph into lines no wider than `max_width`. Lines break
+/// after spaces, and inside words only when a word alone is too wide.
+fn wrap<'a>(font: &Font, paragraph: &'a str, size: f32, max_width: f32) -> Vec<&'a str> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    // End of the last word that fit, lines break there
+    let mut fits = None;
+
+    // Candidate breaks are the ends of words, and the end of the paragraph
+    let mut word_ends: Vec<usize> = paragraph
+        .char_indices()
+        .zip(paragraph.chars().skip(1).chain([' ']))
+        .filter(|&((_, c), next)| c != ' ' && next == ' ')
+        .map(|((i, c), _)| i + c.len_utf8())
+        .collect();
+    word_ends.dedup();
+
+    let mut words = word_ends.into_iter().peekable();
+    while let Some(&end) = words.peek() {
+        if measure_line(font, &paragraph[start..end], size) <= max_width {
+            fits = Some(end);
+            words.next();
+            continue;
+        }
+        match fits.take() {
+            Some(fit) => {
+                lines.push(&paragraph[start..fit]);
+                start = fit + paragraph[fit..].len() - paragraph[fit..].trim_start().len();
+            }
+            None => {
+                // A single word wider than the line is split between characters
+                let word = &paragraph[start..end];
+                let mut split = word
+                    .char_indices()
+                    .skip(1)
+                    .map(|(i, _)| i)
+                    .take_while(|&i| measure_line(font, &word[..i], size) <= max_width)
+                    .last()
+                    .unwrap_or_else(|| word.chars().next().map_or(0, char::len_utf8));
+                if split == 0 {
+                    split = word.len();
+                }
+                lines.push(&word[..split]);
+                start += split;
+            }
+        }
+    }
+    let rest = &paragraph[start..];
+    if !rest.trim().is_empty() || lines.is_empty() {
+        lines.push(rest);
+    }
+    lines
+}
+
+/// Lays out `text` in pixels, y pointing down. Lines break at `\n` and, with
+/// a `max_width`, wherever they would get too long.
+pub fn layout_text(font: &Font, text: &str, style: &TextStyle) -> TextLayout {
+    let size = style.size;
+    let metrics = font.line_metrics(size);
+    let line_height = metrics.line_height * style.line_spacing;
+
+    let lines: Vec<&str> = text
+        .lines()
+        .flat_map(|paragraph| match style.max_width {
+            Some(max_width) => wrap(font, paragraph, size, max_width),
+            None => vec![paragraph],
+        })
+        .collect();
+    let widths: Vec<f32> = lines
+        .iter()
+        .map(|line| measure_line(font, line, size))
+        .collect();
+    let block_width = style
+        .max_width
+        .unwrap_or_else(|| widths.iter().copied().fold(0.0, f32::max));
+
+    let mut glyphs = Vec::new();
+    for (index, (line, width)) in lines.iter().zip(&widths).enumerate() {
+        let mut x = match style.align {
+            TextAlign::Left => 0.0,
+            TextAlign::Center => (block_width - width) * 0.5,
+            TextAlign::Right => block_width - width,
+        };
+        let y = metrics.ascent + index as f32 * line_height;
+        let mut previous = None;
+        for c in line.chars() {
+            let id = font.glyph_id(c);
+            if let Some(previous) = previous {
+                x += font.kern(previous, id, size);
+            }
+            if !c.is_whitespace() {
+                glyphs.push(PositionedGlyph {
+                    id,
+                    position: Vec2::new(x, y),
+                });
+            }
+            x += font.advance(id, size);
+            previous = Some(id);
+        }
+    }
+
+    let line_count = lines.len();
+    TextLayout {
+        glyphs,
+        size: Vec2::new(
+            block_width,
+            if line_count == 0 {
+                0.0
+            } else {
+                (line_count - 1) as f32 * line_height + metrics.ascent - metrics.descent
+            },
+        ),
+        line_count,
+    }
+}
@@ -0,0 +1,5 @@
+pub mod atlas;
+pub mod font;
+pub mod layout;
+pub mod renderer;
+pub mod vector;
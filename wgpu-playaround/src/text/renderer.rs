@@ -0,0 +1,397 @@
+use bytemuck::{Pod, Zeroable};
+use glam::Vec2;
+use wgpu::util::DeviceExt;
+
+use crate::text::{
+    atlas::GlyphAtlas,
+    font::Font,
+    layout::{TextLayout, TextStyle, layout_text},
+};
+
+const ATLAS_SIZE: u32 = 256;
+const ATLAS_MAX_SIZE: u32 = 2048;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Pod, Zeroable)]
+pub struct TextVertex {
+    /// Physical pixels, y pointing down
+    pub position: [f32; 2],
+    pub uv: [f32; 2],
+    pub color: [f32; 3],
+}
+
+impl TextVertex {
+    const ATTRIBS: [wgpu::VertexAttribute; 3] =
+        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x2, 2 => Float32x3];
+    pub fn desc() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<TextVertex>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &Self::ATTRIBS,
+        }
+    }
+}
+
+/// Text queued for the next frame.
+struct Section {
+    layout: TextLayout,
+    position: Vec2,
+    style: TextStyle,
+}
+
+fn create_atlas_texture(device: &wgpu::Device, size: u32) -> wgpu::Texture {
+    device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Glyph Atlas"),
+        size: wgpu::Extent3d {
+            width: size,
+            height: size,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::R8Unorm,
+        usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+        view_formats: &[],
+    })
+}
+
+/// Draws screen-space text on top of whatever is already in the target.
+///
+/// Text is queued during the frame, then `prepare` lays it out, rasterizes
+/// new glyphs into the atlas and uploads one batch of quads, which `draw`
+/// renders in a single call.
+pub struct TextRenderer {
+    font: Font,
+    atlas: GlyphAtlas,
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    screen_buffer: wgpu::Buffer,
+    atlas_texture: wgpu::Texture,
+    bind_group: wgpu::BindGroup,
+
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    quad_capacity: usize,
+    num_indices: u32,
+
+    sections: Vec<Section>,
+}
+
+impl TextRenderer {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        Self::with_font(device, format, Font::default())
+    }
+
+    pub fn with_font(device: &wgpu::Device, format: wgpu::TextureFormat, font: Font) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Text Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../text.wgsl").into()),
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Text Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Text Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            immediate_size: 0,
+        });
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Text Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[TextVertex::desc()],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                strip_index_format: None,
+                front_face: wgpu::FrontFace::Ccw,
+                cull_mode: None,
+                polygon_mode: wgpu::PolygonMode::Fill,
+                unclipped_depth: false,
+                conservative: false,
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState {
+                count: 1,
+                mask: !0,
+                alpha_to_coverage_enabled: false,
+            },
+            multiview_mask: None,
+            cache: None,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Glyph Atlas Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+        let screen_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Text Screen Buffer"),
+            contents: bytemuck::cast_slice(&[1.0f32, 1.0]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let atlas = GlyphAtlas::new(ATLAS_SIZE, ATLAS_MAX_SIZE);
+        let atlas_texture = create_atlas_texture(device, atlas.size());
+        let bind_group = Self::create_bind_group(
+            device,
+            &bind_group_layout,
+            &screen_buffer,
+            &atlas_texture,
+            &sampler,
+        );
+
+        let quad_capacity = 256;
+        let (vertex_buffer, index_buffer) = Self::create_buffers(device, quad_capacity);
+
+        Self {
+            font,
+            atlas,
+            pipeline,
+            bind_group_layout,
+            sampler,
+            screen_buffer,
+            atlas_texture,
+            bind_group,
+            vertex_buffer,
+            index_buffer,
+            quad_capacity,
+            num_indices: 0,
+            sections: Vec::new(),
+        }
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        screen_buffer: &wgpu::Buffer,
+        atlas_texture: &wgpu::Texture,
+        sampler: &wgpu::Sampler,
+    ) -> wgpu::BindGroup {
+        let view = atlas_texture.create_view(&wgpu::TextureViewDescriptor::default());
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Text Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: screen_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        })
+    }
+
+    fn create_buffers(device: &wgpu::Device, quads: usize) -> (wgpu::Buffer, wgpu::Buffer) {
+        let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Text Vertex Buffer"),
+            size: (quads * 4 * std::mem::size_of::<TextVertex>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let index_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Text Index Buffer"),
+            size: (quads * 6 * std::mem::size_of::<u32>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        (vertex_buffer, index_buffer)
+    }
+
+    pub fn font(&self) -> &Font {
+        &self.font
+    }
+
+    pub fn atlas(&self) -> &GlyphAtlas {
+        &self.atlas
+    }
+
+    /// Size `text` would take up, in pixels.
+    pub fn measure(&self, text: &str, style: &TextStyle) -> Vec2 {
+        layout_text(&self.font, text, style).size
+    }
+
+    /// Queues `text` for the next frame with its top left corner at `position`,
+    /// in physical pixels from the top left of the target.
+    pub fn queue(&mut self, text: &str, position: Vec2, style: &TextStyle) {
+        self.sections.push(Section {
+            layout: layout_text(&self.font, text, style),
+            position,
+            style: *style,
+        });
+    }
+
+    /// Builds the quads for everything queued, rasterizing glyphs the atlas does not have yet.
+    fn build_quads(&mut self) -> (Vec<TextVertex>, Vec<u32>) {
+        let mut vertices = Vec::new();
+        let mut indices = Vec::new();
+        let atlas_size = self.atlas.size() as f32;
+        for section in &self.sections {
+            for glyph in &section.layout.glyphs {
+                let Some(entry) = self.atlas.glyph(&self.font, glyph.id, section.style.size) else {
+                    continue;
+                };
+                // Whole pixels keep the glyphs crisp
+                let min = (section.position + glyph.position).round() + entry.offset;
+                let max = min + entry.size.as_vec2();
+                let uv_min = entry.origin.as_vec2() / atlas_size;
+                let uv_max = (entry.origin + entry.size).as_vec2() / atlas_size;
+
+                let first = vertices.len() as u32;
+                vertices.extend(
+                    [
+                        ([min.x, min.y], [uv_min.x, uv_min.y]),
+                        ([max.x, min.y], [uv_max.x, uv_min.y]),
+                        ([max.x, max.y], [uv_max.x, uv_max.y]),
+                        ([min.x, max.y], [uv_min.x, uv_max.y]),
+                    ]
+                    .map(|(position, uv)| TextVertex {
+                        position,
+                        uv,
+                        color: section.style.color,
+                    }),
+                );
+                indices.extend([0, 1, 2, 0, 2, 3].map(|i| first + i));
+            }
+        }
+        (vertices, indices)
+    }
+
+    /// Lays out and uploads the queued text for a `width` by `height` target.
+    pub fn prepare(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, width: u32, height: u32) {
+        let (generation, size) = (self.atlas.generation(), self.atlas.size());
+        let (mut vertices, mut indices) = self.build_quads();
+        if self.atlas.generation() != generation || self.atlas.size() != size {
+            // Glyphs placed before the atlas changed point at stale pixels
+            (vertices, indices) = self.build_quads();
+        }
+        self.sections.clear();
+
+        if self.atlas.size() != self.atlas_texture.width() {
+            self.atlas_texture = create_atlas_texture(device, self.atlas.size());
+            self.bind_group = Self::create_bind_group(
+                device,
+                &self.bind_group_layout,
+                &self.screen_buffer,
+                &self.atlas_texture,
+                &self.sampler,
+            );
+        }
+        if self.atlas.is_dirty() {
+            let size = self.atlas.size();
+            queue.write_texture(
+                self.atlas_texture.as_image_copy(),
+                self.atlas.pixels(),
+                wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(size),
+                    rows_per_image: Some(size),
+                },
+                wgpu::Extent3d {
+                    width: size,
+                    height: size,
+                    depth_or_array_layers: 1,
+                },
+            );
+            self.atlas.mark_clean();
+        }
+
+        queue.write_buffer(
+            &self.screen_buffer,
+            0,
+            bytemuck::cast_slice(&[width.max(1) as f32, height.max(1) as f32]),
+        );
+
+        let quads = vertices.len() / 4;
+        if quads > self.quad_capacity {
+            self.quad_capacity = quads.next_power_of_two();
+            (self.vertex_buffer, self.index_buffer) =
+                Self::create_buffers(device, self.quad_capacity);
+        }
+        if !indices.is_empty() {
+            queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+            queue.write_buffer(&self.index_buffer, 0, bytemuck::cast_slice(&indices));
+        }
+        self.num_indices = indices.len() as u32;
+    }
+
+    /// Draws the prepared text over the current contents of `view`.
+    pub fn draw(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        if self.num_indices == 0 {
+            return;
+        }
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Text Render Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                depth_slice: None,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+            timestamp_writes: None,
+            multiview_mask: None,
+        });
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
+    }
+}
@@ -0,0 +1,111 @@
+use ab_glyph::{Font as _, OutlineCurve, Point};
+use glam::Vec2;
+
+use crate::{
+    geometry::{
+        fill::{FillRule, fill_contours},
+        flatten::{flatten_cubic, flatten_quadratic},
+        mesh_builder::merge_meshes,
+    },
+    models::vertex::Vertex,
+    text::{
+        font::Font,
+        layout::{TextAlign, TextStyle, layout_text},
+    },
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VectorTextOptions {
+    /// World units per em
+    pub size: f32,
+    /// Largest distance between a glyph curve and its line segments, in world units
+    pub tolerance: f32,
+    pub align: TextAlign,
+    pub color: [f32; 3],
+}
+
+impl Default for VectorTextOptions {
+    fn default() -> Self {
+        Self {
+            size: 0.25,
+            tolerance: 0.0005,
+            align: TextAlign::Center,
+            color: [1.0, 1.0, 1.0],
+        }
+    }
+}
+
+/// Splits glyph curves into closed contours of points, mapped by `to_world`.
+fn glyph_contours(
+    curves: &[OutlineCurve],
+    to_world: impl Fn(Point) -> Vec2,
+    tolerance: f32,
+) -> Vec<Vec<Vec2>> {
+    let mut contours: Vec<Vec<Vec2>> = Vec::new();
+    let mut end = None;
+    for curve in curves {
+        let (start, last) = match *curve {
+            OutlineCurve::Line(p0, p1) => (p0, p1),
+            OutlineCurve::Quad(p0, _, p2) => (p0, p2),
+            OutlineCurve::Cubic(p0, _, _, p3) => (p0, p3),
+        };
+        // A curve that does not continue the previous one starts a new contour
+        if end != Some(start) || contours.is_empty() {
+            contours.push(vec![to_world(start)]);
+        }
+        let contour = contours.last_mut().expect("a contour was started");
+        match *curve {
+            OutlineCurve::Line(_, p1) => contour.push(to_world(p1)),
+            OutlineCurve::Quad(p0, p1, p2) => {
+                flatten_quadratic(to_world(p0), to_world(p1), to_world(p2), tolerance, contour);
+            }
+            OutlineCurve::Cubic(p0, p1, p2, p3) => {
+                flatten_cubic(
+                    to_world(p0),
+                    to_world(p1),
+                    to_world(p2),
+                    to_world(p3),
+                    tolerance,
+                    contour,
+                );
+            }
+        }
+        end = Some(last);
+    }
+    contours
+}
+
+/// Turns `text` into one filled mesh, centered on the origin with y pointing up.
+///
+/// Glyph outlines are flattened and filled with the nonzero rule, which is
+/// what TrueType expects, so counters like the inside of an `o` stay open.
+/// Lines break at `\n` and are aligned with `options.align`.
+pub fn text_mesh(
+    font: &Font,
+    text: &str,
+    options: &VectorTextOptions,
+) -> anyhow::Result<(Vec<Vertex>, Vec<u16>)> {
+    // Layout happens directly in world units, with y pointing down
+    let style = TextStyle {
+        size: options.size,
+        align: options.align,
+        ..TextStyle::default()
+    };
+    let layout = layout_text(font, text, &style);
+    let units_per_em = font.inner().units_per_em().unwrap_or(1000.0);
+    let scale = options.size / units_per_em;
+    let half = layout.size * 0.5;
+
+    let mut meshes = Vec::new();
+    for glyph in &layout.glyphs {
+        let Some(outline) = font.inner().outline(glyph.id) else {
+            continue;
+        };
+        let origin = Vec2::new(glyph.position.x - half.x, half.y - glyph.position.y);
+        // Outlines are in font units with y pointing up
+        let to_world = |p: Point| origin + Vec2::new(p.x, p.y) * scale;
+        let contours = glyph_contours(&outline.curves, to_world, options.tolerance);
+        meshes.push(fill_contours(&contours, FillRule::NonZero, options.color)?);
+    }
+    merge_meshes(meshes)
+}
@@ -0,0 +1,402 @@
+pub mod renderer;
+
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::RangeInclusive,
+};
+
+use glam::Vec2;
+use winit::event::{ElementState, MouseButton, WindowEvent};
+
+use crate::{
+    consts::UI_FONT_SIZE,
+    text::{
+        font::Font,
+        layout::{TextStyle, measure_line},
+    },
+};
+
+// Layout in logical pixels
+const ROW_HEIGHT: f32 = 22.0;
+const ROW_SPACING: f32 = 4.0;
+const PADDING: f32 = 8.0;
+
+// Linear colors, the panel is drawn to an sRGB target
+const PANEL_COLOR: [f32; 4] = [0.02, 0.02, 0.025, 0.85];
+const WIDGET_COLOR: [f32; 4] = [0.06, 0.06, 0.08, 1.0];
+const HOVERED_COLOR: [f32; 4] = [0.12, 0.12, 0.16, 1.0];
+const ACTIVE_COLOR: [f32; 4] = [0.2, 0.2, 0.28, 1.0];
+const ACCENT_COLOR: [f32; 4] = [0.1, 0.3, 0.7, 1.0];
+const TEXT_COLOR: [f32; 3] = [0.9, 0.9, 0.9];
+
+/// Axis aligned rectangle in physical pixels, y pointing down.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl Rect {
+    pub fn new(min: Vec2, max: Vec2) -> Self {
+        Self { min, max }
+    }
+
+    pub fn size(&self) -> Vec2 {
+        self.max - self.min
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        point.cmpge(self.min).all() && point.cmplt(self.max).all()
+    }
+}
+
+/// Filled rectangle with a linear RGBA color.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UiQuad {
+    pub rect: Rect,
+    pub color: [f32; 4],
+}
+
+/// Text with its top left corner at `position`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UiText {
+    pub text: String,
+    pub position: Vec2,
+    pub style: TextStyle,
+}
+
+/// Everything one frame of widgets wants drawn, quads below text.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UiOutput {
+    pub quads: Vec<UiQuad>,
+    pub texts: Vec<UiText>,
+}
+
+/// How the pointer interacted with a widget this frame.
+#[derive(Clone, Copy, Debug, Default)]
+struct Response {
+    hovered: bool,
+    held: bool,
+    clicked: bool,
+}
+
+impl Response {
+    fn color(&self) -> [f32; 4] {
+        match (self.held, self.hovered) {
+            (true, _) => ACTIVE_COLOR,
+            (false, true) => HOVERED_COLOR,
+            (false, false) => WIDGET_COLOR,
+        }
+    }
+}
+
+fn widget_id(label: &str, part: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    (label, part).hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Immediate-mode widgets laid out top to bottom in a single panel.
+///
+/// Window events go to `handle_event` first, which says whether the panel
+/// took them. Each frame the widgets are declared between `begin` and `end`;
+/// widget calls return what the user did and `end` returns what to draw.
+/// Widgets are identified by their labels, so labels in one panel must differ.
+pub struct Ui {
+    font: Font,
+    pointer: Option<Vec2>,
+    // Buttons pressed on the panel, it keeps their drags and releases
+    down: Vec<MouseButton>,
+    // Buttons pressed elsewhere, the panel stays out of their drags
+    down_elsewhere: Vec<MouseButton>,
+    pressed: bool,
+    released: bool,
+    // Widget that got the press, it keeps the pointer until release
+    active: Option<u64>,
+    // Area covered in the last frame, used to route events
+    panel: Option<Rect>,
+
+    origin: Vec2,
+    width: f32,
+    scale: f32,
+    cursor_y: f32,
+    output: UiOutput,
+}
+
+impl Ui {
+    pub fn new(font: Font) -> Self {
+        Self {
+            font,
+            pointer: None,
+            down: Vec::new(),
+            down_elsewhere: Vec::new(),
+            pressed: false,
+            released: false,
+            active: None,
+            panel: None,
+            origin: Vec2::ZERO,
+            width: 0.0,
+            scale: 1.0,
+            cursor_y: 0.0,
+            output: UiOutput::default(),
+        }
+    }
+
+    /// Whether pointer events currently belong to the panel rather than the scene.
+    pub fn wants_pointer(&self) -> bool {
+        !self.down.is_empty() || (self.down_elsewhere.is_empty() && self.is_over_panel())
+    }
+
+    fn is_over_panel(&self) -> bool {
+        matches!((self.pointer, self.panel), (Some(p), Some(panel)) if panel.contains(p))
+    }
+
+    /// Feeds a window event to the panel and returns whether it was consumed.
+    pub fn handle_event(&mut self, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::CursorMoved { position, .. } => {
+                self.pointer_moved(Vec2::new(position.x as f32, position.y as f32))
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.pointer_left();
+                false
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                self.mouse_button(*button, *state == ElementState::Pressed)
+            }
+            WindowEvent::MouseWheel { .. } => self.wants_pointer(),
+            _ => false,
+        }
+    }
+
+    /// Moves the pointer to `position` in physical pixels.
+    pub fn pointer_moved(&mut self, position: Vec2) -> bool {
+        self.pointer = Some(position);
+        self.wants_pointer()
+    }
+
+    pub fn pointer_left(&mut self) {
+        self.pointer = None;
+    }
+
+    /// Presses or releases the primary button, see `mouse_button`.
+    pub fn pointer_button(&mut self, pressed: bool) -> bool {
+        self.mouse_button(MouseButton::Left, pressed)
+    }
+
+    /// Presses or releases a mouse button. Only presses on the panel are
+    /// taken, and then so is their release wherever it happens. Widgets only
+    /// react to the primary button.
+    pub fn mouse_button(&mut self, button: MouseButton, pressed: bool) -> bool {
+        if pressed {
+            if !self.is_over_panel() {
+                self.down_elsewhere.push(button);
+                return false;
+            }
+            self.down.push(button);
+            self.pressed |= button == MouseButton::Left;
+            true
+        } else {
+            self.down_elsewhere.retain(|&b| b != button);
+            let Some(index) = self.down.iter().position(|&b| b == button) else {
+                return false;
+            };
+            self.down.remove(index);
+            self.released |= button == MouseButton::Left;
+            true
+        }
+    }
+
+    /// Starts a panel with its top left corner at `origin`, `width` physical
+    /// pixels wide, for a window with the given scale factor.
+    pub fn begin(&mut self, origin: Vec2, width: f32, scale: f32) {
+        self.origin = origin;
+        self.width = width;
+        self.scale = scale;
+        self.cursor_y = origin.y + PADDING * scale;
+        self.output = UiOutput::default();
+    }
+
+    /// Finishes the panel and returns what to draw.
+    pub fn end(&mut self) -> UiOutput {
+        let panel = Rect::new(
+            self.origin,
+            Vec2::new(
+                self.origin.x + self.width,
+                self.cursor_y - ROW_SPACING * self.scale + PADDING * self.scale,
+            ),
+        );
+        self.panel = Some(panel);
+        self.output.quads.insert(
+            0,
+            UiQuad {
+                rect: panel,
+                color: PANEL_COLOR,
+            },
+        );
+
+        // Clicks last one frame, drags until the release
+        self.pressed = false;
+        if self.released || !self.down.contains(&MouseButton::Left) {
+            self.active = None;
+        }
+        self.released = false;
+        std::mem::take(&mut self.output)
+    }
+
+    fn text_style(&self) -> TextStyle {
+        TextStyle {
+            size: UI_FONT_SIZE * self.scale,
+            color: TEXT_COLOR,
+            ..TextStyle::default()
+        }
+    }
+
+    /// Next row of the panel, inside its padding.
+    fn row(&mut self) -> Rect {
+        let min = Vec2::new(self.origin.x + PADDING * self.scale, self.cursor_y);
+        let max = Vec2::new(
+            self.origin.x + self.width - PADDING * self.scale,
+            self.cursor_y + ROW_HEIGHT * self.scale,
+        );
+        self.cursor_y = max.y + ROW_SPACING * self.scale;
+        Rect::new(min, max)
+    }
+
+    fn quad(&mut self, rect: Rect, color: [f32; 4]) {
+        self.output.quads.push(UiQuad { rect, color });
+    }
+
+    /// Text vertically centered in `rect`, starting at its left edge or centered in it.
+    fn text(&mut self, text: &str, rect: Rect, centered: bool) {
+        let style = self.text_style();
+        let height = self.font.line_metrics(style.size).line_height;
+        let x = if centered {
+            let width = measure_line(&self.font, text, style.size);
+            rect.min.x + (rect.size().x - width) * 0.5
+        } else {
+            rect.min.x
+        };
+        let y = rect.min.y + (rect.size().y - height) * 0.5;
+        self.output.texts.push(UiText {
+            text: text.to_string(),
+            position: Vec2::new(x, y),
+            style,
+        });
+    }
+
+    fn interact(&mut self, id: u64, rect: Rect) -> Response {
+        let hovered = self.pointer.is_some_and(|p| rect.contains(p));
+        if self.pressed && hovered {
+            self.active = Some(id);
+        }
+        let held = self.active == Some(id);
+        Response {
+            hovered,
+            held,
+            clicked: held && self.released && hovered,
+        }
+    }
+
+    /// Splits `row` into a label column and a widget column.
+    fn columns(&self, row: Rect) -> (Rect, Rect) {
+        let split = row.min.x + row.size().x * 0.4;
+        (
+            Rect::new(row.min, Vec2::new(split, row.max.y)),
+            Rect::new(Vec2::new(split, row.min.y), row.max),
+        )
+    }
+
+    pub fn label(&mut self, text: &str) {
+        let row = self.row();
+        self.text(text, row, false);
+    }
+
+    /// Returns true when clicked.
+    pub fn button(&mut self, label: &str) -> bool {
+        let row = self.row();
+        let response = self.interact(widget_id(label, ""), row);
+        self.quad(row, response.color());
+        self.text(label, row, true);
+        response.clicked
+    }
+
+    /// Flips `value` when clicked and returns whether it did.
+    pub fn checkbox(&mut self, label: &str, value: &mut bool) -> bool {
+        let row = self.row();
+        let response = self.interact(widget_id(label, ""), row);
+        let side = row.size().y;
+        let inset = side * 0.25;
+        let check = Rect::new(row.min, row.min + Vec2::splat(side));
+        self.quad(check, response.color());
+        if response.clicked {
+            *value = !*value;
+        }
+        if *value {
+            let mark = Rect::new(check.min + inset, check.max - inset);
+            self.quad(mark, ACCENT_COLOR);
+        }
+        let text = Rect::new(Vec2::new(check.max.x + inset, row.min.y), row.max);
+        self.text(label, text, false);
+        response.clicked
+    }
+
+    /// Drags `value` within `range` and returns whether it changed.
+    pub fn slider(&mut self, label: &str, value: &mut f32, range: RangeInclusive<f32>) -> bool {
+        let row = self.row();
+        let (text, track) = self.columns(row);
+        self.text(label, text, false);
+        let response = self.interact(widget_id(label, ""), track);
+
+        let (start, end) = (*range.start(), *range.end());
+        let before = *value;
+        if response.held
+            && let Some(pointer) = self.pointer
+        {
+            let t = ((pointer.x - track.min.x) / track.size().x).clamp(0.0, 1.0);
+            *value = start + (end - start) * t;
+        }
+        *value = value.clamp(start.min(end), start.max(end));
+
+        self.quad(track, response.color());
+        let t = if end != start {
+            (*value - start) / (end - start)
+        } else {
+            0.0
+        };
+        let fill = Rect::new(
+            track.min,
+            Vec2::new(track.min.x + track.size().x * t, track.max.y),
+        );
+        self.quad(fill, ACCENT_COLOR);
+        self.text(&format!("{value:.2}"), track, true);
+        *value != before
+    }
+
+    /// Shows `value` between previous and next arrows. Returns -1 or 1 when
+    /// one of them is clicked, 0 otherwise.
+    pub fn selector(&mut self, label: &str, value: &str) -> i32 {
+        let row = self.row();
+        let (text, area) = self.columns(row);
+        self.text(label, text, false);
+
+        let side = area.size().y;
+        let previous = Rect::new(area.min, area.min + Vec2::splat(side));
+        let next = Rect::new(Vec2::new(area.max.x - side, area.min.y), area.max);
+        let mut step = 0;
+        for (rect, arrow, direction) in [(previous, "<", -1), (next, ">", 1)] {
+            let response = self.interact(widget_id(label, arrow), rect);
+            self.quad(rect, response.color());
+            self.text(arrow, rect, true);
+            if response.clicked {
+                step = direction;
+            }
+        }
+        let middle = Rect::new(
+            Vec2::new(previous.max.x, area.min.y),
+            Vec2::new(next.min.x, area.max.y),
+        );
+        self.text(value, middle, true);
+        step
+    }
+}
@@ -0,0 +1,157 @@
+mod common;
+
+use common::request_device;
+use glam::Vec3;
+use wgpu_playaround::post::{
+    PostSettings,
+    bloom::{BLOOM_MAX_MIPS, BloomSettings, bloom_threshold, mip_sizes},
+    renderer::PostProcessor,
+    tonemap::Tonemap,
+};
+
+#[test]
+fn threshold_has_a_soft_knee() {
+    let threshold = 1.0;
+    // Well below the knee nothing blooms
+    assert_eq!(bloom_threshold(Vec3::splat(0.4), threshold), Vec3::ZERO);
+    // Inside the knee a little does, continuously up to the threshold
+    let inside = bloom_threshold(Vec3::splat(0.75), threshold).x;
+    assert!(inside > 0.0 && inside < 0.25, "{inside}");
+    let at = bloom_threshold(Vec3::splat(1.0), threshold).x;
+    let above = bloom_threshold(Vec3::splat(1.001), threshold).x;
+    assert!((at - 0.125).abs() < 1e-3, "{at}");
+    assert!((above - at).abs() < 1e-2);
+    // Far above it the excess over the threshold blooms, keeping the hue
+    let bright = bloom_threshold(Vec3::new(8.0, 4.0, 0.0), threshold);
+    assert!((bright.x - 7.0).abs() < 1e-4, "{bright}");
+    assert!((bright.y - 3.5).abs() < 1e-4, "{bright}");
+    assert_eq!(bright.z, 0.0);
+}
+
+#[test]
+fn mip_chain_halves_down_to_a_pixel() {
+    assert_eq!(
+        mip_sizes(1280, 720),
+        [
+            (640, 360),
+            (320, 180),
+            (160, 90),
+            (80, 45),
+            (40, 22),
+            (20, 11)
+        ]
+    );
+    assert_eq!(mip_sizes(64, 5), [(32, 2), (16, 1)]);
+    assert_eq!(mip_sizes(8, 8).len(), 3);
+    assert!(mip_sizes(1, 500).is_empty());
+    assert!(mip_sizes(100_000, 100_000).len() == BLOOM_MAX_MIPS);
+}
+
+/// Output of a scene cleared to a uniform `value`, at the first pixel.
+fn render_uniform(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    post: &mut PostProcessor,
+    settings: &PostSettings,
+    value: f64,
+) -> f32 {
+    const SIZE: u32 = 64;
+    let output = device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width: SIZE,
+            height: SIZE,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let readback = device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: (SIZE * SIZE * 4) as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&Default::default());
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: None,
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: post.scene_view(),
+            depth_slice: None,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(wgpu::Color {
+                    r: value,
+                    g: value,
+                    b: value,
+                    a: 1.0,
+                }),
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        occlusion_query_set: None,
+        timestamp_writes: None,
+        multiview_mask: None,
+    });
+    post.prepare(device, queue, settings);
+    post.draw(&mut encoder, &output.create_view(&Default::default()));
+    encoder.copy_texture_to_buffer(
+        output.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer: &readback,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(SIZE * 4),
+                rows_per_image: None,
+            },
+        },
+        output.size(),
+    );
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let slice = readback.slice(..);
+    slice.map_async(wgpu::MapMode::Read, |result| result.unwrap());
+    device.poll(wgpu::PollType::wait_indefinitely()).unwrap();
+    let value = slice.get_mapped_range()[0] as f32 / 255.0;
+    readback.unmap();
+    value
+}
+
+#[test]
+#[ignore = "needs a graphics adapter"]
+fn bloom_adds_the_light_above_the_threshold() {
+    let (device, queue) = request_device().expect("no graphics adapter");
+    let mut post = PostProcessor::new(&device, wgpu::TextureFormat::Rgba8Unorm, 64, 64);
+    assert_eq!(post.bloom().mip_count(), 6);
+    let mut settings = PostSettings {
+        bloom: BloomSettings {
+            enabled: false,
+            threshold: 1.0,
+            intensity: 1.0,
+            radius: 1.0,
+        },
+        tonemap: Tonemap::Clamp,
+        exposure: -2.0,
+        ..PostSettings::default()
+    };
+    let without = render_uniform(&device, &queue, &mut post, &settings, 2.0);
+    assert!((without - 0.5).abs() < 2.0 / 255.0, "{without}");
+
+    // Every mip holds the thresholded 1.0 and the chain averages them back out
+    settings.bloom.enabled = true;
+    let with = render_uniform(&device, &queue, &mut post, &settings, 2.0);
+    assert!((with - 0.75).abs() < 2.0 / 255.0, "{with}");
+
+    // Dim scenes are left alone
+    let dim = render_uniform(&device, &queue, &mut post, &settings, 0.4);
+    assert!((dim - 0.1).abs() < 2.0 / 255.0, "{dim}");
+
+    post.resize(&device, 16, 8);
+    assert_eq!(post.bloom().mip_count(), 3);
+}
@@ -0,0 +1,15 @@
+use wgpu_playaround::color::{linear_to_srgb, srgb_to_linear};
+
+#[test]
+fn srgb_round_trips_through_linear() {
+    for value in 0..=255u8 {
+        let c = value as f32 / 255.0;
+        let back = (linear_to_srgb(srgb_to_linear(c)) * 255.0).round() as u8;
+        assert_eq!(back, value);
+    }
+    assert_eq!(srgb_to_linear(0.04045), 0.04045 / 12.92);
+    assert!((srgb_to_linear(0.5) - 0.214).abs() < 1e-3);
+    // Linear values outside the displayable range are clamped
+    assert_eq!(linear_to_srgb(4.0), linear_to_srgb(1.0));
+    assert_eq!(linear_to_srgb(-1.0), 0.0);
+}
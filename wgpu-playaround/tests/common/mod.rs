@@ -0,0 +1,87 @@
+// Shared by several test crates, each of which only uses some of it
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use glam::{IVec3, Vec2, Vec3};
+use wgpu_playaround::{geometry::primitives::Mesh3d, models::vertex::Vertex};
+
+/// Device and queue of the default adapter, `None` without a GPU.
+pub fn request_device() -> Option<(wgpu::Device, wgpu::Queue)> {
+    let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+        backends: wgpu::Backends::PRIMARY,
+        ..Default::default()
+    });
+    let adapter =
+        pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+            .ok()?;
+    pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default())).ok()
+}
+
+pub fn position(v: &Vertex) -> Vec2 {
+    Vec2::new(v.position[0], v.position[1])
+}
+
+pub fn triangle_points(vertices: &[Vertex], triangle: &[u16]) -> [Vec2; 3] {
+    [0, 1, 2].map(|k| position(&vertices[triangle[k] as usize]))
+}
+
+/// Summed area of the triangles, whichever way they wind.
+pub fn area(vertices: &[Vertex], indices: &[u16]) -> f32 {
+    indices
+        .chunks_exact(3)
+        .map(|t| {
+            let [a, b, c] = triangle_points(vertices, t);
+            (b - a).perp_dot(c - a).abs() * 0.5
+        })
+        .sum()
+}
+
+/// Checks every triangle is counter-clockwise and returns the summed area.
+pub fn ccw_area(vertices: &[Vertex], indices: &[u16]) -> f32 {
+    assert_eq!(indices.len() % 3, 0);
+    indices
+        .chunks_exact(3)
+        .map(|t| {
+            let [a, b, c] = triangle_points(vertices, t);
+            let doubled = (b - a).perp_dot(c - a);
+            assert!(doubled > 0.0, "triangle {t:?} is not counter-clockwise");
+            doubled * 0.5
+        })
+        .sum()
+}
+
+pub fn corners(mesh: &Mesh3d, triangle: &[u32]) -> [Vec3; 3] {
+    [0, 1, 2].map(|k| mesh.positions[triangle[k] as usize])
+}
+
+/// Counts edges that are not shared with exactly one triangle winding the
+/// other way, after welding vertices with the same position.
+pub fn open_edges(mesh: &Mesh3d) -> usize {
+    let weld = |p: Vec3| (p * 1e4).round().as_ivec3();
+    let mut edges: HashMap<(IVec3, IVec3), i32> = HashMap::new();
+    for triangle in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = corners(mesh, triangle).map(weld);
+        assert!(a != b && b != c && c != a, "degenerate triangle");
+        for (from, to) in [(a, b), (b, c), (c, a)] {
+            // Each edge counts up one way round and down the other
+            if (from.to_array()) < (to.to_array()) {
+                *edges.entry((from, to)).or_default() += 1;
+            } else {
+                *edges.entry((to, from)).or_default() -= 1;
+            }
+        }
+    }
+    edges.values().filter(|&&balance| balance != 0).count()
+}
+
+/// Volume enclosed by a closed mesh, negative when it is inside out.
+pub fn volume(mesh: &Mesh3d) -> f32 {
+    mesh.indices
+        .chunks_exact(3)
+        .map(|triangle| {
+            let [a, b, c] = corners(mesh, triangle);
+            a.dot(b.cross(c)) / 6.0
+        })
+        .sum()
+}
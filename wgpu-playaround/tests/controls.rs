@@ -0,0 +1,206 @@
+use glam::{Vec2, Vec3};
+use wgpu_playaround::{
+    controls::{FlyController, OrbitController, PanZoomController},
+    models::camera::{Camera2d, Camera3d},
+    picking::ndc_to_world,
+};
+use winit::{event::MouseButton, keyboard::KeyCode};
+
+fn assert_close(a: Vec3, b: Vec3) {
+    assert!((a - b).length() < 1e-4, "{a} against {b}");
+}
+
+/// World point under `ndc` on the `z = 0` plane.
+fn under_2d(camera: &Camera2d, ndc: Vec2) -> Vec2 {
+    ndc_to_world(ndc, camera.view_proj().inverse())
+}
+
+#[test]
+fn the_default_3d_camera_looks_at_the_origin() {
+    let camera = Camera3d::default();
+    assert_close(camera.forward(), -Vec3::Z);
+    assert_close(camera.right(), Vec3::X);
+    assert_close(camera.up(), Vec3::Y);
+    let inverse = camera.view_proj(1.0).inverse();
+    assert!(ndc_to_world(Vec2::ZERO, inverse).length() < 1e-5);
+    // About as much of the plane as the default 2D camera
+    let corner = ndc_to_world(Vec2::ONE, inverse);
+    assert!(
+        (corner.x - 1.0).abs() < 0.2 && corner.x == corner.y,
+        "{corner}"
+    );
+    // The sky turns with the camera
+    let turned = Camera3d {
+        yaw: 0.7,
+        pitch: -0.3,
+        ..Camera3d::default()
+    };
+    assert_close(turned.sky_view().forward(), turned.forward());
+}
+
+#[test]
+fn orbit_cameras_look_at_the_target_from_their_distance() {
+    let mut orbit = OrbitController::default();
+    orbit.target = Vec3::new(1.0, 2.0, 0.0);
+    orbit.distance = 3.0;
+    for pixels in [
+        Vec2::new(120.0, 0.0),
+        Vec2::new(-40.0, 75.0),
+        Vec2::new(0.0, -10_000.0),
+    ] {
+        orbit.rotate(pixels);
+        let camera = orbit.camera();
+        assert!((camera.eye.distance(orbit.target) - 3.0).abs() < 1e-5);
+        assert_close(camera.forward() * 3.0, orbit.target - camera.eye);
+    }
+    // Dragging up far enough looks up from below without flipping over
+    assert!(orbit.pitch > 1.5 && orbit.pitch < std::f32::consts::FRAC_PI_2);
+
+    orbit.zoom(3.0);
+    assert!((orbit.distance - 3.0 / 1.1f32.powi(3)).abs() < 1e-4);
+    orbit.zoom(-1000.0);
+    let far = orbit.distance;
+    orbit.zoom(-1.0);
+    assert_eq!(orbit.distance, far);
+}
+
+#[test]
+fn orbit_panning_keeps_the_target_under_the_cursor() {
+    let mut orbit = OrbitController::default();
+    orbit.yaw = 0.4;
+    orbit.pitch = 0.3;
+    let old_target = orbit.target;
+    let pixels = Vec2::new(30.0, -45.0);
+    orbit.pan(pixels, 600.0);
+    // A square 600 pixel viewport, window y grows downwards
+    let moved = orbit.camera().view_proj(1.0).project_point3(old_target);
+    let expected = Vec2::new(pixels.x, -pixels.y) * 2.0 / 600.0;
+    assert!((moved.truncate() - expected).length() < 1e-4, "{moved}");
+}
+
+#[test]
+fn orbit_buttons_pick_what_dragging_does() {
+    let mut orbit = OrbitController::default();
+    let start = orbit.clone();
+    orbit.cursor_moved(Vec2::new(100.0, 100.0), 600.0);
+    orbit.cursor_moved(Vec2::new(150.0, 100.0), 600.0);
+    assert_eq!(orbit.camera(), start.camera());
+
+    assert!(orbit.mouse_input(MouseButton::Left, true));
+    orbit.cursor_moved(Vec2::new(200.0, 100.0), 600.0);
+    assert!(orbit.yaw > start.yaw);
+    assert_eq!(orbit.target, start.target);
+    assert!(orbit.mouse_input(MouseButton::Left, false));
+
+    assert!(orbit.mouse_input(MouseButton::Right, true));
+    let yaw = orbit.yaw;
+    orbit.cursor_moved(Vec2::new(250.0, 100.0), 600.0);
+    assert_eq!(orbit.yaw, yaw);
+    assert!(orbit.target.x < start.target.x);
+    orbit.mouse_input(MouseButton::Right, false);
+
+    assert!(!orbit.mouse_input(MouseButton::Middle, true));
+}
+
+#[test]
+fn fly_cameras_move_where_they_look() {
+    let mut fly = FlyController::default();
+    fly.yaw = 0.5;
+    fly.pitch = 0.2;
+    fly.speed = 2.0;
+    let start = fly.position;
+    let forward = fly.camera().forward();
+    assert!(fly.key(KeyCode::KeyW, true));
+    fly.update(0.5);
+    assert_close(fly.position, start + forward);
+
+    // Strafing diagonally is no faster
+    assert!(fly.key(KeyCode::KeyD, true));
+    let before = fly.position;
+    fly.update(0.5);
+    assert!((fly.position.distance(before) - 1.0).abs() < 1e-5);
+    let sideways = (fly.position - before).dot(fly.camera().right());
+    assert!((sideways - 0.5f32.sqrt()).abs() < 1e-5);
+
+    // Opposite keys cancel out
+    assert!(fly.key(KeyCode::KeyS, true));
+    assert!(fly.key(KeyCode::KeyA, true));
+    let before = fly.position;
+    fly.update(0.5);
+    assert_eq!(fly.position, before);
+
+    fly.release_keys();
+    fly.key(KeyCode::KeyA, true);
+    fly.update(0.5);
+    assert_close(fly.position, before - fly.camera().right());
+    assert!(!fly.key(KeyCode::Space, true));
+}
+
+#[test]
+fn fly_cameras_only_look_around_while_grabbed() {
+    let mut fly = FlyController::default();
+    fly.look(Vec2::new(50.0, 20.0));
+    assert_eq!(fly.camera(), FlyController::default().camera());
+
+    fly.set_grabbed(true);
+    fly.look(Vec2::new(50.0, 20.0));
+    let camera = fly.camera();
+    // Right and down
+    assert!(camera.yaw > 0.0 && camera.pitch < 0.0);
+    fly.look(Vec2::new(0.0, 1e6));
+    assert!(fly.pitch > -std::f32::consts::FRAC_PI_2);
+}
+
+#[test]
+fn zooming_keeps_the_point_under_the_cursor() {
+    let mut controller = PanZoomController::default();
+    let mut camera = Camera2d {
+        center: Vec2::new(0.5, -0.25),
+        zoom: 2.0,
+    };
+    let ndc = Vec2::new(0.6, -0.3);
+    controller.cursor_moved(&mut camera, ndc);
+    let world = under_2d(&camera, ndc);
+
+    controller.zoom(&mut camera, 2.0);
+    assert!((camera.zoom - 2.0 * 1.1 * 1.1).abs() < 1e-5);
+    assert!((under_2d(&camera, ndc) - world).length() < 1e-5);
+    controller.zoom(&mut camera, -5.0);
+    assert!((under_2d(&camera, ndc) - world).length() < 1e-5);
+
+    // Zooming stops at the limits
+    controller.zoom(&mut camera, 1000.0);
+    let closest = camera;
+    controller.zoom(&mut camera, 1.0);
+    assert_eq!(camera, closest);
+
+    // Without a cursor the center stays
+    controller.cursor_left();
+    controller.zoom(&mut camera, -3.0);
+    assert_eq!(camera.center, closest.center);
+}
+
+#[test]
+fn middle_dragging_moves_the_world_with_the_cursor() {
+    let mut controller = PanZoomController::default();
+    let mut camera = Camera2d {
+        center: Vec2::ZERO,
+        zoom: 4.0,
+    };
+    let from = Vec2::new(-0.2, 0.1);
+    assert!(!controller.cursor_moved(&mut camera, from));
+    assert!(!controller.mouse_input(MouseButton::Left, true));
+    assert!(!controller.cursor_moved(&mut camera, Vec2::ZERO));
+
+    assert!(controller.mouse_input(MouseButton::Middle, true));
+    controller.cursor_moved(&mut camera, from);
+    let grabbed = under_2d(&camera, from);
+    let to = Vec2::new(0.5, -0.4);
+    assert!(controller.cursor_moved(&mut camera, to));
+    assert!((under_2d(&camera, to) - grabbed).length() < 1e-5);
+
+    controller.mouse_input(MouseButton::Middle, false);
+    let released = camera;
+    assert!(!controller.cursor_moved(&mut camera, from));
+    assert_eq!(camera, released);
+}
@@ -0,0 +1,158 @@
+use glam::{Mat4, Vec2, Vec3};
+use wgpu_playaround::{
+    debug_draw::{self, DebugDraw, DebugStyle, MAX_GRID_LINES},
+    picking::{Hit, Picker},
+};
+
+const RED: [f32; 3] = [1.0, 0.0, 0.0];
+
+fn segments(draw: &DebugDraw) -> Vec<(Vec2, Vec2)> {
+    draw.vertices()
+        .chunks_exact(2)
+        .map(|pair| {
+            let [a, b] = [0, 1].map(|k| Vec2::new(pair[k].position[0], pair[k].position[1]));
+            (a, b)
+        })
+        .collect()
+}
+
+#[test]
+fn primitives_become_line_segments() {
+    let mut draw = DebugDraw::default();
+    draw.line(Vec2::ZERO, Vec2::X, RED);
+    assert_eq!(draw.line_count(), 1);
+    draw.aabb(Vec2::splat(-1.0), Vec2::splat(1.0), RED);
+    assert_eq!(draw.line_count(), 5);
+    draw.cross(Vec2::ZERO, 0.5, RED);
+    assert_eq!(draw.line_count(), 7);
+
+    let vertices = draw.vertices();
+    assert_eq!(vertices.len(), 14);
+    assert!(
+        vertices
+            .iter()
+            .all(|v| v.color == RED && v.position[2] == 0.0)
+    );
+
+    // The box is closed and every corner is used twice
+    let boxed = &segments(&draw)[1..5];
+    for (i, &(_, end)) in boxed.iter().enumerate() {
+        assert_eq!(end, boxed[(i + 1) % 4].0);
+    }
+}
+
+#[test]
+fn circles_and_arrows_have_the_right_shape() {
+    let mut draw = DebugDraw::default();
+    let center = Vec2::new(2.0, -1.0);
+    draw.circle(center, 0.5, RED);
+    for (a, b) in segments(&draw) {
+        assert!(((a - center).length() - 0.5).abs() < 1e-5);
+        assert!(((b - center).length() - 0.5).abs() < 1e-5);
+    }
+    let (first, last) = (segments(&draw)[0].0, segments(&draw).last().unwrap().1);
+    assert!(first.distance(last) < 1e-5, "circle is closed");
+
+    let mut draw = DebugDraw::default();
+    draw.arrow(Vec2::ZERO, Vec2::new(1.0, 0.0), RED);
+    let lines = segments(&draw);
+    assert_eq!(lines.len(), 3);
+    // Both sides of the head start at the tip, point back and mirror each other
+    let (left, right) = (lines[1].1, lines[2].1);
+    assert!(lines[1..].iter().all(|&(a, _)| a == Vec2::X));
+    assert!(left.x < 1.0 && right.x < 1.0);
+    assert!((left.y + right.y).abs() < 1e-5 && left.y != 0.0);
+
+    // A zero length arrow has no head
+    draw.clear();
+    draw.arrow(Vec2::ONE, Vec2::ONE, RED);
+    assert_eq!(draw.line_count(), 1);
+}
+
+#[test]
+fn grids_are_aligned_to_the_origin() {
+    let mut draw = DebugDraw::default();
+    draw.grid(Vec2::new(-0.25, -0.1), Vec2::new(0.35, 0.1), 0.1, RED);
+    let lines = segments(&draw);
+    let vertical: Vec<f32> = lines
+        .iter()
+        .filter(|(a, b)| a.x == b.x)
+        .map(|(a, _)| (a.x * 10.0).round())
+        .collect();
+    assert_eq!(vertical, [-2.0, -1.0, 0.0, 1.0, 2.0, 3.0]);
+    // Both horizontal edges fall on multiples of the spacing
+    assert_eq!(lines.len() - vertical.len(), 3);
+
+    // Too many lines, a bad spacing or flipped corners draw nothing
+    draw.clear();
+    let far = Vec2::splat(MAX_GRID_LINES as f32);
+    draw.grid(-far, far, 1.0, RED);
+    draw.grid(Vec2::ZERO, Vec2::ONE, 0.0, RED);
+    draw.grid(Vec2::ONE, Vec2::ZERO, 0.1, RED);
+    assert_eq!(draw.line_count(), 0);
+}
+
+#[test]
+fn lifetimes_keep_lines_across_frames() {
+    let mut draw = DebugDraw::default();
+    draw.line(Vec2::ZERO, Vec2::X, RED);
+    let style = DebugStyle {
+        color: RED,
+        lifetime: 0.1,
+    };
+    draw.cross(Vec2::ZERO, 1.0, style);
+    assert_eq!(draw.line_count(), 3);
+
+    // Lines without a lifetime are gone once the next frame starts
+    draw.advance(0.04);
+    assert_eq!(draw.line_count(), 2);
+    draw.advance(0.04);
+    assert_eq!(draw.line_count(), 2);
+    draw.advance(0.04);
+    assert_eq!(draw.line_count(), 0);
+}
+
+#[test]
+fn free_functions_collect_per_thread() {
+    debug_draw::with(DebugDraw::clear);
+    debug_draw::line(Vec2::ZERO, Vec2::ONE, RED);
+    debug_draw::circle(Vec2::ZERO, 1.0, RED);
+    let count = debug_draw::with(|draw| draw.line_count());
+    assert!(count > 1);
+
+    let other = std::thread::spawn(|| debug_draw::with(|draw| draw.line_count()));
+    assert_eq!(other.join().unwrap(), 0);
+
+    debug_draw::with(|draw| draw.advance(0.016));
+    assert_eq!(debug_draw::with(|draw| draw.line_count()), 0);
+}
+
+#[test]
+fn picker_reports_world_bounds_and_triangles() {
+    let mut picker = Picker::default();
+    let positions = [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]];
+    let transform = Mat4::from_translation(Vec3::new(2.0, 0.0, 0.0))
+        * Mat4::from_rotation_z(std::f32::consts::FRAC_PI_2);
+    picker.add(3, transform, &positions, &[0, 1, 2]);
+
+    let bounds: Vec<_> = picker.bounds().collect();
+    assert_eq!(bounds.len(), 1);
+    let (shape, aabb) = bounds[0];
+    assert_eq!(shape, 3);
+    assert!(
+        aabb.min.distance(Vec2::new(1.0, 0.0)) < 1e-5,
+        "{:?}",
+        aabb.min
+    );
+    assert!(
+        aabb.max.distance(Vec2::new(2.0, 1.0)) < 1e-5,
+        "{:?}",
+        aabb.max
+    );
+
+    let hit = picker.pick(Vec2::new(1.8, 0.2)).unwrap();
+    let corners = picker.triangle(&hit).unwrap();
+    assert!(corners[1].distance(Vec2::new(2.0, 1.0)) < 1e-5);
+    let missing = Hit { triangle: 1, ..hit };
+    assert_eq!(picker.triangle(&missing), None);
+}
@@ -0,0 +1,191 @@
+use glam::Vec2;
+use wgpu_playaround::{
+    consts::EDIT_MAX_VERTICES,
+    editor::{EditorAction, VertexEditor},
+    models::vertex::Vertex,
+};
+
+fn outline(points: &[[f32; 2]]) -> Vec<Vertex> {
+    points
+        .iter()
+        .map(|&[x, y]| Vertex {
+            position: [x, y, 0.0],
+            color: [1.0, 0.0, 0.0],
+        })
+        .collect()
+}
+
+/// Counter-clockwise unit square around the origin.
+fn square() -> Vec<Vertex> {
+    outline(&[[-0.5, -0.5], [0.5, -0.5], [0.5, 0.5], [-0.5, 0.5]])
+}
+
+fn positions(points: &[Vertex]) -> Vec<Vec2> {
+    points
+        .iter()
+        .map(|v| Vec2::new(v.position[0], v.position[1]))
+        .collect()
+}
+
+#[test]
+fn clicking_vertices_selects_them() {
+    let points = square();
+    let mut editor = VertexEditor::new();
+    assert_eq!(
+        editor.pointer_down(&points, Vec2::new(0.51, 0.49), false),
+        None
+    );
+    editor.pointer_up(&points);
+    assert_eq!(editor.selected().iter().copied().collect::<Vec<_>>(), [2]);
+
+    // Additive clicks toggle, plain clicks replace
+    editor.pointer_down(&points, Vec2::new(-0.5, 0.5), true);
+    editor.pointer_up(&points);
+    assert_eq!(editor.selected().len(), 2);
+    editor.pointer_down(&points, Vec2::new(-0.5, 0.5), true);
+    editor.pointer_up(&points);
+    assert_eq!(editor.selected().iter().copied().collect::<Vec<_>>(), [2]);
+    editor.pointer_down(&points, Vec2::new(-0.5, -0.5), false);
+    editor.pointer_up(&points);
+    assert_eq!(editor.selected().iter().copied().collect::<Vec<_>>(), [0]);
+
+    // Clicking empty space clears the selection
+    editor.pointer_down(&points, Vec2::ZERO, false);
+    editor.pointer_up(&points);
+    assert!(editor.selected().is_empty());
+}
+
+#[test]
+fn marquees_select_the_vertices_inside() {
+    let points = square();
+    let mut editor = VertexEditor::new();
+    editor.pointer_down(&points, Vec2::new(0.8, 0.8), false);
+    assert_eq!(editor.pointer_moved(Vec2::new(0.0, -0.8)), None);
+    // The frame is drawn while selecting
+    let (vertices, _) = editor.overlay_mesh(&points);
+    assert_eq!(vertices.len(), (4 + 4) * 4);
+    editor.pointer_up(&points);
+    assert_eq!(
+        editor.selected().iter().copied().collect::<Vec<_>>(),
+        [1, 2]
+    );
+    assert_eq!(editor.overlay_mesh(&points).0.len(), 4 * 4);
+}
+
+#[test]
+fn dragging_moves_every_selected_vertex() {
+    let points = square();
+    let mut editor = VertexEditor::new();
+    editor.pointer_down(&points, Vec2::new(0.5, 0.5), false);
+    editor.pointer_up(&points);
+    editor.pointer_down(&points, Vec2::new(-0.5, 0.5), true);
+    let action = editor.pointer_moved(Vec2::new(-0.4, 0.7));
+    assert_eq!(
+        action,
+        Some(EditorAction::Move {
+            vertices: vec![2, 3],
+            from: vec![Vec2::new(0.5, 0.5), Vec2::new(-0.5, 0.5)],
+            to: vec![Vec2::new(0.6, 0.7), Vec2::new(-0.4, 0.7)],
+        })
+    );
+    editor.pointer_up(&points);
+    assert_eq!(editor.pointer_moved(Vec2::ZERO), None);
+}
+
+#[test]
+fn grid_snapping_rounds_dragged_and_inserted_vertices() {
+    let points = square();
+    let mut editor = VertexEditor::new();
+    editor.grid = Some(0.25);
+    editor.pointer_down(&points, Vec2::new(0.5, -0.5), false);
+    let Some(EditorAction::Move { to, .. }) = editor.pointer_moved(Vec2::new(0.62, -0.3)) else {
+        panic!("not dragging");
+    };
+    assert_eq!(to, [Vec2::new(0.5, -0.25)]);
+    editor.pointer_up(&points);
+
+    // Split the bottom edge a bit right of its middle
+    let Some(EditorAction::Replace(outline)) =
+        editor.pointer_down(&points, Vec2::new(0.1, -0.5), false)
+    else {
+        panic!("no vertex inserted");
+    };
+    assert_eq!(positions(&outline)[1], Vec2::new(0.0, -0.5));
+}
+
+#[test]
+fn clicking_edges_inserts_a_vertex_and_grabs_it() {
+    let points = square();
+    let mut editor = VertexEditor::new();
+    let Some(EditorAction::Replace(outline)) =
+        editor.pointer_down(&points, Vec2::new(0.505, 0.1), false)
+    else {
+        panic!("no vertex inserted");
+    };
+    assert_eq!(outline.len(), 5);
+    let inserted = positions(&outline)[2];
+    assert!(
+        (inserted - Vec2::new(0.5, 0.1)).length() < 1e-6,
+        "{inserted}"
+    );
+    // Colors are blended like the positions
+    assert_eq!(outline[2].color, [1.0, 0.0, 0.0]);
+    assert_eq!(editor.selected().iter().copied().collect::<Vec<_>>(), [2]);
+    let Some(EditorAction::Move { vertices, from, .. }) = editor.pointer_moved(Vec2::new(0.7, 0.1))
+    else {
+        panic!("not dragging the new vertex");
+    };
+    assert_eq!((vertices, from), (vec![2], vec![inserted]));
+}
+
+#[test]
+fn deleting_keeps_at_least_a_triangle() {
+    let points = square();
+    let mut editor = VertexEditor::new();
+    assert_eq!(editor.delete_selected(&points), None);
+
+    editor.pointer_down(&points, Vec2::new(0.5, 0.5), false);
+    editor.pointer_up(&points);
+    let Some(EditorAction::Replace(outline)) = editor.delete_selected(&points) else {
+        panic!("nothing deleted");
+    };
+    assert_eq!(
+        positions(&outline),
+        [
+            Vec2::new(-0.5, -0.5),
+            Vec2::new(0.5, -0.5),
+            Vec2::new(-0.5, 0.5)
+        ]
+    );
+    assert!(editor.selected().is_empty());
+
+    editor.pointer_down(&outline, Vec2::new(0.5, -0.5), false);
+    editor.pointer_up(&outline);
+    assert_eq!(editor.delete_selected(&outline), None);
+}
+
+#[test]
+fn large_outlines_stay_within_the_overlay_indices() {
+    let n = EDIT_MAX_VERTICES;
+    let circle: Vec<[f32; 2]> = (0..n)
+        .map(|i| {
+            let angle = std::f32::consts::TAU * i as f32 / n as f32;
+            [angle.cos(), angle.sin()]
+        })
+        .collect();
+    let points = outline(&circle);
+    let mut editor = VertexEditor::new();
+    editor.pick_radius = 1e-5;
+    editor.pointer_down(&points, Vec2::new(5.0, 5.0), false);
+    editor.pointer_moved(Vec2::new(-5.0, -5.0));
+    let (vertices, indices) = editor.overlay_mesh(&points);
+    assert_eq!(vertices.len(), (n + 4) * 4);
+    assert!(vertices.len() <= u16::MAX as usize + 1);
+    assert_eq!(indices.iter().max(), Some(&((vertices.len() - 1) as u16)));
+    editor.pointer_up(&points);
+    assert_eq!(editor.selected().len(), n);
+
+    // A full outline gets no more vertices
+    let edge = (Vec2::from(circle[0]) + Vec2::from(circle[1])) / 2.0;
+    assert_eq!(editor.pointer_down(&points, edge, false), None);
+}
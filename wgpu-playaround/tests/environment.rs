@@ -0,0 +1,300 @@
+mod common;
+
+use common::request_device;
+use glam::{Vec2, Vec3, Vec4, Vec4Swizzles};
+use wgpu_playaround::environment::{
+    EnvironmentSettings, SkyView, brdf_lut, cube_direction, equirect_uv,
+    gpu::{Environment, EnvironmentMap},
+    hdr::HdrImage,
+    irradiance, prefiltered,
+};
+
+fn assert_close(a: Vec3, b: Vec3, tolerance: f32) {
+    assert!((a - b).abs().max_element() < tolerance, "{a} vs {b}");
+}
+
+/// Smooth colors that differ in every direction.
+fn gradient(width: u32, height: u32) -> HdrImage {
+    HdrImage::from_fn(width, height, |x, y| {
+        let u = x as f32 / width as f32;
+        let v = y as f32 / height as f32;
+        Vec3::new(
+            2.0 + (u * std::f32::consts::TAU).sin(),
+            4.0 * v,
+            0.25 + u * v,
+        )
+    })
+}
+
+#[test]
+fn hdr_files_round_trip_within_rgbe_precision() {
+    let image = gradient(16, 8);
+    let decoded = HdrImage::decode(&image.encode()).unwrap();
+    assert_eq!((decoded.width, decoded.height), (16, 8));
+    for (a, b) in image.pixels.iter().zip(&decoded.pixels) {
+        // Eight bit mantissas relative to the brightest channel
+        assert_close(*a, *b, a.max_element() / 128.0);
+    }
+}
+
+#[test]
+fn run_length_encoded_scanlines_decode() {
+    let mut bytes = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\nEXPOSURE=1.0\n\n-Y 1 +X 8\n".to_vec();
+    bytes.extend([2, 2, 0, 8]);
+    // Red is one run, green literal values, blue a run of zeros, then the exponents
+    bytes.extend([128 + 8, 128]);
+    bytes.extend([8, 0, 16, 32, 48, 64, 80, 96, 112]);
+    bytes.extend([128 + 8, 0]);
+    bytes.extend([128 + 8, 129]);
+    let image = HdrImage::decode(&bytes).unwrap();
+    assert_eq!((image.width, image.height), (8, 1));
+    for (x, pixel) in image.pixels.iter().enumerate() {
+        // Mantissas are scaled by 2^(129 - 136)
+        assert_eq!(*pixel, Vec3::new(1.0, x as f32 * 0.125, 0.0));
+    }
+}
+
+#[test]
+fn broken_hdr_files_are_rejected() {
+    assert!(HdrImage::decode(b"P6\n1 1\n255\n").is_err());
+    assert!(HdrImage::decode(b"#?RADIANCE\n\n-Y 2 +X 2\n\x01\x02").is_err());
+    assert!(HdrImage::decode(b"#?RADIANCE\n\n+Y 1 +X 1\n\0\0\0\0").is_err());
+}
+
+#[test]
+fn cube_faces_point_along_the_axes_and_share_edges() {
+    let center = Vec2::splat(0.5);
+    let axes = [Vec3::X, -Vec3::X, Vec3::Y, -Vec3::Y, Vec3::Z, -Vec3::Z];
+    for (face, axis) in axes.into_iter().enumerate() {
+        assert_close(cube_direction(face as u32, center), axis, 1e-6);
+    }
+    // +X continues to the right of +Z and -Z to the right of +X
+    assert_close(
+        cube_direction(4, Vec2::new(1.0, 0.5)),
+        cube_direction(0, Vec2::new(0.0, 0.5)),
+        1e-6,
+    );
+    assert_close(
+        cube_direction(0, Vec2::new(1.0, 0.5)),
+        cube_direction(5, Vec2::new(0.0, 0.5)),
+        1e-6,
+    );
+    // The top of the side faces meets +Y
+    assert_close(
+        cube_direction(4, Vec2::new(0.5, 0.0)),
+        cube_direction(2, Vec2::new(0.5, 1.0)),
+        1e-6,
+    );
+}
+
+#[test]
+fn panoramas_wrap_around_the_vertical_axis() {
+    assert_eq!(equirect_uv(-Vec3::Z), Vec2::new(0.5, 0.5));
+    assert!(equirect_uv(Vec3::Y).y.abs() < 1e-6);
+    assert!((equirect_uv(-Vec3::Y).y - 1.0).abs() < 1e-6);
+    assert!((equirect_uv(Vec3::X).x - 0.75).abs() < 1e-6);
+    assert!((equirect_uv(-Vec3::X).x - 0.25).abs() < 1e-6);
+    // Bilinear lookups blend across the seam
+    let image = HdrImage::from_fn(4, 1, |x, _| Vec3::splat(if x == 0 { 1.0 } else { 0.0 }));
+    assert_close(image.sample(Vec2::new(0.0, 0.5)), Vec3::splat(0.5), 1e-6);
+    assert_close(image.sample(Vec2::new(1.0, 0.5)), Vec3::splat(0.5), 1e-6);
+}
+
+#[test]
+fn irradiance_averages_the_hemisphere_by_cosine() {
+    let constant = |_| Vec3::new(0.5, 1.0, 2.0);
+    assert_close(
+        irradiance(constant, Vec3::Y, 256),
+        constant(Vec3::ZERO),
+        1e-5,
+    );
+    // Light from above only, a wall sees half of it
+    let sky = |direction: Vec3| Vec3::splat(if direction.y > 0.0 { 1.0 } else { 0.0 });
+    assert_close(irradiance(sky, Vec3::Y, 1024), Vec3::ONE, 1e-5);
+    assert_close(irradiance(sky, -Vec3::Y, 1024), Vec3::ZERO, 1e-5);
+    assert_close(irradiance(sky, Vec3::X, 1024), Vec3::splat(0.5), 0.02);
+}
+
+#[test]
+fn prefiltering_blurs_more_with_roughness() {
+    let constant = |_| Vec3::splat(3.0);
+    for roughness in [0.0, 0.3, 1.0] {
+        assert_close(
+            prefiltered(constant, Vec3::Z, roughness, 128),
+            Vec3::splat(3.0),
+            1e-4,
+        );
+    }
+    // A bright spot straight ahead, rougher lobes spread it out
+    let spot = |direction: Vec3| Vec3::splat(if direction.z > 0.99 { 1.0 } else { 0.0 });
+    let sharp = prefiltered(spot, Vec3::Z, 0.0, 512).x;
+    let glossy = prefiltered(spot, Vec3::Z, 0.3, 512).x;
+    let rough = prefiltered(spot, Vec3::Z, 1.0, 512).x;
+    assert_eq!(sharp, 1.0);
+    assert!(
+        sharp > glossy && glossy > rough && rough > 0.0,
+        "{glossy} {rough}"
+    );
+}
+
+#[test]
+fn brdf_lut_keeps_energy_below_one() {
+    // Smooth surfaces seen head on reflect exactly the base reflectance
+    let head_on = brdf_lut(1.0, 0.0, 512);
+    assert!(
+        (head_on.x - 1.0).abs() < 0.01 && head_on.y < 0.01,
+        "{head_on}"
+    );
+    for n_dot_v in [0.05, 0.3, 0.6, 1.0] {
+        for roughness in [0.1, 0.5, 1.0] {
+            let lut = brdf_lut(n_dot_v, roughness, 512);
+            assert!(lut.min_element() >= 0.0 && lut.x + lut.y <= 1.0, "{lut}");
+        }
+    }
+    // Fresnel makes grazing angles more reflective on smooth surfaces
+    assert!(brdf_lut(0.05, 0.1, 512).y > brdf_lut(0.9, 0.1, 512).y);
+}
+
+#[test]
+fn sky_view_looks_down_negative_z_by_default() {
+    let view = SkyView::default();
+    assert_close(view.forward(), -Vec3::Z, 1e-6);
+    let direction = |view: &SkyView, ndc: Vec2| {
+        let far = view.inverse_view_proj(16.0 / 9.0) * ndc.extend(1.0).extend(1.0);
+        (far.xyz() / far.w).normalize()
+    };
+    assert_close(direction(&view, Vec2::ZERO), -Vec3::Z, 1e-5);
+    // The top edge is half the field of view up
+    let up = direction(&view, Vec2::new(0.0, 1.0));
+    assert!((up.y.asin() - view.fov_y / 2.0).abs() < 1e-4, "{up}");
+    let right = SkyView {
+        yaw: std::f32::consts::FRAC_PI_2,
+        ..view
+    };
+    assert_close(direction(&right, Vec2::ZERO), Vec3::X, 1e-5);
+}
+
+const SETTINGS: EnvironmentSettings = EnvironmentSettings {
+    cube_size: 32,
+    irradiance_size: 8,
+    prefiltered_size: 16,
+    prefiltered_mips: 4,
+    brdf_lut_size: 16,
+    sample_count: 128,
+};
+
+fn texel_uv(index: usize, size: u32) -> (u32, Vec2) {
+    let size = size as usize;
+    let face = index / (size * size);
+    let x = index % size;
+    let y = index / size % size;
+    let uv = (Vec2::new(x as f32, y as f32) + 0.5) / size as f32;
+    (face as u32, uv)
+}
+
+fn assert_texels_close(gpu: &[Vec4], cpu: impl Fn(usize) -> Vec3, tolerance: f32) {
+    for (index, texel) in gpu.iter().enumerate() {
+        let expected = cpu(index);
+        // Half floats keep about three decimal digits
+        let allowed = tolerance + expected.max_element() * 2e-3;
+        assert!(
+            (texel.truncate() - expected).abs().max_element() < allowed,
+            "texel {index}: {texel} vs {expected}"
+        );
+    }
+}
+
+#[test]
+#[ignore = "needs a graphics adapter"]
+fn gpu_bake_matches_the_cpu_reference() {
+    let (device, queue) = request_device().expect("no graphics adapter");
+    let image = gradient(64, 32);
+    let mut environment = Environment::new(&device, SETTINGS);
+    assert!(!environment.is_loaded());
+    environment.load(&device, &queue, &image);
+    assert!(environment.is_loaded());
+
+    let cube = environment
+        .read(&device, &queue, EnvironmentMap::Cube, 0)
+        .unwrap();
+    assert_eq!(cube.len(), 6 * 32 * 32);
+    assert_texels_close(
+        &cube,
+        |index| {
+            let (face, uv) = texel_uv(index, 32);
+            image.sample(equirect_uv(cube_direction(face, uv)))
+        },
+        1e-3,
+    );
+
+    let lut = environment
+        .read(&device, &queue, EnvironmentMap::BrdfLut, 0)
+        .unwrap();
+    assert_eq!(lut.len(), 16 * 16);
+    assert_texels_close(
+        &lut,
+        |index| {
+            let (_, uv) = texel_uv(index, 16);
+            brdf_lut(uv.x, uv.y, SETTINGS.sample_count).extend(0.0)
+        },
+        2e-3,
+    );
+}
+
+#[test]
+#[ignore = "needs a graphics adapter"]
+fn gpu_bake_of_a_uniform_sky_is_uniform() {
+    let (device, queue) = request_device().expect("no graphics adapter");
+    let color = Vec3::new(0.25, 1.0, 4.0);
+    let mut environment = Environment::new(&device, SETTINGS);
+    environment.load(&device, &queue, &HdrImage::from_fn(8, 4, |_, _| color));
+
+    let baked = environment
+        .read(&device, &queue, EnvironmentMap::Irradiance, 0)
+        .unwrap();
+    assert_texels_close(&baked, |_| color, 1e-3);
+    for mip in 0..SETTINGS.prefiltered_mips {
+        let prefiltered = environment
+            .read(&device, &queue, EnvironmentMap::Prefiltered, mip)
+            .unwrap();
+        assert_eq!(prefiltered.len(), 6 * (16 >> mip) * (16 >> mip));
+        assert_texels_close(&prefiltered, |_| color, 1e-3);
+    }
+    // Past the mips that were baked
+    assert!(
+        environment
+            .read(
+                &device,
+                &queue,
+                EnvironmentMap::Prefiltered,
+                SETTINGS.prefiltered_mips
+            )
+            .is_err()
+    );
+}
+
+#[test]
+#[ignore = "needs a graphics adapter"]
+fn gpu_bake_of_a_half_lit_sky_lights_walls_halfway() {
+    let (device, queue) = request_device().expect("no graphics adapter");
+    // The top half of the panorama is the upper hemisphere
+    let mut environment = Environment::new(&device, SETTINGS);
+    environment.load(
+        &device,
+        &queue,
+        &HdrImage::from_fn(64, 32, |_, y| Vec3::splat(if y < 16 { 1.0 } else { 0.0 })),
+    );
+    let baked = environment
+        .read(&device, &queue, EnvironmentMap::Irradiance, 0)
+        .unwrap();
+    let sky = |direction: Vec3| Vec3::splat(if direction.y > 0.0 { 1.0 } else { 0.0 });
+    // Blurrier mips for sparse samples leak a little across the horizon
+    assert_texels_close(
+        &baked,
+        |index| {
+            let (face, uv) = texel_uv(index, SETTINGS.irradiance_size);
+            irradiance(sky, cube_direction(face, uv), 1024)
+        },
+        0.1,
+    );
+}
@@ -0,0 +1,226 @@
+mod common;
+
+use common::area;
+use glam::{Vec2, Vec3};
+use wgpu_playaround::{
+    document::{Document, ShapeGeometry, ShapeNode, Transform2d},
+    enums::ShapeType,
+    models::vertex::Vertex,
+    obj::{export_obj, import_obj},
+    svg::{
+        export::{SvgColorMode, SvgExportOptions, export_svg},
+        import::{SvgImportOptions, import_svg},
+    },
+};
+
+type Mesh = (Vec<Vertex>, Vec<u16>);
+
+fn document() -> Document {
+    let mut star = ShapeNode::new(ShapeGeometry::Star {
+        points: 5,
+        outer_radius: 0.4,
+        inner_radius: 0.2,
+    });
+    star.transform = Transform2d {
+        translation: Vec2::new(0.3, -0.2),
+        rotation: 0.4,
+        scale: Vec2::new(1.5, 0.75),
+    };
+    star.color = [0.5, 1.0, 0.25];
+    Document {
+        shapes: vec![
+            ShapeNode::new(ShapeGeometry::Preset(ShapeType::Pentagon)),
+            star,
+        ],
+    }
+}
+
+fn world_meshes(document: &Document) -> Vec<Mesh> {
+    document
+        .shapes
+        .iter()
+        .map(|shape| shape.world_mesh().unwrap())
+        .collect()
+}
+
+fn mesh(shape: &ShapeNode) -> (&[Vertex], &[u16]) {
+    match &shape.geometry {
+        ShapeGeometry::Mesh { vertices, indices } => (vertices, indices),
+        other => panic!("expected a mesh, got {}", other.name()),
+    }
+}
+
+fn bounds(vertices: &[Vertex]) -> (Vec2, Vec2) {
+    vertices.iter().fold(
+        (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+        |(min, max), v| {
+            let p = Vec2::new(v.position[0], v.position[1]);
+            (min.min(p), max.max(p))
+        },
+    )
+}
+
+fn average_color(vertices: &[Vertex], triangle: &[u16]) -> Vec3 {
+    triangle
+        .iter()
+        .map(|&i| Vec3::from_array(vertices[i as usize].color))
+        .sum::<Vec3>()
+        / 3.0
+}
+
+/// Every exported triangle comes back as one filled shape in the same place.
+fn check_svg_round_trip(meshes: &[Mesh], colors: SvgColorMode) -> String {
+    let view = (Vec2::splat(-1.0), Vec2::splat(1.0));
+    let options = SvgExportOptions {
+        colors,
+        ..SvgExportOptions::default()
+    };
+    let svg = export_svg(meshes, view, &options);
+    let shapes = import_svg(
+        &svg,
+        &SvgImportOptions {
+            size: 2.0,
+            ..SvgImportOptions::default()
+        },
+    )
+    .unwrap();
+
+    let triangles: Vec<(&[Vertex], &[u16])> = meshes
+        .iter()
+        .flat_map(|(vertices, indices)| {
+            indices
+                .chunks_exact(3)
+                .map(move |t| (vertices.as_slice(), t))
+        })
+        .collect();
+    assert_eq!(shapes.len(), triangles.len());
+
+    for ((vertices, triangle), shape) in triangles.into_iter().zip(&shapes) {
+        let (imported, imported_indices) = mesh(shape);
+        let corners: Vec<Vertex> = triangle.iter().map(|&i| vertices[i as usize]).collect();
+        let expected_area = area(&corners, &[0, 1, 2]);
+        let actual_area = area(imported, imported_indices);
+        assert!(
+            (expected_area - actual_area).abs() < 1e-4,
+            "{expected_area} vs {actual_area}"
+        );
+
+        let (min, max) = bounds(&corners);
+        let (imported_min, imported_max) = bounds(imported);
+        assert!(
+            min.abs_diff_eq(imported_min, 1e-4),
+            "{min} vs {imported_min}"
+        );
+        assert!(
+            max.abs_diff_eq(imported_max, 1e-4),
+            "{max} vs {imported_max}"
+        );
+
+        // Colors go through 8 bit sRGB, which is coarsest for bright colors
+        let expected = average_color(vertices, triangle);
+        for v in imported {
+            let actual = Vec3::from_array(v.color);
+            assert!(expected.abs_diff_eq(actual, 0.01), "{expected} vs {actual}");
+        }
+    }
+    svg
+}
+
+#[test]
+fn svg_round_trip_with_flat_colors() {
+    let svg = check_svg_round_trip(&world_meshes(&document()), SvgColorMode::Flat);
+    assert!(!svg.contains("linearGradient"));
+}
+
+#[test]
+fn svg_round_trip_with_gradients() {
+    // The pentagon has a different color on every vertex
+    let svg = check_svg_round_trip(&world_meshes(&document()), SvgColorMode::Gradient);
+    assert!(svg.contains("<linearGradient"));
+    assert!(svg.contains("fill=\"url(#gradient-0) #"));
+}
+
+#[test]
+fn gradients_follow_the_vertex_colors() {
+    // Red on the left fading to blue on the right
+    let vertex = |x: f32, y: f32, color: [f32; 3]| Vertex {
+        position: [x, y, 0.0],
+        color,
+    };
+    let vertices = vec![
+        vertex(-0.5, -0.5, [1.0, 0.0, 0.0]),
+        vertex(0.5, -0.5, [0.0, 0.0, 1.0]),
+        vertex(0.5, 0.5, [0.0, 0.0, 1.0]),
+    ];
+    let options = SvgExportOptions {
+        colors: SvgColorMode::Gradient,
+        pixels_per_unit: 100.0,
+    };
+    let svg = export_svg(
+        &[(vertices, vec![0, 1, 2])],
+        (Vec2::splat(-1.0), Vec2::splat(1.0)),
+        &options,
+    );
+    assert!(
+        svg.contains("x1=\"50\" y1=\"") && svg.contains("x2=\"150\" y2=\""),
+        "{svg}"
+    );
+    let red = svg.find("stop-color=\"#ff0000\"").expect("red stop");
+    let blue = svg.find("stop-color=\"#0000ff\"").expect("blue stop");
+    assert!(red < blue);
+}
+
+#[test]
+fn svg_export_writes_one_group_per_shape() {
+    let svg = export_svg(
+        &world_meshes(&document()),
+        (Vec2::splat(-1.0), Vec2::splat(1.0)),
+        &SvgExportOptions::default(),
+    );
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"800\""));
+    assert!(svg.contains("<g id=\"shape-0\">") && svg.contains("<g id=\"shape-1\">"));
+    assert!(!svg.contains("shape-2"));
+    // The pentagon's first corner at (-0.087, 0.492), with y pointing down
+    assert!(svg.contains("365.27,203.038"), "{svg}");
+}
+
+#[test]
+fn obj_round_trip_is_exact() {
+    let meshes = world_meshes(&document());
+    let obj = export_obj(&meshes);
+    assert!(obj.contains("o shape_0\n") && obj.contains("o shape_1\n"));
+
+    let shapes = import_obj(&obj).unwrap();
+    assert_eq!(shapes.len(), meshes.len());
+    for (shape, (vertices, indices)) in shapes.iter().zip(&meshes) {
+        let (imported, imported_indices) = mesh(shape);
+        assert_eq!(imported, vertices.as_slice());
+        assert_eq!(imported_indices, indices.as_slice());
+    }
+}
+
+#[test]
+fn obj_import_handles_common_variations() {
+    let obj = "\
+# quad with texture coordinates and normals, relative indices and no colors
+v 0 0 0
+v 1 0 0
+v 1 1 0
+v 0 1 0
+vt 0 0
+vn 0 0 1
+g quad
+f -4/1/1 -3/1/1 -2/1/1 -1/1/1
+";
+    let shapes = import_obj(obj).unwrap();
+    assert_eq!(shapes.len(), 1);
+    let (vertices, indices) = mesh(&shapes[0]);
+    assert_eq!(indices, &[0, 1, 2, 0, 2, 3]);
+    assert!((area(vertices, indices) - 1.0).abs() < 1e-6);
+    assert!(vertices.iter().all(|v| v.color == [1.0, 1.0, 1.0]));
+
+    assert!(import_obj("v 0 0\n").is_err());
+    assert!(import_obj("v 0 0 0\nf 1 2 3\n").is_err());
+    assert!(import_obj("v 0 0 0\nv 1 0 0\nf 1 2\n").is_err());
+    assert!(import_obj("v 0 0 0\nf 0 1 1\n").is_err());
+}
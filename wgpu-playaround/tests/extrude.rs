@@ -0,0 +1,262 @@
+mod common;
+
+use std::f32::consts::PI;
+
+use common::{area, corners, open_edges, volume};
+use glam::{Vec2, Vec3};
+use wgpu_playaround::{
+    consts::{INDICES, STAR_INDICES, STAR_VERTICES, VERTICES},
+    geometry::{
+        extrude::{Bevel, boundary_loops, extrude},
+        lathe::lathe,
+        normals::triangle_normal,
+        primitives::Mesh3d,
+        procedural::regular_polygon,
+        triangulate::{signed_area, triangulate_polygon},
+    },
+    svg::import::{SvgImportOptions, import_svg},
+};
+
+/// Closed, facing out, with the given volume and every triangle facing
+/// the way its vertex normals point.
+fn assert_solid(mesh: &Mesh3d, expected_volume: f32, tolerance: f32) {
+    assert!(
+        mesh.indices
+            .iter()
+            .all(|&i| (i as usize) < mesh.vertex_count())
+    );
+    assert_eq!(open_edges(mesh), 0);
+    let volume = volume(mesh);
+    assert!(
+        (volume / expected_volume - 1.0).abs() < tolerance,
+        "volume {volume}, expected {expected_volume}"
+    );
+    for triangle in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = corners(mesh, triangle);
+        let face = triangle_normal(a, b, c).normalize();
+        for &i in triangle {
+            let normal = mesh.normals[i as usize];
+            assert!((normal.length() - 1.0).abs() < 1e-5, "{normal}");
+            assert!(
+                face.dot(normal) > 0.0,
+                "face {face} against normal {normal}"
+            );
+        }
+    }
+}
+
+#[test]
+fn presets_extrude_into_prisms() {
+    for (vertices, indices) in [(VERTICES, INDICES), (STAR_VERTICES, STAR_INDICES)] {
+        let mesh = extrude(vertices, indices, 0.4, None);
+        assert_solid(&mesh, area(vertices, indices) * 0.4, 1e-4);
+        for (position, normal) in mesh.positions.iter().zip(&mesh.normals) {
+            if normal.z != 0.0 {
+                // Caps face straight out of the front and back
+                assert_eq!(normal.z.abs(), 1.0);
+                assert_eq!(position.z, 0.2 * normal.z);
+            }
+        }
+    }
+}
+
+#[test]
+fn sharp_corners_keep_hard_side_normals() {
+    let mesh = extrude(STAR_VERTICES, STAR_INDICES, 0.2, None);
+    // Every side triangle is flat shaded, the star has no shallow corners
+    for triangle in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = corners(&mesh, triangle);
+        let face = triangle_normal(a, b, c).normalize();
+        for &i in triangle {
+            assert!(face.dot(mesh.normals[i as usize]) > 0.9999);
+        }
+    }
+}
+
+#[test]
+fn flattened_curves_get_smooth_side_normals() {
+    let outline = regular_polygon(48, 0.5);
+    let indices = triangulate_polygon(&outline.positions_2d()).unwrap();
+    let mesh = extrude(&outline.points, &indices, 1.0, None);
+    let expected = signed_area(&outline.positions_2d()) / 2.0;
+    assert_solid(&mesh, expected, 1e-4);
+    for (position, normal) in mesh.positions.iter().zip(&mesh.normals) {
+        if normal.z == 0.0 {
+            // Points away from the center like on a cylinder
+            let radial = position.truncate().normalize().extend(0.0);
+            assert!((radial - *normal).length() < 1e-4, "{normal} at {position}");
+        }
+    }
+}
+
+#[test]
+fn side_uvs_wrap_around_and_run_front_to_back() {
+    let outline = regular_polygon(4, 1.0);
+    let indices = triangulate_polygon(&outline.positions_2d()).unwrap();
+    let mesh = extrude(&outline.points, &indices, 2.0, None);
+    for ((position, normal), uv) in mesh.positions.iter().zip(&mesh.normals).zip(&mesh.uvs) {
+        assert!(uv.min_element() >= 0.0 && uv.max_element() <= 1.0, "{uv}");
+        if normal.z == 0.0 {
+            assert_eq!(uv.y, if position.z > 0.0 { 0.0 } else { 1.0 });
+        }
+    }
+    let side_u: Vec<f32> = mesh
+        .uvs
+        .iter()
+        .zip(&mesh.normals)
+        .filter(|(_, normal)| normal.z == 0.0)
+        .map(|(uv, _)| uv.x)
+        .collect();
+    // Four equal sides split the texture in quarters
+    for u in [0.0, 0.25, 0.5, 0.75, 1.0] {
+        assert!(
+            side_u.iter().any(|&x| (x - u).abs() < 1e-6),
+            "no side at u = {u}"
+        );
+    }
+}
+
+#[test]
+fn bevels_shrink_the_caps_and_round_the_edges() {
+    let outline = regular_polygon(4, 0.5);
+    let indices = triangulate_polygon(&outline.positions_2d()).unwrap();
+    let plain = extrude(&outline.points, &indices, 0.5, None);
+    for segments in [1, 4] {
+        let bevel = Bevel {
+            width: 0.05,
+            depth: 0.05,
+            segments,
+        };
+        let mesh = extrude(&outline.points, &indices, 0.5, Some(bevel));
+        let cut = volume(&mesh);
+        assert_solid(&mesh, cut, 1e-6);
+        assert!(cut > 0.0 && cut < volume(&plain));
+        // The caps of the diamond are inset by the bevel width along every edge
+        for (position, normal) in mesh.positions.iter().zip(&mesh.normals) {
+            if normal.z.abs() == 1.0 {
+                let reach = position.x.abs() + position.y.abs();
+                assert!(reach <= 0.5 - 0.05 * 2f32.sqrt() + 1e-6, "{position}");
+            }
+        }
+        // Bevel normals lean between the side and the caps
+        assert!(
+            mesh.normals
+                .iter()
+                .any(|n| n.z > 0.1 && n.z < 0.9 && n.truncate().length() > 0.1)
+        );
+    }
+    // Bevels deeper than half the prism meet in the middle
+    let deep = Bevel {
+        width: 0.05,
+        depth: 1.0,
+        segments: 2,
+    };
+    let mesh = extrude(&outline.points, &indices, 0.5, Some(deep));
+    assert_solid(&mesh, volume(&mesh), 1e-6);
+    assert!(mesh.positions.iter().all(|p| p.z.abs() <= 0.25));
+}
+
+#[test]
+fn triangulated_svg_paths_keep_their_holes() {
+    let svg = r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">
+        <path fill-rule="evenodd" d="M 1 1 L 9 1 L 9 9 L 1 9 Z M 3 3 L 7 3 L 7 7 L 3 7 Z"/>
+    </svg>"#;
+    let shapes = import_svg(svg, &SvgImportOptions::default()).unwrap();
+    let (vertices, indices) = shapes[0].geometry.mesh().unwrap();
+
+    let loops = boundary_loops(&vertices, &indices);
+    assert_eq!(loops.len(), 2);
+    let areas: Vec<f32> = loops
+        .iter()
+        .map(|l| signed_area(&l.iter().map(|p| p.to_array()).collect::<Vec<_>>()) / 2.0)
+        .collect();
+    // 1.6 world units across 10 view box units
+    let unit = 0.16f32;
+    let (outer, hole) = (64.0 * unit * unit, 16.0 * unit * unit);
+    assert!(areas.iter().any(|&a| (a - outer).abs() < 1e-4), "{areas:?}");
+    assert!(areas.iter().any(|&a| (a + hole).abs() < 1e-4), "{areas:?}");
+
+    let mesh = extrude(&vertices, &indices, 0.3, None);
+    assert_solid(&mesh, (outer - hole) * 0.3, 1e-4);
+    // The wall of the hole faces into it
+    for (position, normal) in mesh.positions.iter().zip(&mesh.normals) {
+        let inside_hole = position.x.abs().max(position.y.abs()) <= 2.0 * unit + 1e-5;
+        if normal.z == 0.0 && inside_hole {
+            assert!(normal.truncate().dot(position.truncate()) < 0.0);
+        }
+    }
+}
+
+#[test]
+fn boundary_loops_ignore_shared_edges() {
+    // A lone triangle is its own outline
+    let loops = boundary_loops(&VERTICES[..3], &[0, 1, 2]);
+    assert_eq!(loops.len(), 1);
+    assert_eq!(loops[0].len(), 3);
+    let loops = boundary_loops(STAR_VERTICES, STAR_INDICES);
+    assert_eq!(loops.len(), 1);
+    assert_eq!(loops[0].len(), 10);
+    assert!(boundary_loops(&[], &[]).is_empty());
+}
+
+#[test]
+fn lathed_profiles_close_on_the_axis() {
+    // A cylinder, hard edges where the side meets the ends
+    let profile = [
+        Vec2::new(0.0, 1.0),
+        Vec2::new(0.5, 1.0),
+        Vec2::new(0.5, -1.0),
+        Vec2::new(0.0, -1.0),
+    ];
+    let mesh = lathe(&profile, 64);
+    let polygon_area = 64.0 * 0.5 * 0.25 * (2.0 * PI / 64.0).sin();
+    assert_solid(&mesh, polygon_area * 2.0, 1e-4);
+    let ends = mesh.normals.iter().filter(|n| n.y.abs() == 1.0).count();
+    let sides = mesh.normals.iter().filter(|n| n.y == 0.0).count();
+    assert_eq!((ends, sides), (4 * 65, 2 * 65));
+
+    // The same profile walked the other way still faces out
+    let reversed: Vec<Vec2> = profile.iter().rev().copied().collect();
+    assert_solid(&lathe(&reversed, 64), polygon_area * 2.0, 1e-4);
+}
+
+#[test]
+fn lathed_arcs_shade_smoothly() {
+    let profile: Vec<Vec2> = (0..=32)
+        .map(|i| {
+            let angle = PI * i as f32 / 32.0;
+            Vec2::new(angle.sin().max(0.0), angle.cos())
+        })
+        .collect();
+    let mesh = lathe(&profile, 64);
+    assert_solid(&mesh, 4.0 / 3.0 * PI, 0.01);
+    for (position, normal) in mesh.positions.iter().zip(&mesh.normals) {
+        // Ends of the profile only see one segment
+        assert!(
+            position.normalize().dot(*normal) > 0.99,
+            "{normal} at {position}"
+        );
+    }
+}
+
+#[test]
+fn open_profiles_leave_a_rim() {
+    // A bowl open at the top
+    let profile = [
+        Vec2::new(1.0, 1.0),
+        Vec2::new(0.8, 0.0),
+        Vec2::new(0.0, 0.0),
+    ];
+    let mesh = lathe(&profile, 32);
+    assert_eq!(open_edges(&mesh), 32);
+    for (position, normal) in mesh.positions.iter().zip(&mesh.normals) {
+        // Outside of the wall and the underside of the base
+        let radial = Vec3::new(position.x, 0.0, position.z);
+        assert!(normal.dot(radial) >= 0.0 && normal.y <= 0.2, "{normal}");
+    }
+    for uv in &mesh.uvs {
+        assert!(uv.min_element() >= 0.0 && uv.max_element() <= 1.0);
+    }
+    assert!(mesh.uvs.iter().any(|uv| uv.y == 1.0) && mesh.uvs.iter().any(|uv| uv.y == 0.0));
+    assert!(lathe(&profile[..1], 32).indices.is_empty());
+}
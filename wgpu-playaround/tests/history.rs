@@ -0,0 +1,179 @@
+use glam::Vec2;
+use wgpu_playaround::{
+    document::{Document, ShapeGeometry, ShapeNode, commands::EditCommand},
+    enums::ShapeType,
+    history::{Command, History},
+};
+
+/// Adds a value to a counter, merging consecutive adds inside a group.
+#[derive(Debug)]
+struct Add(i32);
+
+impl Command<i32> for Add {
+    fn apply(&self, target: &mut i32) {
+        *target += self.0;
+    }
+
+    fn revert(&self, target: &mut i32) {
+        *target -= self.0;
+    }
+
+    fn merge(&mut self, next: &Self) -> bool {
+        self.0 += next.0;
+        true
+    }
+}
+
+#[test]
+fn undo_and_redo_restore_state() {
+    let mut history = History::new(usize::MAX);
+    let mut value = 0;
+
+    history.execute(Add(1), &mut value);
+    history.execute(Add(2), &mut value);
+    assert_eq!(value, 3);
+
+    assert!(history.undo(&mut value));
+    assert_eq!(value, 1);
+    assert!(history.undo(&mut value));
+    assert_eq!(value, 0);
+    assert!(!history.undo(&mut value));
+
+    assert!(history.redo(&mut value));
+    assert!(history.redo(&mut value));
+    assert_eq!(value, 3);
+    assert!(!history.redo(&mut value));
+}
+
+#[test]
+fn new_command_clears_redo() {
+    let mut history = History::new(usize::MAX);
+    let mut value = 0;
+
+    history.execute(Add(1), &mut value);
+    history.undo(&mut value);
+    assert!(history.can_redo());
+
+    history.execute(Add(5), &mut value);
+    assert!(!history.can_redo());
+    assert_eq!(value, 5);
+}
+
+#[test]
+fn group_merges_into_one_step() {
+    let mut history = History::new(usize::MAX);
+    let mut value = 0;
+
+    history.execute(Add(10), &mut value);
+    history.begin_group();
+    for _ in 0..5 {
+        history.execute(Add(1), &mut value);
+    }
+    history.end_group();
+    history.execute(Add(100), &mut value);
+    assert_eq!(history.undo_len(), 3);
+
+    history.undo(&mut value);
+    history.undo(&mut value);
+    assert_eq!(value, 10);
+}
+
+#[test]
+fn commands_outside_groups_are_not_merged() {
+    let mut history = History::new(usize::MAX);
+    let mut value = 0;
+
+    history.execute(Add(1), &mut value);
+    history.execute(Add(1), &mut value);
+    assert_eq!(history.undo_len(), 2);
+}
+
+#[test]
+fn memory_cap_drops_oldest_commands() {
+    let size = std::mem::size_of::<Add>();
+    let mut history = History::new(size * 3);
+    let mut value = 0;
+
+    for _ in 0..10 {
+        history.execute(Add(1), &mut value);
+    }
+    assert_eq!(history.undo_len(), 3);
+    assert!(history.size_bytes() <= size * 3);
+
+    while history.undo(&mut value) {}
+    assert_eq!(value, 7);
+}
+
+fn outline_document() -> Document {
+    let outline = ShapeType::Pentagon.outline().points;
+    Document {
+        shapes: vec![ShapeNode::new(ShapeGeometry::Outline(outline))],
+    }
+}
+
+fn vertex_position(document: &Document, index: usize) -> Vec2 {
+    match &document.shapes[0].geometry {
+        ShapeGeometry::Outline(points) => {
+            Vec2::new(points[index].position[0], points[index].position[1])
+        }
+        _ => panic!("expected an outline"),
+    }
+}
+
+#[test]
+fn vertex_drag_undoes_in_one_step() {
+    let mut document = outline_document();
+    let original = document.clone();
+    let start = vertex_position(&document, 2);
+    let mut history = History::new(usize::MAX);
+
+    history.begin_group();
+    for step in 1..=4 {
+        let to = start + Vec2::splat(0.1 * step as f32);
+        let command = EditCommand::MoveVertices {
+            shape: 0,
+            vertices: vec![2],
+            from: vec![start],
+            to: vec![to],
+        };
+        history.execute(command, &mut document);
+    }
+    history.end_group();
+
+    assert_eq!(history.undo_len(), 1);
+    assert!((vertex_position(&document, 2) - (start + Vec2::splat(0.4))).length() < 1e-6);
+
+    history.undo(&mut document);
+    assert_eq!(document, original);
+    history.redo(&mut document);
+    assert!((vertex_position(&document, 2) - (start + Vec2::splat(0.4))).length() < 1e-6);
+}
+
+#[test]
+fn add_and_remove_shapes_round_trip() {
+    let mut document = outline_document();
+    let original = document.clone();
+    let mut history = History::new(usize::MAX);
+
+    let star = ShapeNode::new(ShapeGeometry::Preset(ShapeType::Star));
+    history.execute(
+        EditCommand::AddShape {
+            index: 1,
+            shape: star.clone(),
+        },
+        &mut document,
+    );
+    let first = document.shapes[0].clone();
+    history.execute(
+        EditCommand::RemoveShape {
+            index: 0,
+            shape: first,
+        },
+        &mut document,
+    );
+    assert_eq!(document.shapes, vec![star]);
+
+    history.undo(&mut document);
+    history.undo(&mut document);
+    assert_eq!(document, original);
+}
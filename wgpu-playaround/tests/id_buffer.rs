@@ -0,0 +1,65 @@
+mod common;
+
+use common::request_device;
+use wgpu_playaround::{
+    models::{headless::HeadlessRenderer, scene::Scene},
+    picking::gpu::{IdBuffer, IdPick, decode_object_id},
+};
+
+#[test]
+fn the_background_is_not_an_object() {
+    assert_eq!(decode_object_id(0), None);
+    assert_eq!(decode_object_id(1), Some(0));
+    assert_eq!(decode_object_id(u32::MAX), Some(u32::MAX - 1));
+}
+
+/// Draws the scene with an ID buffer and reads back the pixel at `(x, y)`.
+fn pick(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    scene: &Scene,
+    ids: &mut IdBuffer,
+    (x, y): (f64, f64),
+) -> Option<IdPick> {
+    let target = device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width: 64,
+            height: 64,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: HeadlessRenderer::FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    let view = target.create_view(&Default::default());
+    let mut encoder = device.create_command_encoder(&Default::default());
+    scene.draw(&mut encoder, &view, Some(&ids.view));
+    ids.request(&mut encoder, x, y);
+    queue.submit(std::iter::once(encoder.finish()));
+    ids.after_submit();
+    device.poll(wgpu::PollType::wait_indefinitely()).unwrap();
+    ids.poll(device)
+}
+
+#[test]
+#[ignore = "needs a graphics adapter"]
+fn readbacks_find_the_shape_under_the_cursor() {
+    let (device, queue) = request_device().expect("no graphics adapter");
+    let mut scene = Scene::new(&device, HeadlessRenderer::FORMAT);
+    scene.set_viewport_size(64, 64);
+    scene.update(&device, &queue, 0.0);
+    let mut ids = IdBuffer::new(&device, 64, 64);
+
+    // The default pentagon covers the middle of the screen
+    let center = pick(&device, &queue, &scene, &mut ids, (32.0, 32.0)).unwrap();
+    assert_eq!(center.object_id, Some(0));
+    assert_eq!(center.pixel, (32, 32));
+    let corner = pick(&device, &queue, &scene, &mut ids, (1.0, 1.0)).unwrap();
+    assert_eq!(corner.object_id, None);
+    // Outside the window nothing is requested
+    assert_eq!(pick(&device, &queue, &scene, &mut ids, (64.0, 10.0)), None);
+}
@@ -0,0 +1,162 @@
+use glam::{Vec3, Vec4};
+use wgpu_playaround::{
+    geometry::normals::{flat_normals, smooth_normals},
+    lighting::{Light, Lighting},
+    models::vertex::Vertex,
+};
+
+fn vertex(x: f32, y: f32, z: f32) -> Vertex {
+    Vertex {
+        position: [x, y, z],
+        color: [1.0, 0.5, 0.25],
+    }
+}
+
+/// Two triangles folded 90 degrees along the shared edge on the y axis.
+fn folded() -> (Vec<Vertex>, Vec<u16>) {
+    let vertices = vec![
+        vertex(0.0, 0.0, 0.0),
+        vertex(0.0, 1.0, 0.0),
+        vertex(1.0, 0.0, 0.0),
+        vertex(0.0, 0.0, 1.0),
+    ];
+    // The first faces +z, the second +x
+    (vertices, vec![0, 2, 1, 0, 1, 3])
+}
+
+fn assert_close(a: Vec3, b: Vec3) {
+    assert!((a - b).abs().max_element() < 1e-5, "{a} vs {b}");
+}
+
+#[test]
+fn smooth_normals_average_the_faces_around_a_vertex() {
+    let (vertices, indices) = folded();
+    let lit = smooth_normals(&vertices, &indices);
+    assert_eq!(lit.len(), vertices.len());
+    let normal = |i: usize| Vec3::from_array(lit[i].normal);
+    // The shared edge is half way between both faces
+    assert_close(normal(0), Vec3::new(1.0, 0.0, 1.0).normalize());
+    assert_close(normal(1), Vec3::new(1.0, 0.0, 1.0).normalize());
+    assert_close(normal(2), Vec3::Z);
+    assert_close(normal(3), Vec3::X);
+    assert_eq!(lit[2].position, vertices[2].position);
+    assert_eq!(lit[2].color, vertices[2].color);
+}
+
+#[test]
+fn flat_normals_split_the_vertices_per_face() {
+    let (vertices, indices) = folded();
+    let (lit, flat_indices) = flat_normals(&vertices, &indices).unwrap();
+    assert_eq!(lit.len(), 6);
+    assert_eq!(flat_indices, [0, 1, 2, 3, 4, 5]);
+    for v in &lit[..3] {
+        assert_close(Vec3::from_array(v.normal), Vec3::Z);
+    }
+    for v in &lit[3..] {
+        assert_close(Vec3::from_array(v.normal), Vec3::X);
+    }
+    assert_eq!(lit[4].position, vertices[1].position);
+
+    // Every triangle is kept or the whole mesh fails
+    let indices = indices.repeat(u16::MAX as usize / 6);
+    assert_eq!(flat_normals(&vertices, &indices).unwrap().0.len(), 65532);
+    let indices = [indices.as_slice(), &[0, 1, 2, 0, 1, 2]].concat();
+    assert!(flat_normals(&vertices, &indices).is_err());
+}
+
+#[test]
+fn unused_and_degenerate_vertices_face_the_camera() {
+    let vertices = vec![
+        vertex(0.0, 0.0, 0.0),
+        vertex(1.0, 0.0, 0.0),
+        vertex(2.0, 0.0, 0.0),
+    ];
+    let lit = smooth_normals(&vertices, &[0, 1, 2]);
+    assert!(lit.iter().all(|v| v.normal == [0.0, 0.0, 1.0]));
+    let lit = smooth_normals(&vertices, &[]);
+    assert!(lit.iter().all(|v| v.normal == [0.0, 0.0, 1.0]));
+}
+
+fn lighting(lights: Vec<Light>) -> Lighting {
+    Lighting {
+        lights,
+        ambient: Vec3::ZERO,
+        specular: 0.0,
+        shininess: 16.0,
+        environment_intensity: 0.0,
+    }
+}
+
+const EYE: Vec4 = Vec4::Z;
+
+#[test]
+fn lambert_follows_the_cosine_of_the_light_angle() {
+    let sun = |direction| {
+        lighting(vec![Light::Directional {
+            direction,
+            color: Vec3::ONE,
+            intensity: 2.0,
+        }])
+    };
+    let white = Vec3::ONE;
+    let head_on = sun(-Vec3::Z).shade(EYE, Vec3::ZERO, Vec3::Z, white);
+    assert_close(head_on, Vec3::splat(2.0));
+    let slanted = sun(Vec3::new(1.0, 0.0, -1.0)).shade(EYE, Vec3::ZERO, Vec3::Z, white);
+    assert_close(slanted, Vec3::splat(2.0 * std::f32::consts::FRAC_1_SQRT_2));
+    // Lights behind the surface add nothing, the ambient term remains
+    let mut behind = sun(Vec3::Z);
+    behind.ambient = Vec3::splat(0.1);
+    let color = Vec3::new(1.0, 0.5, 0.0);
+    assert_close(behind.shade(EYE, Vec3::ZERO, Vec3::Z, color), color * 0.1);
+}
+
+#[test]
+fn blinn_phong_highlight_peaks_at_the_mirror_direction() {
+    let mut shiny = lighting(vec![Light::Directional {
+        direction: Vec3::new(1.0, 0.0, -1.0),
+        color: Vec3::ONE,
+        intensity: 1.0,
+    }]);
+    shiny.specular = 1.0;
+    let black = Vec3::ZERO;
+    // Mirrored about the normal the light leaves towards +x
+    let mirror = shiny.shade(Vec4::new(1.0, 0.0, 1.0, 0.0), Vec3::ZERO, Vec3::Z, black);
+    assert_close(mirror, Vec3::ONE);
+    let off = shiny.shade(EYE, Vec3::ZERO, Vec3::Z, black);
+    assert!(off.x > 0.0 && off.x < 0.5, "{off}");
+    // A camera position instead of a direction
+    let mirror = shiny.shade(Vec4::new(3.0, 0.0, 3.0, 1.0), Vec3::ZERO, Vec3::Z, black);
+    assert_close(mirror, Vec3::ONE);
+}
+
+#[test]
+fn point_and_spot_lights_fade_out_with_distance_and_angle() {
+    let point = Light::Point {
+        position: Vec3::new(0.0, 0.0, 1.0),
+        color: Vec3::ONE,
+        intensity: 1.0,
+        range: 4.0,
+    };
+    let (to_light, near) = point.incident(Vec3::ZERO);
+    assert_close(to_light, Vec3::Z);
+    let (_, far) = point.incident(Vec3::new(0.0, 0.0, -1.0));
+    assert!(far.x < near.x * 0.25 && far.x > 0.0, "{near} {far}");
+    let (_, outside) = point.incident(Vec3::new(0.0, 0.0, -3.0));
+    assert_eq!(outside, Vec3::ZERO);
+
+    let spot = Light::Spot {
+        position: Vec3::new(0.0, 0.0, 1.0),
+        direction: -Vec3::Z,
+        color: Vec3::ONE,
+        intensity: 1.0,
+        range: 4.0,
+        inner_angle: 20f32.to_radians(),
+        outer_angle: 30f32.to_radians(),
+    };
+    assert_eq!(spot.incident(Vec3::ZERO).1, near);
+    let edge = 25f32.to_radians().tan();
+    let (_, half) = spot.incident(Vec3::new(edge, 0.0, 0.0));
+    let (_, unlit) = spot.incident(Vec3::new(1.0, 0.0, 0.0));
+    assert!(half.x > 0.0 && half.x < point.incident(Vec3::new(edge, 0.0, 0.0)).1.x);
+    assert_eq!(unlit, Vec3::ZERO);
+}
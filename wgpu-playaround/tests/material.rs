@@ -0,0 +1,199 @@
+use std::f32::consts::PI;
+
+use glam::{Vec2, Vec3, Vec4};
+use wgpu_playaround::{
+    lighting::{Light, Lighting},
+    material::{
+        Image, Material, Surface,
+        brdf::{
+            DIELECTRIC_F0, base_reflectance, cook_torrance, distribution_ggx, fresnel_schlick,
+            geometry_smith,
+        },
+        shade,
+    },
+};
+
+fn assert_close(a: Vec3, b: Vec3) {
+    assert!((a - b).abs().max_element() < 1e-4, "{a} vs {b}");
+}
+
+#[test]
+fn ggx_distribution_integrates_to_one_over_the_hemisphere() {
+    // Projected onto the surface the microfacets cover it exactly once
+    for roughness in [0.2, 0.5, 1.0] {
+        let steps = 20_000;
+        let integral: f32 = (0..steps)
+            .map(|i| {
+                let theta = (i as f32 + 0.5) / steps as f32 * PI / 2.0;
+                let cos = theta.cos();
+                distribution_ggx(cos, roughness) * cos * theta.sin() * 2.0 * PI * (PI / 2.0)
+                    / steps as f32
+            })
+            .sum();
+        assert!((integral - 1.0).abs() < 1e-2, "{roughness}: {integral}");
+    }
+}
+
+#[test]
+fn fresnel_goes_from_base_reflectance_to_white_at_grazing_angles() {
+    let f0 = Vec3::new(0.9, 0.6, 0.2);
+    assert_close(fresnel_schlick(1.0, f0), f0);
+    assert_close(fresnel_schlick(0.0, f0), Vec3::ONE);
+    assert_close(base_reflectance(f0, 0.0), Vec3::splat(DIELECTRIC_F0));
+    assert_close(base_reflectance(f0, 1.0), f0);
+}
+
+#[test]
+fn geometry_term_only_darkens() {
+    for roughness in [0.1, 0.5, 1.0] {
+        assert!((geometry_smith(1.0, 1.0, roughness) - 1.0).abs() < 1e-6);
+        let grazing = geometry_smith(0.1, 1.0, roughness);
+        assert!(grazing > 0.0 && grazing < 1.0);
+    }
+}
+
+#[test]
+fn cook_torrance_is_reciprocal_and_dark_below_the_horizon() {
+    let normal = Vec3::Z;
+    let a = Vec3::new(0.3, 0.2, 1.0).normalize();
+    let b = Vec3::new(-0.5, 0.1, 0.7).normalize();
+    let base = Vec3::new(0.8, 0.4, 0.2);
+    // Without the cosine of the light angle the BRDF is symmetric
+    let brdf = |to_eye: Vec3, to_light: Vec3| {
+        cook_torrance(normal, to_eye, to_light, base, 0.5, 0.4) / normal.dot(to_light)
+    };
+    assert_close(brdf(a, b), brdf(b, a));
+    assert_eq!(cook_torrance(normal, a, -b, base, 0.5, 0.4), Vec3::ZERO);
+}
+
+#[test]
+fn metals_have_no_diffuse_reflection() {
+    let normal = Vec3::Z;
+    let to_light = Vec3::new(0.6, 0.0, 0.8);
+    // Far away from the mirror direction only the diffuse part is left
+    let to_eye = Vec3::new(0.6, 0.0, 0.8);
+    let metal = cook_torrance(normal, to_eye, to_light, Vec3::ONE, 1.0, 0.05);
+    let plastic = cook_torrance(normal, to_eye, to_light, Vec3::ONE, 0.0, 0.05);
+    assert!(metal.max_element() < 1e-3, "{metal}");
+    let lambert = Vec3::splat(0.8 / PI);
+    assert!(plastic.cmplt(lambert).all() && plastic.cmpgt(lambert * 0.9).all());
+}
+
+#[test]
+fn reflected_energy_stays_below_the_incoming_energy() {
+    let to_light = Vec3::new(0.0, 0.5, 1.0).normalize();
+    for roughness in [0.1, 0.5, 1.0] {
+        for metallic in [0.0, 1.0] {
+            // Integrated over all view directions
+            let steps = 200;
+            let mut total = Vec3::ZERO;
+            for i in 0..steps {
+                let theta = (i as f32 + 0.5) / steps as f32 * PI / 2.0;
+                for j in 0..steps {
+                    let phi = (j as f32 + 0.5) / steps as f32 * 2.0 * PI;
+                    let to_eye = Vec3::new(
+                        theta.sin() * phi.cos(),
+                        theta.sin() * phi.sin(),
+                        theta.cos(),
+                    );
+                    let solid_angle =
+                        theta.sin() * (PI / 2.0 / steps as f32) * (2.0 * PI / steps as f32);
+                    total +=
+                        cook_torrance(Vec3::Z, to_eye, to_light, Vec3::ONE, metallic, roughness)
+                            * theta.cos()
+                            * solid_angle;
+                }
+            }
+            let incoming = to_light.z;
+            assert!(
+                total.max_element() < incoming * 1.05,
+                "{roughness} {metallic}: {total} of {incoming}"
+            );
+        }
+    }
+}
+
+#[test]
+fn surface_multiplies_maps_with_factors() {
+    let material = Material {
+        base_color_factor: Vec4::new(0.5, 1.0, 1.0, 1.0),
+        metallic_factor: 0.5,
+        roughness_factor: 0.5,
+        emissive_factor: Vec3::splat(2.0),
+        occlusion_strength: 0.5,
+        base_color_texture: Some(Image::from_fn(2, 1, |x, _| {
+            if x == 0 {
+                [255, 255, 255, 255]
+            } else {
+                [0, 0, 0, 255]
+            }
+        })),
+        metallic_roughness_texture: Some(Image::from_fn(1, 1, |_, _| [0, 255, 255, 255])),
+        occlusion_texture: Some(Image::from_fn(1, 1, |_, _| [0, 0, 0, 255])),
+        emissive_texture: Some(Image::from_fn(1, 1, |_, _| [188, 188, 188, 255])),
+        ..Material::default()
+    };
+    let surface = material.surface(Vec3::new(1.0, 0.5, 1.0), Vec2::new(0.25, 0.5));
+    assert_close(surface.base_color, Vec3::new(0.5, 0.5, 1.0));
+    assert!((surface.metallic - 0.5).abs() < 1e-6);
+    assert!((surface.roughness - 0.5).abs() < 1e-6);
+    assert!((surface.occlusion - 0.5).abs() < 1e-6);
+    // sRGB 188 is about half the linear intensity
+    assert!(
+        (surface.emissive.x - 1.0).abs() < 0.01,
+        "{}",
+        surface.emissive
+    );
+    // Texture coordinates wrap around
+    let wrapped = material.surface(Vec3::ONE, Vec2::new(1.75, -0.5));
+    assert_close(wrapped.base_color, Vec3::ZERO);
+}
+
+#[test]
+fn png_maps_decode_to_rgba() {
+    let mut bytes = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut bytes, 2, 1);
+        encoder.set_color(png::ColorType::GrayscaleAlpha);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&[10, 255, 200, 128]).unwrap();
+    }
+    let image = Image::decode_png(&bytes).unwrap();
+    assert_eq!((image.width, image.height), (2, 1));
+    assert_eq!(image.pixels, [10, 10, 10, 255, 200, 200, 200, 128]);
+}
+
+#[test]
+fn shade_adds_ambient_emissive_and_each_light() {
+    let lighting = Lighting {
+        lights: vec![Light::Directional {
+            direction: Vec3::new(0.0, -0.6, -0.8),
+            color: Vec3::ONE,
+            intensity: 2.0,
+        }],
+        ambient: Vec3::splat(0.1),
+        ..Lighting::default()
+    };
+    let surface = Surface {
+        base_color: Vec3::new(0.9, 0.5, 0.1),
+        metallic: 0.2,
+        roughness: 0.6,
+        occlusion: 0.5,
+        emissive: Vec3::new(0.0, 0.0, 0.3),
+    };
+    let to_eye = Vec3::Z;
+    let color = shade(&lighting, Vec4::Z, Vec3::ZERO, Vec3::Z, &surface);
+    let expected = 0.1 * surface.base_color * 0.5
+        + surface.emissive
+        + 2.0
+            * cook_torrance(
+                Vec3::Z,
+                to_eye,
+                Vec3::new(0.0, 0.6, 0.8),
+                surface.base_color,
+                0.2,
+                0.6,
+            );
+    assert_close(color, expected);
+}
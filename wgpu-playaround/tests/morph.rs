@@ -0,0 +1,225 @@
+use wgpu_playaround::{
+    enums::ShapeType,
+    geometry::{
+        morph::Morph,
+        outline::Outline,
+        triangulate::{signed_area, triangulate_polygon},
+    },
+    models::vertex::Vertex,
+};
+
+fn outline(points: &[[f32; 2]]) -> Outline {
+    Outline {
+        points: points
+            .iter()
+            .map(|&[x, y]| Vertex {
+                position: [x, y, 0.0],
+                color: [1.0; 3],
+            })
+            .collect(),
+    }
+}
+
+/// Checks every triangle winds counter-clockwise with some area and that
+/// together they cover the polygon exactly once.
+fn assert_covers(points: &[[f32; 2]], indices: &[u16]) {
+    assert_eq!(indices.len() % 3, 0);
+    let mut total = 0.0;
+    for triangle in indices.chunks_exact(3) {
+        let corners = [0, 1, 2].map(|k| points[triangle[k] as usize]);
+        let area = signed_area(&corners);
+        assert!(area > 0.0, "triangle {triangle:?} has area {area}");
+        total += area;
+    }
+    let expected = signed_area(points).abs();
+    assert!(
+        (total / expected - 1.0).abs() < 1e-5,
+        "triangles cover {total}, the polygon {expected}"
+    );
+}
+
+#[test]
+fn concave_outlines_are_covered_once() {
+    let star = ShapeType::Star.outline().positions_2d();
+    let indices = triangulate_polygon(&star).unwrap();
+    assert_eq!(indices.len(), (star.len() - 2) * 3);
+    assert_covers(&star, &indices);
+
+    // Clockwise input still gives counter-clockwise triangles
+    let mut clockwise = star.clone();
+    clockwise.reverse();
+    assert_covers(&clockwise, &triangulate_polygon(&clockwise).unwrap());
+
+    let l_shape = [
+        [0.0, 0.0],
+        [2.0, 0.0],
+        [2.0, 1.0],
+        [1.0, 1.0],
+        [1.0, 2.0],
+        [0.0, 2.0],
+    ];
+    assert_covers(&l_shape, &triangulate_polygon(&l_shape).unwrap());
+}
+
+#[test]
+fn collinear_and_repeated_points_get_no_slivers() {
+    let with_midpoints = [
+        [0.0, 0.0],
+        [0.5, 0.0],
+        [1.0, 0.0],
+        [1.0, 0.5],
+        [1.0, 1.0],
+        [0.0, 1.0],
+        [0.0, 0.5],
+    ];
+    let indices = triangulate_polygon(&with_midpoints).unwrap();
+    assert_covers(&with_midpoints, &indices);
+    assert_eq!(indices.len(), 2 * 3);
+
+    let repeated = [
+        [0.0, 0.0],
+        [1.0, 0.0],
+        [1.0, 0.0],
+        [1.0, 1.0],
+        [0.0, 1.0],
+        [0.0, 0.0],
+    ];
+    let indices = triangulate_polygon(&repeated).unwrap();
+    assert_covers(&repeated, &indices);
+    assert_eq!(indices.len(), 2 * 3);
+
+    // A resampled star has runs of points along every edge
+    let dense = ShapeType::Star.outline().resample(200).positions_2d();
+    assert_covers(&dense, &triangulate_polygon(&dense).unwrap());
+
+    // Nothing to fill
+    assert!(
+        triangulate_polygon(&[[0.0, 0.0], [1.0, 1.0], [2.0, 2.0]])
+            .unwrap()
+            .is_empty()
+    );
+    assert!(
+        triangulate_polygon(&[[0.0, 0.0], [1.0, 1.0]])
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn tiny_outlines_are_triangulated_like_big_ones() {
+    let star: Vec<[f32; 2]> = ShapeType::Star
+        .outline()
+        .positions_2d()
+        .into_iter()
+        .map(|[x, y]| [x * 1e-4, y * 1e-4])
+        .collect();
+    let indices = triangulate_polygon(&star).unwrap();
+    assert_eq!(indices.len(), (star.len() - 2) * 3);
+    assert_covers(&star, &indices);
+}
+
+#[test]
+fn crossing_and_oversized_outlines_are_errors() {
+    let bow_tie = [[0.0, 0.0], [1.0, 1.0], [1.0, 0.0], [0.0, 1.0]];
+    assert!(triangulate_polygon(&bow_tie).is_err());
+    // A pentagram drawn in one stroke
+    let pentagram: Vec<[f32; 2]> = (0..5)
+        .map(|i| {
+            let angle = (i * 2) as f32 * std::f32::consts::TAU / 5.0;
+            [angle.cos(), angle.sin()]
+        })
+        .collect();
+    assert!(triangulate_polygon(&pentagram).is_err());
+
+    let huge: Vec<[f32; 2]> = (0..70_000)
+        .map(|i| {
+            let angle = i as f32 / 70_000.0 * std::f32::consts::TAU;
+            [angle.cos(), angle.sin()]
+        })
+        .collect();
+    assert!(triangulate_polygon(&huge).is_err());
+}
+
+#[test]
+fn resampling_keeps_the_corners() {
+    let square = outline(&[[0.0, 0.0], [4.0, 0.0], [4.0, 1.0], [0.0, 1.0]]);
+    let resampled = square.resample(10);
+    assert_eq!(resampled.len(), 10);
+    let positions = resampled.positions_2d();
+    for corner in square.positions_2d() {
+        assert!(positions.contains(&corner));
+    }
+    // The long edges take the extra points
+    let on_bottom = resampled
+        .positions_2d()
+        .iter()
+        .filter(|p| p[1] == 0.0)
+        .count();
+    assert_eq!(on_bottom, 5);
+    assert_eq!(
+        signed_area(&resampled.positions_2d()),
+        signed_area(&square.positions_2d())
+    );
+    // Never fewer points than before
+    assert_eq!(square.resample(2).len(), 4);
+}
+
+#[test]
+fn alignment_rotates_to_the_closest_start() {
+    let square = outline(&[[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]]);
+    let mut shifted = square.clone();
+    shifted.points.rotate_left(3);
+    let aligned = shifted.aligned_to(&square);
+    assert_eq!(aligned.positions_2d(), square.positions_2d());
+}
+
+#[test]
+fn morphs_run_from_one_outline_to_the_other() {
+    let from = ShapeType::Pentagon.outline();
+    let to = ShapeType::Star.outline();
+    let mut morph = Morph::new(&from, &to, 60, 1.0);
+    assert_eq!(morph.progress(), 0.0);
+    assert_eq!(morph.current().len(), 60);
+    let start = signed_area(&morph.current().positions_2d());
+    assert!((start - signed_area(&from.positions_2d())).abs() < 1e-5);
+
+    for _ in 0..10 {
+        let (vertices, indices) = morph.mesh().unwrap();
+        let points: Vec<[f32; 2]> = vertices
+            .iter()
+            .map(|v| [v.position[0], v.position[1]])
+            .collect();
+        assert_covers(&points, &indices);
+        morph.advance(0.1);
+    }
+    assert!(morph.is_finished());
+    assert_eq!(morph.progress(), 1.0);
+    let end = morph.current();
+    assert!((signed_area(&end.positions_2d()) - signed_area(&to.positions_2d())).abs() < 1e-5);
+    for corner in to.positions_2d() {
+        let closest = end
+            .positions_2d()
+            .iter()
+            .map(|p| (p[0] - corner[0]).hypot(p[1] - corner[1]))
+            .fold(f32::MAX, f32::min);
+        assert!(closest < 1e-5, "{corner:?} is {closest} away");
+    }
+    // Eased, so half way in time is half way in progress
+    let mut half = Morph::new(&from, &to, 60, 1.0);
+    half.advance(0.5);
+    assert_eq!(half.progress(), 0.5);
+    half.advance(0.25);
+    assert!(half.progress() > 0.75);
+}
+
+#[test]
+fn zero_length_morphs_are_finished() {
+    let morph = Morph::new(
+        &ShapeType::Pentagon.outline(),
+        &ShapeType::Star.outline(),
+        10,
+        0.0,
+    );
+    assert!(morph.is_finished());
+    assert_eq!(morph.progress(), 1.0);
+}
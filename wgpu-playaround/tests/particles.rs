@@ -0,0 +1,266 @@
+mod common;
+
+use common::request_device;
+use glam::Vec2;
+use wgpu_playaround::{
+    enums::ShapeType,
+    models::camera::Camera2d,
+    particles::{
+        Attractor, Curve, Emitter, EmitterPoint, Particle, ParticleSettings, ParticleSim,
+        gpu::ParticleSystem, initial_particles, step_particle,
+    },
+};
+
+fn outline(shape: ShapeType) -> Vec<Vec2> {
+    shape
+        .outline()
+        .points
+        .iter()
+        .map(|v| Vec2::new(v.position[0], v.position[1]))
+        .collect()
+}
+
+/// A particle that has just spawned at the origin, at rest.
+fn spawned() -> Particle {
+    let mut particle = Particle::default();
+    particle.generation = 1;
+    particle
+}
+
+fn still(settings: ParticleSettings) -> ParticleSettings {
+    ParticleSettings {
+        gravity: Vec2::ZERO,
+        drag: 0.0,
+        lifetime: 100.0,
+        ..settings
+    }
+}
+
+#[test]
+fn curves_interpolate_and_clamp() {
+    let curve = Curve {
+        keys: vec![(0.25, [0.0, 1.0]), (0.5, [1.0, 1.0]), (1.0, [0.0, 0.0])],
+    };
+    assert_eq!(curve.sample(0.0), [0.0, 1.0]);
+    assert_eq!(curve.sample(0.375), [0.5, 1.0]);
+    assert_eq!(curve.sample(0.75), [0.5, 0.5]);
+    assert_eq!(curve.sample(2.0), [0.0, 0.0]);
+    assert_eq!(Curve::constant([3.0]).sample(0.7), [3.0]);
+    assert_eq!(Curve::<1> { keys: vec![] }.sample(0.5), [1.0]);
+
+    // Keys past the GPU limit are ignored
+    let long = Curve {
+        keys: (0..6).map(|i| (i as f32 / 5.0, [i as f32])).collect(),
+    };
+    assert_eq!(long.sample(1.0), [3.0]);
+}
+
+#[test]
+fn star_tips_point_outwards() {
+    let star = Emitter::tips(&outline(ShapeType::Star));
+    assert_eq!(star.points.len(), 5);
+    for point in &star.points {
+        let position = Vec2::from_array(point.position);
+        let direction = Vec2::from_array(point.direction);
+        assert!((direction.length() - 1.0).abs() < 1e-5);
+        assert!(direction.dot(position) > 0.0);
+    }
+    // Every point of a regular pentagon sticks out as far as its neighbours
+    let pentagon = outline(ShapeType::Pentagon);
+    assert_eq!(
+        Emitter::from_outline(&pentagon).points.len(),
+        pentagon.len()
+    );
+    assert!(Emitter::tips(&[]).points.is_empty());
+}
+
+#[test]
+fn slots_spawn_staggered_over_one_lifetime() {
+    let emitter = Emitter::tips(&outline(ShapeType::Star));
+    let settings = ParticleSettings {
+        lifetime: 1.0,
+        ..ParticleSettings::default()
+    };
+    let mut sim = ParticleSim::new(100, settings, emitter.clone());
+    let dt = 0.1;
+    let mut alive = Vec::new();
+    for _ in 0..10 {
+        sim.step(dt);
+        alive.push(sim.alive().count());
+    }
+    assert_eq!(alive[0], 11);
+    assert!(alive.windows(2).all(|w| w[1] > w[0]), "{alive:?}");
+    assert_eq!(*alive.last().unwrap(), 100);
+
+    // Everything spawns on an emitter point, faster than half the speed
+    let speed = sim.settings.speed;
+    let mut sim = ParticleSim::new(100, sim.settings.clone(), emitter.clone());
+    sim.step(0.0);
+    let particle = sim.particles[0];
+    assert_eq!(particle.generation, 1);
+    assert!(
+        emitter
+            .points
+            .iter()
+            .any(|p| p.position == particle.position)
+    );
+    let velocity = Vec2::from_array(particle.velocity).length();
+    assert!(velocity >= speed * 0.5 && velocity <= speed);
+}
+
+#[test]
+fn particles_respawn_when_their_life_is_over() {
+    let emitter = Emitter::from_outline(&outline(ShapeType::Pentagon));
+    let settings = ParticleSettings {
+        lifetime: 0.5,
+        ..ParticleSettings::default()
+    };
+    let mut particle = initial_particles(1, settings.lifetime)[0];
+    step_particle(0, &mut particle, &settings, &emitter.points, 0.0);
+    assert_eq!(particle.generation, 1);
+    let first = particle.position;
+    for _ in 0..4 {
+        step_particle(0, &mut particle, &settings, &emitter.points, 0.1);
+    }
+    assert_ne!(particle.position, first, "it moved");
+    step_particle(0, &mut particle, &settings, &emitter.points, 0.1);
+    assert_eq!(particle.generation, 2);
+    assert_eq!(particle.age, 0.0);
+    assert!(
+        emitter
+            .points
+            .iter()
+            .any(|p| p.position == particle.position)
+    );
+
+    // Without an emitter nothing comes back
+    let mut particle = spawned();
+    step_particle(0, &mut particle, &settings, &[], 1.0);
+    assert!(!particle.is_alive(settings.lifetime));
+}
+
+#[test]
+fn gravity_matches_semi_implicit_euler() {
+    let settings = ParticleSettings {
+        gravity: Vec2::new(0.0, -2.0),
+        ..still(ParticleSettings::default())
+    };
+    let mut particle = spawned();
+    let (dt, steps) = (0.01, 50);
+    for _ in 0..steps {
+        step_particle(0, &mut particle, &settings, &[], dt);
+    }
+    let n = steps as f32;
+    let expected_velocity = -2.0 * dt * n;
+    let expected_y = -2.0 * dt * dt * n * (n + 1.0) / 2.0;
+    assert!((particle.velocity[1] - expected_velocity).abs() < 1e-4);
+    assert!((particle.position[1] - expected_y).abs() < 1e-4);
+    assert_eq!(particle.position[0], 0.0);
+}
+
+#[test]
+fn drag_slows_and_attractors_pull() {
+    let settings = ParticleSettings {
+        drag: 1.0,
+        ..still(ParticleSettings::default())
+    };
+    let mut particle = spawned();
+    particle.velocity = [1.0, 0.0];
+    for _ in 0..10 {
+        step_particle(0, &mut particle, &settings, &[], 0.1);
+    }
+    let expected = 1.0 / 1.1f32.powi(10);
+    assert!((particle.velocity[0] - expected).abs() < 1e-5);
+
+    let target = Vec2::new(1.0, 0.5);
+    let settings = ParticleSettings {
+        attractor: Some(Attractor {
+            position: target,
+            strength: 1.0,
+        }),
+        ..still(ParticleSettings::default())
+    };
+    let mut particle = spawned();
+    let start = target.length();
+    step_particle(0, &mut particle, &settings, &[], 0.1);
+    let velocity = Vec2::from_array(particle.velocity);
+    assert!(velocity.normalize().dot(target.normalize()) > 0.9999);
+    assert!(Vec2::from_array(particle.position).distance(target) < start);
+}
+
+#[test]
+fn simulation_is_deterministic() {
+    let emitter = Emitter::tips(&outline(ShapeType::Star));
+    let run = || {
+        let mut sim = ParticleSim::new(64, ParticleSettings::default(), emitter.clone());
+        for _ in 0..120 {
+            sim.step(1.0 / 60.0);
+        }
+        sim.particles
+    };
+    let particles = run();
+    assert_eq!(particles, run());
+    // Slots spread over all the tips
+    let used = emitter
+        .points
+        .iter()
+        .filter(|point| {
+            particles.iter().any(|p| {
+                Vec2::from_array(p.position).distance(Vec2::from_array(point.position)) < 0.5
+            })
+        })
+        .count();
+    assert_eq!(used, emitter.points.len());
+}
+
+#[test]
+#[ignore = "needs a graphics adapter"]
+fn gpu_simulation_matches_the_cpu_reference() {
+    let (device, queue) = request_device().expect("no graphics adapter");
+    let format = wgpu::TextureFormat::Rgba8UnormSrgb;
+    let emitter = Emitter {
+        points: vec![
+            EmitterPoint {
+                position: [0.5, 0.0],
+                direction: [1.0, 0.0],
+            },
+            EmitterPoint {
+                position: [-0.5, 0.2],
+                direction: [0.0, 1.0],
+            },
+        ],
+    };
+    let settings = ParticleSettings {
+        lifetime: 0.5,
+        attractor: Some(Attractor {
+            position: Vec2::new(0.1, 0.3),
+            strength: 0.5,
+        }),
+        ..ParticleSettings::default()
+    };
+
+    let capacity = 300;
+    let mut gpu = ParticleSystem::new(&device, format, capacity, settings.clone());
+    gpu.set_emitter(&device, &queue, &emitter);
+    let mut cpu = ParticleSim::new(capacity, settings, emitter);
+    let dt = 1.0 / 60.0;
+    for _ in 0..90 {
+        gpu.prepare(&queue, dt, &Camera2d::default().uniform());
+        let mut encoder = device.create_command_encoder(&Default::default());
+        gpu.compute(&mut encoder);
+        queue.submit(std::iter::once(encoder.finish()));
+        cpu.step(dt);
+    }
+
+    let particles = gpu.read_particles(&device, &queue).unwrap();
+    assert_eq!(particles.len(), capacity);
+    for (gpu, cpu) in particles.iter().zip(&cpu.particles) {
+        assert_eq!(gpu.generation, cpu.generation);
+        let position = Vec2::from_array(gpu.position);
+        assert!(
+            position.distance(Vec2::from_array(cpu.position)) < 1e-3,
+            "{gpu:?} {cpu:?}"
+        );
+        assert!((gpu.age - cpu.age).abs() < 1e-4);
+    }
+}
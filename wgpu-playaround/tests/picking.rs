@@ -0,0 +1,166 @@
+use std::sync::Arc;
+
+use glam::{Mat4, Vec2, Vec3};
+use wgpu_playaround::picking::{
+    Picker,
+    bvh::{Bvh, barycentric},
+    cursor_to_ndc, ndc_to_world,
+};
+
+/// A `columns` by `rows` grid of unit squares, two triangles each.
+fn grid(columns: u16, rows: u16) -> (Vec<[f32; 3]>, Vec<u16>) {
+    let mut positions = Vec::new();
+    for y in 0..=rows {
+        for x in 0..=columns {
+            positions.push([x as f32, y as f32, 0.0]);
+        }
+    }
+    let mut indices = Vec::new();
+    for y in 0..rows {
+        for x in 0..columns {
+            let a = y * (columns + 1) + x;
+            let (b, c, d) = (a + 1, a + columns + 2, a + columns + 1);
+            indices.extend_from_slice(&[a, b, c, a, c, d]);
+        }
+    }
+    (positions, indices)
+}
+
+fn positions_2d(positions: &[[f32; 3]]) -> Vec<Vec2> {
+    positions.iter().map(|p| Vec2::new(p[0], p[1])).collect()
+}
+
+#[test]
+fn barycentric_weights_rebuild_the_point() {
+    let (a, b, c) = (Vec2::ZERO, Vec2::new(2.0, 0.0), Vec2::new(0.0, 2.0));
+    let p = Vec2::new(0.5, 0.25);
+    let [u, v, w] = barycentric(p, a, b, c).unwrap();
+    assert!((a * u + b * v + c * w - p).length() < 1e-6);
+    assert!(barycentric(Vec2::new(1.5, 1.5), a, b, c).is_none());
+    // Clockwise triangles are hit too
+    assert!(barycentric(p, a, c, b).is_some());
+    // Flat triangles cover nothing
+    assert!(barycentric(Vec2::new(1.0, 0.0), a, b, Vec2::new(4.0, 0.0)).is_none());
+}
+
+#[test]
+fn tiny_triangles_can_be_hit() {
+    let scale = 1e-4;
+    let (a, b, c) = (Vec2::ZERO, Vec2::new(scale, 0.0), Vec2::new(0.0, scale));
+    assert!(barycentric(Vec2::splat(scale * 0.25), a, b, c).is_some());
+
+    let bvh = Bvh::new(vec![a, b, c], &[0, 1, 2]);
+    assert_eq!(bvh.hit(Vec2::splat(scale * 0.25)).unwrap().0, 0);
+    assert!(bvh.hit(Vec2::splat(scale)).is_none());
+}
+
+#[test]
+fn the_bvh_finds_the_triangle_under_a_point() {
+    let (positions, indices) = grid(16, 12);
+    let bvh = Bvh::new(positions_2d(&positions), &indices);
+    assert_eq!(bvh.bounds().min, Vec2::ZERO);
+    assert_eq!(bvh.bounds().max, Vec2::new(16.0, 12.0));
+    for (x, y) in [(0, 0), (7, 3), (15, 11), (3, 10)] {
+        // Below the diagonal is the first triangle of the square, above it the second
+        let square = (y * 16 + x) as usize;
+        let lower = Vec2::new(x as f32 + 0.7, y as f32 + 0.2);
+        let upper = Vec2::new(x as f32 + 0.2, y as f32 + 0.7);
+        assert_eq!(bvh.hit(lower).unwrap().0, square * 2);
+        assert_eq!(bvh.hit(upper).unwrap().0, square * 2 + 1);
+        let corners = bvh.triangle(square * 2).unwrap();
+        assert!(corners.contains(&Vec2::new(x as f32, y as f32)));
+    }
+    for miss in [
+        Vec2::new(-0.1, 5.0),
+        Vec2::new(16.1, 5.0),
+        Vec2::new(3.0, 12.5),
+    ] {
+        assert!(bvh.hit(miss).is_none(), "{miss}");
+    }
+    assert!(bvh.triangle(16 * 12 * 2).is_none());
+    assert!(Bvh::new(Vec::new(), &[]).hit(Vec2::ZERO).is_none());
+}
+
+#[test]
+fn overlapping_triangles_pick_the_last_one_drawn() {
+    let (mut positions, mut indices) = grid(8, 8);
+    // One big triangle across the whole grid, drawn after it
+    let first = positions.len() as u16;
+    positions.extend([[0.0, 0.0, 0.0], [8.0, 0.0, 0.0], [0.0, 8.0, 0.0]]);
+    indices.extend([first, first + 1, first + 2]);
+    let bvh = Bvh::new(positions_2d(&positions), &indices);
+    assert_eq!(bvh.hit(Vec2::new(1.5, 1.5)).unwrap().0, 8 * 8 * 2);
+    // Outside the big triangle the grid is on top
+    assert_eq!(bvh.hit(Vec2::new(7.5, 7.5)).unwrap().0, (7 * 8 + 7) * 2 + 1);
+}
+
+#[test]
+fn later_shapes_are_picked_over_earlier_ones() {
+    let (positions, indices) = grid(2, 2);
+    let mut picker = Picker::default();
+    picker.add(0, Mat4::IDENTITY, &positions, &indices);
+    picker.add(
+        1,
+        Mat4::from_translation(Vec3::new(1.0, 1.0, 0.0)),
+        &positions,
+        &indices,
+    );
+    picker.add(
+        2,
+        Mat4::from_translation(Vec3::new(10.0, 0.0, 0.0)) * Mat4::from_scale(Vec3::splat(0.5)),
+        &positions,
+        &indices,
+    );
+
+    assert_eq!(picker.pick(Vec2::new(0.5, 0.5)).unwrap().shape, 0);
+    // Both squares cover this point, the second one is drawn on top
+    let hit = picker.pick(Vec2::new(1.5, 1.8)).unwrap();
+    assert_eq!(hit.shape, 1);
+    assert_eq!(hit.triangle, 1);
+    let corners = picker.triangle(&hit).unwrap();
+    assert!(corners.contains(&Vec2::new(1.0, 1.0)));
+    assert_eq!(picker.pick(Vec2::new(10.9, 0.2)).unwrap().shape, 2);
+    assert!(picker.pick(Vec2::new(11.1, 0.2)).is_none());
+    assert!(picker.pick(Vec2::new(-0.5, 0.5)).is_none());
+
+    let bounds: Vec<_> = picker.bounds().collect();
+    assert_eq!(bounds[1].1.min, Vec2::ONE);
+    assert_eq!(bounds[2].1.max, Vec2::new(11.0, 1.0));
+
+    picker.clear();
+    assert!(picker.pick(Vec2::new(0.5, 0.5)).is_none());
+}
+
+#[test]
+fn cursors_map_to_the_world_plane() {
+    assert_eq!(cursor_to_ndc(0.0, 0.0, 800, 600), Vec2::new(-1.0, 1.0));
+    assert_eq!(cursor_to_ndc(400.0, 450.0, 800, 600), Vec2::new(0.0, -0.5));
+
+    let view_proj = Mat4::from_scale(Vec3::new(2.0, 2.0, 1.0))
+        * Mat4::from_translation(Vec3::new(-1.0, 0.0, 0.0));
+    let world = ndc_to_world(Vec2::new(0.5, -0.5), view_proj.inverse());
+    assert!((world - Vec2::new(1.25, -0.25)).length() < 1e-6);
+
+    // A perspective camera looking down at the plane from an angle
+    let eye = Vec3::new(0.0, -2.0, 3.0);
+    let view = Mat4::look_at_rh(eye, Vec3::ZERO, Vec3::Y);
+    let proj = Mat4::perspective_rh(1.0, 1.0, 0.1, 10.0);
+    let target = Vec2::new(0.3, 0.4);
+    let ndc = (proj * view).project_point3(target.extend(0.0)).truncate();
+    let world = ndc_to_world(ndc, (proj * view).inverse());
+    assert!((world - target).length() < 1e-4, "{world}");
+}
+
+#[test]
+fn shapes_can_share_a_hierarchy_under_different_transforms() {
+    let (positions, indices) = grid(2, 2);
+    let local = positions.iter().map(|p| Vec2::new(p[0], p[1])).collect();
+    let bvh = Arc::new(Bvh::new(local, &indices));
+    let mut picker = Picker::default();
+    picker.add_bvh(0, Mat4::IDENTITY, bvh.clone());
+    picker.add_bvh(1, Mat4::from_translation(Vec3::new(5.0, 0.0, 0.0)), bvh);
+
+    assert_eq!(picker.pick(Vec2::new(0.5, 0.5)).unwrap().shape, 0);
+    assert_eq!(picker.pick(Vec2::new(6.5, 1.5)).unwrap().shape, 1);
+    assert!(picker.pick(Vec2::new(3.0, 1.0)).is_none());
+}
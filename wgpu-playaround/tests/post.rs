@@ -0,0 +1,73 @@
+use glam::Vec2;
+use wgpu_playaround::post::{Effect, EffectChain};
+
+fn names(chain: &EffectChain) -> Vec<&'static str> {
+    chain.effects.iter().map(Effect::name).collect()
+}
+
+fn chain() -> EffectChain {
+    let mut chain = EffectChain::default();
+    chain.push(Effect::Grayscale { amount: 1.0 });
+    chain.push(Effect::Vignette { strength: 0.5 });
+    chain.push(Effect::Pixelate { size: 4.0 });
+    chain
+}
+
+#[test]
+fn effects_can_be_removed_and_reordered() {
+    let mut chain = chain();
+    assert_eq!(chain.shift(0, 1), Some(1));
+    assert_eq!(names(&chain), ["Vignette", "Grayscale", "Pixelate"]);
+    // Moves past either end stop there
+    assert_eq!(chain.shift(1, 5), Some(2));
+    assert_eq!(names(&chain), ["Vignette", "Pixelate", "Grayscale"]);
+    assert_eq!(chain.shift(2, -5), Some(0));
+    assert_eq!(names(&chain), ["Grayscale", "Vignette", "Pixelate"]);
+    assert_eq!(chain.shift(3, 1), None);
+
+    assert_eq!(chain.remove(1), Some(Effect::Vignette { strength: 0.5 }));
+    assert_eq!(chain.remove(2), None);
+    assert_eq!(names(&chain), ["Grayscale", "Pixelate"]);
+    chain.remove(0);
+    chain.remove(0);
+    assert!(chain.is_empty());
+    assert!(chain.passes().is_empty());
+}
+
+#[test]
+fn blurs_take_a_pass_per_axis() {
+    let mut chain = chain();
+    chain.push(Effect::Blur { radius: 3.0 });
+    chain.shift(3, -1);
+    let passes = chain.passes();
+    let entry_points: Vec<_> = passes.iter().map(|p| p.entry_point).collect();
+    assert_eq!(
+        entry_points,
+        [
+            "fs_grayscale",
+            "fs_vignette",
+            "fs_blur",
+            "fs_blur",
+            "fs_pixelate"
+        ]
+    );
+    assert_eq!(passes[2].direction, Vec2::X);
+    assert_eq!(passes[3].direction, Vec2::Y);
+    assert!(passes[2..4].iter().all(|p| p.amount == 3.0));
+    assert_eq!(passes[0].direction, Vec2::ZERO);
+    assert_eq!(passes[4].amount, 4.0);
+}
+
+#[test]
+fn every_effect_has_an_editable_parameter() {
+    for default in Effect::ALL {
+        let mut effect = default;
+        let (_, value, range) = effect.parameter_mut();
+        assert!(range.contains(value), "{default:?}");
+        *value = *range.end();
+        let mut chain = EffectChain::default();
+        chain.push(effect);
+        assert_eq!(chain.passes()[0].amount, *range.end());
+        assert_eq!(chain.passes()[0].entry_point, default.entry_point());
+    }
+}
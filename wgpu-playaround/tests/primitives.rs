@@ -0,0 +1,230 @@
+mod common;
+
+use std::f32::consts::PI;
+
+use common::{corners, open_edges, volume};
+use glam::Vec3;
+use wgpu_playaround::geometry::{
+    normals::triangle_normal,
+    primitives::{Mesh3d, capsule, cone, cube, cylinder, icosphere, plane, torus, uv_sphere},
+};
+
+/// Every index points at a vertex and every vertex has a unit normal.
+fn assert_well_formed(mesh: &Mesh3d) {
+    let count = mesh.vertex_count();
+    assert!(count > 0);
+    assert_eq!(mesh.normals.len(), count);
+    assert_eq!(mesh.uvs.len(), count);
+    assert_eq!(mesh.indices.len() % 3, 0);
+    assert!(mesh.indices.iter().all(|&i| (i as usize) < count));
+    for normal in &mesh.normals {
+        assert!((normal.length() - 1.0).abs() < 1e-5, "{normal}");
+    }
+    assert!(mesh.uvs.iter().all(|uv| uv.is_finite()));
+}
+
+/// Triangles face the same way as the normals of their vertices.
+fn assert_faces_match_normals(mesh: &Mesh3d) {
+    for triangle in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = corners(mesh, triangle);
+        let face = triangle_normal(a, b, c).normalize();
+        for &i in triangle {
+            let normal = mesh.normals[i as usize];
+            assert!(
+                face.dot(normal) > 0.0,
+                "face {face} against normal {normal}"
+            );
+        }
+    }
+}
+
+fn assert_closed(mesh: &Mesh3d, expected_volume: f32, tolerance: f32) {
+    assert_well_formed(mesh);
+    assert_eq!(open_edges(mesh), 0);
+    assert_faces_match_normals(mesh);
+    let volume = volume(mesh);
+    assert!(
+        (volume / expected_volume - 1.0).abs() < tolerance,
+        "volume {volume}, expected {expected_volume}"
+    );
+}
+
+#[test]
+fn cubes_are_closed_with_hard_edges() {
+    for subdivisions in [1, 3] {
+        let mesh = cube(2.0, subdivisions);
+        let quads = 6 * subdivisions * subdivisions;
+        assert_eq!(mesh.triangle_count(), 2 * quads as usize);
+        assert_closed(&mesh, 8.0, 1e-5);
+        for (position, normal) in mesh.positions.iter().zip(&mesh.normals) {
+            // The normal is the axis of the face the vertex is on
+            assert_eq!(position.dot(*normal), 1.0);
+        }
+        assert!(
+            mesh.uvs
+                .iter()
+                .all(|uv| uv.min_element() >= 0.0 && uv.max_element() <= 1.0)
+        );
+    }
+}
+
+#[test]
+fn planes_face_up_with_an_open_border() {
+    let mesh = plane(4.0, 5);
+    assert_well_formed(&mesh);
+    assert_eq!(mesh.vertex_count(), 36);
+    assert_eq!(mesh.triangle_count(), 50);
+    assert!(mesh.normals.iter().all(|&normal| normal == Vec3::Y));
+    assert_faces_match_normals(&mesh);
+    assert_eq!(open_edges(&mesh), 4 * 5);
+    // Texture coordinates cover the plane once
+    for (position, uv) in mesh.positions.iter().zip(&mesh.uvs) {
+        assert!((uv.x - (position.x / 4.0 + 0.5)).abs() < 1e-6);
+        assert!((uv.y - (position.z / 4.0 + 0.5)).abs() < 1e-6);
+    }
+}
+
+fn assert_sphere_normals(mesh: &Mesh3d, center: Vec3, radius: f32) {
+    for (position, normal) in mesh.positions.iter().zip(&mesh.normals) {
+        let expected = (*position - center) / radius;
+        assert!(
+            (expected - *normal).length() < 1e-5,
+            "{normal} at {position}"
+        );
+    }
+}
+
+#[test]
+fn uv_spheres_are_closed_and_point_outwards() {
+    let mesh = uv_sphere(2.0, 48, 24);
+    assert_closed(&mesh, 4.0 / 3.0 * PI * 8.0, 0.01);
+    assert_sphere_normals(&mesh, Vec3::ZERO, 2.0);
+    // The poles are fans of single triangles
+    assert_eq!(mesh.triangle_count(), 48 * (2 * 24 - 2));
+    assert_eq!(mesh.vertex_count(), 49 * 25);
+    // The coarsest sphere is two pyramids on a triangle
+    assert_closed(&uv_sphere(1.0, 3, 2), 3f32.sqrt() / 2.0, 1e-5);
+}
+
+#[test]
+fn icospheres_split_every_triangle_in_four() {
+    for subdivisions in 0..4 {
+        let mesh = icosphere(1.5, subdivisions);
+        assert_eq!(mesh.triangle_count(), 20 * 4usize.pow(subdivisions));
+        assert_sphere_normals(&mesh, Vec3::ZERO, 1.5);
+        assert_well_formed(&mesh);
+        assert_eq!(open_edges(&mesh), 0);
+        assert_faces_match_normals(&mesh);
+    }
+    let mesh = icosphere(1.5, 4);
+    assert_closed(&mesh, 4.0 / 3.0 * PI * 1.5f32.powi(3), 0.01);
+    // No triangle stretches across the texture seam
+    for triangle in mesh.indices.chunks_exact(3) {
+        let u = triangle.iter().map(|&i| mesh.uvs[i as usize].x);
+        let (low, high) = u.fold((f32::MAX, f32::MIN), |(low, high), u| {
+            (low.min(u), high.max(u))
+        });
+        assert!(high - low < 0.25, "u from {low} to {high}");
+    }
+}
+
+/// Vertex normals point away from the `+Y` axis on the sides and along it
+/// on flat caps.
+fn assert_normals_leave_axis(mesh: &Mesh3d) {
+    for (position, normal) in mesh.positions.iter().zip(&mesh.normals) {
+        let radial = Vec3::new(position.x, 0.0, position.z);
+        if normal.y.abs() == 1.0 {
+            assert!(normal.y * position.y > 0.0, "{normal} at {position}");
+        } else {
+            assert!(normal.dot(radial) >= 0.0, "{normal} at {position}");
+        }
+    }
+}
+
+#[test]
+fn cylinders_are_closed_with_capped_ends() {
+    let mesh = cylinder(0.5, 2.0, 64, 3);
+    assert_closed(&mesh, PI * 0.25 * 2.0, 0.01);
+    assert_normals_leave_axis(&mesh);
+    // The side has no slope
+    let side = mesh.normals.iter().filter(|normal| normal.y == 0.0).count();
+    assert_eq!(side, 65 * 4);
+    assert_eq!(mesh.triangle_count(), 64 * 3 * 2 + 2 * 64);
+}
+
+#[test]
+fn cones_close_at_the_tip() {
+    let (radius, height) = (1.0, 3.0);
+    let mesh = cone(radius, height, 64, 4);
+    assert_closed(&mesh, PI * radius * radius * height / 3.0, 0.01);
+    assert_normals_leave_axis(&mesh);
+    for (position, normal) in mesh.positions.iter().zip(&mesh.normals) {
+        let radial = Vec3::new(position.x, 0.0, position.z);
+        if normal.y.abs() != 1.0 && radial != Vec3::ZERO {
+            // Perpendicular to the slant from the rim to the tip
+            let rim = radial.normalize() * radius - Vec3::Y * height / 2.0;
+            let slant = Vec3::Y * height / 2.0 - rim;
+            assert!(normal.dot(slant).abs() < 1e-5 && normal.y > 0.0, "{normal}");
+        }
+    }
+}
+
+#[test]
+fn tori_are_closed_around_the_tube() {
+    let (major, minor) = (2.0, 0.5);
+    let mesh = torus(major, minor, 64, 32);
+    assert_closed(&mesh, 2.0 * PI * PI * major * minor * minor, 0.01);
+    assert_eq!(mesh.triangle_count(), 2 * 64 * 32);
+    for (position, normal) in mesh.positions.iter().zip(&mesh.normals) {
+        let ring = Vec3::new(position.x, 0.0, position.z).normalize() * major;
+        assert!(((*position - ring) / minor - *normal).length() < 1e-5);
+    }
+}
+
+#[test]
+fn capsules_join_hemispheres_to_a_cylinder() {
+    let (radius, length) = (0.5, 1.0);
+    let mesh = capsule(radius, length, 48, 12);
+    let expected = PI * radius * radius * length + 4.0 / 3.0 * PI * radius.powi(3);
+    assert_closed(&mesh, expected, 0.01);
+    let height = mesh.positions.iter().map(|p| p.y).fold(f32::MIN, f32::max)
+        - mesh.positions.iter().map(|p| p.y).fold(f32::MAX, f32::min);
+    assert_eq!(height, length + 2.0 * radius);
+    for (position, normal) in mesh.positions.iter().zip(&mesh.normals) {
+        // Nearest point on the segment between the hemisphere centers
+        let center = Vec3::Y * position.y.clamp(-length / 2.0, length / 2.0);
+        assert!(((*position - center) / radius - *normal).length() < 1e-5);
+    }
+}
+
+#[test]
+fn subdivision_is_clamped_to_a_valid_shape() {
+    for mesh in [
+        cube(1.0, 0),
+        plane(1.0, 0),
+        uv_sphere(1.0, 0, 0),
+        cylinder(1.0, 1.0, 0, 0),
+        cone(1.0, 1.0, 0, 0),
+        torus(1.0, 0.25, 0, 0),
+        capsule(1.0, 1.0, 0, 0),
+    ] {
+        assert_well_formed(&mesh);
+        assert!(mesh.triangle_count() > 0);
+    }
+    assert_eq!(icosphere(1.0, 0).triangle_count(), 20);
+}
+
+#[test]
+fn lit_meshes_need_u16_indices() {
+    let mesh = torus(1.0, 0.25, 16, 8);
+    let (vertices, indices) = mesh.lit_mesh([1.0, 0.5, 0.25]).unwrap();
+    assert_eq!(vertices.len(), mesh.vertex_count());
+    assert_eq!(indices.len(), mesh.indices.len());
+    assert_eq!(vertices[3].normal, mesh.normals[3].to_array());
+    assert_eq!(vertices[3].uv, mesh.uvs[3].to_array());
+    assert!(vertices.iter().all(|v| v.color == [1.0, 0.5, 0.25]));
+    assert!(uv_sphere(1.0, 512, 256).lit_mesh([1.0; 3]).is_err());
+    // Textures wrap once around shapes of revolution
+    let u_span = mesh.uvs.iter().map(|uv| uv.x).fold(0.0, f32::max);
+    assert_eq!(u_span, 1.0);
+}
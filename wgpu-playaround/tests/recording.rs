@@ -0,0 +1,90 @@
+use std::path::PathBuf;
+
+use wgpu_playaround::{
+    models::clock::Clock,
+    recording::{RecordingFormat, RecordingSettings, gif_delay},
+};
+
+fn parse(args: &str) -> anyhow::Result<Option<RecordingSettings>> {
+    RecordingSettings::from_args(args.split_whitespace().map(String::from))
+}
+
+#[test]
+fn fixed_clocks_step_one_frame_at_a_time() {
+    let mut clock = Clock::fixed(30);
+    assert_eq!(clock.elapsed(), 0.0);
+    for _ in 0..300 {
+        assert_eq!(clock.tick(), 1.0 / 30.0);
+    }
+    // Counted in frames, so a long recording does not drift
+    assert_eq!(clock.elapsed(), 10.0);
+
+    // Zero frames per second would never move
+    let mut clock = Clock::fixed(0);
+    assert_eq!(clock.tick(), 1.0);
+}
+
+#[test]
+fn all_recording_arguments_are_parsed() {
+    let settings = parse(
+        "--record 3.5 --fps 24 --gif --out clip.gif --size 320x240 \
+         --toggle-every 0.5 --scene a.ron --environment sky.hdr",
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(settings.seconds, 3.5);
+    assert_eq!(settings.fps, 24);
+    assert_eq!(settings.format, RecordingFormat::Gif);
+    assert_eq!(settings.output, PathBuf::from("clip.gif"));
+    assert_eq!((settings.width, settings.height), (320, 240));
+    assert_eq!(settings.toggle_every, Some(0.5));
+    assert_eq!(settings.scene, Some(PathBuf::from("a.ron")));
+    assert_eq!(settings.environment, Some(PathBuf::from("sky.hdr")));
+    assert_eq!(settings.frame_count(), 84);
+
+    let defaults = parse("--record 1").unwrap().unwrap();
+    assert_eq!(defaults.format, RecordingFormat::PngSequence);
+    assert_eq!(defaults.frame_count(), 30);
+    let never = parse("--record 1 --toggle-every 0").unwrap().unwrap();
+    assert_eq!(never.toggle_every, None);
+}
+
+#[test]
+fn windowed_runs_ignore_the_arguments() {
+    assert!(parse("").unwrap().is_none());
+    assert!(parse("--fps 0 -psn_0_12345 whatever").unwrap().is_none());
+}
+
+#[test]
+fn bad_recording_arguments_are_errors() {
+    for args in [
+        "--record",
+        "--record 2 --fps",
+        "--record 2 --unknown",
+        "--record ten",
+        "--record 0",
+        "--record -1",
+        "--record 2 --fps 0",
+        "--record 2 --fps -3",
+        "--record 2 --size 0x0",
+        "--record 2 --size 640x0",
+        "--record 2 --size 640",
+        "--record 2 --size wide",
+    ] {
+        assert!(parse(args).is_err(), "{args}");
+    }
+}
+
+#[test]
+fn gif_delays_keep_to_the_frame_rate() {
+    for fps in [1, 7, 24, 30, 60] {
+        let total: u64 = (0..fps as u64 * 10).map(|i| gif_delay(i, fps) as u64).sum();
+        assert_eq!(total, 1000, "{fps} fps");
+        for i in 0..fps as u64 * 10 {
+            let delay = gif_delay(i, fps) as f32;
+            assert!((delay - 100.0 / fps as f32).abs() <= 1.0, "{fps} fps");
+        }
+    }
+    let delays: Vec<u16> = (0..3).map(|i| gif_delay(i, 30)).collect();
+    assert_eq!(delays, [3, 4, 3]);
+}
@@ -0,0 +1,282 @@
+use glam::Vec2;
+use wgpu_playaround::{
+    document::{
+        Document, ShapeGeometry, ShapeNode, Transform2d,
+        file::{SCENE_FILE_VERSION, SceneDescription, SceneFormat},
+    },
+    enums::{PipelineKind, ShapeType},
+    models::{camera::Camera2d, headless::HeadlessRenderer, vertex::Vertex},
+};
+
+fn vertex(x: f32, y: f32, color: [f32; 3]) -> Vertex {
+    Vertex {
+        position: [x, y, 0.0],
+        color,
+    }
+}
+
+/// A scene using every kind of geometry and non-default settings everywhere.
+fn sample_scene() -> SceneDescription {
+    let mut star = ShapeNode::new(ShapeGeometry::Star {
+        points: 7,
+        outer_radius: 0.4,
+        inner_radius: 0.15,
+    });
+    star.transform = Transform2d {
+        translation: Vec2::new(0.3, -0.2),
+        rotation: 0.7,
+        scale: Vec2::new(1.5, 0.5),
+    };
+    star.color = [0.4, 1.0, 0.4];
+
+    let outline = ShapeNode::new(ShapeGeometry::Outline(vec![
+        vertex(-0.9, -0.9, [1.0, 0.0, 0.0]),
+        vertex(-0.6, -0.9, [0.0, 1.0, 0.0]),
+        vertex(-0.75, -0.6, [0.0, 0.0, 1.0]),
+    ]));
+
+    let mesh = ShapeNode::new(ShapeGeometry::Mesh {
+        vertices: vec![
+            vertex(0.5, 0.5, [0.1, 0.2, 0.3]),
+            vertex(0.9, 0.5, [0.4, 0.5, 0.6]),
+            vertex(0.9, 0.9, [0.7, 0.8, 0.9]),
+            vertex(0.5, 0.9, [1.0, 1.0, 1.0]),
+        ],
+        indices: vec![0, 1, 2, 0, 2, 3],
+    });
+
+    SceneDescription {
+        document: Document {
+            shapes: vec![
+                ShapeNode::new(ShapeGeometry::Preset(ShapeType::Star)),
+                ShapeNode::new(ShapeGeometry::RegularPolygon {
+                    sides: 6,
+                    radius: 0.25,
+                }),
+                star,
+                outline,
+                mesh,
+            ],
+        },
+        pipeline: PipelineKind::Colored,
+        clear_color: wgpu::Color {
+            r: 0.1,
+            g: 0.2,
+            b: 0.3,
+            a: 1.0,
+        },
+        camera: Camera2d {
+            center: Vec2::new(0.1, -0.05),
+            zoom: 0.8,
+        },
+    }
+}
+
+type Meshes = Vec<(Vec<Vertex>, Vec<u16>)>;
+
+/// Everything the renderer consumes: baked meshes, pipeline, clear color and camera.
+fn render_inputs(scene: &SceneDescription) -> (Meshes, PipelineKind, wgpu::Color, [f32; 16]) {
+    let meshes = scene
+        .document
+        .shapes
+        .iter()
+        .map(|shape| shape.world_mesh().unwrap())
+        .collect();
+    (
+        meshes,
+        scene.pipeline,
+        scene.clear_color,
+        scene.camera.view_proj().to_cols_array(),
+    )
+}
+
+fn assert_round_trip(format: SceneFormat) {
+    let scene = sample_scene();
+    let text = scene.serialize(format).unwrap();
+    let loaded = SceneDescription::parse(&text, format).unwrap();
+
+    assert_eq!(loaded, scene);
+    assert_eq!(render_inputs(&loaded), render_inputs(&scene));
+    // Saving again gives the same file
+    assert_eq!(loaded.serialize(format).unwrap(), text);
+}
+
+#[test]
+fn ron_round_trip() {
+    assert_round_trip(SceneFormat::Ron);
+}
+
+#[test]
+fn json_round_trip() {
+    assert_round_trip(SceneFormat::Json);
+}
+
+#[test]
+fn save_and_load_by_extension() {
+    let dir = std::env::temp_dir().join(format!("scene-file-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let scene = sample_scene();
+
+    for name in ["scene.ron", "scene.json"] {
+        let path = dir.join(name);
+        scene.save(&path).unwrap();
+        assert_eq!(SceneDescription::load(&path).unwrap(), scene);
+    }
+    assert!(scene.save(dir.join("scene.txt")).is_err());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn files_carry_the_schema_version() {
+    let text = sample_scene().serialize(SceneFormat::Json).unwrap();
+    let value: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(value["version"], SCENE_FILE_VERSION);
+}
+
+#[test]
+fn newer_versions_are_rejected() {
+    let text = sample_scene().serialize(SceneFormat::Json).unwrap();
+    let mut value: serde_json::Value = serde_json::from_str(&text).unwrap();
+    value["version"] = (SCENE_FILE_VERSION + 1).into();
+    let newer = serde_json::to_string(&value).unwrap();
+    let error = SceneDescription::parse(&newer, SceneFormat::Json).unwrap_err();
+    assert!(error.to_string().contains("newer"), "{error}");
+}
+
+#[test]
+fn unversioned_files_are_upgraded() {
+    let scene = sample_scene();
+    let text = scene.serialize(SceneFormat::Json).unwrap();
+    let mut value: serde_json::Value = serde_json::from_str(&text).unwrap();
+    value.as_object_mut().unwrap().remove("version");
+    let missing = serde_json::to_string(&value).unwrap();
+    assert_eq!(
+        SceneDescription::parse(&missing, SceneFormat::Json).unwrap(),
+        scene
+    );
+
+    let ron = scene.serialize(SceneFormat::Ron).unwrap();
+    let unversioned: String = ron
+        .lines()
+        .filter(|line| !line.trim_start().starts_with("version:"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert_ne!(unversioned, ron);
+    assert_eq!(
+        SceneDescription::parse(&unversioned, SceneFormat::Ron).unwrap(),
+        scene
+    );
+    // Still has to look like a scene
+    assert!(SceneDescription::parse("{}", SceneFormat::Json).is_err());
+}
+
+#[test]
+fn invalid_meshes_are_rejected() {
+    let mut scene = sample_scene();
+    scene.document.shapes = vec![ShapeNode::new(ShapeGeometry::Mesh {
+        vertices: vec![vertex(0.0, 0.0, [1.0; 3]); 3],
+        indices: vec![0, 1, 3],
+    })];
+    let text = scene.serialize(SceneFormat::Ron).unwrap();
+    assert!(SceneDescription::parse(&text, SceneFormat::Ron).is_err());
+}
+
+#[test]
+#[ignore = "needs a graphics adapter"]
+fn loaded_scene_renders_identically() {
+    let mut renderer =
+        pollster::block_on(HeadlessRenderer::new(64, 64)).expect("no graphics adapter");
+    let scene = sample_scene();
+
+    renderer.scene.set_description(scene.clone());
+    renderer.update(0.0);
+    let expected = renderer.render_frame().unwrap();
+
+    let text = scene.serialize(SceneFormat::Ron).unwrap();
+    renderer.scene.set_description(SceneDescription::default());
+    renderer.update(0.0);
+    renderer
+        .scene
+        .set_description(SceneDescription::parse(&text, SceneFormat::Ron).unwrap());
+    renderer.update(0.0);
+    assert_eq!(renderer.render_frame().unwrap(), expected);
+}
+
+/// Loads the sample scene after `edit` changed its JSON, expecting an error mentioning `message`.
+fn assert_rejected(edit: impl FnOnce(&mut serde_json::Value), message: &str) {
+    let text = sample_scene().serialize(SceneFormat::Json).unwrap();
+    let mut value: serde_json::Value = serde_json::from_str(&text).unwrap();
+    edit(&mut value);
+    let text = serde_json::to_string(&value).unwrap();
+    let error = SceneDescription::parse(&text, SceneFormat::Json).unwrap_err();
+    assert!(format!("{error:#}").contains(message), "{error:#}");
+}
+
+fn shape_with(geometry: serde_json::Value) -> impl FnOnce(&mut serde_json::Value) {
+    move |value| value["shapes"][0]["geometry"] = geometry
+}
+
+#[test]
+fn oversized_shapes_are_rejected() {
+    assert_rejected(
+        shape_with(serde_json::json!({"RegularPolygon": {"sides": 70000, "radius": 0.5}})),
+        "at most 65535 sides",
+    );
+    assert_rejected(
+        shape_with(serde_json::json!({
+            "Star": {"points": 40000, "outer_radius": 0.5, "inner_radius": 0.2}
+        })),
+        "at most 32767 points",
+    );
+    let point = serde_json::json!({"position": [0.0, 0.0, 0.0], "color": [1.0, 1.0, 1.0]});
+    let points = vec![point; 65536];
+    assert_rejected(
+        shape_with(serde_json::json!({"Outline": {"points": points}})),
+        "at most 65535 points",
+    );
+}
+
+/// Saves `scene` as RON, which unlike JSON can hold NaN and infinity, and
+/// expects loading it to fail mentioning `message`.
+fn assert_unloadable(scene: SceneDescription, message: &str) {
+    let text = scene.serialize(SceneFormat::Ron).unwrap();
+    let error = SceneDescription::parse(&text, SceneFormat::Ron).unwrap_err();
+    assert!(format!("{error:#}").contains(message), "{error:#}");
+}
+
+#[test]
+fn broken_cameras_are_rejected() {
+    for zoom in [0.0, -1.0, f32::NAN] {
+        let mut scene = sample_scene();
+        scene.camera.zoom = zoom;
+        assert_unloadable(scene, "zoom");
+    }
+    let mut scene = sample_scene();
+    scene.camera.center.y = f32::INFINITY;
+    assert_unloadable(scene, "camera center");
+}
+
+#[test]
+fn non_finite_coordinates_are_rejected() {
+    let mut scene = sample_scene();
+    scene.document.shapes[0].transform.translation.x = f32::NAN;
+    assert_unloadable(scene, "translation");
+    let mut scene = sample_scene();
+    scene.document.shapes[1].transform.scale.y = f32::NEG_INFINITY;
+    assert_unloadable(scene, "scale");
+
+    let mut scene = sample_scene();
+    scene.document.shapes = vec![ShapeNode::new(ShapeGeometry::Outline(vec![
+        vertex(0.0, 0.0, [1.0; 3]),
+        vertex(1.0, f32::NAN, [1.0; 3]),
+        vertex(0.0, 1.0, [1.0; 3]),
+    ]))];
+    assert_unloadable(scene, "point position");
+    let mut scene = sample_scene();
+    scene.document.shapes = vec![ShapeNode::new(ShapeGeometry::RegularPolygon {
+        sides: 5,
+        radius: f32::NAN,
+    })];
+    assert_unloadable(scene, "radius");
+}
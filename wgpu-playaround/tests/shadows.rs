@@ -0,0 +1,280 @@
+use glam::{Mat4, Vec2, Vec3};
+use wgpu_playaround::{
+    document::{Document, ShapeGeometry, ShapeNode},
+    enums::PipelineKind,
+    lighting::{
+        Light, Lighting,
+        shadow::{MAX_SHADOW_MAPS, ShadowSettings, cascade_splits, shadow_views, spot_view_proj},
+    },
+    models::{
+        camera::{Camera2d, ViewFrustum},
+        headless::HeadlessRenderer,
+        vertex::Vertex,
+    },
+};
+
+fn assert_inside(view_proj: Mat4, point: Vec3) {
+    let ndc = view_proj.project_point3(point);
+    let inside = ndc.x.abs() <= 1.0 && ndc.y.abs() <= 1.0 && (0.0..=1.0).contains(&ndc.z);
+    assert!(inside, "{point} lands on {ndc}");
+}
+
+fn sun() -> Light {
+    Light::Directional {
+        direction: Vec3::new(0.3, -1.0, -0.4),
+        color: Vec3::ONE,
+        intensity: 1.0,
+    }
+}
+
+fn spot() -> Light {
+    Light::Spot {
+        position: Vec3::new(0.0, 4.0, 0.0),
+        direction: -Vec3::Y,
+        color: Vec3::ONE,
+        intensity: 1.0,
+        range: 10.0,
+        inner_angle: 20f32.to_radians(),
+        outer_angle: 30f32.to_radians(),
+    }
+}
+
+fn perspective_frustum() -> ViewFrustum {
+    let (near, far) = (0.1, 40.0);
+    let view_proj = Mat4::perspective_rh(60f32.to_radians(), 1.5, near, far)
+        * Mat4::look_at_rh(Vec3::new(2.0, 3.0, 8.0), Vec3::ZERO, Vec3::Y);
+    ViewFrustum {
+        inverse_view_proj: view_proj.inverse(),
+        near,
+        far,
+    }
+}
+
+fn lighting(lights: Vec<Light>) -> Lighting {
+    Lighting {
+        lights,
+        ..Lighting::default()
+    }
+}
+
+#[test]
+fn cascade_splits_blend_even_and_logarithmic() {
+    assert_eq!(cascade_splits(0.0, 8.0, 4, 0.0), [2.0, 4.0, 6.0, 8.0]);
+    assert_eq!(cascade_splits(1.0, 16.0, 4, 1.0), [2.0, 4.0, 8.0, 16.0]);
+    let blended = cascade_splits(0.1, 100.0, 3, 0.5);
+    assert_eq!(blended.len(), 3);
+    assert!((blended[2] - 100.0).abs() < 1e-3);
+    assert!(blended.windows(2).all(|pair| pair[0] < pair[1]));
+    // Closer to the camera than the even split
+    assert!(blended[0] < 100.0 / 3.0);
+}
+
+#[test]
+fn cascades_cover_their_slice_of_the_view() {
+    let frustum = perspective_frustum();
+    let settings = ShadowSettings {
+        cascades: 4,
+        ..ShadowSettings::default()
+    };
+    let views = shadow_views(&lighting(vec![sun()]), &settings, &frustum);
+    assert_eq!(views.len(), 4);
+    let splits = cascade_splits(frustum.near, frustum.far, 4, settings.split_lambda);
+    let mut from = frustum.near;
+    for (view, &to) in views.iter().zip(&splits) {
+        assert_eq!(view.light, 0);
+        for corner in frustum.slice(from, to) {
+            assert_inside(view.view_proj, corner);
+        }
+        from = to;
+    }
+}
+
+#[test]
+fn the_2d_camera_frustum_is_its_visible_area() {
+    let camera = Camera2d {
+        center: Vec2::new(1.0, -0.5),
+        zoom: 2.0,
+    };
+    let (min, max) = camera.visible_bounds();
+    let corners = camera.frustum().slice(0.0, 1.0);
+    for (corner, z) in corners.iter().zip([0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0]) {
+        assert!((corner.z - z).abs() < 1e-5, "{corner}");
+    }
+    assert!((corners[0].truncate() - min).length() < 1e-5);
+    assert!((corners[2].truncate() - max).length() < 1e-5);
+}
+
+#[test]
+fn spot_shadows_cover_the_cone() {
+    let view_proj = spot_view_proj(Vec3::new(0.0, 4.0, 0.0), -Vec3::Y, 30f32.to_radians(), 10.0);
+    assert_inside(view_proj, Vec3::ZERO);
+    // Just inside the cone at the ground
+    assert_inside(
+        view_proj,
+        Vec3::new(4.0 * 29f32.to_radians().tan(), 0.0, 0.0),
+    );
+    // Behind the light and past the range
+    let behind = view_proj * Vec3::new(0.0, 5.0, 0.0).extend(1.0);
+    assert!(behind.w < 0.0);
+    let far = view_proj.project_point3(Vec3::new(0.0, -7.0, 0.0));
+    assert!(far.z > 1.0);
+}
+
+#[test]
+fn layers_are_handed_out_until_the_array_is_full() {
+    let point = Light::Point {
+        position: Vec3::ZERO,
+        color: Vec3::ONE,
+        intensity: 1.0,
+        range: 1.0,
+    };
+    let settings = ShadowSettings::default();
+    let lighting = lighting(vec![sun(), point, sun(), sun(), spot()]);
+    let views = shadow_views(&lighting, &settings, &perspective_frustum());
+    let lights: Vec<usize> = views.iter().map(|view| view.light).collect();
+    // Three cascades each for two suns, the third does not fit any more
+    assert_eq!(lights, [0, 0, 0, 2, 2, 2, 4]);
+    assert!(views.len() <= MAX_SHADOW_MAPS);
+
+    let uniform = settings.uniform(&views);
+    assert_eq!(uniform.layers(0), Some((0, 3)));
+    assert_eq!(uniform.layers(1), None);
+    assert_eq!(uniform.layers(2), Some((3, 3)));
+    assert_eq!(uniform.layers(3), None);
+    assert_eq!(uniform.layers(4), Some((6, 1)));
+
+    let disabled = ShadowSettings {
+        enabled: false,
+        ..settings
+    };
+    assert!(shadow_views(&lighting, &disabled, &perspective_frustum()).is_empty());
+    assert_eq!(disabled.uniform(&[]).layers(0), None);
+}
+
+// Light falling at 45 degrees along +x, so shadows land half a unit right of
+// casters half a unit above the ground
+const SLANTED: Vec3 = Vec3::new(1.0, 0.0, -1.0);
+
+/// Counter-clockwise white square at height `z`.
+fn square(half: f32, z: f32) -> ShapeNode {
+    let vertices = [[-half, -half], [half, -half], [half, half], [-half, half]]
+        .map(|[x, y]| Vertex {
+            position: [x, y, z],
+            color: [1.0, 1.0, 1.0],
+        })
+        .to_vec();
+    ShapeNode::new(ShapeGeometry::Mesh {
+        vertices,
+        indices: vec![0, 1, 2, 0, 2, 3],
+    })
+}
+
+#[test]
+fn casters_above_receivers_are_nearer_the_light_than_the_bias() {
+    let settings = ShadowSettings::default();
+    let lighting = lighting(vec![Light::Directional {
+        direction: SLANTED,
+        color: Vec3::ONE,
+        intensity: 1.0,
+    }]);
+    let camera = Camera2d {
+        center: Vec2::ZERO,
+        zoom: 1.0,
+    };
+    let views = shadow_views(&lighting, &settings, &camera.frustum());
+    let view_proj = views[0].view_proj;
+
+    // The same spot of the shadow map sees the caster and then the receiver
+    let caster = view_proj.project_point3(Vec3::new(0.0, 0.0, 0.5));
+    let receiver = view_proj.project_point3(Vec3::new(0.5, 0.0, 0.0));
+    assert!((caster.truncate() - receiver.truncate()).length() < 1e-4);
+    // Even at the steepest slope the bias allows the receiver stays occluded
+    let bias = settings.depth_bias + settings.slope_bias * 10.0;
+    assert!(receiver.z - bias > caster.z, "{caster} vs {receiver}");
+}
+
+/// Renders a large receiver with a small caster above it and returns the red
+/// channel of the 64x64 frame, covering -1..1 in x and y.
+fn render_shadow(shadows: ShadowSettings) -> Vec<u8> {
+    let mut renderer =
+        pollster::block_on(HeadlessRenderer::new(64, 64)).expect("no graphics adapter");
+    let mut description = renderer.scene.description();
+    description.document = Document {
+        shapes: vec![square(1.0, 0.0), square(0.25, 0.5)],
+    };
+    description.pipeline = PipelineKind::Lit;
+    description.camera = Camera2d {
+        center: Vec2::ZERO,
+        zoom: 1.0,
+    };
+    renderer.scene.set_description(description);
+    renderer.scene.lighting = Lighting {
+        lights: vec![Light::Directional {
+            direction: SLANTED,
+            color: Vec3::ONE,
+            intensity: 1.0,
+        }],
+        ambient: Vec3::splat(0.1),
+        specular: 0.0,
+        ..Lighting::default()
+    };
+    renderer.scene.shadow_settings = shadows;
+    // Bloom would carry the shadow's edge into the texels compared below
+    renderer.post_settings.bloom.enabled = false;
+    renderer.update(0.0);
+    let pixels = renderer.render_frame().unwrap();
+    pixels.chunks_exact(4).map(|pixel| pixel[0]).collect()
+}
+
+/// Index of the pixel showing world position `(x, y)`.
+fn texel(x: f32, y: f32) -> usize {
+    let column = ((x + 1.0) * 32.0) as usize;
+    let row = ((1.0 - y) * 32.0) as usize;
+    row * 64 + column
+}
+
+#[test]
+#[ignore = "needs a graphics adapter"]
+fn casters_darken_the_receiver_where_the_light_is_blocked() {
+    let settings = ShadowSettings {
+        resolution: 64,
+        cascades: 1,
+        pcf_radius: 0,
+        ..ShadowSettings::default()
+    };
+    let unshadowed = render_shadow(ShadowSettings {
+        enabled: false,
+        ..settings
+    });
+    let hard = render_shadow(settings);
+    let soft = render_shadow(ShadowSettings {
+        pcf_radius: 3,
+        ..settings
+    });
+
+    let lit = unshadowed[texel(-0.6, -0.6)];
+    let shadowed = hard[texel(0.5, 0.0)];
+    assert!(shadowed < lit / 2, "{shadowed} vs {lit}");
+    assert_eq!(unshadowed[texel(0.5, 0.0)], lit);
+    // The caster itself faces the light
+    assert_eq!(hard[texel(0.0, 0.0)], unshadowed[texel(0.0, 0.0)]);
+
+    // Away from the shadow the bias keeps the receiver from shadowing itself
+    for y in [-0.9, -0.6, 0.6, 0.9] {
+        for x in [-0.9, -0.5, 0.0, 0.5, 0.9] {
+            let i = texel(x, y);
+            assert!(hard[i].abs_diff(unshadowed[i]) <= 1, "({x}, {y})");
+            assert!(soft[i].abs_diff(unshadowed[i]) <= 1, "({x}, {y})");
+        }
+    }
+
+    // PCF widens the penumbra along the shadow's right edge
+    let penumbra = |frame: &[u8]| {
+        (0..64)
+            .map(|column| frame[32 * 64 + column])
+            .filter(|&value| value > shadowed + 2 && value + 2 < lit)
+            .count()
+    };
+    assert!(penumbra(&soft) > penumbra(&hard) + 2);
+    assert!(soft[texel(0.5, 0.0)].abs_diff(shadowed) <= 1);
+}
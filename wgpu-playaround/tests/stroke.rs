@@ -0,0 +1,279 @@
+use std::f32::consts::PI;
+
+use glam::Vec2;
+use wgpu_playaround::{
+    geometry::stroke::{LineCap, LineJoin, StrokeStyle, stroke_polyline},
+    models::vertex::Vertex,
+};
+
+fn position(v: &Vertex) -> Vec2 {
+    Vec2::new(v.position[0], v.position[1])
+}
+
+/// Checks what every stroke must satisfy and returns the summed triangle area.
+fn check_mesh(vertices: &[Vertex], indices: &[u16]) -> f32 {
+    assert_eq!(indices.len() % 3, 0);
+    assert!(vertices.iter().all(|v| position(v).is_finite()));
+    let mut area = 0.0;
+    for triangle in indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|k| position(&vertices[triangle[k] as usize]));
+        let doubled = (b - a).perp_dot(c - a);
+        // Counter-clockwise, otherwise back face culling drops it
+        assert!(
+            doubled > 0.0,
+            "triangle {triangle:?} is not counter-clockwise"
+        );
+        area += doubled * 0.5;
+    }
+    area
+}
+
+fn max_distance(vertices: &[Vertex], from: Vec2) -> f32 {
+    vertices
+        .iter()
+        .map(|v| position(v).distance(from))
+        .fold(0.0, f32::max)
+}
+
+fn style(width: f32, join: LineJoin, cap: LineCap) -> StrokeStyle {
+    StrokeStyle {
+        width,
+        join,
+        cap,
+        ..StrokeStyle::default()
+    }
+}
+
+const LINE: [Vec2; 2] = [Vec2::new(0.0, 0.0), Vec2::new(1.0, 0.0)];
+
+#[test]
+fn butt_cap_covers_the_segment() {
+    let (vertices, indices) =
+        stroke_polyline(&LINE, false, &style(0.2, LineJoin::Miter, LineCap::Butt)).unwrap();
+    let area = check_mesh(&vertices, &indices);
+    assert!((area - 0.2).abs() < 1e-5, "{area}");
+}
+
+#[test]
+fn square_cap_extends_by_half_the_width() {
+    let (vertices, indices) =
+        stroke_polyline(&LINE, false, &style(0.2, LineJoin::Miter, LineCap::Square)).unwrap();
+    let area = check_mesh(&vertices, &indices);
+    assert!((area - 1.2 * 0.2).abs() < 1e-5, "{area}");
+    let min_x = vertices
+        .iter()
+        .map(|v| v.position[0])
+        .fold(f32::MAX, f32::min);
+    assert!((min_x + 0.1).abs() < 1e-6);
+}
+
+#[test]
+fn round_cap_adds_half_circles() {
+    let (vertices, indices) =
+        stroke_polyline(&LINE, false, &style(0.2, LineJoin::Miter, LineCap::Round)).unwrap();
+    let area = check_mesh(&vertices, &indices);
+    let expected = 0.2 + PI * 0.1 * 0.1;
+    assert!((area - expected).abs() < 1e-3, "{area} vs {expected}");
+    assert!(max_distance(&vertices, Vec2::new(0.5, 0.0)) <= 0.6 + 1e-5);
+}
+
+#[test]
+fn repeated_points_are_ignored() {
+    let style = style(0.1, LineJoin::Round, LineCap::Round);
+    let points = [
+        Vec2::new(0.0, 0.0),
+        Vec2::new(0.0, 0.0),
+        Vec2::new(1.0, 0.0),
+        Vec2::new(1.0, 0.0),
+        Vec2::new(1.0, 1.0),
+    ];
+    let clean = [points[0], points[2], points[4]];
+    assert_eq!(
+        stroke_polyline(&points, false, &style).unwrap(),
+        stroke_polyline(&clean, false, &style).unwrap()
+    );
+}
+
+#[test]
+fn single_point_draws_a_dot_or_nothing() {
+    let p = [Vec2::new(0.3, 0.3); 3];
+
+    let (vertices, indices) =
+        stroke_polyline(&p, false, &style(0.2, LineJoin::Miter, LineCap::Butt)).unwrap();
+    assert!(vertices.is_empty() && indices.is_empty());
+
+    let (vertices, indices) =
+        stroke_polyline(&p, false, &style(0.2, LineJoin::Miter, LineCap::Round)).unwrap();
+    let area = check_mesh(&vertices, &indices);
+    assert!((area - PI * 0.01).abs() < 1e-3, "{area}");
+
+    let (vertices, indices) =
+        stroke_polyline(&p, false, &style(0.2, LineJoin::Miter, LineCap::Square)).unwrap();
+    let area = check_mesh(&vertices, &indices);
+    assert!((area - 0.04).abs() < 1e-6, "{area}");
+}
+
+#[test]
+fn empty_input_and_zero_width_draw_nothing() {
+    let (vertices, _) = stroke_polyline(&[], false, &StrokeStyle::default()).unwrap();
+    assert!(vertices.is_empty());
+    let (vertices, _) =
+        stroke_polyline(&LINE, false, &style(0.0, LineJoin::Miter, LineCap::Round)).unwrap();
+    assert!(vertices.is_empty());
+    let (vertices, _) = stroke_polyline(
+        &LINE,
+        false,
+        &style(f32::NAN, LineJoin::Miter, LineCap::Round),
+    )
+    .unwrap();
+    assert!(vertices.is_empty());
+}
+
+/// Open path with a corner of `degrees` between the two segments at the origin.
+fn corner(degrees: f32) -> [Vec2; 3] {
+    let angle = degrees.to_radians();
+    [Vec2::new(1.0, 0.0), Vec2::ZERO, Vec2::from_angle(angle)]
+}
+
+#[test]
+fn miter_reaches_the_corner_tip() {
+    let half = 0.05;
+    let (vertices, indices) = stroke_polyline(
+        &corner(90.0),
+        false,
+        &style(2.0 * half, LineJoin::Miter, LineCap::Butt),
+    )
+    .unwrap();
+    check_mesh(&vertices, &indices);
+    // A right angle puts the tip sqrt(2) half widths from the corner
+    let expected = half * 2f32.sqrt();
+    assert!(
+        vertices
+            .iter()
+            .any(|v| (position(v).length() - expected).abs() < 1e-5),
+        "no miter tip at {expected}"
+    );
+}
+
+#[test]
+fn sharp_miter_falls_back_to_bevel() {
+    let half = 0.05;
+    // 10 degrees needs a miter of 1 / sin(5°) ≈ 11.5 half widths
+    let path = corner(10.0);
+
+    let limited = style(2.0 * half, LineJoin::Miter, LineCap::Butt);
+    let (vertices, indices) = stroke_polyline(&path, false, &limited).unwrap();
+    check_mesh(&vertices, &indices);
+    let near_corner = vertices
+        .iter()
+        .map(|v| position(v).length())
+        .filter(|&d| d < 0.5)
+        .fold(0.0, f32::max);
+    assert!(
+        near_corner <= half * limited.miter_limit + 1e-5,
+        "{near_corner}"
+    );
+
+    let unlimited = StrokeStyle {
+        miter_limit: 20.0,
+        ..limited
+    };
+    let (vertices, indices) = stroke_polyline(&path, false, &unlimited).unwrap();
+    check_mesh(&vertices, &indices);
+    let expected = half / 5f32.to_radians().sin();
+    assert!(
+        vertices
+            .iter()
+            .any(|v| (position(v).length() - expected).abs() < 1e-4),
+        "no miter tip at {expected}"
+    );
+}
+
+#[test]
+fn sharp_round_and_bevel_joins_stay_within_half_width() {
+    let half = 0.05;
+    for join in [LineJoin::Round, LineJoin::Bevel] {
+        for degrees in [1.0, 10.0, 45.0, 179.0] {
+            let (vertices, indices) = stroke_polyline(
+                &corner(degrees),
+                false,
+                &style(2.0 * half, join, LineCap::Butt),
+            )
+            .unwrap();
+            check_mesh(&vertices, &indices);
+            let near_corner = vertices
+                .iter()
+                .map(|v| position(v).length())
+                .filter(|&d| d < 0.5)
+                .fold(0.0, f32::max);
+            assert!(
+                near_corner <= half + 1e-5,
+                "{join:?} {degrees}: {near_corner}"
+            );
+        }
+    }
+}
+
+#[test]
+fn full_reversal_is_handled() {
+    let path = [Vec2::ZERO, Vec2::new(1.0, 0.0), Vec2::ZERO];
+    for join in [LineJoin::Miter, LineJoin::Round, LineJoin::Bevel] {
+        let (vertices, indices) =
+            stroke_polyline(&path, false, &style(0.1, join, LineCap::Butt)).unwrap();
+        check_mesh(&vertices, &indices);
+        assert!(max_distance(&vertices, Vec2::new(1.0, 0.0)) <= 1.0 + 0.05 + 1e-5);
+    }
+}
+
+#[test]
+fn closed_square_gets_joins_on_every_corner() {
+    let square = [
+        Vec2::new(-1.0, -1.0),
+        Vec2::new(1.0, -1.0),
+        Vec2::new(1.0, 1.0),
+        Vec2::new(-1.0, 1.0),
+        // Repeating the first point is the same as leaving it out
+        Vec2::new(-1.0, -1.0),
+    ];
+    let style = style(0.2, LineJoin::Miter, LineCap::Round);
+    let (vertices, indices) = stroke_polyline(&square, true, &style).unwrap();
+    check_mesh(&vertices, &indices);
+    assert_eq!(
+        (vertices.clone(), indices.clone()),
+        stroke_polyline(&square[..4], true, &style).unwrap()
+    );
+
+    // Every outer corner is mitered and round caps are not used
+    for corner in [
+        Vec2::new(-1.1, -1.1),
+        Vec2::new(1.1, -1.1),
+        Vec2::new(1.1, 1.1),
+        Vec2::new(-1.1, 1.1),
+    ] {
+        assert!(
+            vertices.iter().any(|v| position(v).distance(corner) < 1e-5),
+            "{corner}"
+        );
+    }
+    assert!(
+        vertices
+            .iter()
+            .all(|v| position(v).abs().max_element() <= 1.1 + 1e-5)
+    );
+}
+
+#[test]
+fn strokes_past_u16_indices_fail() {
+    // A zigzag with a bevel at every corner, seven vertices per point
+    let zigzag: Vec<Vec2> = (0..10_000)
+        .map(|i| Vec2::new(i as f32 * 0.01, (i % 2) as f32 * 0.01))
+        .collect();
+    let bevel = style(0.002, LineJoin::Bevel, LineCap::Butt);
+    let (vertices, indices) = stroke_polyline(&zigzag[..9_000], false, &bevel).unwrap();
+    check_mesh(&vertices, &indices);
+    let error = stroke_polyline(&zigzag, false, &bevel).unwrap_err();
+    assert!(
+        error.to_string().contains("more than 65535 vertices"),
+        "{error}"
+    );
+}
@@ -0,0 +1,185 @@
+mod common;
+
+use common::ccw_area;
+use glam::Vec2;
+use wgpu_playaround::{
+    document::ShapeGeometry,
+    geometry::{
+        fill::{FillRule, fill_contours},
+        flatten::{flatten_cubic, flatten_quadratic},
+    },
+    models::vertex::Vertex,
+    svg::import::{SvgImportOptions, import_svg},
+};
+
+fn square(center: Vec2, half: f32, clockwise: bool) -> Vec<Vec2> {
+    let mut points = vec![
+        center + Vec2::new(-half, -half),
+        center + Vec2::new(half, -half),
+        center + Vec2::new(half, half),
+        center + Vec2::new(-half, half),
+    ];
+    if clockwise {
+        points.reverse();
+    }
+    points
+}
+
+#[test]
+fn flattened_curves_stay_within_tolerance() {
+    let tolerance = 0.001;
+    let (p0, p1, p2) = (Vec2::ZERO, Vec2::new(0.5, 1.0), Vec2::new(1.0, 0.0));
+    let mut points = vec![p0];
+    flatten_quadratic(p0, p1, p2, tolerance, &mut points);
+    assert_eq!(*points.last().unwrap(), p2);
+
+    // Sample the curve densely and compare against the polyline
+    for i in 0..=200 {
+        let t = i as f32 / 200.0;
+        let mt = 1.0 - t;
+        let p = p0 * mt * mt + p1 * 2.0 * mt * t + p2 * t * t;
+        let distance = points
+            .windows(2)
+            .map(|s| {
+                let d = s[1] - s[0];
+                let k = ((p - s[0]).dot(d) / d.length_squared()).clamp(0.0, 1.0);
+                p.distance(s[0] + d * k)
+            })
+            .fold(f32::MAX, f32::min);
+        assert!(distance <= tolerance + 1e-6, "{distance} at t = {t}");
+    }
+
+    // Straight control points need a single segment
+    let mut line = Vec::new();
+    flatten_cubic(
+        Vec2::ZERO,
+        Vec2::new(1.0, 0.0),
+        Vec2::new(2.0, 0.0),
+        Vec2::new(3.0, 0.0),
+        tolerance,
+        &mut line,
+    );
+    assert_eq!(line, vec![Vec2::new(3.0, 0.0)]);
+}
+
+#[test]
+fn fill_rules_treat_nested_contours_differently() {
+    let outer = square(Vec2::ZERO, 1.0, false);
+    let inner_same = square(Vec2::ZERO, 0.5, false);
+    let inner_reversed = square(Vec2::ZERO, 0.5, true);
+
+    let fill = |contours: &[Vec<Vec2>], rule| {
+        let (vertices, indices) = fill_contours(contours, rule, [1.0; 3]).unwrap();
+        ccw_area(&vertices, &indices)
+    };
+
+    // Same orientation stays filled with nonzero and becomes a hole with even-odd
+    let both = [outer.clone(), inner_same];
+    assert!((fill(&both, FillRule::NonZero) - 4.0).abs() < 1e-5);
+    assert!((fill(&both, FillRule::EvenOdd) - 3.0).abs() < 1e-5);
+
+    // Opposite orientation is a hole either way
+    let hole = [outer, inner_reversed];
+    assert!((fill(&hole, FillRule::NonZero) - 3.0).abs() < 1e-5);
+    assert!((fill(&hole, FillRule::EvenOdd) - 3.0).abs() < 1e-5);
+}
+
+#[test]
+fn self_intersecting_bowtie_is_filled() {
+    let bowtie = [
+        Vec2::new(-1.0, -1.0),
+        Vec2::new(1.0, 1.0),
+        Vec2::new(1.0, -1.0),
+        Vec2::new(-1.0, 1.0),
+    ];
+    for rule in [FillRule::NonZero, FillRule::EvenOdd] {
+        let (vertices, indices) = fill_contours(&[bowtie.to_vec()], rule, [1.0; 3]).unwrap();
+        // Two triangles of area 1 meeting at the crossing
+        assert!((ccw_area(&vertices, &indices) - 2.0).abs() < 1e-5);
+    }
+}
+
+fn mesh(geometry: &ShapeGeometry) -> (&[Vertex], &[u16]) {
+    match geometry {
+        ShapeGeometry::Mesh { vertices, indices } => (vertices, indices),
+        other => panic!("expected a mesh, got {}", other.name()),
+    }
+}
+
+#[test]
+fn paths_are_mapped_into_world_space() {
+    let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 100 100">
+        <path d="M 0 0 L 100 0 L 100 50 L 0 50 Z" fill="#ff0000"/>
+    </svg>"##;
+    let shapes = import_svg(svg, &SvgImportOptions::default()).unwrap();
+    assert_eq!(shapes.len(), 1);
+
+    let (vertices, indices) = mesh(&shapes[0].geometry);
+    // The 100 unit view box becomes 1.6 world units and y points up
+    assert!((ccw_area(vertices, indices) - 1.6 * 0.8).abs() < 1e-4);
+    for v in vertices {
+        assert!(v.position[1] >= 0.0 - 1e-5 && v.position[1] <= 0.8 + 1e-5);
+        assert_eq!(v.color, [1.0, 0.0, 0.0]);
+    }
+}
+
+#[test]
+fn transforms_styles_and_strokes_are_applied() {
+    let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="-50 -50 100 100">
+        <g transform="translate(10 0)" style="fill: none">
+            <path d="M -10 -10 h 20 v 20 h -20 z" stroke="blue" stroke-width="4"
+                transform="scale(2)"/>
+        </g>
+        <defs><path d="M 0 0 L 10 0 L 10 10 Z"/></defs>
+    </svg>"##;
+    let shapes = import_svg(svg, &SvgImportOptions::default()).unwrap();
+    // Only the stroke of the first path, the one in defs is never drawn
+    assert_eq!(shapes.len(), 1);
+
+    let (vertices, indices) = mesh(&shapes[0].geometry);
+    ccw_area(vertices, indices);
+    assert!(vertices.iter().all(|v| v.color == [0.0, 0.0, 1.0]));
+    // The square spans -10..30 in x after both transforms, plus half the scaled width
+    let scale = 1.6 / 100.0;
+    let max_x = vertices
+        .iter()
+        .map(|v| v.position[0])
+        .fold(f32::MIN, f32::max);
+    let min_x = vertices
+        .iter()
+        .map(|v| v.position[0])
+        .fold(f32::MAX, f32::min);
+    assert!((max_x - 34.0 * scale).abs() < 1e-4, "{max_x}");
+    assert!((min_x + 14.0 * scale).abs() < 1e-4, "{min_x}");
+}
+
+#[test]
+fn arcs_and_even_odd_fill_are_supported() {
+    // Two circles drawn with arcs, the inner one punching a hole
+    let svg = r##"<svg xmlns="http://www.w3.org/2000/svg" width="20" height="20">
+        <path fill-rule="evenodd" fill="white" d="
+            M 0 10 A 10 10 0 1 1 20 10 A 10 10 0 1 1 0 10 Z
+            M 5 10 A 5 5 0 1 1 15 10 A 5 5 0 1 1 5 10 Z"/>
+    </svg>"##;
+    let options = SvgImportOptions {
+        tolerance: 0.0005,
+        size: 2.0,
+    };
+    let shapes = import_svg(svg, &options).unwrap();
+    let (vertices, indices) = mesh(&shapes[0].geometry);
+    let expected = std::f32::consts::PI * (1.0 - 0.25);
+    let area = ccw_area(vertices, indices);
+    assert!((area - expected).abs() < 0.01, "{area} vs {expected}");
+}
+
+#[test]
+fn invalid_documents_are_rejected() {
+    let options = SvgImportOptions::default();
+    assert!(import_svg("not xml", &options).is_err());
+    assert!(import_svg("<html/>", &options).is_err());
+    assert!(import_svg(r#"<svg viewBox="0 0 0 10"/>"#, &options).is_err());
+    assert!(import_svg("<svg/>", &options).is_err());
+    // Broken path data keeps what was parsed before the error
+    let svg = r#"<svg viewBox="0 0 10 10"><path d="M 0 0 L 10 0 L 10 10 Z L ?"/></svg>"#;
+    assert_eq!(import_svg(svg, &options).unwrap().len(), 1);
+}
@@ -0,0 +1,145 @@
+use wgpu_playaround::text::{
+    atlas::GlyphAtlas,
+    font::Font,
+    layout::{TextAlign, TextStyle, layout_text, measure_line},
+};
+
+fn style(max_width: Option<f32>, align: TextAlign) -> TextStyle {
+    TextStyle {
+        size: 20.0,
+        align,
+        max_width,
+        ..TextStyle::default()
+    }
+}
+
+#[test]
+fn embedded_font_has_kerning() {
+    let font = Font::default();
+    let (a, v) = (font.glyph_id('A'), font.glyph_id('V'));
+    assert!(font.kern(a, v, 20.0) < 0.0);
+
+    // "AV" is narrower than its two advances
+    let unkerned = font.advance(a, 20.0) + font.advance(v, 20.0);
+    assert!(measure_line(&font, "AV", 20.0) < unkerned);
+    let layout = layout_text(&font, "AV", &style(None, TextAlign::Left));
+    let gap = layout.glyphs[1].position.x - layout.glyphs[0].position.x;
+    assert!(gap < font.advance(a, 20.0));
+}
+
+#[test]
+fn newlines_and_spaces() {
+    let font = Font::default();
+    let layout = layout_text(&font, "a b\n\nc", &style(None, TextAlign::Left));
+    assert_eq!(layout.line_count, 3);
+    // Spaces take room but have no glyph
+    assert_eq!(layout.glyphs.len(), 3);
+    let line_height = font.line_metrics(20.0).line_height;
+    let c = layout.glyphs[2].position;
+    assert_eq!(c.x, 0.0);
+    assert!((c.y - layout.glyphs[0].position.y - 2.0 * line_height).abs() < 1e-4);
+
+    let empty = layout_text(&font, "", &TextStyle::default());
+    assert_eq!(empty.line_count, 0);
+    assert_eq!(empty.size.y, 0.0);
+}
+
+#[test]
+fn long_lines_wrap_at_spaces() {
+    let font = Font::default();
+    let text = "the quick brown fox jumps over the lazy dog";
+    let max_width = measure_line(&font, "the quick brown", 20.0) + 1.0;
+    let layout = layout_text(&font, text, &style(Some(max_width), TextAlign::Left));
+    assert_eq!(layout.line_count, 3);
+
+    // Every line starts at the left edge with the first letter of a word
+    let starts: Vec<_> = layout
+        .glyphs
+        .iter()
+        .filter(|g| g.position.x == 0.0)
+        .map(|g| g.id)
+        .collect();
+    assert_eq!(starts, ['t', 'f', 't'].map(|c| font.glyph_id(c)).to_vec());
+    for glyph in &layout.glyphs {
+        assert!(glyph.position.x < max_width);
+    }
+}
+
+#[test]
+fn words_wider_than_a_line_are_split() {
+    let font = Font::default();
+    let max_width = measure_line(&font, "abc", 20.0) + 0.5;
+    let layout = layout_text(&font, "abcdefgh", &style(Some(max_width), TextAlign::Left));
+    assert_eq!(layout.line_count, 3);
+    assert_eq!(layout.glyphs.len(), 8);
+
+    // Even a single character that does not fit makes progress
+    let layout = layout_text(&font, "www", &style(Some(1.0), TextAlign::Left));
+    assert_eq!(layout.line_count, 3);
+}
+
+#[test]
+fn alignment_moves_lines_within_the_block() {
+    let font = Font::default();
+    let text = "wide line\nx";
+    let x_width = measure_line(&font, "x", 20.0);
+    let block = measure_line(&font, "wide line", 20.0);
+
+    let x_position = |align| {
+        let layout = layout_text(&font, text, &style(None, align));
+        assert!((layout.size.x - block).abs() < 1e-4);
+        layout.glyphs.last().unwrap().position.x
+    };
+    assert_eq!(x_position(TextAlign::Left), 0.0);
+    assert!((x_position(TextAlign::Center) - (block - x_width) / 2.0).abs() < 1e-4);
+    assert!((x_position(TextAlign::Right) - (block - x_width)).abs() < 1e-4);
+
+    // With a maximum width lines align within it instead
+    let layout = layout_text(&font, "x", &style(Some(100.0), TextAlign::Right));
+    assert!((layout.glyphs[0].position.x - (100.0 - x_width)).abs() < 1e-4);
+}
+
+#[test]
+fn atlas_caches_grows_and_clears() {
+    let font = Font::default();
+    let mut atlas = GlyphAtlas::new(32, 64);
+
+    let a = atlas.glyph(&font, font.glyph_id('A'), 20.0).unwrap();
+    assert!(a.size.x > 0 && a.size.y > 0);
+    assert!(atlas.pixels().contains(&255));
+    assert_eq!(atlas.glyph(&font, font.glyph_id('A'), 20.0), Some(a));
+    assert_eq!(atlas.glyph(&font, font.glyph_id(' '), 20.0), None);
+    // Glyphs sit above the baseline, so their top is negative
+    assert!(a.offset.y < 0.0);
+
+    let mut placed = vec![a];
+    for c in "BCDEFGHIJ".chars() {
+        if let Some(glyph) = atlas.glyph(&font, font.glyph_id(c), 20.0) {
+            placed.push(glyph);
+        }
+    }
+    assert!(atlas.size() > 32, "atlas should have grown");
+
+    // Glyphs never overlap while they are valid
+    if atlas.generation() == 0 {
+        for (i, a) in placed.iter().enumerate() {
+            for b in &placed[i + 1..] {
+                let overlap_x =
+                    a.origin.x < b.origin.x + b.size.x && b.origin.x < a.origin.x + a.size.x;
+                let overlap_y =
+                    a.origin.y < b.origin.y + b.size.y && b.origin.y < a.origin.y + a.size.y;
+                assert!(!(overlap_x && overlap_y), "{a:?} overlaps {b:?}");
+            }
+        }
+    }
+
+    // Far more glyphs than fit make it start over
+    for c in 'a'..='z' {
+        atlas.glyph(&font, font.glyph_id(c), 20.0);
+    }
+    assert_eq!(atlas.size(), 64);
+    assert!(atlas.generation() > 0);
+
+    // A glyph bigger than the largest atlas is skipped
+    assert_eq!(atlas.glyph(&font, font.glyph_id('W'), 200.0), None);
+}
@@ -0,0 +1,269 @@
+mod common;
+
+use common::request_device;
+use glam::{Vec2, Vec3};
+use wgpu_playaround::{
+    color::linear_to_srgb,
+    document::{Document, ShapeGeometry, ShapeNode},
+    enums::PipelineKind,
+    models::{camera::Camera2d, headless::HeadlessRenderer, vertex::Vertex},
+    post::{
+        PostSettings,
+        bloom::BloomSettings,
+        renderer::PostProcessor,
+        tonemap::{Tonemap, exposure_scale},
+    },
+};
+
+fn gray(tonemap: Tonemap, value: f32) -> f32 {
+    tonemap.apply(Vec3::splat(value), 0.0).x
+}
+
+#[test]
+fn curves_map_black_to_black_and_stay_in_range() {
+    for tonemap in Tonemap::ALL {
+        assert!(gray(tonemap, 0.0) < 1e-3, "{tonemap:?}");
+        let mut previous = 0.0;
+        for i in 1..200 {
+            let value = gray(tonemap, i as f32 * 0.25);
+            assert!((0.0..=1.0).contains(&value), "{tonemap:?} {value}");
+            assert!(value >= previous, "{tonemap:?} is not monotonic at {i}");
+            previous = value;
+        }
+        // Negative input is treated as black
+        assert_eq!(
+            tonemap.apply(Vec3::splat(-1.0), 0.0),
+            tonemap.apply(Vec3::ZERO, 0.0)
+        );
+    }
+}
+
+#[test]
+fn reference_values() {
+    assert_eq!(gray(Tonemap::Clamp, 0.5), 0.5);
+    assert_eq!(gray(Tonemap::Clamp, 8.0), 1.0);
+    assert_eq!(gray(Tonemap::Reinhard, 1.0), 0.5);
+    assert_eq!(gray(Tonemap::Reinhard, 3.0), 0.75);
+    // The ACES fit keeps mid gray a little darker and reaches white early
+    assert!((gray(Tonemap::Aces, 0.18) - 0.2670).abs() < 1e-3);
+    assert!((gray(Tonemap::Aces, 1.0) - 0.8038).abs() < 1e-3);
+    assert_eq!(gray(Tonemap::Aces, 100.0), 1.0);
+    // AgX keeps a highlight headroom of a few stops above white
+    let agx_white = gray(Tonemap::AgX, 1.0);
+    assert!(agx_white > 0.3 && agx_white < 0.9, "{agx_white}");
+    assert!(gray(Tonemap::AgX, 16.0) > 0.9);
+}
+
+#[test]
+fn agx_desaturates_bright_colors() {
+    let red = Tonemap::AgX.apply(Vec3::new(64.0, 0.0, 0.0), 0.0);
+    assert!(red.x > 0.9);
+    // Very bright pure colors drift towards white instead of clipping
+    assert!(red.y > 0.1 && red.z > 0.1, "{red}");
+    let dim = Tonemap::AgX.apply(Vec3::new(0.2, 0.0, 0.0), 0.0);
+    assert!(dim.y < 0.01 * dim.x.max(1e-3) + 1e-3, "{dim}");
+}
+
+#[test]
+fn exposure_is_in_stops() {
+    assert_eq!(exposure_scale(0.0), 1.0);
+    assert_eq!(exposure_scale(2.0), 4.0);
+    assert_eq!(exposure_scale(-1.0), 0.5);
+    let color = Vec3::new(0.3, 1.5, 6.0);
+    for tonemap in Tonemap::ALL {
+        let brighter = tonemap.apply(color, 1.0);
+        assert_eq!(brighter, tonemap.apply(color * 2.0, 0.0), "{tonemap:?}");
+    }
+}
+
+/// Tonemaps a scene cleared to `color` and reads back one pixel of the output.
+fn tonemap_on_gpu(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    post: &mut PostProcessor,
+    tonemap: Tonemap,
+    color: Vec3,
+) -> Vec3 {
+    const SIZE: u32 = 64;
+    let output = device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width: SIZE,
+            height: SIZE,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let view = output.create_view(&Default::default());
+    let readback = device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: (SIZE * SIZE * 4) as u64,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = device.create_command_encoder(&Default::default());
+    encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: None,
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+            view: post.scene_view(),
+            depth_slice: None,
+            resolve_target: None,
+            ops: wgpu::Operations {
+                load: wgpu::LoadOp::Clear(wgpu::Color {
+                    r: color.x as f64,
+                    g: color.y as f64,
+                    b: color.z as f64,
+                    a: 1.0,
+                }),
+                store: wgpu::StoreOp::Store,
+            },
+        })],
+        depth_stencil_attachment: None,
+        occlusion_query_set: None,
+        timestamp_writes: None,
+        multiview_mask: None,
+    });
+    let settings = PostSettings {
+        bloom: BloomSettings {
+            enabled: false,
+            ..BloomSettings::default()
+        },
+        tonemap,
+        ..PostSettings::default()
+    };
+    post.prepare(device, queue, &settings);
+    post.draw(&mut encoder, &view);
+    encoder.copy_texture_to_buffer(
+        output.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer: &readback,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(SIZE * 4),
+                rows_per_image: None,
+            },
+        },
+        wgpu::Extent3d {
+            width: SIZE,
+            height: SIZE,
+            depth_or_array_layers: 1,
+        },
+    );
+    queue.submit(std::iter::once(encoder.finish()));
+
+    let slice = readback.slice(..);
+    slice.map_async(wgpu::MapMode::Read, |result| result.unwrap());
+    device.poll(wgpu::PollType::wait_indefinitely()).unwrap();
+    let pixel = Vec3::from_slice(
+        &slice.get_mapped_range()[..3]
+            .iter()
+            .map(|&c| c as f32 / 255.0)
+            .collect::<Vec<_>>(),
+    );
+    readback.unmap();
+    pixel
+}
+
+#[test]
+#[ignore = "needs a graphics adapter"]
+fn gpu_tonemapping_matches_the_cpu_reference() {
+    let (device, queue) = request_device().expect("no graphics adapter");
+    let mut post = PostProcessor::new(&device, wgpu::TextureFormat::Rgba8Unorm, 64, 64);
+    for tonemap in Tonemap::ALL {
+        for color in [
+            Vec3::new(0.05, 0.2, 0.5),
+            Vec3::new(1.0, 1.0, 1.0),
+            Vec3::new(6.0, 2.0, 0.25),
+        ] {
+            let gpu = tonemap_on_gpu(&device, &queue, &mut post, tonemap, color);
+            let cpu = tonemap.apply(color, 0.0);
+            assert!(
+                (gpu - cpu).abs().max_element() < 2.0 / 255.0,
+                "{tonemap:?} {color}: {gpu} vs {cpu}"
+            );
+        }
+    }
+}
+
+/// Renders a white square tinted `tint` over -0.5..0.5 on a black background
+/// through the headless renderer and returns the 64x64 RGBA8 frame.
+fn render_headless(tint: Vec3, post_settings: PostSettings) -> Vec<u8> {
+    let mut renderer =
+        pollster::block_on(HeadlessRenderer::new(64, 64)).expect("no graphics adapter");
+    let vertices = [[-0.5, -0.5], [0.5, -0.5], [0.5, 0.5], [-0.5, 0.5]]
+        .map(|[x, y]| Vertex {
+            position: [x, y, 0.0],
+            color: [1.0, 1.0, 1.0],
+        })
+        .to_vec();
+    let mut square = ShapeNode::new(ShapeGeometry::Mesh {
+        vertices,
+        indices: vec![0, 1, 2, 0, 2, 3],
+    });
+    square.color = tint.to_array();
+    let mut description = renderer.scene.description();
+    description.document = Document {
+        shapes: vec![square],
+    };
+    description.pipeline = PipelineKind::Colored;
+    description.clear_color = wgpu::Color::BLACK;
+    description.camera = Camera2d {
+        center: Vec2::ZERO,
+        zoom: 1.0,
+    };
+    renderer.scene.set_description(description);
+    renderer.post_settings = post_settings;
+    renderer.update(0.0);
+    renderer.render_frame().unwrap()
+}
+
+#[test]
+#[ignore = "needs a graphics adapter"]
+fn headless_frames_are_tonemapped_like_the_window() {
+    // Bright enough to clip without tonemapping
+    let tint = Vec3::new(4.0, 2.5, 1.0);
+    let no_bloom = BloomSettings {
+        enabled: false,
+        ..BloomSettings::default()
+    };
+    for (tonemap, exposure) in [(Tonemap::Aces, 0.0), (Tonemap::Reinhard, -1.5)] {
+        let settings = PostSettings {
+            bloom: no_bloom,
+            tonemap,
+            exposure,
+            ..PostSettings::default()
+        };
+        let frame = render_headless(tint, settings);
+        let center = &frame[(32 * 64 + 32) * 4..][..3];
+        let expected = tonemap
+            .apply(tint, exposure)
+            .to_array()
+            .map(|c| (linear_to_srgb(c) * 255.0).round() as u8);
+        for (gpu, cpu) in center.iter().zip(expected) {
+            assert!(
+                gpu.abs_diff(cpu) <= 2,
+                "{tonemap:?}: {center:?} vs {expected:?}"
+            );
+        }
+        assert!(center[0] > center[1] && center[1] > center[2]);
+    }
+
+    // Bloom spreads the bright square into the background around it
+    let outside = (32 * 64 + 56) * 4;
+    let dark = render_headless(
+        tint,
+        PostSettings {
+            bloom: no_bloom,
+            ..PostSettings::default()
+        },
+    );
+    let bloomed = render_headless(tint, PostSettings::default());
+    assert_eq!(dark[outside], 0);
+    assert!(bloomed[outside] > 0);
+}
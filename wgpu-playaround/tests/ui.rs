@@ -0,0 +1,182 @@
+use glam::Vec2;
+use wgpu_playaround::{text::font::Font, ui::Ui};
+use winit::event::MouseButton;
+
+const WIDTH: f32 = 200.0;
+
+fn ui() -> Ui {
+    let mut ui = Ui::new(Font::default());
+    // The panel area is only known once a frame has been laid out
+    ui.begin(Vec2::ZERO, WIDTH, 1.0);
+    ui.button("Button");
+    ui.end();
+    ui
+}
+
+/// Point inside the first row, `t` of the way across the widget column.
+fn first_row(t: f32) -> Vec2 {
+    let left = 8.0 + (WIDTH - 16.0) * 0.4;
+    Vec2::new(left + (WIDTH - 8.0 - left) * t, 15.0)
+}
+
+#[test]
+fn buttons_click_on_release_over_them() {
+    let mut ui = ui();
+    let center = Vec2::new(WIDTH / 2.0, 15.0);
+
+    ui.pointer_moved(center);
+    ui.pointer_button(true);
+    ui.pointer_button(false);
+    ui.begin(Vec2::ZERO, WIDTH, 1.0);
+    assert!(ui.button("Button"));
+    ui.end();
+
+    // Pressed but released elsewhere
+    ui.pointer_button(true);
+    ui.begin(Vec2::ZERO, WIDTH, 1.0);
+    assert!(!ui.button("Button"));
+    ui.end();
+    ui.pointer_moved(Vec2::new(WIDTH / 2.0, 100.0));
+    ui.pointer_button(false);
+    ui.begin(Vec2::ZERO, WIDTH, 1.0);
+    assert!(!ui.button("Button"));
+    ui.end();
+}
+
+#[test]
+fn checkboxes_flip() {
+    let mut ui = ui();
+    let mut value = false;
+    for expected in [true, false] {
+        ui.pointer_moved(Vec2::new(20.0, 15.0));
+        ui.pointer_button(true);
+        ui.pointer_button(false);
+        ui.begin(Vec2::ZERO, WIDTH, 1.0);
+        assert!(ui.checkbox("Check", &mut value));
+        ui.end();
+        assert_eq!(value, expected);
+    }
+}
+
+#[test]
+fn sliders_follow_drags_and_clamp() {
+    let mut ui = ui();
+    let mut value = 0.0;
+    let frame = |ui: &mut Ui, value: &mut f32| {
+        ui.begin(Vec2::ZERO, WIDTH, 1.0);
+        let changed = ui.slider("Value", value, 0.0..=2.0);
+        ui.end();
+        changed
+    };
+
+    ui.pointer_moved(first_row(0.25));
+    ui.pointer_button(true);
+    assert!(frame(&mut ui, &mut value));
+    assert!((value - 0.5).abs() < 1e-4, "{value}");
+
+    // The drag keeps going outside the widget and the panel
+    assert!(ui.pointer_moved(Vec2::new(WIDTH * 3.0, 500.0)));
+    assert!(frame(&mut ui, &mut value));
+    assert_eq!(value, 2.0);
+
+    ui.pointer_button(false);
+    frame(&mut ui, &mut value);
+    ui.pointer_moved(first_row(0.0));
+    assert!(!frame(&mut ui, &mut value));
+    assert_eq!(value, 2.0);
+
+    // Values set elsewhere are kept in range
+    value = -1.0;
+    frame(&mut ui, &mut value);
+    assert_eq!(value, 0.0);
+}
+
+#[test]
+fn selectors_step_both_ways() {
+    let mut ui = ui();
+    let mut steps = Vec::new();
+    for t in [0.05, 0.95, 0.5] {
+        ui.pointer_moved(first_row(t));
+        ui.pointer_button(true);
+        ui.pointer_button(false);
+        ui.begin(Vec2::ZERO, WIDTH, 1.0);
+        steps.push(ui.selector("Mode", "Value"));
+        ui.end();
+    }
+    assert_eq!(steps, [-1, 1, 0]);
+}
+
+#[test]
+fn events_over_the_panel_are_consumed() {
+    let mut ui = ui();
+    let inside = Vec2::new(WIDTH / 2.0, 15.0);
+    let outside = Vec2::new(WIDTH * 2.0, 15.0);
+
+    assert!(ui.pointer_moved(inside));
+    assert!(ui.pointer_button(true));
+    assert!(ui.pointer_moved(outside), "drags started on the panel stay");
+    assert!(ui.pointer_button(false));
+
+    assert!(!ui.pointer_moved(outside));
+    assert!(!ui.pointer_button(true));
+    // A scene drag crossing the panel stays with the scene
+    assert!(!ui.pointer_moved(inside));
+    assert!(!ui.pointer_button(false));
+    assert!(ui.wants_pointer());
+
+    ui.pointer_left();
+    assert!(!ui.wants_pointer());
+}
+
+#[test]
+fn every_button_keeps_its_release_with_its_press() {
+    let mut ui = ui();
+    let inside = Vec2::new(WIDTH / 2.0, 15.0);
+    let outside = Vec2::new(WIDTH * 2.0, 15.0);
+
+    // Camera drags started in the scene end there even over the panel
+    for button in [MouseButton::Middle, MouseButton::Right] {
+        ui.pointer_moved(outside);
+        assert!(!ui.mouse_button(button, true));
+        assert!(!ui.pointer_moved(inside));
+        assert!(!ui.mouse_button(button, false), "{button:?}");
+    }
+
+    ui.pointer_moved(inside);
+    assert!(ui.mouse_button(MouseButton::Right, true));
+    assert!(ui.pointer_moved(outside));
+    assert!(!ui.mouse_button(MouseButton::Middle, true));
+    assert!(ui.mouse_button(MouseButton::Right, false));
+    assert!(!ui.pointer_moved(inside));
+    assert!(!ui.mouse_button(MouseButton::Middle, false));
+    assert!(ui.wants_pointer());
+
+    // Only the primary button clicks widgets
+    ui.mouse_button(MouseButton::Right, true);
+    ui.mouse_button(MouseButton::Right, false);
+    ui.begin(Vec2::ZERO, WIDTH, 1.0);
+    assert!(!ui.button("Button"));
+    ui.end();
+}
+
+#[test]
+fn output_draws_the_panel_below_its_widgets() {
+    let mut ui = ui();
+    let mut value = true;
+    ui.begin(Vec2::new(10.0, 20.0), WIDTH, 2.0);
+    ui.label("Stats");
+    ui.checkbox("Check", &mut value);
+    let output = ui.end();
+
+    let panel = output.quads[0].rect;
+    assert_eq!(panel.min, Vec2::new(10.0, 20.0));
+    assert_eq!(panel.max.x, 10.0 + WIDTH);
+    for quad in &output.quads[1..] {
+        assert!(panel.contains(quad.rect.min) && quad.rect.max.cmple(panel.max).all());
+    }
+    let texts: Vec<_> = output.texts.iter().map(|t| t.text.as_str()).collect();
+    assert_eq!(texts, ["Stats", "Check"]);
+    // Everything scales with the window
+    assert!(output.texts[1].position.y > output.texts[0].position.y + 40.0);
+    assert_eq!(output.texts[0].style.size, 28.0);
+}
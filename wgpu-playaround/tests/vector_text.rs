@@ -0,0 +1,97 @@
+mod common;
+
+use common::{ccw_area, position};
+use glam::Vec2;
+use wgpu_playaround::{
+    models::vertex::Vertex,
+    text::{
+        font::Font,
+        layout::TextAlign,
+        vector::{VectorTextOptions, text_mesh},
+    },
+};
+
+fn contains(vertices: &[Vertex], indices: &[u16], p: Vec2) -> bool {
+    indices.chunks_exact(3).any(|t| {
+        let [a, b, c] = [0, 1, 2].map(|k| position(&vertices[t[k] as usize]));
+        [(a, b), (b, c), (c, a)]
+            .iter()
+            .all(|&(from, to)| (to - from).perp_dot(p - from) >= 0.0)
+    })
+}
+
+fn bounds(vertices: &[Vertex]) -> (Vec2, Vec2) {
+    vertices.iter().fold(
+        (Vec2::splat(f32::MAX), Vec2::splat(f32::MIN)),
+        |(min, max), v| (min.min(position(v)), max.max(position(v))),
+    )
+}
+
+#[test]
+fn counters_stay_open() {
+    let font = Font::default();
+    let (vertices, indices) = text_mesh(&font, "O", &VectorTextOptions::default()).unwrap();
+    let area = ccw_area(&vertices, &indices);
+    let (min, max) = bounds(&vertices);
+    let center = (min + max) * 0.5;
+
+    // The middle of the O is a hole, its stroke is not
+    assert!(!contains(&vertices, &indices, center));
+    assert!(contains(
+        &vertices,
+        &indices,
+        Vec2::new(min.x + 0.01, center.y)
+    ));
+    let size = max - min;
+    assert!(area < 0.5 * size.x * size.y, "{area}");
+}
+
+#[test]
+fn text_is_centered_and_scales_with_size() {
+    let font = Font::default();
+    let small = VectorTextOptions::default();
+    let large = VectorTextOptions {
+        size: small.size * 2.0,
+        ..small
+    };
+
+    let (vertices, indices) = text_mesh(&font, "Hi", &small).unwrap();
+    let small_area = ccw_area(&vertices, &indices);
+    let (min, max) = bounds(&vertices);
+    // Ink is centered horizontally up to side bearings
+    assert!((min.x + max.x).abs() < 0.02, "{min} {max}");
+    assert!(min.y < 0.0 && max.y > 0.0);
+
+    let (vertices, indices) = text_mesh(&font, "Hi", &large).unwrap();
+    let ratio = ccw_area(&vertices, &indices) / small_area;
+    assert!((ratio - 4.0).abs() < 0.01, "{ratio}");
+}
+
+#[test]
+fn lines_are_stacked_and_aligned() {
+    let font = Font::default();
+    let options = VectorTextOptions {
+        align: TextAlign::Left,
+        ..VectorTextOptions::default()
+    };
+    let (one, _) = text_mesh(&font, "I", &options).unwrap();
+    let (two, indices) = text_mesh(&font, "I\nI", &options).unwrap();
+    ccw_area(&two, &indices);
+    assert_eq!(two.len(), one.len() * 2);
+
+    // Same x for both lines, the second one further down
+    let (first, second) = two.split_at(one.len());
+    let (first_min, _) = bounds(first);
+    let (second_min, _) = bounds(second);
+    assert!((first_min.x - second_min.x).abs() < 1e-5);
+    assert!(second_min.y < first_min.y - options.size * 0.5);
+}
+
+#[test]
+fn whitespace_and_empty_text_make_no_triangles() {
+    let font = Font::default();
+    for text in ["", " ", "\n\n"] {
+        let (vertices, indices) = text_mesh(&font, text, &VectorTextOptions::default()).unwrap();
+        assert!(vertices.is_empty() && indices.is_empty(), "{text:?}");
+    }
+}